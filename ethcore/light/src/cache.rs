@@ -19,16 +19,35 @@
 //! Stores ancient block headers, bodies, receipts, and total difficulties.
 //! Furthermore, stores a "gas price corpus" of relative recency, which is a sorted
 //! vector of all gas prices from a recent range of blocks.
+//!
+//! Verified bodies, receipts, accounts and code can additionally be kept on disk
+//! in a `PersistentCache`, so they survive restarts.
 
+use ethcore::basic_account::BasicAccount;
 use ethcore::encoded;
 use ethcore::header::BlockNumber;
 use ethcore::receipt::Receipt;
 
 use stats::Corpus;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 use std::time::{Instant, Duration};
 use heapsize::HeapSizeOf;
-use ethereum_types::{H256, U256};
+use ethereum_types::{H256, U256, Address};
+use kvdb::{DBTransaction, KeyValueDB};
 use memory_cache::MemoryLruCache;
+use rlp::{self, Rlp, RlpStream};
+
+// prefix of all keys of the persistent cache.
+const PERSISTENT_PREFIX: &'static [u8] = b"ondemand-cache/";
+// prefix of the sequence numbers of last use of persistent cache entries.
+const SEQUENCE_PREFIX: &'static [u8] = b"ondemand-cache-seq/";
+
+// kinds of persisted entries, following the prefix.
+const BODY_KEY: u8 = b'b';
+const RECEIPTS_KEY: u8 = b'r';
+const ACCOUNT_KEY: u8 = b'a';
+const CODE_KEY: u8 = b'c';
 
 /// Configuration for how much data to cache.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -43,6 +62,8 @@ pub struct CacheSizes {
 	pub receipts: usize,
 	/// Maximum size, in bytes, of cached chain score for the block.
 	pub chain_score: usize,
+	/// Maximum size, in bytes, of cached accounts.
+	pub accounts: usize,
 }
 
 impl Default for CacheSizes {
@@ -54,6 +75,7 @@ impl Default for CacheSizes {
 			bodies: 20 * MB,
 			receipts: 10 * MB,
 			chain_score: 7 * MB,
+			accounts: 2 * MB,
 		}
 	}
 }
@@ -69,8 +91,11 @@ pub struct Cache {
 	bodies: MemoryLruCache<H256, encoded::Body>,
 	receipts: MemoryLruCache<H256, Vec<Receipt>>,
 	chain_score: MemoryLruCache<H256, U256>,
+	// encoded accounts by block hash and address.
+	accounts: MemoryLruCache<(H256, Address), Vec<u8>>,
 	corpus: Option<(Corpus<U256>, Instant)>,
 	corpus_expiration: Duration,
+	persistent: Option<PersistentCache>,
 }
 
 impl Cache {
@@ -82,11 +107,18 @@ impl Cache {
 			bodies: MemoryLruCache::new(sizes.bodies),
 			receipts: MemoryLruCache::new(sizes.receipts),
 			chain_score: MemoryLruCache::new(sizes.chain_score),
+			accounts: MemoryLruCache::new(sizes.accounts),
 			corpus: None,
 			corpus_expiration: corpus_expiration,
+			persistent: None,
 		}
 	}
 
+	/// Keep verified bodies, receipts, accounts and code in the given persistent cache as well.
+	pub fn set_persistent(&mut self, persistent: PersistentCache) {
+		self.persistent = Some(persistent);
+	}
+
 	/// Query header by hash.
 	pub fn block_header(&mut self, hash: &H256) -> Option<encoded::Header> {
 		self.headers.get_mut(hash).map(|x| x.clone())
@@ -99,12 +131,53 @@ impl Cache {
 
 	/// Query block body by block hash.
 	pub fn block_body(&mut self, hash: &H256) -> Option<encoded::Body> {
-		self.bodies.get_mut(hash).map(|x| x.clone())
+		if let Some(body) = self.bodies.get_mut(hash) {
+			return Some(body.clone());
+		}
+
+		let body = self.persistent.as_mut()
+			.and_then(|p| p.get(&persistent_key(BODY_KEY, &[&hash[..]])))
+			.map(encoded::Body::new)?;
+		self.bodies.insert(*hash, body.clone());
+		Some(body)
 	}
 
 	/// Query block receipts by block hash.
 	pub fn block_receipts(&mut self, hash: &H256) -> Option<Vec<Receipt>> {
-		self.receipts.get_mut(hash).map(|x| x.clone())
+		if let Some(receipts) = self.receipts.get_mut(hash) {
+			return Some(receipts.clone());
+		}
+
+		let receipts: Vec<Receipt> = self.persistent.as_mut()
+			.and_then(|p| p.get(&persistent_key(RECEIPTS_KEY, &[&hash[..]])))
+			.and_then(|raw| Rlp::new(&raw).as_list().ok())?;
+		self.receipts.insert(*hash, receipts.clone());
+		Some(receipts)
+	}
+
+	/// Query an account by block hash and address.
+	/// `Some(None)` means the account is known not to exist.
+	pub fn account(&mut self, block_hash: &H256, address: &Address) -> Option<Option<BasicAccount>> {
+		let key = (*block_hash, *address);
+		let raw = match self.accounts.get_mut(&key).map(|x| x.clone()) {
+			Some(raw) => raw,
+			None => {
+				let raw = self.persistent.as_mut()?.get(&persistent_key(ACCOUNT_KEY, &[&block_hash[..], &address[..]]))?;
+				self.accounts.insert(key, raw.clone());
+				raw
+			}
+		};
+
+		let rlp = Rlp::new(&raw);
+		match rlp.item_count().ok()? {
+			0 => Some(None),
+			_ => rlp.as_val().ok().map(Some),
+		}
+	}
+
+	/// Query contract code by code hash.
+	pub fn code(&mut self, code_hash: &H256) -> Option<Vec<u8>> {
+		self.persistent.as_mut()?.get(&persistent_key(CODE_KEY, &[&code_hash[..]]))
 	}
 
 	/// Query chain score by block hash.
@@ -124,14 +197,39 @@ impl Cache {
 
 	/// Cache the given block body.
 	pub fn insert_block_body(&mut self, hash: H256, body: encoded::Body) {
+		if let Some(persistent) = self.persistent.as_mut() {
+			persistent.insert(persistent_key(BODY_KEY, &[&hash[..]]), body.rlp().as_raw());
+		}
 		self.bodies.insert(hash, body);
 	}
 
 	/// Cache the given block receipts.
 	pub fn insert_block_receipts(&mut self, hash: H256, receipts: Vec<Receipt>) {
+		if let Some(persistent) = self.persistent.as_mut() {
+			persistent.insert(persistent_key(RECEIPTS_KEY, &[&hash[..]]), &rlp::encode_list::<Receipt, _>(&receipts));
+		}
 		self.receipts.insert(hash, receipts);
 	}
 
+	/// Cache the given account, `None` if it doesn't exist.
+	pub fn insert_account(&mut self, block_hash: H256, address: Address, account: Option<BasicAccount>) {
+		let raw = match account {
+			Some(ref account) => rlp::encode(account).into_vec(),
+			None => RlpStream::new_list(0).out(),
+		};
+		if let Some(persistent) = self.persistent.as_mut() {
+			persistent.insert(persistent_key(ACCOUNT_KEY, &[&block_hash[..], &address[..]]), &raw);
+		}
+		self.accounts.insert((block_hash, address), raw);
+	}
+
+	/// Cache the given contract code. Only kept in the persistent cache.
+	pub fn insert_code(&mut self, code_hash: H256, code: &[u8]) {
+		if let Some(persistent) = self.persistent.as_mut() {
+			persistent.insert(persistent_key(CODE_KEY, &[&code_hash[..]]), code);
+		}
+	}
+
 	/// Cache the given chain scoring.
 	pub fn insert_chain_score(&mut self, hash: H256, score: U256) {
 		self.chain_score.insert(hash, score);
@@ -155,6 +253,13 @@ impl Cache {
 		self.corpus = Some((corpus, Instant::now()))
 	}
 
+	/// Store the order of use of persistent cache entries.
+	pub fn flush_persistent(&mut self) {
+		if let Some(persistent) = self.persistent.as_mut() {
+			persistent.flush();
+		}
+	}
+
 	/// Get the memory used.
 	pub fn mem_used(&self) -> usize {
 		self.heap_size_of_children()
@@ -168,14 +273,218 @@ impl HeapSizeOf for Cache {
 			+ self.bodies.current_size()
 			+ self.receipts.current_size()
 			+ self.chain_score.current_size()
+			+ self.accounts.current_size()
 			// TODO: + corpus
 	}
 }
 
+fn persistent_key(kind: u8, parts: &[&[u8]]) -> Vec<u8> {
+	let mut key = PERSISTENT_PREFIX.to_vec();
+	key.push(kind);
+	for part in parts {
+		key.extend_from_slice(part);
+	}
+	key
+}
+
+fn sequence_key(key: &[u8]) -> Vec<u8> {
+	let mut seq_key = SEQUENCE_PREFIX.to_vec();
+	seq_key.extend_from_slice(key);
+	seq_key
+}
+
+/// Disk-backed cache of verified network data, limited in total size.
+///
+/// Entries are evicted in least-recently-used order. The order survives restarts,
+/// since the sequence number of each entry's last use is stored next to it. Uses are only
+/// tracked in memory and written out in batches, see `flush`.
+pub struct PersistentCache {
+	db: Arc<KeyValueDB>,
+	col: Option<u32>,
+	max_size: usize,
+	size: usize,
+	next_seq: u64,
+	// key -> (sequence number of last use, size)
+	entries: HashMap<Vec<u8>, (u64, usize)>,
+	// sequence number of last use -> key
+	by_use: BTreeMap<u64, Vec<u8>>,
+	// keys used since the last flush.
+	dirty: HashSet<Vec<u8>>,
+}
+
+impl PersistentCache {
+	/// Open the persistent cache stored in the given database column, evicting entries
+	/// beyond `max_size` bytes.
+	pub fn new(db: Arc<KeyValueDB>, col: Option<u32>, max_size: usize) -> Self {
+		let mut cache = PersistentCache {
+			db: db,
+			col: col,
+			max_size: max_size,
+			size: 0,
+			next_seq: 0,
+			entries: HashMap::new(),
+			by_use: BTreeMap::new(),
+			dirty: HashSet::new(),
+		};
+
+		let mut seqs: HashMap<Vec<u8>, u64> = cache.db.iter_from_prefix(col, SEQUENCE_PREFIX)
+			.take_while(|&(ref key, _)| key.starts_with(SEQUENCE_PREFIX))
+			.filter_map(|(key, value)| {
+				let seq: u64 = Rlp::new(&value).as_val().ok()?;
+				Some((key[SEQUENCE_PREFIX.len()..].to_vec(), seq))
+			})
+			.collect();
+		let stored: Vec<_> = cache.db.iter_from_prefix(col, PERSISTENT_PREFIX)
+			.take_while(|&(ref key, _)| key.starts_with(PERSISTENT_PREFIX))
+			.map(|(key, value)| (key.into_vec(), value.len()))
+			.collect();
+
+		let mut batch = DBTransaction::new();
+		for (key, len) in stored {
+			let seq = match seqs.remove(&key) {
+				Some(seq) => seq,
+				None => {
+					batch.delete(col, &key);
+					continue
+				}
+			};
+			let size = key.len() + len;
+			cache.size += size;
+			cache.next_seq = ::std::cmp::max(cache.next_seq, seq + 1);
+			cache.by_use.insert(seq, key.clone());
+			cache.entries.insert(key, (seq, size));
+		}
+		for key in seqs.keys() {
+			batch.delete(col, &sequence_key(key));
+		}
+
+		cache.evict(&mut batch);
+		cache.write(batch);
+
+		cache
+	}
+
+	/// Total size of the stored entries, in bytes.
+	pub fn size(&self) -> usize {
+		self.size
+	}
+
+	/// Store the order in which entries were used since the last flush.
+	pub fn flush(&mut self) {
+		if self.dirty.is_empty() {
+			return;
+		}
+
+		let mut batch = DBTransaction::new();
+		self.write_dirty(&mut batch);
+		self.write(batch);
+	}
+
+	// get an entry's data, marking it as recently used.
+	fn get(&mut self, key: &[u8]) -> Option<Vec<u8>> {
+		let (seq, size) = *self.entries.get(key)?;
+		let data = match self.db.get(self.col, key) {
+			Ok(Some(data)) => data.into_vec(),
+			Ok(None) => {
+				self.remove_entry(key, seq);
+				return None
+			}
+			Err(e) => {
+				warn!(target: "on_demand", "Error reading from the persistent cache: {}", e);
+				return None
+			}
+		};
+
+		self.by_use.remove(&seq);
+		let seq = self.use_seq();
+		self.by_use.insert(seq, key.to_vec());
+		self.entries.insert(key.to_vec(), (seq, size));
+		self.dirty.insert(key.to_vec());
+		Some(data)
+	}
+
+	// store an entry, evicting the least recently used ones if over the size limit.
+	fn insert(&mut self, key: Vec<u8>, data: &[u8]) {
+		if let Some((seq, _)) = self.entries.get(&key).cloned() {
+			self.remove_entry(&key, seq);
+		}
+
+		let seq = self.use_seq();
+		let size = key.len() + data.len();
+		let mut batch = DBTransaction::new();
+		batch.put(self.col, &key, data);
+		batch.put_vec(self.col, &sequence_key(&key), rlp::encode(&seq).into_vec());
+		self.size += size;
+		self.by_use.insert(seq, key.clone());
+		self.entries.insert(key, (seq, size));
+
+		if self.evict(&mut batch) {
+			self.write_dirty(&mut batch);
+		}
+		self.write(batch);
+	}
+
+	fn use_seq(&mut self) -> u64 {
+		let seq = self.next_seq;
+		self.next_seq += 1;
+		seq
+	}
+
+	// returns whether any entries were evicted.
+	fn evict(&mut self, batch: &mut DBTransaction) -> bool {
+		let mut evicted = false;
+		while self.size > self.max_size {
+			let (seq, key) = match self.by_use.iter().next() {
+				Some((seq, key)) => (*seq, key.clone()),
+				None => break,
+			};
+
+			batch.delete(self.col, &key);
+			batch.delete(self.col, &sequence_key(&key));
+			self.remove_entry(&key, seq);
+			evicted = true;
+		}
+		evicted
+	}
+
+	fn write_dirty(&mut self, batch: &mut DBTransaction) {
+		for key in self.dirty.drain() {
+			if let Some(&(seq, _)) = self.entries.get(&key) {
+				batch.put_vec(self.col, &sequence_key(&key), rlp::encode(&seq).into_vec());
+			}
+		}
+	}
+
+	fn remove_entry(&mut self, key: &[u8], seq: u64) {
+		self.by_use.remove(&seq);
+		self.dirty.remove(key);
+		if let Some((_, size)) = self.entries.remove(key) {
+			self.size -= size;
+		}
+	}
+
+	fn write(&self, batch: DBTransaction) {
+		if let Err(e) = self.db.write(batch) {
+			warn!(target: "on_demand", "Error writing to the persistent cache: {}", e);
+		}
+	}
+}
+
+impl Drop for PersistentCache {
+	fn drop(&mut self) {
+		self.flush();
+	}
+}
+
 #[cfg(test)]
 mod tests {
-	use super::Cache;
+	use super::{Cache, PersistentCache, CODE_KEY, persistent_key, sequence_key};
+	use std::sync::Arc;
 	use std::time::Duration;
+	use ethcore::encoded;
+	use ethereum_types::H256;
+	use kvdb_memorydb;
+	use rlp::RlpStream;
 
 	#[test]
 	fn corpus_inaccessible() {
@@ -190,4 +499,84 @@ mod tests {
 		}
 		assert!(cache.gas_price_corpus().is_none());
 	}
+
+	fn body(uncles: usize) -> encoded::Body {
+		let mut stream = RlpStream::new_list(2);
+		stream.begin_list(0);
+		stream.begin_list(uncles);
+		for _ in 0..uncles {
+			stream.append_raw(&::rlp::encode(&::ethcore::header::Header::default()), 1);
+		}
+		encoded::Body::new(stream.out())
+	}
+
+	#[test]
+	fn persistent_entries_survive_restart() {
+		let db = Arc::new(kvdb_memorydb::create(1));
+		let hash = H256::from(1);
+
+		{
+			let mut cache = Cache::new(Default::default(), Duration::from_secs(1));
+			cache.set_persistent(PersistentCache::new(db.clone(), None, 1024 * 1024));
+			cache.insert_block_body(hash, body(1));
+			cache.insert_code(H256::from(2), &[1, 2, 3]);
+			cache.insert_account(hash, 3.into(), None);
+		}
+
+		let mut cache = Cache::new(Default::default(), Duration::from_secs(1));
+		cache.set_persistent(PersistentCache::new(db.clone(), None, 1024 * 1024));
+		assert_eq!(cache.block_body(&hash), Some(body(1)));
+		assert_eq!(cache.code(&H256::from(2)), Some(vec![1, 2, 3]));
+		assert_eq!(cache.account(&hash, &3.into()), Some(None));
+		assert_eq!(cache.account(&hash, &4.into()), None);
+	}
+
+	#[test]
+	fn persistent_cache_evicts_least_recently_used() {
+		let db = Arc::new(kvdb_memorydb::create(1));
+		let mut cache = Cache::new(Default::default(), Duration::from_secs(1));
+		cache.set_persistent(PersistentCache::new(db.clone(), None, 250));
+
+		cache.insert_code(H256::from(1), &[0; 50]);
+		cache.insert_code(H256::from(2), &[0; 50]);
+		// use the first entry, so the second one is evicted instead.
+		assert!(cache.code(&H256::from(1)).is_some());
+		cache.insert_code(H256::from(3), &[0; 50]);
+
+		assert!(cache.code(&H256::from(2)).is_none());
+		assert!(cache.code(&H256::from(1)).is_some());
+		assert!(cache.code(&H256::from(3)).is_some());
+
+		// the limit also applies when reopening.
+		let persistent = PersistentCache::new(db, None, 100);
+		assert!(persistent.size() <= 100);
+	}
+
+	#[test]
+	fn persistent_cache_stores_order_of_use_on_flush() {
+		let db = Arc::new(kvdb_memorydb::create(1));
+		let key = |n: u64| persistent_key(CODE_KEY, &[&H256::from(n)[..]]);
+		let stored_seq = |n: u64| db.get(None, &sequence_key(&key(n))).unwrap();
+
+		{
+			let mut persistent = PersistentCache::new(db.clone(), None, 250);
+			persistent.insert(key(1), &[0; 50]);
+			persistent.insert(key(2), &[0; 50]);
+
+			// a hit doesn't write to the database.
+			let seq = stored_seq(1);
+			assert!(persistent.get(&key(1)).is_some());
+			assert_eq!(stored_seq(1), seq);
+
+			persistent.flush();
+			assert!(stored_seq(1) != seq);
+		}
+
+		// the order of use is kept after restart.
+		let mut persistent = PersistentCache::new(db.clone(), None, 250);
+		persistent.insert(key(3), &[0; 50]);
+		assert!(persistent.get(&key(2)).is_none());
+		assert!(persistent.get(&key(1)).is_some());
+		assert!(stored_seq(2).is_none());
+	}
 }
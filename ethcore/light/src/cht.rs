@@ -19,9 +19,15 @@
 //! request an inclusion proof of a specific block number against the trie with the
 //! root has. A correct proof implies that the claimed block is identical to the one
 //! we discarded.
+//!
+//! Alongside each CHT, a bloom trie is kept mapping bloom group numbers to the
+//! aggregated log blooms of `BLOOM_GROUP_SIZE` consecutive blocks, which allows
+//! searching ancient blocks for logs without fetching their headers.
+
+use std::collections::HashSet;
 
 use ethcore::ids::BlockId;
-use ethereum_types::{H256, U256};
+use ethereum_types::{H256, U256, Bloom};
 use hashdb::HashDB;
 use memorydb::MemoryDB;
 use bytes::Bytes;
@@ -44,6 +50,9 @@ macro_rules! val {
 /// The size of each CHT.
 pub const SIZE: u64 = 2048;
 
+/// The number of blocks whose log blooms are aggregated into a single bloom group.
+pub const BLOOM_GROUP_SIZE: u64 = 16;
+
 /// A canonical hash trie. This is generic over any database it can query.
 /// See module docs for more details.
 #[derive(Debug, Clone)]
@@ -163,6 +172,124 @@ pub fn check_proof(proof: &[Bytes], num: u64, root: H256) -> Option<(H256, U256)
 	}
 }
 
+// aggregate the log blooms of the blocks of a CHT into (group number, bloom group) pairs.
+// fails if shorter than SIZE items.
+fn aggregate_blooms<I>(cht_num: u64, iterable: I) -> Option<Vec<(u64, Bloom)>>
+	where I: IntoIterator<Item=Bloom>
+{
+	let first_group = cht_num * (SIZE / BLOOM_GROUP_SIZE);
+	let mut groups: Vec<(u64, Bloom)> = Vec::with_capacity((SIZE / BLOOM_GROUP_SIZE) as usize);
+	let mut count = 0;
+
+	for (i, bloom) in iterable.into_iter().take(SIZE as usize).enumerate() {
+		if i as u64 % BLOOM_GROUP_SIZE == 0 {
+			groups.push((first_group + i as u64 / BLOOM_GROUP_SIZE, Bloom::default()));
+		}
+
+		groups.last_mut().expect("a group is pushed for the first block of every group; qed").1.accrue_bloom(&bloom);
+		count += 1;
+	}
+
+	if count == SIZE {
+		Some(groups)
+	} else {
+		None
+	}
+}
+
+/// Compute the root of a CHT's bloom trie from an iterator of the log blooms of its blocks.
+/// Fails if shorter than SIZE items. The items are assumed to proceed sequentially from
+/// `start_number(cht_num)`. Discards the trie's nodes.
+pub fn compute_bloom_root<I>(cht_num: u64, iterable: I) -> Option<H256>
+	where I: IntoIterator<Item=Bloom>
+{
+	aggregate_blooms(cht_num, iterable).map(|groups| ::triehash::trie_root(
+		groups.into_iter().map(|(num, bloom)| (key!(num).into_vec(), ::rlp::encode(&bloom).into_vec()))
+	))
+}
+
+/// Build a CHT's bloom trie from an iterator of the log blooms of its blocks and
+/// prove the bloom groups `start..start + count`, which must all belong to the CHT.
+/// Returns the bloom groups along with the trie nodes proving them.
+pub fn prove_bloom_groups<I>(cht_num: u64, iterable: I, start: u64, count: u64) -> Option<(Vec<Bloom>, Vec<Bytes>)>
+	where I: IntoIterator<Item=Bloom>
+{
+	if count == 0 || bloom_group_to_cht_number(start) != cht_num
+		|| bloom_group_to_cht_number(start + count - 1) != cht_num { return None }
+
+	let groups = aggregate_blooms(cht_num, iterable)?;
+
+	let mut db = MemoryDB::new();
+	let mut root = H256::default();
+
+	{
+		let mut t = TrieDBMut::new(&mut db, &mut root);
+		for &(num, ref bloom) in &groups {
+			t.insert(&key!(num), &::rlp::encode(bloom))
+				.expect("fresh in-memory database is infallible; qed");
+		}
+	}
+
+	let first_group = groups[0].0;
+	let blooms = groups[(start - first_group) as usize..][..count as usize].iter()
+		.map(|&(_, ref bloom)| bloom.clone())
+		.collect();
+
+	let mut recorder = Recorder::new();
+	let t = TrieDB::new(&db, &root).expect("root was just committed to the database; qed");
+	for num in start..start + count {
+		t.get_with(&key!(num), &mut recorder).expect("fresh in-memory database is infallible; qed");
+	}
+
+	// nodes close to the root are visited by every lookup.
+	let mut seen = HashSet::new();
+	let proof = recorder.drain().into_iter()
+		.filter(|record| seen.insert(record.hash))
+		.map(|record| record.data)
+		.collect();
+
+	Some((blooms, proof))
+}
+
+/// Check a proof of the bloom groups `start..start + count` against the root of
+/// a bloom trie and extract the bloom groups.
+pub fn check_bloom_proof(proof: &[Bytes], start: u64, count: u64, root: H256) -> Option<Vec<Bloom>> {
+	let mut db = MemoryDB::new();
+
+	for node in proof { db.insert(&node[..]); }
+	let trie = match TrieDB::new(&db, &root) {
+		Err(_) => return None,
+		Ok(trie) => trie,
+	};
+
+	(start..start + count).map(|num| {
+		match trie.get_with(&key!(num), |val: &[u8]| Rlp::new(val).as_val::<Bloom>().ok()) {
+			Ok(Some(Some(bloom))) => Some(bloom),
+			_ => None,
+		}
+	}).collect()
+}
+
+/// Convert a block number to the number of the bloom group containing it.
+/// Returns `None` for `block_num` == 0, `Some` otherwise.
+pub fn block_to_bloom_group(block_num: u64) -> Option<u64> {
+	match block_num {
+		0 => None,
+		n => Some((n - 1) / BLOOM_GROUP_SIZE),
+	}
+}
+
+/// Convert a bloom group number to the number of the CHT covering it.
+pub fn bloom_group_to_cht_number(group: u64) -> u64 {
+	group * BLOOM_GROUP_SIZE / SIZE
+}
+
+/// Get the first block of a given bloom group.
+/// Like CHTs, bloom group N includes block (1 + N*BLOOM_GROUP_SIZE)...((N+1)*BLOOM_GROUP_SIZE).
+pub fn bloom_group_start_number(group: u64) -> u64 {
+	(group * BLOOM_GROUP_SIZE) + 1
+}
+
 /// Convert a block number to a CHT number.
 /// Returns `None` for `block_num` == 0, `Some` otherwise.
 pub fn block_to_cht_number(block_num: u64) -> Option<u64> {
@@ -198,6 +325,52 @@ mod tests {
 		assert_eq!(::cht::block_to_cht_number(::cht::SIZE).unwrap(), 0);
 	}
 
+	#[test]
+	fn block_to_bloom_group() {
+		assert!(::cht::block_to_bloom_group(0).is_none());
+		assert_eq!(::cht::block_to_bloom_group(1).unwrap(), 0);
+		assert_eq!(::cht::block_to_bloom_group(::cht::BLOOM_GROUP_SIZE).unwrap(), 0);
+		assert_eq!(::cht::block_to_bloom_group(::cht::BLOOM_GROUP_SIZE + 1).unwrap(), 1);
+		assert_eq!(::cht::bloom_group_to_cht_number(::cht::SIZE / ::cht::BLOOM_GROUP_SIZE - 1), 0);
+		assert_eq!(::cht::bloom_group_to_cht_number(::cht::SIZE / ::cht::BLOOM_GROUP_SIZE), 1);
+		assert_eq!(::cht::bloom_group_start_number(1), ::cht::BLOOM_GROUP_SIZE + 1);
+	}
+
+	#[test]
+	fn bloom_group_proofs() {
+		use ethereum_types::{H256, Bloom, BloomInput};
+
+		let cht_num = 1;
+		let blooms: Vec<Bloom> = (0..::cht::SIZE).map(|i| {
+			let mut bloom = Bloom::default();
+			bloom.accrue(BloomInput::Raw(&H256::from(i)[..]));
+			bloom
+		}).collect();
+
+		let root = ::cht::compute_bloom_root(cht_num, blooms.iter().cloned()).unwrap();
+		assert!(::cht::compute_bloom_root(cht_num, blooms.iter().cloned().take(10)).is_none());
+
+		let per_cht = ::cht::SIZE / ::cht::BLOOM_GROUP_SIZE;
+		let start = per_cht + 3;
+		let (groups, proof) = ::cht::prove_bloom_groups(cht_num, blooms.iter().cloned(), start, 4).unwrap();
+		assert_eq!(groups.len(), 4);
+
+		// the first proved group aggregates the blooms of its blocks.
+		let mut expected = Bloom::default();
+		for bloom in &blooms[(3 * ::cht::BLOOM_GROUP_SIZE) as usize..][..::cht::BLOOM_GROUP_SIZE as usize] {
+			expected.accrue_bloom(bloom);
+		}
+		assert_eq!(groups[0], expected);
+
+		assert_eq!(::cht::check_bloom_proof(&proof, start, 4, root), Some(groups));
+		assert!(::cht::check_bloom_proof(&proof, start, 5, root).is_none());
+		assert!(::cht::check_bloom_proof(&proof, start, 4, H256::default()).is_none());
+
+		// groups outside the CHT can't be proved.
+		assert!(::cht::prove_bloom_groups(cht_num, blooms.iter().cloned(), per_cht - 1, 2).is_none());
+		assert!(::cht::prove_bloom_groups(cht_num, blooms.iter().cloned(), 2 * per_cht - 1, 2).is_none());
+	}
+
 	#[test]
 	fn start_number() {
 		assert_eq!(::cht::start_number(0), 1);
@@ -20,6 +20,8 @@
 //! It stores candidates for the last 2048-4096 blocks as well as CHT roots for
 //! historical blocks all the way to the genesis. If the engine makes use
 //! of epoch transitions, those are stored as well.
+//! The headers of all but the most recent candidates may be pruned, keeping
+//! only their hashes and total difficulties until they are covered by a CHT.
 //!
 //! This is separate from the `BlockChain` for two reasons:
 //!   - It stores only headers (and a pruned subset of them)
@@ -27,6 +29,7 @@
 
 use std::collections::BTreeMap;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
 use cht;
 
@@ -151,6 +154,10 @@ fn cht_key(number: u64) -> String {
 	format!("{:08x}_canonical", number)
 }
 
+fn bloom_key(number: u64) -> String {
+	format!("{:08x}_blooms", number)
+}
+
 fn era_key(number: u64) -> String {
 	format!("candidates_{}", number)
 }
@@ -200,17 +207,55 @@ pub struct PendingChanges {
 pub enum HardcodedSync {
 	Allow,
 	Deny,
+	/// Start from the given checkpoint instead of the spec's hardcoded sync.
+	/// Unlike the latter, it is verified before being applied.
+	Checkpoint(SpecHardcodedSync),
+}
+
+// Checks that a checkpoint fits the CHT layout and that its header is valid
+// in the epoch it claims to be part of.
+fn verify_checkpoint(spec: &Spec, checkpoint: &SpecHardcodedSync) -> Result<(), Error> {
+	let header = checkpoint.header.decode()?;
+
+	// CHTs are produced from eras starting right after the last known CHT.
+	if checkpoint.chts.is_empty() || header.number() != cht::start_number(checkpoint.chts.len() as u64) {
+		bail!("Checkpoint block #{} doesn't directly follow its {} CHT roots", header.number(), checkpoint.chts.len());
+	}
+
+	if checkpoint.total_difficulty < *header.difficulty() {
+		bail!("Checkpoint total difficulty is lower than the difficulty of its block");
+	}
+
+	let (transition_header, proof) = match checkpoint.epoch_transition {
+		Some((ref transition_header, ref proof)) => (transition_header.decode()?, proof.clone()),
+		None => (spec.genesis_header(), spec.genesis_epoch_data()?),
+	};
+
+	if transition_header.number() > header.number() {
+		bail!("Checkpoint epoch transition #{} is after checkpoint block #{}", transition_header.number(), header.number());
+	}
+
+	// a transition block itself is sealed under the previous epoch.
+	if transition_header.number() < header.number() {
+		let verifier = spec.engine.epoch_verifier(&transition_header, &proof).known_confirmed()?;
+		verifier.verify_light(&header)?;
+	}
+
+	Ok(())
 }
 
 /// Header chain. See module docs for more details.
 pub struct HeaderChain {
 	genesis_header: encoded::Header, // special-case the genesis.
 	candidates: RwLock<BTreeMap<u64, Entry>>,
+	candidate_numbers: RwLock<H256FastMap<u64>>, // era numbers of all candidates, by hash.
 	best_block: RwLock<BlockDescriptor>,
 	live_epoch_proofs: RwLock<H256FastMap<EpochTransition>>,
 	db: Arc<KeyValueDB>,
 	col: Option<u32>,
 	cache: Arc<Mutex<Cache>>,
+	pruned_until: Mutex<u64>, // eras before this have had their headers pruned.
+	header_pruning: AtomicBool, // whether headers of candidates may have been pruned.
 }
 
 impl HeaderChain {
@@ -230,6 +275,10 @@ impl HeaderChain {
 		let chain = if let Some(current) = db.get(col, CURRENT_KEY)? {
 			let curr : BestAndLatest = ::rlp::decode(&current).expect("decoding db value failed");
 
+			if let HardcodedSync::Checkpoint(_) = allow_hs {
+				warn!(target: "chain", "Ignoring checkpoint since the database isn't empty");
+			}
+
 			let mut cur_number = curr.latest_num;
 			let mut candidates = BTreeMap::new();
 
@@ -271,14 +320,21 @@ impl HeaderChain {
 				}
 			};
 
+			let candidate_numbers = candidates.iter()
+				.flat_map(|(&num, entry)| entry.candidates.iter().map(move |c| (c.hash, num)))
+				.collect();
+
 			HeaderChain {
 				genesis_header: encoded::Header::new(genesis),
 				best_block: RwLock::new(best_block),
 				candidates: RwLock::new(candidates),
+				candidate_numbers: RwLock::new(candidate_numbers),
 				live_epoch_proofs: RwLock::new(live_epoch_proofs),
 				db: db,
 				col: col,
 				cache: cache,
+				pruned_until: Mutex::new(0),
+				header_pruning: AtomicBool::new(false),
 			}
 
 		} else {
@@ -290,14 +346,26 @@ impl HeaderChain {
 					total_difficulty: *decoded_header.difficulty(),
 				}),
 				candidates: RwLock::new(BTreeMap::new()),
+				candidate_numbers: RwLock::new(H256FastMap::default()),
 				live_epoch_proofs: RwLock::new(live_epoch_proofs),
 				db: db.clone(),
 				col: col,
 				cache: cache,
+				pruned_until: Mutex::new(0),
+				header_pruning: AtomicBool::new(false),
+			};
+
+			let hardcoded_sync = match allow_hs {
+				HardcodedSync::Allow => spec.hardcoded_sync.as_ref(),
+				HardcodedSync::Checkpoint(ref checkpoint) => {
+					verify_checkpoint(spec, checkpoint)?;
+					Some(checkpoint)
+				}
+				HardcodedSync::Deny => None,
 			};
 
 			// insert the hardcoded sync into the database.
-			if let (&Some(ref hardcoded_sync), HardcodedSync::Allow) = (&spec.hardcoded_sync, allow_hs) {
+			if let Some(hardcoded_sync) = hardcoded_sync {
 				let mut batch = db.transaction();
 
 				// insert the hardcoded CHT roots into the database.
@@ -314,6 +382,12 @@ impl HeaderChain {
 				let pending = chain.insert_with_td(&mut batch, decoded_header,
 												hardcoded_sync.total_difficulty, None)?;
 
+				// blocks after the hardcoded one are part of its epoch.
+				if let Some((ref transition_header, ref proof)) = hardcoded_sync.epoch_transition {
+					let data = encode_canonical_transition(&transition_header.decode()?, proof);
+					batch.put_vec(col, LAST_CANONICAL_TRANSITION, data);
+				}
+
 				// check that we have enough hardcoded CHT roots. avoids panicking later.
 				let cht_num = cht::block_to_cht_number(decoded_header_num - 1)
 					.expect("specs provided a hardcoded block with height 0");
@@ -397,6 +471,7 @@ impl HeaderChain {
 
 		// hold candidates the whole time to guard import order.
 		let mut candidates = self.candidates.write();
+		let mut candidate_numbers = self.candidate_numbers.write();
 
 		// find total difficulty.
 		let total_difficulty = match total_difficulty {
@@ -426,6 +501,7 @@ impl HeaderChain {
 				parent_hash: parent_hash,
 				total_difficulty: total_difficulty,
 			});
+			candidate_numbers.insert(hash, number);
 
 			// fix ordering of era before writing.
 			if total_difficulty > cur_era.candidates[0].total_difficulty {
@@ -497,6 +573,7 @@ impl HeaderChain {
 					.expect("fails only for number == 0; genesis never imported; qed");
 
 				let mut last_canonical_transition = None;
+				let mut blooms = Vec::with_capacity(cht::SIZE as usize);
 				let cht_root = {
 					let mut i = earliest_era;
 					let mut live_epoch_proofs = self.live_epoch_proofs.write();
@@ -512,6 +589,7 @@ impl HeaderChain {
 
 						// prune old blocks and epoch proofs.
 						for ancient in &era_entry.candidates {
+							candidate_numbers.remove(&ancient.hash);
 							let maybe_transition = live_epoch_proofs.remove(&ancient.hash);
 							if let Some(epoch_transition) = maybe_transition {
 								transaction.delete(self.col, &*transition_key(ancient.hash));
@@ -536,6 +614,17 @@ impl HeaderChain {
 						}
 
 						let canon = &era_entry.candidates[0];
+
+						// the deletion above isn't committed yet, so the header can still be read.
+						blooms.push(match self.db.get(self.col, &canon.hash) {
+							Ok(Some(header)) => ::rlp::decode::<Header>(&header).ok().map(|header| *header.log_bloom()),
+							Ok(None) => None,
+							Err(e) => {
+								warn!(target: "chain", "Error reading from DB: {}", e);
+								None
+							}
+						});
+
 						(canon.hash, canon.total_difficulty)
 					};
 					cht::compute_root(cht_num, ::itertools::repeat_call(iter))
@@ -546,6 +635,15 @@ impl HeaderChain {
 				debug!(target: "chain", "Produced CHT {} root: {:?}", cht_num, cht_root);
 				transaction.put(self.col, cht_key(cht_num).as_bytes(), &::rlp::encode(&cht_root));
 
+				// and the root of its bloom trie, unless a header was unavailable.
+				match blooms.into_iter().collect::<Option<Vec<_>>>().and_then(|blooms| cht::compute_bloom_root(cht_num, blooms)) {
+					Some(bloom_root) => {
+						debug!(target: "chain", "Produced bloom trie {} root: {:?}", cht_num, bloom_root);
+						transaction.put(self.col, bloom_key(cht_num).as_bytes(), &::rlp::encode(&bloom_root));
+					}
+					None => warn!(target: "chain", "Unable to produce bloom trie {}: missing headers", cht_num),
+				}
+
 				// update the last canonical transition proof
 				if let Some((epoch_transition, header)) = last_canonical_transition {
 					let x = encode_canonical_transition(&header, &epoch_transition.proof);
@@ -606,10 +704,18 @@ impl HeaderChain {
 						})?
 						.total_difficulty;
 
+					// the genesis epoch is known from the spec.
+					let epoch_transition = self.epoch_transition_for(decoded.hash())
+						.and_then(|(transition, proof)| match transition.number() {
+							0 => None,
+							_ => Some((encoded::Header::new(::rlp::encode(&transition).into_vec()), proof)),
+						});
+
 					break Ok(Some(SpecHardcodedSync {
 						header,
 						total_difficulty,
 						chts,
+						epoch_transition,
 					}));
 				},
 				None => {
@@ -753,6 +859,20 @@ impl HeaderChain {
 		}
 	}
 
+	/// Get the root of the nth CHT's bloom trie, if it's been computed.
+	///
+	/// Bloom tries are only computed for CHTs produced by this node, so
+	/// those covered by hardcoded sync have none.
+	pub fn bloom_root(&self, n: usize) -> Option<H256> {
+		match self.db.get(self.col, bloom_key(n as u64).as_bytes()) {
+			Ok(db_fetch) => db_fetch.map(|bytes| ::rlp::decode(&bytes).expect("decoding value from db failed")),
+			Err(e) => {
+				warn!(target: "chain", "Error reading from database: {}", e);
+				None
+			}
+		}
+	}
+
 	/// Get the genesis hash.
 	pub fn genesis_hash(&self) -> H256 {
 		self.genesis_header.hash()
@@ -778,9 +898,50 @@ impl HeaderChain {
 		}
 	}
 
+	/// Delete the headers of eras more than `keep` blocks behind the best block,
+	/// at most `max` eras at a time. Returns the number of eras pruned.
+	///
+	/// Hashes and total difficulties of the pruned blocks are kept until they are
+	/// covered by a CHT, and so are the headers of epoch transitions.
+	pub fn prune_headers(&self, keep: u64, max: usize) -> Result<usize, Error> {
+		self.enable_header_pruning();
+
+		let candidates = self.candidates.read();
+		let live_proofs = self.live_epoch_proofs.read();
+		let mut pruned_until = self.pruned_until.lock();
+
+		let until = self.best_block.read().number.saturating_sub(keep);
+		if *pruned_until >= until { return Ok(0) }
+
+		let mut batch = self.db.transaction();
+		let mut pruned = 0;
+		for (&num, entry) in candidates.range(*pruned_until..until).take(max) {
+			for candidate in &entry.candidates {
+				if !live_proofs.contains_key(&candidate.hash) {
+					batch.delete(self.col, &candidate.hash);
+				}
+			}
+
+			*pruned_until = num + 1;
+			pruned += 1;
+		}
+
+		self.db.write(batch)?;
+		Ok(pruned)
+	}
+
+	/// Note that headers of old eras are pruned, including in earlier runs,
+	/// so that pruned candidates are still reported as known.
+	pub fn enable_header_pruning(&self) {
+		self.header_pruning.store(true, AtomicOrdering::Relaxed);
+	}
+
 	/// Get block status.
 	pub fn status(&self, hash: &H256) -> BlockStatus {
-		match self.db.get(self.col, &*hash).ok().map_or(false, |x| x.is_some()) {
+		let known = self.db.get(self.col, &*hash).ok().map_or(false, |x| x.is_some())
+			|| (self.header_pruning.load(AtomicOrdering::Relaxed) && self.candidate_numbers.read().contains_key(hash));
+
+		match known {
 			true => BlockStatus::InChain,
 			false => BlockStatus::Unknown,
 		}
@@ -808,17 +969,29 @@ impl HeaderChain {
 	/// or transitions to.
 	/// This will give the epoch that any children of this parent belong to.
 	///
-	/// The header corresponding the the parent hash must be stored already.
+	/// The parent must be stored already.
 	pub fn epoch_transition_for(&self, parent_hash: H256) -> Option<(Header, Vec<u8>)> {
-		// slow path: loop back block by block
+		// slow path: loop back block by block, following the candidates
+		// since the headers may have been pruned.
+		let candidates = self.candidates.read();
 		let live_proofs = self.live_epoch_proofs.read();
 
-		for hdr in self.ancestry_iter(BlockId::Hash(parent_hash)) {
-			if let Some(transition) = live_proofs.get(&hdr.hash()).cloned() {
-				return hdr.decode().map(|decoded_hdr| {
-					(decoded_hdr, transition.proof)
-				}).ok();
+		let parent_num = self.block_header(BlockId::Hash(parent_hash))
+			.map(|hdr| hdr.number())
+			.or_else(|| self.candidate_numbers.read().get(&parent_hash).cloned());
+
+		let mut next = parent_num.map(|num| (num, parent_hash));
+		while let Some((num, hash)) = next.take() {
+			if let Some(transition) = live_proofs.get(&hash) {
+				// headers of live transitions are never pruned.
+				return self.block_header(BlockId::Hash(hash))
+					.and_then(|hdr| hdr.decode().ok())
+					.map(|decoded_hdr| (decoded_hdr, transition.proof.clone()));
 			}
+
+			next = candidates.get(&num)
+				.and_then(|entry| entry.candidates.iter().find(|c| c.hash == hash))
+				.map(|c| (num - 1, c.parent_hash));
 		}
 
 		// any blocks left must be descendants of the last canonical transition block.
@@ -837,11 +1010,47 @@ impl HeaderChain {
 			}
 		}
 	}
+
+	/// Get all stored epoch transitions along with their proofs and whether they
+	/// are on the canonical chain: the last canonical transition, followed by
+	/// the transitions of live blocks ordered by number.
+	pub fn epoch_transitions(&self) -> Vec<(Header, Vec<u8>, bool)> {
+		let mut transitions = Vec::new();
+
+		match self.db.get(self.col, LAST_CANONICAL_TRANSITION) {
+			Ok(Some(x)) => {
+				let (hdr, proof) = decode_canonical_transition(&x)
+					.expect("last canonical transition always encoded correctly; qed");
+
+				transitions.push((hdr, proof.to_vec(), true));
+			}
+			Ok(None) => {}
+			Err(e) => warn!("Error reading from DB: {}", e),
+		}
+
+		let live_proofs: Vec<_> = self.live_epoch_proofs.read().iter()
+			.map(|(hash, transition)| (*hash, transition.proof.clone()))
+			.collect();
+
+		let mut live: Vec<_> = live_proofs.into_iter()
+			.filter_map(|(hash, proof)| self.block_header(BlockId::Hash(hash))
+				.and_then(|hdr| hdr.decode().ok())
+				.map(|decoded_hdr| (decoded_hdr, proof)))
+			.map(|(hdr, proof)| {
+				let canonical = self.block_hash(BlockId::Number(hdr.number())) == Some(hdr.hash());
+				(hdr, proof, canonical)
+			})
+			.collect();
+
+		live.sort_by_key(|&(ref hdr, _, canonical)| (hdr.number(), !canonical));
+		transitions.extend(live);
+		transitions
+	}
 }
 
 impl HeapSizeOf for HeaderChain {
 	fn heap_size_of_children(&self) -> usize {
-		self.candidates.read().heap_size_of_children()
+		self.candidates.read().heap_size_of_children() + self.candidate_numbers.read().heap_size_of_children()
 	}
 }
 
@@ -869,7 +1078,8 @@ mod tests {
 	use super::{HeaderChain, HardcodedSync};
 	use std::sync::Arc;
 
-	use ethereum_types::U256;
+	use ethereum_types::{H256, U256};
+	use ethcore::block_status::BlockStatus;
 	use ethcore::ids::BlockId;
 	use ethcore::header::Header;
 	use ethcore::spec::Spec;
@@ -884,6 +1094,36 @@ mod tests {
 		Arc::new(kvdb_memorydb::create(0))
 	}
 
+	// import blocks `1..until` on top of the genesis, with an epoch transition at #3.
+	// returns the hash of the last block.
+	fn import_with_transition(chain: &HeaderChain, db: &Arc<KeyValueDB>, genesis_header: &Header, until: u64) -> H256 {
+		let mut parent_hash = genesis_header.hash();
+		let mut rolling_timestamp = genesis_header.timestamp();
+		for i in 1..until {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(i);
+			header.set_timestamp(rolling_timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * i as u32);
+			parent_hash = header.hash();
+
+			let mut tx = db.transaction();
+			let epoch_proof = if i == 3 {
+				Some(vec![1, 2, 3, 4])
+			} else {
+				None
+			};
+
+			let pending = chain.insert(&mut tx, header, epoch_proof).unwrap();
+			db.write(tx).unwrap();
+			chain.apply_pending(pending);
+
+			rolling_timestamp += 10;
+		}
+
+		parent_hash
+	}
+
 	#[test]
 	fn basic_chain() {
 		let spec = Spec::new_test();
@@ -916,6 +1156,11 @@ mod tests {
 		assert!(chain.block_header(BlockId::Number(9000)).is_some());
 		assert!(chain.cht_root(2).is_some());
 		assert!(chain.cht_root(3).is_none());
+
+		// every header in the chain has an empty log bloom.
+		let empty_blooms = ::std::iter::repeat(Default::default()).take(::cht::SIZE as usize);
+		assert_eq!(chain.bloom_root(2), ::cht::compute_bloom_root(2, empty_blooms));
+		assert!(chain.bloom_root(3).is_none());
 	}
 
 	#[test]
@@ -1189,6 +1434,58 @@ mod tests {
 		assert_eq!(chain.epoch_transition_for(parent_hash).unwrap().1, vec![1, 2, 3, 4]);
 	}
 
+	#[test]
+	fn lists_epoch_transitions() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let chain = HeaderChain::new(db.clone(), None, &spec, cache, HardcodedSync::Allow).unwrap();
+
+		let mut parent_hash = genesis_header.hash();
+		let mut rolling_timestamp = genesis_header.timestamp();
+		for i in 1..6 {
+			let mut header = Header::new();
+			header.set_parent_hash(parent_hash);
+			header.set_number(i);
+			header.set_timestamp(rolling_timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * i as u32);
+			parent_hash = header.hash();
+
+			let mut tx = db.transaction();
+			let pending = chain.insert(&mut tx, header, if i == 3 { Some(vec![1, 2, 3, 4]) } else { None }).unwrap();
+			db.write(tx).unwrap();
+			chain.apply_pending(pending);
+
+			rolling_timestamp += 10;
+		}
+
+		// a competing transition at the same height.
+		{
+			let mut header = Header::new();
+			header.set_parent_hash(chain.block_hash(BlockId::Number(2)).unwrap());
+			header.set_number(3);
+			header.set_timestamp(rolling_timestamp);
+			header.set_difficulty(*genesis_header.difficulty() * 3u32);
+
+			let mut tx = db.transaction();
+			let pending = chain.insert(&mut tx, header, Some(vec![5, 6])).unwrap();
+			db.write(tx).unwrap();
+			chain.apply_pending(pending);
+		}
+
+		let transitions: Vec<_> = chain.epoch_transitions().into_iter()
+			.map(|(hdr, proof, canonical)| (hdr.number(), proof, canonical))
+			.collect();
+
+		assert_eq!(transitions, vec![
+			(0, Vec::new(), true),
+			(3, vec![1, 2, 3, 4], true),
+			(3, vec![5, 6], false),
+		]);
+	}
+
 	#[test]
 	fn hardcoded_sync_gen() {
 		let spec = Spec::new_test();
@@ -1229,4 +1526,74 @@ mod tests {
 		let decoded: Header = hardcoded_sync.header.decode().expect("decoding failed");
 		assert_eq!(decoded.number(), h_num);
 	}
+
+	#[test]
+	fn start_from_checkpoint() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let chain = HeaderChain::new(db.clone(), None, &spec, cache.clone(), HardcodedSync::Allow).unwrap();
+		import_with_transition(&chain, &db, &genesis_header, 10000);
+
+		let checkpoint = chain.read_hardcoded_sync().unwrap().unwrap();
+		let transition_number = checkpoint.epoch_transition.as_ref()
+			.map(|&(ref header, ref proof)| (header.number(), proof.clone()));
+		assert_eq!(transition_number, Some((3, vec![1, 2, 3, 4])));
+
+		// a chain started from the checkpoint continues in its epoch.
+		let fresh_db = make_db();
+		let fresh = HeaderChain::new(fresh_db, None, &spec, cache.clone(), HardcodedSync::Checkpoint(checkpoint.clone())).unwrap();
+		let best = fresh.best_block();
+
+		assert_eq!(best.number, 3 * ::cht::SIZE + 1);
+		assert_eq!(fresh.cht_root(2), chain.cht_root(2));
+		assert!(fresh.bloom_root(2).is_none());
+		assert_eq!(fresh.epoch_transition_for(best.hash).unwrap().1, vec![1, 2, 3, 4]);
+
+		// checkpoints which don't directly follow their CHTs are rejected.
+		let mut missing_cht = checkpoint;
+		missing_cht.chts.pop();
+		assert!(HeaderChain::new(make_db(), None, &spec, cache, HardcodedSync::Checkpoint(missing_cht)).is_err());
+	}
+
+	#[test]
+	fn prune_headers() {
+		let spec = Spec::new_test();
+		let genesis_header = spec.genesis_header();
+		let db = make_db();
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+
+		let chain = HeaderChain::new(db.clone(), None, &spec, cache.clone(), HardcodedSync::Allow).unwrap();
+		let best_hash = import_with_transition(&chain, &db, &genesis_header, 300);
+
+		// best is #299: eras up to #198 are pruned, in batches.
+		assert_eq!(chain.prune_headers(100, 150).unwrap(), 150);
+		assert_eq!(chain.prune_headers(100, 150).unwrap(), 48);
+		assert_eq!(chain.prune_headers(100, 150).unwrap(), 0);
+
+		assert!(chain.block_header(BlockId::Number(198)).is_none());
+		assert!(chain.block_header(BlockId::Number(199)).is_some());
+		assert!(chain.block_hash(BlockId::Number(198)).is_some());
+		assert!(chain.score(BlockId::Number(198)).is_some());
+
+		// the transition is kept and still found.
+		assert!(chain.block_header(BlockId::Number(3)).is_some());
+		assert_eq!(chain.epoch_transition_for(best_hash).unwrap().1, vec![1, 2, 3, 4]);
+		let pruned_hash = chain.block_hash(BlockId::Number(100)).unwrap();
+		assert_eq!(chain.epoch_transition_for(pruned_hash).unwrap().1, vec![1, 2, 3, 4]);
+
+		// pruned blocks are still known.
+		assert_eq!(chain.status(&pruned_hash), BlockStatus::InChain);
+		assert_eq!(chain.status(&H256::from(1)), BlockStatus::Unknown);
+
+		// also after a restart, once pruning is enabled.
+		let chain = HeaderChain::new(db.clone(), None, &spec, cache, HardcodedSync::Allow).unwrap();
+		assert_eq!(chain.status(&pruned_hash), BlockStatus::Unknown);
+		chain.enable_header_pruning();
+		assert_eq!(chain.status(&pruned_hash), BlockStatus::InChain);
+		assert_eq!(chain.status(&H256::from(1)), BlockStatus::Unknown);
+	}
 }
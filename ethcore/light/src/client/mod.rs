@@ -46,6 +46,13 @@ pub use self::service::Service;
 mod header_chain;
 mod service;
 
+/// Minimum number of recent eras to keep the headers of when pruning.
+/// Importing blocks and detecting epoch transitions may require recent ancestors.
+pub const MIN_HEADER_HISTORY: u64 = 256;
+
+// maximum number of eras to prune the headers of at once.
+const PRUNE_HEADERS_BATCH: usize = 512;
+
 pub mod fetch;
 
 /// Configuration for the light client.
@@ -61,6 +68,12 @@ pub struct Config {
 	pub check_seal: bool,
 	/// Disable hardcoded sync.
 	pub no_hardcoded_sync: bool,
+	/// Checkpoint to start from instead of the spec's hardcoded sync.
+	/// Only applied when the database is empty.
+	pub checkpoint: Option<SpecHardcodedSync>,
+	/// Number of recent eras to keep the headers of, at least `MIN_HEADER_HISTORY`.
+	/// `None` keeps all headers until they are covered by a CHT.
+	pub header_history: Option<u64>,
 }
 
 impl Default for Config {
@@ -71,6 +84,8 @@ impl Default for Config {
 			verify_full: true,
 			check_seal: true,
 			no_hardcoded_sync: false,
+			checkpoint: None,
+			header_history: None,
 		}
 	}
 }
@@ -127,6 +142,12 @@ pub trait LightChainClient: Send + Sync {
 	/// Get the `i`th CHT root.
 	fn cht_root(&self, i: usize) -> Option<H256>;
 
+	/// Get the root of the `i`th CHT's bloom trie.
+	fn bloom_root(&self, i: usize) -> Option<H256>;
+
+	/// Replay all stored epoch transition proofs through the engine.
+	fn verify_epoch_transitions(&self) -> Vec<EpochTransitionStatus>;
+
 	/// Get the EIP-86 transition block number.
 	fn eip86_transition(&self) -> BlockNumber;
 
@@ -134,6 +155,31 @@ pub trait LightChainClient: Send + Sync {
 	fn report(&self) -> ClientReport;
 }
 
+/// Status of a stored epoch transition proof after replaying it through the engine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EpochProofStatus {
+	/// The proof is valid and the transition was checked against the previous epoch.
+	Verified,
+	/// The proof is valid, but the previous epoch is no longer stored so the
+	/// transition couldn't be checked against it.
+	Unconfirmed,
+	/// The proof or the transition failed verification.
+	Invalid(String),
+}
+
+/// A stored epoch transition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochTransitionStatus {
+	/// Hash of the transition block.
+	pub block_hash: H256,
+	/// Number of the transition block.
+	pub block_number: BlockNumber,
+	/// Whether the transition block is on the canonical chain.
+	pub canonical: bool,
+	/// Status of the transition proof.
+	pub status: EpochProofStatus,
+}
+
 /// An actor listening to light chain events.
 pub trait LightChainNotify: Send + Sync {
 	/// Notifies about imported headers.
@@ -166,6 +212,7 @@ pub struct Client<T> {
 	listeners: RwLock<Vec<Weak<LightChainNotify>>>,
 	fetcher: T,
 	verify_full: bool,
+	header_history: Option<u64>,
 }
 
 impl<T: ChainDataFetcher> Client<T> {
@@ -183,8 +230,16 @@ impl<T: ChainDataFetcher> Client<T> {
 			queue: HeaderQueue::new(config.queue, spec.engine.clone(), io_channel, config.check_seal),
 			engine: spec.engine.clone(),
 			chain: {
-				let hs_cfg = if config.no_hardcoded_sync { HardcodedSync::Deny } else { HardcodedSync::Allow };
-				HeaderChain::new(db.clone(), chain_col, &spec, cache, hs_cfg)?
+				let hs_cfg = match config.checkpoint {
+					Some(checkpoint) => HardcodedSync::Checkpoint(checkpoint),
+					None if config.no_hardcoded_sync => HardcodedSync::Deny,
+					None => HardcodedSync::Allow,
+				};
+				let chain = HeaderChain::new(db.clone(), chain_col, &spec, cache, hs_cfg)?;
+				if config.header_history.is_some() {
+					chain.enable_header_pruning();
+				}
+				chain
 			},
 			report: RwLock::new(ClientReport::default()),
 			import_lock: Mutex::new(()),
@@ -192,6 +247,7 @@ impl<T: ChainDataFetcher> Client<T> {
 			listeners: RwLock::new(vec![]),
 			fetcher: fetcher,
 			verify_full: config.verify_full,
+			header_history: config.header_history.map(|n| ::std::cmp::max(n, MIN_HEADER_HISTORY)),
 		})
 	}
 
@@ -288,6 +344,93 @@ impl<T: ChainDataFetcher> Client<T> {
 		self.chain.cht_root(i)
 	}
 
+	/// Get the root of the `i`th CHT's bloom trie.
+	pub fn bloom_root(&self, i: usize) -> Option<H256> {
+		self.chain.bloom_root(i)
+	}
+
+	/// Replay all stored epoch transition proofs through the engine's epoch verifier.
+	///
+	/// Transitions are checked in order: each transition block must be sealed
+	/// under the epoch preceding it and any finality proof must be accepted by
+	/// that epoch.
+	pub fn verify_epoch_transitions(&self) -> Vec<EpochTransitionStatus> {
+		use ethcore::engines::ConstructedVerifier;
+
+		// verifiers of canonical epochs, by transition block number.
+		let mut verifiers: Vec<(BlockNumber, Box<epoch::EpochVerifier<EthereumMachine>>)> = Vec::new();
+		let mut statuses = Vec::new();
+
+		for (header, proof, canonical) in self.chain.epoch_transitions() {
+			let number = header.number();
+			let (status, verifier) = {
+				let previous = verifiers.iter().rev()
+					.find(|&&(n, _)| n < number)
+					.map(|&(_, ref v)| v);
+
+				// the transition block itself is sealed under the previous epoch.
+				let sealed = match previous {
+					Some(prev) => prev.verify_light(&header).map(|_| true),
+					None => Ok(number == 0),
+				};
+
+				match (sealed, self.engine.epoch_verifier(&header, &proof)) {
+					(Err(e), _) => (EpochProofStatus::Invalid(format!("Bad transition block: {}", e)), None),
+					(_, ConstructedVerifier::Err(e)) => (EpochProofStatus::Invalid(format!("Bad epoch proof: {}", e)), None),
+					(Ok(_), ConstructedVerifier::Unconfirmed(_, finality_proof, hash))
+						if previous.map_or(false, |prev| prev.check_finality_proof(finality_proof)
+							.map_or(true, |hashes| !hashes.contains(&hash))) =>
+					{
+						(EpochProofStatus::Invalid("Finality proof not accepted by the previous epoch".into()), None)
+					}
+					(Ok(sealed), ConstructedVerifier::Trusted(v)) | (Ok(sealed), ConstructedVerifier::Unconfirmed(v, _, _)) => {
+						let status = if sealed { EpochProofStatus::Verified } else { EpochProofStatus::Unconfirmed };
+						(status, Some(v))
+					}
+				}
+			};
+
+			if let EpochProofStatus::Invalid(ref e) = status {
+				warn!(target: "client", "Invalid epoch transition at #{} ({}): {}", number, header.hash(), e);
+			}
+
+			if let (true, Some(v)) = (canonical, verifier) {
+				verifiers.push((number, v));
+			}
+
+			statuses.push(EpochTransitionStatus {
+				block_hash: header.hash(),
+				block_number: number,
+				canonical: canonical,
+				status: status,
+			});
+		}
+
+		statuses
+	}
+
+	/// Prune a batch of headers older than the configured history, if any.
+	/// Returns the number of eras pruned.
+	pub fn prune_headers(&self) -> usize {
+		let keep = match self.header_history {
+			Some(keep) => keep,
+			None => return 0,
+		};
+
+		match self.chain.prune_headers(keep, PRUNE_HEADERS_BATCH) {
+			Ok(pruned) => {
+				if pruned != 0 {
+					debug!(target: "client", "Pruned headers of {} eras", pruned);
+				}
+				pruned
+			}
+			Err(e) => {
+				warn!(target: "client", "Error pruning headers: {}", e);
+				0
+			}
+		}
+	}
+
 	/// Import a set of pre-verified headers from the queue.
 	pub fn import_verified(&self) {
 		const MAX: usize = 256;
@@ -586,6 +729,14 @@ impl<T: ChainDataFetcher> LightChainClient for Client<T> {
 		Client::cht_root(self, i)
 	}
 
+	fn bloom_root(&self, i: usize) -> Option<H256> {
+		Client::bloom_root(self, i)
+	}
+
+	fn verify_epoch_transitions(&self) -> Vec<EpochTransitionStatus> {
+		Client::verify_epoch_transitions(self)
+	}
+
 	fn eip86_transition(&self) -> BlockNumber {
 		self.engine().params().eip86_transition
 	}
@@ -626,3 +777,174 @@ impl<T: ChainDataFetcher> ::ethcore::client::EngineClient for Client<T> {
 		Client::block_header(self, id)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{Client, Config, EpochProofStatus};
+
+	use std::sync::Arc;
+	use std::time::Duration;
+
+	use cache::Cache;
+	use client::fetch;
+	use ethcore::encoded;
+	use ethcore::header::Header;
+	use ethcore::log_entry::LogEntry;
+	use ethcore::receipt::{Receipt, TransactionOutcome};
+	use ethcore::spec::{Spec, SpecHardcodedSync};
+	use ethcore::types::metalogs::MetaLogs;
+	use ethereum_types::{Address, H256, H520, U256};
+	use ethkey::{self, KeyPair};
+	use hash::keccak;
+	use io::IoChannel;
+	use kvdb_memorydb;
+	use parking_lot::Mutex;
+	use rlp::RlpStream;
+
+	fn keypair(seed: &str) -> KeyPair {
+		KeyPair::from_secret(keccak(seed).into()).unwrap()
+	}
+
+	fn new_client(spec: &Spec, checkpoint: Option<SpecHardcodedSync>) -> Result<Client<fetch::Unavailable>, ::ethcore::error::Error> {
+		let cache = Arc::new(Mutex::new(Cache::new(Default::default(), Duration::from_secs(6 * 3600))));
+		let config = Config { checkpoint: checkpoint, ..Default::default() };
+		let db = Arc::new(kvdb_memorydb::create(0));
+
+		Client::new(config, db, None, spec, fetch::unavailable(), IoChannel::disconnected(), cache)
+	}
+
+	fn child_of(parent: &Header, number: u64) -> Header {
+		let mut header = Header::new();
+		header.set_parent_hash(parent.hash());
+		header.set_number(number);
+		header.set_difficulty(*parent.difficulty());
+		header.set_timestamp(parent.timestamp() + 10);
+		header
+	}
+
+	// seal the header the way `BasicAuthority` does.
+	fn seal(header: &mut Header, signer: &KeyPair) {
+		header.set_author(signer.address());
+		let signature = ethkey::sign(signer.secret(), &header.bare_hash()).unwrap();
+		header.set_seal(vec![::rlp::encode(&H520::from(signature)).into_vec()]);
+	}
+
+	// make the header's receipts signal a change of the validator contract's set
+	// and return the epoch proof of that change.
+	fn signal_change(header: &mut Header, signer: &KeyPair, new_set: &[Address]) -> Vec<u8> {
+		let mut data = H256::from(U256::from(32)).to_vec();
+		data.extend_from_slice(&H256::from(U256::from(new_set.len())));
+		for address in new_set {
+			let mut word = H256::zero();
+			word[12..].copy_from_slice(address);
+			data.extend_from_slice(&word);
+		}
+
+		let log = LogEntry {
+			address: Address::from(5),
+			topics: vec![keccak("InitiateChange(bytes32,address[])"), *header.parent_hash()],
+			data: data,
+		};
+		let receipts = vec![Receipt::new(TransactionOutcome::StatusCode(1), U256::zero(), U256::zero(), vec![log], MetaLogs::new())];
+
+		header.set_receipts_root(::triehash::ordered_trie_root(receipts.iter().map(::rlp::encode)));
+		seal(header, signer);
+
+		let mut proof = RlpStream::new_list(2);
+		proof.append(&*header).append_list(&receipts);
+		proof.out()
+	}
+
+	fn checkpoint(header: &Header, transition: Option<(&Header, Vec<u8>)>) -> SpecHardcodedSync {
+		SpecHardcodedSync {
+			header: encoded::Header::new(::rlp::encode(header).into_vec()),
+			total_difficulty: *header.difficulty(),
+			chts: vec![H256::default()],
+			epoch_transition: transition.map(|(header, proof)| (encoded::Header::new(::rlp::encode(header).into_vec()), proof)),
+		}
+	}
+
+	#[test]
+	fn verifies_epoch_transitions() {
+		let spec = Spec::new_validator_safe_contract();
+		let client = new_client(&spec, None).unwrap();
+		let genesis = spec.genesis_header();
+
+		// #1 isn't sealed by a validator of the genesis epoch.
+		let mut bad_seal = child_of(&genesis, 1);
+		let proof = signal_change(&mut bad_seal, &keypair("2"), &[keypair("1").address()]);
+
+		// #2 is sealed properly, but its proof is garbage.
+		let mut bad_proof = child_of(&bad_seal, 2);
+		seal(&mut bad_proof, &keypair("0"));
+
+		for (header, proof) in vec![(bad_seal, proof), (bad_proof, vec![1, 2, 3])] {
+			let mut tx = client.db.transaction();
+			let pending = client.chain.insert(&mut tx, header, Some(proof)).unwrap();
+			client.db.write(tx).unwrap();
+			client.chain.apply_pending(pending);
+		}
+
+		let statuses = client.verify_epoch_transitions();
+		assert_eq!(statuses.len(), 3);
+
+		// the genesis proof is checked against the genesis state.
+		assert_eq!(statuses[0].block_number, 0);
+		assert_eq!(statuses[0].status, EpochProofStatus::Verified);
+
+		assert_eq!(statuses[1].block_number, 1);
+		match statuses[1].status {
+			EpochProofStatus::Invalid(ref e) => assert!(e.starts_with("Bad transition block"), "{}", e),
+			ref status => panic!("unexpected status: {:?}", status),
+		}
+
+		assert_eq!(statuses[2].block_number, 2);
+		match statuses[2].status {
+			EpochProofStatus::Invalid(ref e) => assert!(e.starts_with("Bad epoch proof"), "{}", e),
+			ref status => panic!("unexpected status: {:?}", status),
+		}
+
+		// a client started from a checkpoint doesn't have the epoch preceding
+		// the checkpoint's transition.
+		let mut transition = child_of(&genesis, 1);
+		let proof = signal_change(&mut transition, &keypair("0"), &[keypair("1").address()]);
+
+		let mut header = child_of(&transition, ::cht::start_number(1));
+		seal(&mut header, &keypair("1"));
+
+		let client = new_client(&spec, Some(checkpoint(&header, Some((&transition, proof))))).unwrap();
+
+		let statuses = client.verify_epoch_transitions();
+		assert_eq!(statuses.len(), 1);
+		assert_eq!(statuses[0].block_hash, transition.hash());
+		assert_eq!(statuses[0].status, EpochProofStatus::Unconfirmed);
+	}
+
+	#[test]
+	fn rejects_badly_sealed_checkpoints() {
+		let spec = Spec::new_validator_safe_contract();
+		let genesis = spec.genesis_header();
+		let number = ::cht::start_number(1);
+
+		// checkpoints in the genesis epoch must be sealed by one of its validators.
+		let mut header = child_of(&genesis, number);
+		seal(&mut header, &keypair("0"));
+		assert!(new_client(&spec, Some(checkpoint(&header, None))).is_ok());
+
+		seal(&mut header, &keypair("2"));
+		assert!(new_client(&spec, Some(checkpoint(&header, None))).is_err());
+
+		// the validator set changes at #1.
+		let mut transition = child_of(&genesis, 1);
+		let proof = signal_change(&mut transition, &keypair("0"), &[keypair("1").address()]);
+
+		let mut header = child_of(&transition, number);
+		seal(&mut header, &keypair("1"));
+		assert!(new_client(&spec, Some(checkpoint(&header, Some((&transition, proof.clone()))))).is_ok());
+		assert!(new_client(&spec, Some(checkpoint(&header, Some((&transition, vec![1, 2, 3]))))).is_err());
+
+		// sealed by a validator of the previous epoch.
+		seal(&mut header, &keypair("0"));
+		assert!(new_client(&spec, Some(checkpoint(&header, Some((&transition, proof))))).is_err());
+	}
+}
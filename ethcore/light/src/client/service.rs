@@ -15,16 +15,18 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Minimal IO service for light client.
-//! Just handles block import messages and passes them to the client.
+//! Just handles block import messages and passes them to the client,
+//! periodically prunes old headers and stores the order of use of cached data.
 
 use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ethcore::client::ClientIoMessage;
 use ethcore::db;
 use ethcore::error::Error as CoreError;
 use ethcore::spec::Spec;
-use io::{IoContext, IoError, IoHandler, IoService};
+use io::{IoContext, IoError, IoHandler, IoService, TimerToken};
 use kvdb::KeyValueDB;
 
 use cache::Cache;
@@ -74,10 +76,10 @@ impl<T: ChainDataFetcher> Service<T> {
 			spec,
 			fetcher,
 			io_service.channel(),
-			cache,
+			cache.clone(),
 		)?);
 
-		io_service.register_handler(Arc::new(ImportBlocks(client.clone()))).map_err(Error::Io)?;
+		io_service.register_handler(Arc::new(ImportBlocks(client.clone(), cache))).map_err(Error::Io)?;
 		spec.engine.register_client(Arc::downgrade(&client) as _);
 
 		Ok(Service {
@@ -102,9 +104,31 @@ impl<T: ChainDataFetcher> Service<T> {
 	}
 }
 
-struct ImportBlocks<T>(Arc<Client<T>>);
+const PRUNE_HEADERS_TIMER: TimerToken = 0;
+const PRUNE_HEADERS_INTERVAL: Duration = Duration::from_secs(10);
+const FLUSH_CACHE_TIMER: TimerToken = 1;
+const FLUSH_CACHE_INTERVAL: Duration = Duration::from_secs(60);
+
+struct ImportBlocks<T>(Arc<Client<T>>, Arc<Mutex<Cache>>);
 
 impl<T: ChainDataFetcher> IoHandler<ClientIoMessage> for ImportBlocks<T> {
+	fn initialize(&self, io: &IoContext<ClientIoMessage>) {
+		if let Err(e) = io.register_timer(PRUNE_HEADERS_TIMER, PRUNE_HEADERS_INTERVAL) {
+			warn!(target: "client", "Error registering header pruning timer: {}", e);
+		}
+		if let Err(e) = io.register_timer(FLUSH_CACHE_TIMER, FLUSH_CACHE_INTERVAL) {
+			warn!(target: "client", "Error registering cache flushing timer: {}", e);
+		}
+	}
+
+	fn timeout(&self, _io: &IoContext<ClientIoMessage>, timer: TimerToken) {
+		match timer {
+			PRUNE_HEADERS_TIMER => self.0.prune_headers(),
+			FLUSH_CACHE_TIMER => self.1.lock().flush_persistent(),
+			_ => {},
+		}
+	}
+
 	fn message(&self, _io: &IoContext<ClientIoMessage>, message: &ClientIoMessage) {
 		if let ClientIoMessage::BlockVerified = *message {
 			self.0.import_verified();
@@ -83,6 +83,8 @@ extern crate memory_cache;
 #[macro_use]
 extern crate error_chain;
 
+#[cfg(test)]
+extern crate ethkey;
 #[cfg(test)]
 extern crate kvdb_memorydb;
 #[cfg(test)]
@@ -16,11 +16,11 @@
 
 //! I/O and event context generalizations.
 
-use network::{NetworkContext, PeerId, NodeId};
+use network::{NetworkContext, PeerId, NodeId, PeerBehaviour};
 
 use super::{Announcement, LightProtocol, ReqId};
 use super::error::Error;
-use request::NetworkRequests as Requests;
+use request::{Request, NetworkRequests as Requests};
 
 /// An I/O context which allows sending and receiving packets as well as
 /// disconnecting peers. This is used as a generalization of the portions
@@ -39,11 +39,17 @@ pub trait IoContext {
 	/// Disable a peer -- this is a disconnect + a time-out.
 	fn disable_peer(&self, peer: PeerId);
 
+	/// Report behaviour of a peer, affecting its reputation.
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour);
+
 	/// Get a peer's protocol version.
 	fn protocol_version(&self, peer: PeerId) -> Option<u8>;
 
 	/// Persistent peer id
 	fn persistent_peer_id(&self, peer: PeerId) -> Option<NodeId>;
+
+	/// Whether the peer is reserved.
+	fn is_reserved_peer(&self, peer: PeerId) -> bool;
 }
 
 
@@ -70,6 +76,11 @@ impl<T> IoContext for T where T: ?Sized + NetworkContext {
 		NetworkContext::disable_peer(self, peer);
 	}
 
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour) {
+		trace!(target: "pip", "Reporting {:?} of peer {}", behaviour, peer);
+		NetworkContext::report_peer(self, peer, behaviour);
+	}
+
 	fn protocol_version(&self, peer: PeerId) -> Option<u8> {
 		self.protocol_version(self.subprotocol_name(), peer)
 	}
@@ -77,6 +88,10 @@ impl<T> IoContext for T where T: ?Sized + NetworkContext {
 	fn persistent_peer_id(&self, peer: PeerId) -> Option<NodeId> {
 		self.session_info(peer).and_then(|info| info.id)
 	}
+
+	fn is_reserved_peer(&self, peer: PeerId) -> bool {
+		NetworkContext::is_reserved_peer(self, peer)
+	}
 }
 
 /// Basic context for the protocol.
@@ -92,6 +107,9 @@ pub trait BasicContext {
 	/// with an event.
 	fn request_from(&self, peer: PeerId, request: Requests) -> Result<ReqId, Error>;
 
+	/// Whether the peer has enough credits left to be asked for these requests.
+	fn can_afford(&self, peer: PeerId, requests: &[Request]) -> bool;
+
 	/// Make an announcement of new capabilities to the rest of the peers.
 	// TODO: maybe just put this on a timer in LightProtocol?
 	fn make_announcement(&self, announcement: Announcement);
@@ -101,6 +119,9 @@ pub trait BasicContext {
 
 	/// Disable a peer.
 	fn disable_peer(&self, peer: PeerId);
+
+	/// Report behaviour of a peer, affecting its reputation.
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour);
 }
 
 /// Context for a protocol event which has a peer ID attached.
@@ -130,6 +151,10 @@ impl<'a> BasicContext for TickCtx<'a> {
 		self.proto.request_from(self.io, &peer, requests)
 	}
 
+	fn can_afford(&self, peer: PeerId, requests: &[Request]) -> bool {
+		self.proto.can_afford(&peer, requests)
+	}
+
 	fn make_announcement(&self, announcement: Announcement) {
 		self.proto.make_announcement(self.io, announcement);
 	}
@@ -141,6 +166,10 @@ impl<'a> BasicContext for TickCtx<'a> {
 	fn disable_peer(&self, peer: PeerId) {
 		self.io.disable_peer(peer);
 	}
+
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour) {
+		self.io.report_peer(peer, behaviour);
+	}
 }
 
 /// Concrete implementation of `EventContext` over the light protocol struct and
@@ -163,6 +192,10 @@ impl<'a> BasicContext for Ctx<'a> {
 		self.proto.request_from(self.io, &peer, requests)
 	}
 
+	fn can_afford(&self, peer: PeerId, requests: &[Request]) -> bool {
+		self.proto.can_afford(&peer, requests)
+	}
+
 	fn make_announcement(&self, announcement: Announcement) {
 		self.proto.make_announcement(self.io, announcement);
 	}
@@ -174,6 +207,10 @@ impl<'a> BasicContext for Ctx<'a> {
 	fn disable_peer(&self, peer: PeerId) {
 		self.io.disable_peer(peer);
 	}
+
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour) {
+		self.io.report_peer(peer, behaviour);
+	}
 }
 
 impl<'a> EventContext for Ctx<'a> {
@@ -19,11 +19,12 @@
 
 use std::fmt;
 use {rlp, network};
+use network::PeerBehaviour;
 
 /// Levels of punishment.
 ///
-/// Currently just encompasses two different kinds of disconnect and
-/// no punishment, but this is where reputation systems might come into play.
+/// Encompasses two different kinds of disconnect and no punishment.
+/// Lasting consequences are handled by the peer reputation, see `Error::behaviour`.
 // In ascending order
 #[derive(Debug, PartialEq, Eq)]
 pub enum Punishment {
@@ -88,6 +89,26 @@ impl Error {
 			Error::RejectedByHandlers => Punishment::Disconnect,
 		}
 	}
+
+	/// Peer behaviour indicated by this error, if it should affect the peer reputation.
+	pub fn behaviour(&self) -> Option<PeerBehaviour> {
+		match *self {
+			Error::Rlp(_) => Some(PeerBehaviour::BadProtocol),
+			Error::Network(_) => None,
+			Error::NoCredits => Some(PeerBehaviour::Spam),
+			Error::UnrecognizedPacket(_) => Some(PeerBehaviour::BadProtocol),
+			Error::UnexpectedHandshake => Some(PeerBehaviour::BadProtocol),
+			Error::WrongNetwork => None,
+			Error::UnknownPeer => None,
+			Error::UnsolicitedResponse => Some(PeerBehaviour::Spam),
+			Error::BadBackReference => Some(PeerBehaviour::InvalidData),
+			Error::NotServer => None,
+			Error::UnsupportedProtocolVersion(_) => None,
+			Error::BadProtocolVersion => Some(PeerBehaviour::BadProtocol),
+			Error::Overburdened => None,
+			Error::RejectedByHandlers => None,
+		}
+	}
 }
 
 impl From<rlp::DecoderError> for Error {
@@ -63,6 +63,7 @@ fn hardcoded_serve_time(kind: Kind) -> Duration {
 		Kind::Code => 1_500_000,
 		Kind::Execution => 250, // per gas.
 		Kind::Signal => 500_000,
+		Kind::BloomGroups => 50_000, // per group.
 	})
 }
 
@@ -103,6 +104,7 @@ impl LoadDistribution {
 		let n = match *req {
 			CompleteRequest::Headers(ref req) => req.max,
 			CompleteRequest::Execution(ref req) => req.gas.low_u64(),
+			CompleteRequest::BloomGroups(ref req) => req.count,
 			_ => 1,
 		};
 
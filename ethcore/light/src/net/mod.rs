@@ -21,13 +21,14 @@
 use transaction::UnverifiedTransaction;
 
 use io::TimerToken;
-use network::{HostInfo, NetworkProtocolHandler, NetworkContext, PeerId};
+use network::{HostInfo, NetworkProtocolHandler, NetworkContext, PeerId, PeerBehaviour};
 use rlp::{RlpStream, Rlp};
 use ethereum_types::{H256, U256};
 use kvdb::DBValue;
 use parking_lot::{Mutex, RwLock};
 use std::time::{Duration, Instant};
 
+use std::cell::Cell;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
@@ -42,6 +43,7 @@ use self::context::{Ctx, TickCtx};
 use self::error::Punishment;
 use self::load_timer::{LoadDistribution, NullStore};
 use self::request_set::RequestSet;
+use self::serving_budget::ServingMeter;
 use self::id_guard::IdGuard;
 
 mod context;
@@ -49,6 +51,7 @@ mod error;
 mod load_timer;
 mod status;
 mod request_set;
+mod serving_budget;
 
 #[cfg(test)]
 mod tests;
@@ -59,6 +62,7 @@ pub use self::context::{BasicContext, EventContext, IoContext};
 pub use self::error::Error;
 pub use self::load_timer::{SampleStore, FileStore};
 pub use self::status::{Status, Capabilities, Announcement};
+pub use self::serving_budget::{ServingBudget, ServingLoad, PeerServingLoad};
 
 const TIMEOUT: TimerToken = 0;
 const TIMEOUT_INTERVAL: Duration = Duration::from_secs(1);
@@ -75,6 +79,9 @@ const RECALCULATE_COSTS_INTERVAL: Duration = Duration::from_secs(60 * 60);
 /// Max number of transactions in a single packet.
 const MAX_TRANSACTIONS_TO_PROPAGATE: usize = 64;
 
+// minimum time before re-sending a transaction which is still ready to the same peer.
+const TRANSACTION_REBROADCAST_INTERVAL: Duration = Duration::from_secs(60 * 5);
+
 // minimum interval between updates.
 const UPDATE_INTERVAL: Duration = Duration::from_millis(5000);
 
@@ -82,12 +89,14 @@ const UPDATE_INTERVAL: Duration = Duration::from_millis(5000);
 const PACKET_COUNT_V1: u8 = 9;
 
 /// Supported protocol versions.
+/// Version 2 adds requests for ranges of headers proved from a CHT.
 pub const PROTOCOL_VERSIONS: &'static [(u8, u8)] = &[
 	(1, PACKET_COUNT_V1),
+	(2, PACKET_COUNT_V1),
 ];
 
 /// Max protocol version.
-pub const MAX_PROTOCOL_VERSION: u8 = 1;
+pub const MAX_PROTOCOL_VERSION: u8 = 2;
 
 
 // packet ID definitions.
@@ -131,6 +140,7 @@ mod timeout {
 	pub const HEADER_PROOF: u64 = 100;
 	pub const TRANSACTION_PROOF: u64 = 1000; // per gas?
 	pub const EPOCH_SIGNAL: u64 = 200;
+	pub const BLOOM_GROUPS: u64 = 250;
 }
 
 /// A request id.
@@ -166,10 +176,12 @@ pub struct Peer {
 	last_update: Instant,
 	pending_requests: RequestSet,
 	failed_requests: Vec<ReqId>,
-	propagated_transactions: HashSet<H256>,
+	propagated_transactions: HashMap<H256, Instant>,
 	skip_update: bool,
 	local_flow: Arc<FlowParams>,
 	awaiting_acknowledge: Option<(Instant, Arc<FlowParams>)>,
+	reserved: bool, // reserved peers aren't limited by serving budgets.
+	serving_meter: ServingMeter,
 }
 
 /// Whether or not a peer was kept by a handler
@@ -264,6 +276,10 @@ pub struct Config {
 	pub max_stored_seconds: u64,
 	/// How much of the total load capacity each peer should be allowed to take.
 	pub load_share: f64,
+	/// Resources which may be spent serving all peers each second.
+	pub total_budget: ServingBudget,
+	/// Resources which may be spent serving each non-reserved peer each second.
+	pub peer_budget: ServingBudget,
 }
 
 impl Default for Config {
@@ -274,6 +290,8 @@ impl Default for Config {
 		Config {
 			max_stored_seconds: MAX_ACCUMULATED,
 			load_share: LOAD_SHARE,
+			total_budget: ServingBudget::default(),
+			peer_budget: ServingBudget::default(),
 		}
 	}
 }
@@ -364,6 +382,7 @@ pub struct LightProtocol {
 	req_id: AtomicUsize,
 	sample_store: Box<SampleStore>,
 	load_distribution: LoadDistribution,
+	serving_meter: Mutex<ServingMeter>,
 }
 
 impl LightProtocol {
@@ -393,6 +412,7 @@ impl LightProtocol {
 			req_id: AtomicUsize::new(0),
 			sample_store: sample_store,
 			load_distribution: load_distribution,
+			serving_meter: Mutex::new(ServingMeter::new(Instant::now())),
 		}
 	}
 
@@ -412,6 +432,38 @@ impl LightProtocol {
 		)
 	}
 
+	/// Get the current load of serving requests and the request credits of each peer.
+	pub fn serving_load(&self, io: &IoContext) -> ServingLoad {
+		let now = Instant::now();
+		let (time, bytes) = self.serving_meter.lock().last(now);
+		let peers = self.peers.read().iter()
+			.map(|(peer_id, peer)| {
+				let mut peer = peer.lock();
+				let mut credits = peer.local_credits.clone();
+				peer.local_flow.recharge(&mut credits);
+				let (time, bytes) = peer.serving_meter.last(now);
+
+				PeerServingLoad {
+					peer: *peer_id,
+					node_id: io.persistent_peer_id(*peer_id),
+					reserved: peer.reserved,
+					time: time,
+					bytes: bytes,
+					credits: credits.current(),
+					credits_limit: *peer.local_flow.limit(),
+				}
+			})
+			.collect();
+
+		ServingLoad {
+			time: time,
+			bytes: bytes,
+			total_budget: self.config.total_budget,
+			peer_budget: self.config.peer_budget,
+			peers: peers,
+		}
+	}
+
 	/// Make a request to a peer.
 	///
 	/// Fails on: nonexistent peer, network error, peer not server,
@@ -427,6 +479,12 @@ impl LightProtocol {
 
 		let mut peer = peer.lock();
 		let peer = &mut *peer;
+
+		let protocol_version = peer.status.protocol_version as u8;
+		if requests.requests().iter().any(|req| req.kind().min_protocol_version() > protocol_version) {
+			return Err(Error::NotServer);
+		}
+
 		match peer.remote_flow {
 			None => Err(Error::NotServer),
 			Some((ref mut creds, ref params)) => {
@@ -461,6 +519,22 @@ impl LightProtocol {
 		}
 	}
 
+	/// Whether the credits the peer last reported cover the cost of requesting
+	/// the given requests from it.
+	pub fn can_afford(&self, peer_id: &PeerId, requests: &[Request]) -> bool {
+		let peers = self.peers.read();
+		let peer = match peers.get(peer_id) {
+			Some(peer) => peer.lock(),
+			None => return false,
+		};
+
+		match peer.remote_flow {
+			Some((ref creds, ref params)) => params.compute_cost_multi(requests)
+				.map_or(false, |cost| cost <= creds.current()),
+			None => false,
+		}
+	}
+
 	/// Make an announcement of new chain head and capabilities to all peers.
 	/// The announcement is expected to be valid.
 	pub fn make_announcement(&self, io: &IoContext, mut announcement: Announcement) {
@@ -633,6 +707,7 @@ impl LightProtocol {
 				let peer = peer.lock();
 				if peer.pending_requests.check_timeout(now) {
 					debug!(target: "pip", "Peer {} request timeout", peer_id);
+					io.report_peer(*peer_id, PeerBehaviour::Timeout);
 					io.disconnect_peer(*peer_id);
 				}
 
@@ -658,21 +733,28 @@ impl LightProtocol {
 
 		let all_transaction_hashes: HashSet<_> = ready_transactions.iter().map(|tx| tx.hash()).collect();
 		let mut buf = Vec::new();
+		let now = Instant::now();
 
 		let peers = self.peers.read();
 		for (peer_id, peer_info) in peers.iter() {
 			let mut peer_info = peer_info.lock();
 			if !peer_info.capabilities.tx_relay { continue }
 
+			// forget transactions which are no longer ready.
 			let prop_filter = &mut peer_info.propagated_transactions;
-			*prop_filter = &*prop_filter & &all_transaction_hashes;
+			prop_filter.retain(|hash, _| all_transaction_hashes.contains(hash));
 
-			// fill the buffer with all non-propagated transactions.
-			let to_propagate = ready_transactions.iter()
-				.filter(|tx| prop_filter.insert(tx.hash()))
-				.map(|tx| &tx.transaction);
+			// fill the buffer with all transactions not propagated recently.
+			for tx in &ready_transactions {
+				let hash = tx.hash();
+				let due = prop_filter.get(&hash)
+					.map_or(true, |sent| now.duration_since(*sent) >= TRANSACTION_REBROADCAST_INTERVAL);
 
-			buf.extend(to_propagate);
+				if due {
+					prop_filter.insert(hash, now);
+					buf.push(&tx.transaction);
+				}
+			}
 
 			// propagate to the given peer.
 			if buf.is_empty() { continue }
@@ -781,18 +863,18 @@ impl LightProtocol {
 		let peers = self.peers.read();
 		let now = Instant::now();
 
-		let packet_body = {
+		let packet_body = |protocol_version: u8| {
 			let mut stream = RlpStream::new_list(3);
 			stream.append(new_params.limit())
 				.append(new_params.recharge_rate())
-				.append(new_params.cost_table());
+				.append(&new_params.cost_table().for_protocol_version(protocol_version));
 			stream.out()
 		};
 
 		for (peer_id, peer_info) in peers.iter() {
 			let mut peer_info = peer_info.lock();
 
-			io.send(*peer_id, packet::UPDATE_CREDITS, packet_body.clone());
+			io.send(*peer_id, packet::UPDATE_CREDITS, packet_body(peer_info.status.protocol_version as u8));
 			peer_info.awaiting_acknowledge = Some((now.clone(), new_params.clone()));
 		}
 	}
@@ -835,10 +917,12 @@ impl LightProtocol {
 			last_update: pending.last_update,
 			pending_requests: RequestSet::default(),
 			failed_requests: Vec::new(),
-			propagated_transactions: HashSet::new(),
+			propagated_transactions: HashMap::new(),
 			skip_update: false,
 			local_flow: local_flow,
 			awaiting_acknowledge: None,
+			reserved: io.is_reserved_peer(*peer),
+			serving_meter: ServingMeter::new(Instant::now()),
 		}));
 
 		let any_kept = self.handlers.iter().map(
@@ -929,12 +1013,37 @@ impl LightProtocol {
 
 		trace!(target: "pip", "Received requests (id: {}) from peer {}", req_id, peer_id);
 
+		// reserved peers are served regardless of budgets, but count towards the total load.
+		let now = Instant::now();
+		let budget = match peer.reserved {
+			true => ServingBudget::default(),
+			false => {
+				let total = self.serving_meter.lock().remaining(&self.config.total_budget, now);
+				peer.serving_meter.remaining(&self.config.peer_budget, now).min(&total)
+			}
+		};
+
+		if budget.is_exhausted() {
+			// respond without charging, so the peer can ask someone else.
+			trace!(target: "pip", "Serving budget exhausted, not responding to requests (id: {}) from peer {}", req_id, peer_id);
+			io.respond(packet::RESPONSE, {
+				let mut stream = RlpStream::new_list(3);
+				peer.local_flow.recharge(&mut peer.local_credits);
+				stream.append(&req_id).append(&peer.local_credits.current()).begin_list(0);
+				stream.out()
+			});
+			return Ok(())
+		}
+
 		// deserialize requests, check costs and request validity.
 		peer.local_flow.recharge(&mut peer.local_credits);
 
 		peer.local_credits.deduct_cost(peer.local_flow.base_cost())?;
 		for request_rlp in raw.at(1)?.iter().take(MAX_REQUESTS) {
 			let request: Request = request_rlp.as_val()?;
+			if request.kind().min_protocol_version() > peer.status.protocol_version as u8 {
+				return Err(Error::BadProtocolVersion);
+			}
 			let cost = peer.local_flow.compute_cost(&request).ok_or(Error::NotServer)?;
 			peer.local_credits.deduct_cost(cost)?;
 			request_builder.push(request).map_err(|_| Error::BadBackReference)?;
@@ -944,10 +1053,16 @@ impl LightProtocol {
 		let num_requests = requests.requests().len();
 		trace!(target: "pip", "Beginning to respond to requests (id: {}) from peer {}", req_id, peer_id);
 
-		// respond to all requests until one fails.
+		// respond to all requests until one fails or the time budget is spent.
+		let serve_time = Cell::new(Duration::from_secs(0));
 		let responses = requests.respond_to_all(|complete_req| {
+			if budget.time.map_or(false, |time| serve_time.get() >= time) {
+				return None
+			}
+
+			let start = Instant::now();
 			let _timer = self.load_distribution.begin_timer(&complete_req);
-			match complete_req {
+			let response = match complete_req {
 				CompleteRequest::Headers(req) => self.provider.block_headers(req).map(Response::Headers),
 				CompleteRequest::HeaderProof(req) => self.provider.header_proof(req).map(Response::HeaderProof),
 				CompleteRequest::TransactionIndex(req) => self.provider.transaction_index(req).map(Response::TransactionIndex),
@@ -958,18 +1073,28 @@ impl LightProtocol {
 				CompleteRequest::Code(req) => self.provider.contract_code(req).map(Response::Code),
 				CompleteRequest::Execution(req) => self.provider.transaction_proof(req).map(Response::Execution),
 				CompleteRequest::Signal(req) => self.provider.epoch_signal(req).map(Response::Signal),
-			}
+				CompleteRequest::BloomGroups(req) => self.provider.bloom_groups(req).map(Response::BloomGroups),
+			};
+			serve_time.set(serve_time.get() + start.elapsed());
+			response
 		});
 
 		trace!(target: "pip", "Responded to {}/{} requests in packet {}", responses.len(), num_requests, req_id);
 		trace!(target: "pip", "Peer {} has {} credits remaining.", peer_id, peer.local_credits.current());
 
-		io.respond(packet::RESPONSE, {
+		let body = {
 			let mut stream = RlpStream::new_list(3);
 			let cur_credits = peer.local_credits.current();
 			stream.append(&req_id).append(&cur_credits).append_list(&responses);
 			stream.out()
-		});
+		};
+
+		let now = Instant::now();
+		let bytes = body.len() as u64;
+		peer.serving_meter.record(serve_time.get(), bytes, now);
+		self.serving_meter.lock().record(serve_time.get(), bytes, now);
+
+		io.respond(packet::RESPONSE, body);
 		Ok(())
 	}
 
@@ -1071,6 +1196,9 @@ impl LightProtocol {
 
 // if something went wrong, figure out how much to punish the peer.
 fn punish(peer: PeerId, io: &IoContext, e: Error) {
+	if let Some(behaviour) = e.behaviour() {
+		io.report_peer(peer, behaviour);
+	}
 	match e.punishment() {
 		Punishment::None => {}
 		Punishment::Disconnect => {
@@ -93,6 +93,7 @@ pub struct CostTable {
 	header_proof: Option<U256>,
 	transaction_proof: Option<U256>, // cost per gas.
 	epoch_signal: Option<U256>,
+	bloom_groups: Option<U256>, // cost per group
 }
 
 impl CostTable {
@@ -111,10 +112,20 @@ impl CostTable {
 			incr_if_set(&self.header_proof);
 			incr_if_set(&self.transaction_proof);
 			incr_if_set(&self.epoch_signal);
+			incr_if_set(&self.bloom_groups);
 		}
 
 		num_set
 	}
+
+	/// The costs of the requests known to peers using the given protocol version.
+	pub fn for_protocol_version(&self, version: u8) -> CostTable {
+		let mut table = self.clone();
+		if version < request::Kind::BloomGroups.min_protocol_version() {
+			table.bloom_groups = None;
+		}
+		table
+	}
 }
 
 impl Default for CostTable {
@@ -132,6 +143,7 @@ impl Default for CostTable {
 			header_proof: Some(15000.into()),
 			transaction_proof: Some(2.into()),
 			epoch_signal: Some(10000.into()),
+			bloom_groups: Some(1000.into()),
 		}
 	}
 }
@@ -158,6 +170,7 @@ impl Encodable for CostTable {
 		append_cost(s, &self.header_proof, request::Kind::HeaderProof);
 		append_cost(s, &self.transaction_proof, request::Kind::Execution);
 		append_cost(s, &self.epoch_signal, request::Kind::Signal);
+		append_cost(s, &self.bloom_groups, request::Kind::BloomGroups);
 	}
 }
 
@@ -175,10 +188,17 @@ impl Decodable for CostTable {
 		let mut header_proof = None;
 		let mut transaction_proof = None;
 		let mut epoch_signal = None;
+		let mut bloom_groups = None;
 
 		for cost_list in rlp.iter().skip(1) {
+			// skip costs of request kinds unknown to us.
+			let kind = match cost_list.val_at(0) {
+				Ok(kind) => kind,
+				Err(_) => continue,
+			};
+
 			let cost = cost_list.val_at(1)?;
-			match cost_list.val_at(0)? {
+			match kind {
 				request::Kind::Headers => headers = Some(cost),
 				request::Kind::TransactionIndex => transaction_index = Some(cost),
 				request::Kind::Body => body = Some(cost),
@@ -189,6 +209,7 @@ impl Decodable for CostTable {
 				request::Kind::HeaderProof => header_proof = Some(cost),
 				request::Kind::Execution => transaction_proof = Some(cost),
 				request::Kind::Signal => epoch_signal = Some(cost),
+				request::Kind::BloomGroups => bloom_groups = Some(cost),
 			}
 		}
 
@@ -204,6 +225,7 @@ impl Decodable for CostTable {
 			header_proof: header_proof,
 			transaction_proof: transaction_proof,
 			epoch_signal: epoch_signal,
+			bloom_groups: bloom_groups,
 		};
 
 		if table.costs_set() == 0 {
@@ -280,6 +302,7 @@ impl FlowParams {
 			header_proof: cost_for_kind(Kind::HeaderProof),
 			transaction_proof: cost_for_kind(Kind::Execution),
 			epoch_signal: cost_for_kind(Kind::Signal),
+			bloom_groups: cost_for_kind(Kind::BloomGroups),
 		};
 
 		FlowParams {
@@ -306,7 +329,8 @@ impl FlowParams {
 				code: free_cost.clone(),
 				header_proof: free_cost.clone(),
 				transaction_proof: free_cost.clone(),
-				epoch_signal: free_cost,
+				epoch_signal: free_cost.clone(),
+				bloom_groups: free_cost,
 			}
 		}
 	}
@@ -337,6 +361,7 @@ impl FlowParams {
 			Request::Code(_) => self.costs.code,
 			Request::Execution(ref req) => self.costs.transaction_proof.map(|c| c * req.gas),
 			Request::Signal(_) => self.costs.epoch_signal,
+			Request::BloomGroups(ref req) => self.costs.bloom_groups.map(|c| c * U256::from(req.count)),
 		}
 	}
 
@@ -412,6 +437,28 @@ mod tests {
 		assert_eq!(costs, new_costs);
 	}
 
+	#[test]
+	fn skips_unknown_request_kinds() {
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&100u64);
+		stream.begin_list(2).append(&0u8).append(&10u64);
+		stream.begin_list(2).append(&255u8).append(&10u64);
+
+		let costs: CostTable = ::rlp::decode(&stream.out()).unwrap();
+		assert_eq!(costs.headers, Some(10.into()));
+		assert_eq!(costs.costs_set(), 1);
+	}
+
+	#[test]
+	fn leaves_out_costs_unknown_to_protocol_version() {
+		let costs = CostTable::default();
+
+		let v1_costs = costs.for_protocol_version(1);
+		assert_eq!(v1_costs.bloom_groups, None);
+		assert_eq!(v1_costs.costs_set(), costs.costs_set() - 1);
+		assert_eq!(costs.for_protocol_version(2), costs);
+	}
+
 	#[test]
 	fn credits_mechanism() {
 		use std::thread;
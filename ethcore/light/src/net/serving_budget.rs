@@ -0,0 +1,199 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Serving budgets.
+//!
+//! Request credits bound the load a single peer may cause on average, but
+//! not the total load of serving many peers at once. Budgets cap the time spent
+//! responding to requests and the number of bytes sent in responses during
+//! each one-second window, both per peer and in total.
+
+use std::cmp;
+use std::time::{Duration, Instant};
+
+use ethereum_types::U256;
+use network::{NodeId, PeerId};
+
+/// Length of a window budgets are applied to.
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Resources which may be spent serving requests each second.
+/// `None` values are unlimited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ServingBudget {
+	/// Time spent responding to requests.
+	pub time: Option<Duration>,
+	/// Bytes sent in responses.
+	pub bytes: Option<u64>,
+}
+
+impl ServingBudget {
+	/// Whether nothing is left in the budget.
+	pub fn is_exhausted(&self) -> bool {
+		self.time.map_or(false, |time| time == Duration::from_secs(0))
+			|| self.bytes.map_or(false, |bytes| bytes == 0)
+	}
+
+	/// The tighter of the two budgets.
+	pub fn min(&self, other: &ServingBudget) -> ServingBudget {
+		fn min_of<T: Ord>(a: Option<T>, b: Option<T>) -> Option<T> {
+			match (a, b) {
+				(Some(a), Some(b)) => Some(cmp::min(a, b)),
+				(a, None) => a,
+				(None, b) => b,
+			}
+		}
+
+		ServingBudget {
+			time: min_of(self.time, other.time),
+			bytes: min_of(self.bytes, other.bytes),
+		}
+	}
+}
+
+/// Measures resources spent serving requests in one-second windows.
+#[derive(Debug, Clone)]
+pub struct ServingMeter {
+	window_start: Instant,
+	time: Duration,
+	bytes: u64,
+	last: (Duration, u64),
+}
+
+impl ServingMeter {
+	/// Create a new meter with nothing spent.
+	pub fn new(now: Instant) -> Self {
+		ServingMeter {
+			window_start: now,
+			time: Duration::from_secs(0),
+			bytes: 0,
+			last: (Duration::from_secs(0), 0),
+		}
+	}
+
+	// begin a new window if the current one is over.
+	fn roll(&mut self, now: Instant) {
+		let elapsed = now.duration_since(self.window_start);
+		if elapsed < WINDOW { return }
+
+		// nothing was spent in the last window if more than one has passed.
+		self.last = match elapsed < WINDOW * 2 {
+			true => (self.time, self.bytes),
+			false => (Duration::from_secs(0), 0),
+		};
+		self.window_start = now;
+		self.time = Duration::from_secs(0);
+		self.bytes = 0;
+	}
+
+	/// What is left of the given budget in the current window.
+	pub fn remaining(&mut self, budget: &ServingBudget, now: Instant) -> ServingBudget {
+		self.roll(now);
+		ServingBudget {
+			time: budget.time.map(|time| time.checked_sub(self.time).unwrap_or_else(|| Duration::from_secs(0))),
+			bytes: budget.bytes.map(|bytes| bytes.saturating_sub(self.bytes)),
+		}
+	}
+
+	/// Record resources spent serving a request.
+	pub fn record(&mut self, time: Duration, bytes: u64, now: Instant) {
+		self.roll(now);
+		self.time += time;
+		self.bytes += bytes;
+	}
+
+	/// Resources spent during the last complete window.
+	pub fn last(&mut self, now: Instant) -> (Duration, u64) {
+		self.roll(now);
+		self.last
+	}
+}
+
+/// Current load of serving a single peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerServingLoad {
+	/// Peer id.
+	pub peer: PeerId,
+	/// Persistent node id, if known.
+	pub node_id: Option<NodeId>,
+	/// Whether the peer is reserved. Reserved peers aren't limited by budgets.
+	pub reserved: bool,
+	/// Time spent responding to the peer's requests during the last second.
+	pub time: Duration,
+	/// Bytes sent to the peer in responses during the last second.
+	pub bytes: u64,
+	/// Request credits the peer has left.
+	pub credits: U256,
+	/// Maximum request credits of the peer.
+	pub credits_limit: U256,
+}
+
+/// Current load of serving light peers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServingLoad {
+	/// Time spent responding to requests during the last second.
+	pub time: Duration,
+	/// Bytes sent in responses during the last second.
+	pub bytes: u64,
+	/// Budget for serving all peers.
+	pub total_budget: ServingBudget,
+	/// Budget for serving each non-reserved peer.
+	pub peer_budget: ServingBudget,
+	/// Load of each connected peer.
+	pub peers: Vec<PeerServingLoad>,
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn unlimited_budget_is_never_exhausted() {
+		let now = Instant::now();
+		let mut meter = ServingMeter::new(now);
+		meter.record(Duration::from_secs(10), 1_000_000, now);
+
+		assert!(!meter.remaining(&ServingBudget::default(), now).is_exhausted());
+	}
+
+	#[test]
+	fn budget_renews_each_window() {
+		let now = Instant::now();
+		let budget = ServingBudget { time: Some(Duration::from_millis(100)), bytes: Some(1000) };
+		let mut meter = ServingMeter::new(now);
+
+		meter.record(Duration::from_millis(40), 1000, now);
+		let remaining = meter.remaining(&budget, now);
+		assert_eq!(remaining.time, Some(Duration::from_millis(60)));
+		assert!(remaining.is_exhausted());
+
+		let next = now + Duration::from_millis(1500);
+		assert_eq!(meter.remaining(&budget, next), budget);
+		assert_eq!(meter.last(next), (Duration::from_millis(40), 1000));
+
+		// an idle window is reported as such.
+		assert_eq!(meter.last(next + Duration::from_secs(5)), (Duration::from_secs(0), 0));
+	}
+
+	#[test]
+	fn tighter_budget() {
+		let a = ServingBudget { time: Some(Duration::from_millis(100)), bytes: None };
+		let b = ServingBudget { time: Some(Duration::from_millis(50)), bytes: Some(10) };
+
+		assert_eq!(a.min(&b), b);
+		assert_eq!(a.min(&ServingBudget::default()), a);
+	}
+}
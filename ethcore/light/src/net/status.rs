@@ -268,7 +268,9 @@ pub fn write_handshake(status: &Status, capabilities: &Capabilities, flow_params
 
 	if let Some(flow_params) = flow_params {
 		pairs.push(encode_pair(Key::BufferLimit, flow_params.limit()));
-		pairs.push(encode_pair(Key::BufferCostTable, flow_params.cost_table()));
+		// peers fail to decode costs of requests unknown to their protocol version.
+		let cost_table = flow_params.cost_table().for_protocol_version(status.protocol_version as u8);
+		pairs.push(encode_pair(Key::BufferCostTable, &cost_table));
 		pairs.push(encode_pair(Key::BufferRechargeRate, flow_params.recharge_rate()));
 	}
 
@@ -379,7 +381,7 @@ mod tests {
 	#[test]
 	fn full_handshake() {
 		let status = Status {
-			protocol_version: 1,
+			protocol_version: 2,
 			network_id: 1,
 			head_td: U256::default(),
 			head_hash: H256::default(),
@@ -414,7 +416,7 @@ mod tests {
 	#[test]
 	fn partial_handshake() {
 		let status = Status {
-			protocol_version: 1,
+			protocol_version: 2,
 			network_id: 1,
 			head_td: U256::default(),
 			head_hash: H256::default(),
@@ -449,7 +451,7 @@ mod tests {
 	#[test]
 	fn skip_unknown_keys() {
 		let status = Status {
-			protocol_version: 1,
+			protocol_version: 2,
 			network_id: 1,
 			head_td: U256::default(),
 			head_hash: H256::default(),
@@ -572,4 +574,28 @@ mod tests {
 		assert_eq!(read_capabilities, capabilities);
 		assert!(read_flow.is_none());
 	}
+
+	#[test]
+	fn handshake_leaves_out_costs_unknown_to_protocol_version() {
+		let status = Status {
+			protocol_version: 1,
+			network_id: 1,
+			head_td: U256::default(),
+			head_hash: H256::default(),
+			head_num: 10,
+			genesis_hash: H256::zero(),
+			last_head: None,
+		};
+
+		let flow_params = FlowParams::new(
+			1_000_000.into(),
+			Default::default(),
+			1000.into(),
+		);
+
+		let handshake = write_handshake(&status, &Capabilities::default(), Some(&flow_params));
+
+		let (_, _, read_flow) = parse_handshake(Rlp::new(&handshake)).unwrap();
+		assert_eq!(read_flow.unwrap().cost_table(), &flow_params.cost_table().for_protocol_version(1));
+	}
 }
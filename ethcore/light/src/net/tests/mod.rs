@@ -21,12 +21,13 @@ use ethcore::blockchain_info::BlockChainInfo;
 use ethcore::client::{EachBlockWith, TestBlockChainClient};
 use ethcore::ids::BlockId;
 use ethcore::encoded;
-use network::{PeerId, NodeId};
+use network::{PeerId, NodeId, PeerBehaviour};
 use transaction::{Action, PendingTransaction};
 
 use net::context::IoContext;
 use net::status::{Capabilities, Status};
-use net::{LightProtocol, Params, packet, Peer};
+use net::{LightProtocol, Params, Config, ServingBudget, packet, Peer};
+use net::serving_budget::ServingMeter;
 use provider::Provider;
 use request;
 use request::*;
@@ -82,6 +83,8 @@ impl IoContext for Expect {
 		assert_eq!(self, &Expect::Punish(peer));
 	}
 
+	fn report_peer(&self, _peer: PeerId, _behaviour: PeerBehaviour) {}
+
 	fn protocol_version(&self, _peer: PeerId) -> Option<u8> {
 		Some(super::MAX_PROTOCOL_VERSION)
 	}
@@ -89,6 +92,45 @@ impl IoContext for Expect {
 	fn persistent_peer_id(&self, _peer: PeerId) -> Option<NodeId> {
 		None
 	}
+
+	fn is_reserved_peer(&self, _peer: PeerId) -> bool {
+		false
+	}
+}
+
+// expected result from a call, for a peer using the first protocol version.
+struct ExpectV1(Expect);
+
+impl IoContext for ExpectV1 {
+	fn send(&self, peer: PeerId, packet_id: u8, packet_body: Vec<u8>) {
+		self.0.send(peer, packet_id, packet_body)
+	}
+
+	fn respond(&self, packet_id: u8, packet_body: Vec<u8>) {
+		self.0.respond(packet_id, packet_body)
+	}
+
+	fn disconnect_peer(&self, peer: PeerId) {
+		self.0.disconnect_peer(peer)
+	}
+
+	fn disable_peer(&self, peer: PeerId) {
+		self.0.disable_peer(peer)
+	}
+
+	fn report_peer(&self, _peer: PeerId, _behaviour: PeerBehaviour) {}
+
+	fn protocol_version(&self, _peer: PeerId) -> Option<u8> {
+		Some(1)
+	}
+
+	fn persistent_peer_id(&self, _peer: PeerId) -> Option<NodeId> {
+		None
+	}
+
+	fn is_reserved_peer(&self, _peer: PeerId) -> bool {
+		false
+	}
 }
 
 // can't implement directly for Arc due to cross-crate orphan rules.
@@ -210,7 +252,7 @@ fn setup(capabilities: Capabilities) -> (Arc<TestProviderInner>, LightProtocol)
 
 fn status(chain_info: BlockChainInfo) -> Status {
 	Status {
-		protocol_version: 1,
+		protocol_version: ::net::MAX_PROTOCOL_VERSION as u32,
 		network_id: 2,
 		head_td: chain_info.total_difficulty,
 		head_hash: chain_info.best_block_hash,
@@ -278,6 +320,34 @@ fn credit_overflow() {
 	proto.handle_packet(&Expect::Punish(1), &1, packet::REQUEST, &request);
 }
 
+#[test]
+fn bloom_groups_requires_v2() {
+	let capabilities = capabilities();
+
+	let (provider, proto) = setup(capabilities.clone());
+
+	let mut status = status(provider.client.chain_info());
+	status.protocol_version = 1;
+
+	{
+		let packet_body = write_handshake(&status, &capabilities, &proto);
+		proto.on_connect(&1, &ExpectV1(Expect::Send(1, packet::STATUS, packet_body)));
+	}
+
+	{
+		let my_status = write_handshake(&status, &capabilities, &proto);
+		proto.handle_packet(&ExpectV1(Expect::Nothing), &1, packet::STATUS, &my_status);
+	}
+
+	let requests = encode_single(Request::BloomGroups(IncompleteBloomGroupsRequest {
+		start: 0,
+		count: 10,
+	}));
+	let request = make_packet(111, &requests);
+
+	proto.handle_packet(&ExpectV1(Expect::Punish(1)), &1, packet::REQUEST, &request);
+}
+
 // test the basic request types -- these just make sure that requests are parsed
 // and sent to the provider correctly as well as testing response formatting.
 
@@ -638,6 +708,58 @@ fn proof_of_execution() {
 	proto.handle_packet(&expected, &1, packet::REQUEST, &request_body);
 }
 
+#[test]
+fn serving_budget_exhausted() {
+	let capabilities = capabilities();
+
+	let provider = Arc::new(TestProviderInner {
+		client: TestBlockChainClient::new(),
+	});
+	let proto = LightProtocol::new(Arc::new(TestProvider(provider.clone())), Params {
+		network_id: 2,
+		config: Config {
+			total_budget: ServingBudget { time: None, bytes: Some(0) },
+			..Default::default()
+		},
+		capabilities: capabilities.clone(),
+		sample_store: None,
+	});
+	let flow_params = proto.flow_params.read().clone();
+
+	let cur_status = status(provider.client.chain_info());
+	let my_status = write_handshake(&cur_status, &capabilities, &proto);
+
+	{
+		let packet_body = write_handshake(&cur_status, &capabilities, &proto);
+		proto.on_connect(&1, &Expect::Send(1, packet::STATUS, packet_body));
+		proto.handle_packet(&Expect::Nothing, &1, packet::STATUS, &my_status);
+	}
+
+	let req_id = 112;
+	let requests = encode_single(Request::Headers(IncompleteHeadersRequest {
+		start: HashOrNumber::Number(0).into(),
+		max: 1,
+		skip: 0,
+		reverse: false,
+	}));
+	let request_body = make_packet(req_id, &requests);
+
+	// nothing is served and nothing is charged.
+	let response = {
+		let mut stream = RlpStream::new_list(3);
+		stream.append(&req_id).append(flow_params.limit()).begin_list(0);
+		stream.out()
+	};
+
+	let expected = Expect::Respond(packet::RESPONSE, response);
+	proto.handle_packet(&expected, &1, packet::REQUEST, &request_body);
+
+	let load = proto.serving_load(&Expect::Nothing);
+	assert_eq!(load.total_budget.bytes, Some(0));
+	assert_eq!(load.peers.len(), 1);
+	assert_eq!(load.peers[0].credits, *flow_params.limit());
+}
+
 #[test]
 fn id_guard() {
 	use super::request_set::RequestSet;
@@ -678,6 +800,8 @@ fn id_guard() {
 		skip_update: false,
 		local_flow: flow_params,
 		awaiting_acknowledge: None,
+		reserved: false,
+		serving_meter: ServingMeter::new(Instant::now()),
 	}));
 
 	// first, malformed responses.
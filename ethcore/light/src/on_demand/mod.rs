@@ -17,17 +17,23 @@
 //! On-demand chain requests over LES. This is a major building block for RPCs.
 //! The request service is implemented using Futures. Higher level request handlers
 //! will take the raw data received here and extract meaningful results from it.
+//!
+//! Requests are dispatched to peers with the fewest recent failures and lowest latency.
+//! Kinds of requests whose responses can't be fully verified may be configured to
+//! require a quorum of peers giving identical responses.
 
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use ethcore::executed::{Executed, ExecutionError};
 
 use futures::{Poll, Future};
+use hash::KECCAK_NULL_RLP;
 use futures::sync::oneshot::{self, Receiver, Canceled};
-use network::PeerId;
+use network::{PeerId, PeerBehaviour};
 use parking_lot::{RwLock, Mutex};
 use rand;
 
@@ -49,13 +55,60 @@ pub mod request;
 /// The result of execution
 pub type ExecutionResult = Result<Executed, ExecutionError>;
 
+// past failures of a peer are halved this often, so peers recover from transient faults.
+const FAILURE_HALF_LIFE: Duration = Duration::from_secs(5 * 60);
+
 // relevant peer info.
 struct Peer {
 	status: Status,
 	capabilities: Capabilities,
+	latency: Option<Duration>, // moving average of response times.
+	failures: usize, // number of invalid, empty or partial responses as of the last one.
+	last_failure: Option<Instant>,
 }
 
 impl Peer {
+	fn new(status: Status, capabilities: Capabilities) -> Self {
+		Peer {
+			status: status,
+			capabilities: capabilities,
+			latency: None,
+			failures: 0,
+			last_failure: None,
+		}
+	}
+
+	// record the time taken to respond to a request.
+	fn note_latency(&mut self, elapsed: Duration) {
+		self.latency = Some(match self.latency {
+			Some(latency) => (latency * 3 + elapsed) / 4,
+			None => elapsed,
+		});
+	}
+
+	// number of past failures, halved for every `FAILURE_HALF_LIFE` since the last one.
+	fn failures(&self) -> usize {
+		match self.last_failure {
+			Some(at) => {
+				let halvings = at.elapsed().as_secs() / FAILURE_HALF_LIFE.as_secs();
+				self.failures.checked_shr(cmp::min(halvings, 64) as u32).unwrap_or(0)
+			}
+			None => 0,
+		}
+	}
+
+	// record an invalid, empty or partial response.
+	fn note_failure(&mut self) {
+		self.failures = self.failures() + 1;
+		self.last_failure = Some(Instant::now());
+	}
+
+	// peers are preferred by fewest recent failures, then by lowest latency.
+	// peers we haven't heard from yet are tried early to learn their latency.
+	fn rank(&self) -> (usize, Duration) {
+		(self.failures(), self.latency.unwrap_or_else(|| Duration::from_secs(0)))
+	}
+
 	// whether this peer can fulfill the necessary capabilities for the given
 	// request.
 	fn can_fulfill(&self, request: &Capabilities) -> bool {
@@ -74,13 +127,72 @@ impl Peer {
 	}
 }
 
+// responses of several peers to the same requests, which must all agree.
+struct Quorum {
+	sender: Option<oneshot::Sender<Vec<Response>>>,
+	size: usize,
+	results: Vec<Vec<Response>>,
+	peers: HashSet<PeerId>,
+}
+
+// where to send the responses to a set of requests.
+enum Sender {
+	Single(oneshot::Sender<Vec<Response>>),
+	Quorum(Arc<Mutex<Quorum>>),
+}
+
+impl Sender {
+	fn is_canceled(&self) -> bool {
+		match *self {
+			Sender::Single(ref sender) => sender.is_canceled(),
+			Sender::Quorum(ref quorum) => quorum.lock().sender.as_ref().map_or(true, |s| s.is_canceled()),
+		}
+	}
+
+	// whether the requests may be dispatched to the given peer.
+	// each member of a quorum must be asked by a different peer.
+	fn can_ask(&self, peer: PeerId) -> bool {
+		match *self {
+			Sender::Single(_) => true,
+			Sender::Quorum(ref quorum) => !quorum.lock().peers.contains(&peer),
+		}
+	}
+
+	fn note_asked(&self, peer: PeerId) {
+		if let Sender::Quorum(ref quorum) = *self {
+			quorum.lock().peers.insert(peer);
+		}
+	}
+
+	fn send(self, responses: Vec<Response>) {
+		match self {
+			Sender::Single(sender) => { let _ = sender.send(responses); }
+			Sender::Quorum(quorum) => {
+				let mut quorum = quorum.lock();
+				quorum.results.push(responses);
+				if quorum.results.len() < quorum.size { return }
+
+				// dropping the sender cancels the request if the peers disagree.
+				let sender = quorum.sender.take();
+				let agreed = quorum.results.iter().all(|r| r == &quorum.results[0]);
+				match (sender, agreed) {
+					(Some(sender), true) => { let _ = sender.send(quorum.results.swap_remove(0)); }
+					(Some(_), false) => debug!(target: "on_demand", "Peers {:?} gave conflicting responses", quorum.peers),
+					(None, _) => {}
+				}
+			}
+		}
+	}
+}
+
 // Attempted request info and sender to put received value.
 struct Pending {
 	requests: basic_request::Batch<CheckedRequest>,
 	net_requests: basic_request::Batch<NetworkRequest>,
 	required_capabilities: Capabilities,
 	responses: Vec<Response>,
-	sender: oneshot::Sender<Vec<Response>>,
+	sender: Sender,
+	dispatched_at: Option<Instant>,
 }
 
 impl Pending {
@@ -98,6 +210,7 @@ impl Pending {
 					// state and increase the likelihood of being able to answer
 					// the next request from cache.
 					self.update_header_refs(idx, &response);
+					self.update_storage_refs(idx, &response);
 					self.fill_unanswered();
 
 					self.responses.push(response);
@@ -127,6 +240,20 @@ impl Pending {
 		}
 	}
 
+	// update storage roots of requests for the storage of an account the given response proved.
+	// a missing account has empty storage.
+	// `idx` is the index of the request the response corresponds to.
+	fn update_storage_refs(&mut self, idx: usize, response: &Response) {
+		if let Response::Account(ref account) = *response {
+			let root = account.as_ref().map_or(KECCAK_NULL_RLP, |acc| acc.storage_root);
+			for r in self.requests.iter_mut().skip(idx + 1) {
+				if r.needs_storage_root() == Some(idx) {
+					r.provide_storage_root(root)
+				}
+			}
+		}
+	}
+
 	// supply a response.
 	fn supply_response(&mut self, cache: &Mutex<Cache>, response: &basic_request::Response)
 		-> Result<(), basic_request::ResponseError<self::request::Error>>
@@ -135,6 +262,7 @@ impl Pending {
 			Ok(response) => {
 				let idx = self.responses.len();
 				self.update_header_refs(idx, &response);
+				self.update_storage_refs(idx, &response);
 				self.responses.push(response);
 				Ok(())
 			}
@@ -145,7 +273,7 @@ impl Pending {
 	// if the requests are complete, send the result and consume self.
 	fn try_complete(self) -> Option<Self> {
 		if self.requests.is_complete() {
-			let _ = self.sender.send(self.responses);
+			self.sender.send(self.responses);
 			None
 		} else {
 			Some(self)
@@ -207,6 +335,8 @@ fn guess_capabilities(requests: &[CheckedRequest]) -> Capabilities {
 			CheckedRequest::TransactionIndex(_, _) => {} // hashes yield no info.
 			CheckedRequest::Signal(_, _) =>
 				caps.serve_headers = true,
+			CheckedRequest::BloomGroups(_, _) =>
+				caps.serve_headers = true,
 			CheckedRequest::Body(ref req, _) => if let Ok(ref hdr) = req.0.as_ref() {
 				update_since(&mut caps.serve_chain_since, hdr.number());
 			},
@@ -219,6 +349,9 @@ fn guess_capabilities(requests: &[CheckedRequest]) -> Capabilities {
 			CheckedRequest::Code(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
 				update_since(&mut caps.serve_state_since, hdr.number());
 			},
+			CheckedRequest::Storage(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
+				update_since(&mut caps.serve_state_since, hdr.number());
+			},
 			CheckedRequest::Execution(ref req, _) => if let Ok(ref hdr) = req.header.as_ref() {
 				update_since(&mut caps.serve_state_since, hdr.number());
 			},
@@ -253,6 +386,7 @@ pub struct OnDemand {
 	peers: RwLock<HashMap<PeerId, Peer>>,
 	in_transit: RwLock<HashMap<ReqId, Pending>>,
 	cache: Arc<Mutex<Cache>>,
+	quorum: HashMap<basic_request::Kind, usize>,
 	no_immediate_dispatch: bool,
 }
 
@@ -264,10 +398,21 @@ impl OnDemand {
 			peers: RwLock::new(HashMap::new()),
 			in_transit: RwLock::new(HashMap::new()),
 			cache: cache,
+			quorum: HashMap::new(),
 			no_immediate_dispatch: false,
 		}
 	}
 
+	/// Require the given number of peers to give identical responses to requests
+	/// of the given kind. Batches containing such requests are dispatched to that
+	/// many different peers, and canceled if their responses differ.
+	pub fn set_quorum(&mut self, kind: basic_request::Kind, peers: usize) {
+		match peers {
+			0 | 1 => { self.quorum.remove(&kind); }
+			_ => { self.quorum.insert(kind, peers); }
+		}
+	}
+
 	// make a test version: this doesn't dispatch pending requests
 	// until you trigger it manually.
 	#[cfg(test)]
@@ -319,6 +464,35 @@ impl OnDemand {
 		let requests = builder.build();
 		let net_requests = requests.clone().map_requests(|req| req.into_net_request());
 		let capabilities = guess_capabilities(requests.requests());
+		let quorum_size = net_requests.requests().iter()
+			.filter_map(|req| self.quorum.get(&req.kind()).cloned())
+			.max();
+
+		let sender = match quorum_size {
+			Some(size) => {
+				let quorum = Arc::new(Mutex::new(Quorum {
+					sender: Some(sender),
+					size: size,
+					results: Vec::with_capacity(size),
+					peers: HashSet::new(),
+				}));
+
+				// members of a quorum are dispatched separately.
+				for _ in 1..size {
+					self.submit_pending(ctx, Pending {
+						requests: requests.clone(),
+						net_requests: net_requests.clone(),
+						required_capabilities: capabilities.clone(),
+						responses: responses.clone(),
+						sender: Sender::Quorum(quorum.clone()),
+						dispatched_at: None,
+					});
+				}
+
+				Sender::Quorum(quorum)
+			}
+			None => Sender::Single(sender),
+		};
 
 		self.submit_pending(ctx, Pending {
 			requests: requests,
@@ -326,6 +500,7 @@ impl OnDemand {
 			required_capabilities: capabilities,
 			responses: responses,
 			sender: sender,
+			dispatched_at: None,
 		});
 
 		Ok(receiver)
@@ -362,22 +537,28 @@ impl OnDemand {
 		// iterate over all pending requests, and check them for hang-up.
 		// then, try and find a peer who can serve it.
 		let peers = self.peers.read();
+
+		// peers are tried in order of preference, equally ranked ones in random order.
+		let num_peers = peers.len();
+		let rng = rand::random::<usize>() % cmp::max(num_peers, 1);
+		let mut ranked: Vec<_> = peers.iter().chain(peers.iter()).skip(rng).take(num_peers).collect();
+		ranked.sort_by_key(|&(_, peer)| peer.rank());
+
 		*pending = ::std::mem::replace(&mut *pending, Vec::new()).into_iter()
 			.filter(|pending| !pending.sender.is_canceled())
-			.filter_map(|pending| {
-				// the peer we dispatch to is chosen randomly
-				let num_peers = peers.len();
-				let rng = rand::random::<usize>() % cmp::max(num_peers, 1);
-				for (peer_id, peer) in peers.iter().chain(peers.iter()).skip(rng).take(num_peers) {
+			.filter_map(|mut pending| {
+				for &(peer_id, peer) in &ranked {
 					// TODO: see which requests can be answered by the cache?
 
-					if !peer.can_fulfill(&pending.required_capabilities) {
+					if !peer.can_fulfill(&pending.required_capabilities) || !pending.sender.can_ask(*peer_id) {
 						continue
 					}
 
 					match ctx.request_from(*peer_id, pending.net_requests.clone()) {
 						Ok(req_id) => {
 							trace!(target: "on_demand", "Dispatched request {} to peer {}", req_id, peer_id);
+							pending.sender.note_asked(*peer_id);
+							pending.dispatched_at = Some(Instant::now());
 							self.in_transit.write().insert(req_id, pending);
 							return None
 						}
@@ -418,7 +599,7 @@ impl Handler for OnDemand {
 	) -> PeerStatus {
 		self.peers.write().insert(
 			ctx.peer(),
-			Peer { status: status.clone(), capabilities: capabilities.clone() }
+			Peer::new(status.clone(), capabilities.clone())
 		);
 		self.attempt_dispatch(ctx.as_basic());
 		PeerStatus::Kept
@@ -463,15 +644,34 @@ impl Handler for OnDemand {
 		//   1. ensure verification data filled.
 		//   2. pending.requests.supply_response
 		//   3. if extracted on-demand response, keep it for later.
+		let mut valid = true;
 		for response in responses {
 			if let Err(e) = pending.supply_response(&*self.cache, response) {
 				let peer = ctx.peer();
 				debug!(target: "on_demand", "Peer {} gave bad response: {:?}", peer, e);
+				ctx.report_peer(peer, PeerBehaviour::InvalidData);
 				ctx.disable_peer(peer);
+				valid = false;
 
 				break;
 			}
 		}
+		if valid && !responses.is_empty() {
+			ctx.report_peer(ctx.peer(), PeerBehaviour::UsefulResponse);
+		}
+
+		if let Some(peer) = self.peers.write().get_mut(&ctx.peer()) {
+			if let Some(dispatched_at) = pending.dispatched_at.take() {
+				peer.note_latency(dispatched_at.elapsed());
+			}
+
+			// responses cut short because the peer ran out of credits
+			// for the rest of the requests aren't the peer's fault.
+			let unanswered = pending.net_requests.requests().get(responses.len()..).unwrap_or(&[]);
+			if !valid || (!unanswered.is_empty() && ctx.can_afford(ctx.peer(), unanswered)) {
+				peer.note_failure();
+			}
+		}
 
 		pending.fill_unanswered();
 		self.submit_pending(ctx.as_basic(), pending);
@@ -20,10 +20,13 @@ use std::sync::Arc;
 
 use ethcore::basic_account::BasicAccount;
 use ethcore::encoded;
+use ethcore::client::CallAnalytics;
 use ethcore::engines::{EthEngine, StateDependentProof};
+use ethcore::executive::TransactOptions;
 use ethcore::machine::EthereumMachine;
 use ethcore::receipt::Receipt;
 use ethcore::state::{self, ProvedExecution};
+use ethcore::trace::{self, FlatTrace, VMTrace};
 use transaction::SignedTransaction;
 use vm::EnvInfo;
 use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY, KECCAK_EMPTY_LIST_RLP, keccak};
@@ -31,7 +34,7 @@ use hash::{KECCAK_NULL_RLP, KECCAK_EMPTY, KECCAK_EMPTY_LIST_RLP, keccak};
 use request::{self as net_request, IncompleteRequest, CompleteRequest, Output, OutputKind, Field};
 
 use rlp::{RlpStream, Rlp};
-use ethereum_types::{H256, U256, Address};
+use ethereum_types::{H256, U256, Address, Bloom};
 use parking_lot::Mutex;
 use hashdb::HashDB;
 use kvdb::DBValue;
@@ -58,10 +61,14 @@ pub enum Request {
 	Account(Account),
 	/// A request for a contract's code.
 	Code(Code),
+	/// A request for a storage value.
+	Storage(Storage),
 	/// A request for proof of execution.
 	Execution(TransactionProof),
 	/// A request for epoch change signal.
 	Signal(Signal),
+	/// A request for the headers of a range of blocks covered by a CHT.
+	BloomGroups(BloomGroups),
 }
 
 /// A request argument.
@@ -142,8 +149,10 @@ impl_single!(Receipts, BlockReceipts, Vec<Receipt>);
 impl_single!(Body, Body, encoded::Block);
 impl_single!(Account, Account, Option<BasicAccount>);
 impl_single!(Code, Code, Bytes);
+impl_single!(Storage, Storage, H256);
 impl_single!(Execution, TransactionProof, super::ExecutionResult);
 impl_single!(Signal, Signal, Vec<u8>);
+impl_single!(BloomGroups, BloomGroups, Vec<Bloom>);
 
 macro_rules! impl_args {
 	() => {
@@ -252,8 +261,10 @@ pub enum CheckedRequest {
 	Body(Body, net_request::IncompleteBodyRequest),
 	Account(Account, net_request::IncompleteAccountRequest),
 	Code(Code, net_request::IncompleteCodeRequest),
+	Storage(Storage, net_request::IncompleteStorageRequest),
 	Execution(TransactionProof, net_request::IncompleteExecutionRequest),
-	Signal(Signal, net_request::IncompleteSignalRequest)
+	Signal(Signal, net_request::IncompleteSignalRequest),
+	BloomGroups(BloomGroups, net_request::IncompleteBloomGroupsRequest),
 }
 
 impl From<Request> for CheckedRequest {
@@ -306,6 +317,14 @@ impl From<Request> for CheckedRequest {
 				};
 				CheckedRequest::Code(req, net_req)
 			}
+			Request::Storage(req) => {
+				let net_req = net_request::IncompleteStorageRequest {
+					block_hash: req.header.field(),
+					address_hash: ::hash::keccak(&req.address).into(),
+					key_hash: ::hash::keccak(&req.key).into(),
+				};
+				CheckedRequest::Storage(req, net_req)
+			}
 			Request::Execution(req) => {
 				let net_req = net_request::IncompleteExecutionRequest {
 					block_hash: req.header.field(),
@@ -324,6 +343,13 @@ impl From<Request> for CheckedRequest {
 				};
 				CheckedRequest::Signal(req, net_req)
 			}
+			Request::BloomGroups(req) => {
+				let net_req = net_request::IncompleteBloomGroupsRequest {
+					start: req.start(),
+					count: req.count(),
+				};
+				CheckedRequest::BloomGroups(req, net_req)
+			}
 		}
 	}
 }
@@ -341,8 +367,10 @@ impl CheckedRequest {
 			CheckedRequest::Body(_, req) => NetRequest::Body(req),
 			CheckedRequest::Account(_, req) => NetRequest::Account(req),
 			CheckedRequest::Code(_, req) => NetRequest::Code(req),
+			CheckedRequest::Storage(_, req) => NetRequest::Storage(req),
 			CheckedRequest::Execution(_, req) => NetRequest::Execution(req),
 			CheckedRequest::Signal(_, req) => NetRequest::Signal(req),
+			CheckedRequest::BloomGroups(_, req) => NetRequest::BloomGroups(req),
 		}
 	}
 
@@ -356,6 +384,7 @@ impl CheckedRequest {
 			CheckedRequest::Body(ref x, _) => x.0.needs_header(),
 			CheckedRequest::Account(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Code(ref x, _) => x.header.needs_header(),
+			CheckedRequest::Storage(ref x, _) => x.header.needs_header(),
 			CheckedRequest::Execution(ref x, _) => x.header.needs_header(),
 			_ => None,
 		}
@@ -370,11 +399,34 @@ impl CheckedRequest {
 			CheckedRequest::Body(ref mut x, _) => x.0 = HeaderRef::Stored(header),
 			CheckedRequest::Account(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Code(ref mut x, _) => x.header = HeaderRef::Stored(header),
+			CheckedRequest::Storage(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			CheckedRequest::Execution(ref mut x, _) => x.header = HeaderRef::Stored(header),
 			_ => {},
 		}
 	}
 
+	/// Whether this request needs the storage root of an account.
+	/// Returns `Some` with the index of the request returning the account if so,
+	/// `None` otherwise.
+	pub fn needs_storage_root(&self) -> Option<usize> {
+		match *self {
+			CheckedRequest::Storage(ref x, _) => match x.storage_root {
+				Field::BackReference(idx, _) => Some(idx),
+				Field::Scalar(_) => None,
+			},
+			_ => None,
+		}
+	}
+
+	/// Provide the storage root where one was needed. Should only be called if
+	/// `needs_storage_root` returns `Some`, and for correctness, only use the storage root
+	/// of the account proved by the correct request.
+	pub fn provide_storage_root(&mut self, root: H256) {
+		if let CheckedRequest::Storage(ref mut x, _) = *self {
+			x.storage_root = Field::Scalar(root);
+		}
+	}
+
 	/// Attempt to complete the request based on data in the cache.
 	pub fn respond_local(&self, cache: &Mutex<::cache::Cache>) -> Option<Response> {
 		match *self {
@@ -442,11 +494,25 @@ impl CheckedRequest {
 						Response::Body(encoded::Block::new_from_header_and_body(&hdr.view(), &body.view()))
 					})
 			}
+			CheckedRequest::Account(ref check, _) => {
+				check.header.as_ref().ok()
+					.and_then(|hdr| cache.lock().account(&hdr.hash(), &check.address))
+					.map(Response::Account)
+			}
 			CheckedRequest::Code(_, ref req) => {
 				if req.code_hash.as_ref().map_or(false, |&h| h == KECCAK_EMPTY) {
 					Some(Response::Code(Vec::new()))
 				} else {
-					None
+					req.code_hash.as_ref()
+						.and_then(|hash| cache.lock().code(hash))
+						.map(Response::Code)
+				}
+			}
+			CheckedRequest::Storage(ref check, _) => {
+				// empty storage -> every value is zero.
+				match check.storage_root {
+					Field::Scalar(root) if root == KECCAK_NULL_RLP => Some(Response::Storage(H256::zero())),
+					_ => None,
 				}
 			}
 			_ => None,
@@ -464,8 +530,10 @@ macro_rules! match_me {
 			CheckedRequest::Body($check, $req) => $e,
 			CheckedRequest::Account($check, $req) => $e,
 			CheckedRequest::Code($check, $req) => $e,
+			CheckedRequest::Storage($check, $req) => $e,
 			CheckedRequest::Execution($check, $req) => $e,
 			CheckedRequest::Signal($check, $req) => $e,
+			CheckedRequest::BloomGroups($check, $req) => $e,
 		}
 	}
 }
@@ -493,8 +561,18 @@ impl IncompleteRequest for CheckedRequest {
 			CheckedRequest::Body(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Account(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Code(_, ref req) => req.check_outputs(f),
+			CheckedRequest::Storage(ref check, ref req) => {
+				req.check_outputs(&mut f)?;
+
+				// make sure the storage root given is definitively a hash.
+				match check.storage_root {
+					Field::BackReference(r, idx) => f(r, idx, OutputKind::Hash),
+					_ => Ok(()),
+				}
+			}
 			CheckedRequest::Execution(_, ref req) => req.check_outputs(f),
 			CheckedRequest::Signal(_, ref req) => req.check_outputs(f),
+			CheckedRequest::BloomGroups(_, ref req) => req.check_outputs(f),
 		}
 	}
 
@@ -502,8 +580,10 @@ impl IncompleteRequest for CheckedRequest {
 		match_me!(*self, (_, ref req) => req.note_outputs(f))
 	}
 
+	// the storage root to check storage proofs against is only taken from verified
+	// account responses (see `provide_storage_root`), never from outputs supplied by peers.
 	fn fill<F>(&mut self, f: F) where F: Fn(usize, usize) -> Result<Output, net_request::NoSuchOutput> {
-		match_me!(*self, (_, ref mut req) => req.fill(f))
+		match_me!(*self, (_, ref mut req) => req.fill(&f))
 	}
 
 	fn complete(self) -> Result<Self::Complete, net_request::NoSuchOutput> {
@@ -515,8 +595,10 @@ impl IncompleteRequest for CheckedRequest {
 			CheckedRequest::Body(_, req) => req.complete().map(CompleteRequest::Body),
 			CheckedRequest::Account(_, req) => req.complete().map(CompleteRequest::Account),
 			CheckedRequest::Code(_, req) => req.complete().map(CompleteRequest::Code),
+			CheckedRequest::Storage(_, req) => req.complete().map(CompleteRequest::Storage),
 			CheckedRequest::Execution(_, req) => req.complete().map(CompleteRequest::Execution),
 			CheckedRequest::Signal(_, req) => req.complete().map(CompleteRequest::Signal),
+			CheckedRequest::BloomGroups(_, req) => req.complete().map(CompleteRequest::BloomGroups),
 		}
 	}
 
@@ -568,18 +650,25 @@ impl net_request::CheckedRequest for CheckedRequest {
 			CheckedRequest::Code(ref prover, _) =>
 				expect!((&NetResponse::Code(ref res), &CompleteRequest::Code(ref req)) =>
 					prover.check_response(cache, &req.code_hash, &res.code).map(Response::Code)),
+			CheckedRequest::Storage(ref prover, _) =>
+				expect!((&NetResponse::Storage(ref res), _) =>
+					prover.check_response(cache, &res.proof).map(Response::Storage)),
 			CheckedRequest::Execution(ref prover, _) =>
 				expect!((&NetResponse::Execution(ref res), _) =>
 					prover.check_response(cache, &res.items).map(Response::Execution)),
 			CheckedRequest::Signal(ref prover, _) =>
 				expect!((&NetResponse::Signal(ref res), _) =>
 					prover.check_response(cache, &res.signal).map(Response::Signal)),
+			CheckedRequest::BloomGroups(ref prover, _) =>
+				expect!((&NetResponse::BloomGroups(ref res), _) =>
+					prover.check_response(cache, &res.proof, &res.blooms).map(Response::BloomGroups)),
 		}
 	 }
 }
 
 /// Responses to on-demand requests.
 /// All of these are checked.
+#[derive(Debug, Clone, PartialEq)]
 pub enum Response {
 	/// Response to a header proof request.
 	/// Returns the hash and chain score.
@@ -597,10 +686,14 @@ pub enum Response {
 	Account(Option<BasicAccount>),
 	/// Response to a request for code.
 	Code(Vec<u8>),
+	/// Response to a request for a storage value.
+	Storage(H256),
 	/// Response to a request for proved execution.
 	Execution(super::ExecutionResult),
 	/// Response to a request for epoch change signal.
 	Signal(Vec<u8>),
+	/// Response to a request for the headers of a range of blocks.
+	BloomGroups(Vec<Bloom>),
 }
 
 impl net_request::ResponseLike for Response {
@@ -615,6 +708,7 @@ impl net_request::ResponseLike for Response {
 				f(0, Output::Hash(acc.code_hash));
 				f(1, Output::Hash(acc.storage_root));
 			}
+			Response::Storage(ref value) => f(0, Output::Hash(*value)),
 			_ => {}
 		}
 	}
@@ -635,6 +729,8 @@ pub enum Error {
 	HeaderByNumber,
 	/// Unresolved header reference.
 	UnresolvedHeader(usize),
+	/// Unresolved storage root reference.
+	UnresolvedStorageRoot(usize),
 	/// Wrong header number.
 	WrongNumber(u64, u64),
 	/// Wrong hash.
@@ -822,25 +918,28 @@ pub struct Account {
 
 impl Account {
 	/// Check a response with an account against the stored header.
-	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<Option<BasicAccount>, Error> {
+	pub fn check_response(&self, cache: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<Option<BasicAccount>, Error> {
 		let header = self.header.as_ref()?;
 		let state_root = header.state_root();
 
 		let mut db = MemoryDB::new();
 		for node in proof { db.insert(&node[..]); }
 
-		match TrieDB::new(&db, &state_root).and_then(|t| t.get(&keccak(&self.address)))? {
+		let account = match TrieDB::new(&db, &state_root).and_then(|t| t.get(&keccak(&self.address)))? {
 			Some(val) => {
 				let rlp = Rlp::new(&val);
-				Ok(Some(BasicAccount {
+				Some(BasicAccount {
 					nonce: rlp.val_at(0)?,
 					balance: rlp.val_at(1)?,
 					storage_root: rlp.val_at(2)?,
 					code_hash: rlp.val_at(3)?,
-				}))
+				})
 			},
-			None => Ok(None),
-		}
+			None => None,
+		};
+
+		cache.lock().insert_account(header.hash(), self.address, account.clone());
+		Ok(account)
 	}
 }
 
@@ -857,12 +956,13 @@ impl Code {
 	/// Check a response with code against the code hash.
 	pub fn check_response(
 		&self,
-		_: &Mutex<::cache::Cache>,
+		cache: &Mutex<::cache::Cache>,
 		code_hash: &H256,
 		code: &[u8]
 	) -> Result<Vec<u8>, Error> {
 		let found_hash = keccak(code);
 		if &found_hash == code_hash {
+			cache.lock().insert_code(*code_hash, code);
 			Ok(code.to_vec())
 		} else {
 			Err(Error::WrongHash(*code_hash, found_hash))
@@ -870,6 +970,37 @@ impl Code {
 	}
 }
 
+/// Request for a storage value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+	/// Header reference.
+	pub header: HeaderRef,
+	/// Root of the account's storage trie.
+	pub storage_root: Field<H256>,
+	/// Address of the account.
+	pub address: Address,
+	/// Storage key requested.
+	pub key: H256,
+}
+
+impl Storage {
+	/// Check a response with a storage proof against the account's storage root.
+	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes]) -> Result<H256, Error> {
+		let storage_root = match self.storage_root {
+			Field::Scalar(root) => root,
+			Field::BackReference(idx, _) => return Err(Error::UnresolvedStorageRoot(idx)),
+		};
+
+		let mut db = MemoryDB::new();
+		for node in proof { db.insert(&node[..]); }
+
+		match TrieDB::new(&db, &storage_root).and_then(|t| t.get(&keccak(&self.key)))? {
+			Some(val) => Ok(Rlp::new(&val).as_val::<U256>()?.into()),
+			None => Ok(H256::zero()),
+		}
+	}
+}
+
 /// Request for transaction execution, along with the parts necessary to verify the proof.
 #[derive(Clone)]
 pub struct TransactionProof {
@@ -882,23 +1013,38 @@ pub struct TransactionProof {
 	pub env_info: EnvInfo,
 	/// Consensus engine.
 	pub engine: Arc<EthEngine>,
+	/// Traces and state diff to produce while replaying the execution.
+	pub analytics: CallAnalytics,
 }
 
 impl TransactionProof {
 	/// Check the proof, returning the proved execution or indicate that the proof was bad.
 	pub fn check_response(&self, _: &Mutex<::cache::Cache>, state_items: &[DBValue]) -> Result<super::ExecutionResult, Error> {
+		fn check<T, V>(proof: &TransactionProof, root: H256, state_items: &[DBValue], options: TransactOptions<T, V>) -> ProvedExecution
+			where T: trace::Tracer<Output=FlatTrace>, V: trace::VMTracer<Output=VMTrace>
+		{
+			state::check_proof_with_options(
+				state_items,
+				root,
+				&proof.tx,
+				proof.engine.machine(),
+				&proof.env_info,
+				options.save_output_from_contract(),
+				proof.analytics.state_diffing,
+			)
+		}
+
 		let root = self.header.as_ref()?.state_root();
 
 		let mut env_info = self.env_info.clone();
 		env_info.gas_limit = self.tx.gas.clone();
 
-		let proved_execution = state::check_proof(
-			state_items,
-			root,
-			&self.tx,
-			self.engine.machine(),
-			&self.env_info,
-		);
+		let proved_execution = match (self.analytics.transaction_tracing, self.analytics.vm_tracing) {
+			(true, true) => check(self, root, state_items, TransactOptions::with_tracing_and_vm_tracing()),
+			(true, false) => check(self, root, state_items, TransactOptions::with_tracing()),
+			(false, true) => check(self, root, state_items, TransactOptions::with_vm_tracing()),
+			(false, false) => check(self, root, state_items, TransactOptions::with_no_tracing()),
+		};
 
 		match proved_execution {
 			ProvedExecution::BadProof => Err(Error::BadProof),
@@ -929,6 +1075,53 @@ impl Signal {
 	}
 }
 
+/// Request for a range of bloom groups covered by a single CHT, proved against
+/// the root of the CHT's bloom trie. Bloom groups matching a filter point to groups
+/// of blocks which may contain matching logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomGroups {
+	start: u64,
+	count: u64,
+	bloom_root: H256,
+}
+
+impl BloomGroups {
+	/// Construct a new request for the `count` bloom groups starting with group `start`.
+	/// Fails if the range is empty, too large, or not covered by a single CHT.
+	/// Provide the root of that CHT's bloom trie to compare against.
+	pub fn new(start: u64, count: u64, bloom_root: H256) -> Option<Self> {
+		if count == 0 || count > net_request::bloom_groups::MAX_COUNT
+			|| ::cht::bloom_group_to_cht_number(start) != ::cht::bloom_group_to_cht_number(start + count - 1)
+		{
+			return None
+		}
+
+		Some(BloomGroups {
+			start: start,
+			count: count,
+			bloom_root: bloom_root,
+		})
+	}
+
+	/// Access the number of the first bloom group.
+	pub fn start(&self) -> u64 { self.start }
+
+	/// Access the number of bloom groups requested.
+	pub fn count(&self) -> u64 { self.count }
+
+	/// Check a response with a proof of the bloom groups, get the bloom groups back.
+	pub fn check_response(&self, _: &Mutex<::cache::Cache>, proof: &[Bytes], blooms: &[Bloom]) -> Result<Vec<Bloom>, Error> {
+		let proved = ::cht::check_bloom_proof(proof, self.start, self.count, self.bloom_root)
+			.ok_or(Error::BadProof)?;
+
+		if &proved[..] != blooms {
+			return Err(Error::BadProof)
+		}
+
+		Ok(proved)
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -942,6 +1135,7 @@ mod tests {
 
 	use ethcore::client::{BlockChainClient, BlockInfo, TestBlockChainClient, EachBlockWith};
 	use ethcore::header::Header;
+	use ethcore::ids::BlockId;
 	use ethcore::encoded;
 	use ethcore::receipt::{Receipt, TransactionOutcome};
 
@@ -982,6 +1176,38 @@ mod tests {
 		assert!(req.check_response(&cache, &proof[..]).is_ok());
 	}
 
+	#[test]
+	fn check_bloom_groups() {
+		use ::cht;
+
+		let test_client = TestBlockChainClient::new();
+		test_client.add_blocks(2100, EachBlockWith::Nothing);
+
+		let blooms: Vec<_> = (1..2049)
+			.map(|num| test_client.block_header(BlockId::Number(num)).unwrap().log_bloom())
+			.collect();
+
+		let root = cht::compute_bloom_root(0, blooms.iter().cloned()).unwrap();
+		let (groups, proof) = cht::prove_bloom_groups(0, blooms.iter().cloned(), 10, 100).unwrap();
+
+		assert!(BloomGroups::new(10, 0, root).is_none());
+		assert!(BloomGroups::new(100, 100, root).is_none());
+		let req = BloomGroups::new(10, 100, root).unwrap();
+
+		let cache = Mutex::new(make_cache());
+		assert_eq!(req.check_response(&cache, &proof[..], &groups[..]).unwrap(), groups);
+
+		// missing bloom group.
+		assert!(req.check_response(&cache, &proof[..], &groups[1..]).is_err());
+
+		// missing proof node.
+		assert!(req.check_response(&cache, &proof[1..], &groups[..]).is_err());
+
+		// wrong bloom root.
+		let req = BloomGroups::new(10, 100, H256::default()).unwrap();
+		assert!(req.check_response(&cache, &proof[..], &groups[..]).is_err());
+	}
+
 	#[test]
 	fn check_header_by_hash() {
 		let mut header = Header::new();
@@ -1095,4 +1321,41 @@ mod tests {
 		assert!(req.check_response(&cache, &code_hash, &code).is_ok());
 		assert!(req.check_response(&cache, &code_hash, &[]).is_err());
 	}
+
+	#[test]
+	fn check_storage_proof() {
+		let mut root = H256::default();
+		let mut db = MemoryDB::new();
+		let key = H256::from(5);
+		{
+			let mut trie = SecTrieDBMut::new(&mut db, &mut root);
+			for i in 0..100u64 {
+				trie.insert(&*H256::from(1000 + i), &::rlp::encode(&U256::from(i))).unwrap();
+			}
+
+			trie.insert(&*key, &::rlp::encode(&U256::from(0xdead))).unwrap();
+		}
+
+		let proof = {
+			let trie = SecTrieDB::new(&db, &root).unwrap();
+			let mut recorder = Recorder::new();
+
+			trie.get_with(&*key, &mut recorder).unwrap().unwrap();
+
+			recorder.drain().into_iter().map(|r| r.data).collect::<Vec<_>>()
+		};
+
+		let mut req = Storage {
+			header: encoded::Header::new(::rlp::encode(&Header::new()).into_vec()).into(),
+			storage_root: root.into(),
+			address: Address::random(),
+			key: key,
+		};
+
+		let cache = Mutex::new(make_cache());
+		assert_eq!(req.check_response(&cache, &proof[..]), Ok(U256::from(0xdead).into()));
+
+		req.storage_root = H256::random().into();
+		assert!(req.check_response(&cache, &proof[..]).is_err());
+	}
 }
@@ -19,16 +19,17 @@
 use cache::Cache;
 use ethcore::header::Header;
 use futures::Future;
-use network::{PeerId, NodeId};
+use network::{PeerId, NodeId, PeerBehaviour};
 use net::*;
-use ethereum_types::H256;
+use ethereum_types::{H256, Address};
+use hash::KECCAK_NULL_RLP;
 use parking_lot::Mutex;
-use std::time::Duration;
-use ::request::{self as basic_request, Response};
+use std::time::{Duration, Instant};
+use ::request::{self as basic_request, Field, Response};
 
 use std::sync::Arc;
 
-use super::{request, OnDemand, Peer, HeaderRef};
+use super::{request, OnDemand, Peer, HeaderRef, Request};
 
 // useful contexts to give the service.
 enum Context {
@@ -36,6 +37,7 @@ enum Context {
 	WithPeer(PeerId),
 	RequestFrom(PeerId, ReqId),
 	Punish(PeerId),
+	OutOfCredits(PeerId),
 }
 
 impl EventContext for Context {
@@ -43,7 +45,8 @@ impl EventContext for Context {
 		match *self {
 			Context::WithPeer(id)
 			| Context::RequestFrom(id, _)
-			| Context::Punish(id) => id,
+			| Context::Punish(id)
+			| Context::OutOfCredits(id) => id,
 			_ => panic!("didn't expect to have peer queried."),
 		}
 	}
@@ -64,6 +67,13 @@ impl BasicContext for Context {
 		}
 	}
 
+	fn can_afford(&self, peer_id: PeerId, _: &[::request::Request]) -> bool {
+		match *self {
+			Context::OutOfCredits(id) => id != peer_id,
+			_ => true,
+		}
+	}
+
 	fn make_announcement(&self, _: Announcement) {
 		panic!("didn't expect to make announcement")
 	}
@@ -78,6 +88,8 @@ impl BasicContext for Context {
 			_ => panic!("Unexpectedly punished peer."),
 		}
 	}
+
+	fn report_peer(&self, _: PeerId, _: PeerBehaviour) {}
 }
 
 // test harness.
@@ -141,10 +153,7 @@ fn single_request() {
 	let peer_id = 10101;
 	let req_id = ReqId(14426);
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let header = Header::default();
 	let encoded = header.encoded();
@@ -178,10 +187,7 @@ fn no_capabilities() {
 	let mut capabilities = dummy_capabilities();
 	capabilities.serve_headers = false;
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: capabilities,
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), capabilities));
 
 	let _recv = harness.service.request_raw(
 		&Context::NoOp,
@@ -202,10 +208,7 @@ fn reassign() {
 	let peer_ids = (10101, 12345);
 	let req_ids = (ReqId(14426), ReqId(555));
 
-	harness.inject_peer(peer_ids.0, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_ids.0, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let header = Header::default();
 	let encoded = header.encoded();
@@ -223,10 +226,7 @@ fn reassign() {
 	harness.service.on_disconnect(&Context::WithPeer(peer_ids.0), &[req_ids.0]);
 	assert_eq!(harness.service.pending.read().len(), 1);
 
-	harness.inject_peer(peer_ids.1, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_ids.1, Peer::new(dummy_status(), dummy_capabilities()));
 
 	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.1, req_ids.1));
 	assert_eq!(harness.service.pending.read().len(), 0);
@@ -247,10 +247,7 @@ fn partial_response() {
 	let peer_id = 111;
 	let req_ids = (ReqId(14426), ReqId(555));
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let make = |num| {
 		let mut hdr = Header::default();
@@ -306,10 +303,7 @@ fn part_bad_part_good() {
 	let peer_id = 111;
 	let req_ids = (ReqId(14426), ReqId(555));
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let make = |num| {
 		let mut hdr = Header::default();
@@ -349,10 +343,7 @@ fn part_bad_part_good() {
 
 	assert_eq!(harness.service.pending.read().len(), 1);
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_ids.1));
 	assert_eq!(harness.service.pending.read().len(), 0);
@@ -374,10 +365,7 @@ fn wrong_kind() {
 	let peer_id = 10101;
 	let req_id = ReqId(14426);
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let _recv = harness.service.request_raw(
 		&Context::NoOp,
@@ -406,10 +394,7 @@ fn back_references() {
 	let peer_id = 10101;
 	let req_id = ReqId(14426);
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let header = Header::default();
 	let encoded = header.encoded();
@@ -463,10 +448,7 @@ fn fill_from_cache() {
 	let peer_id = 10101;
 	let req_id = ReqId(14426);
 
-	harness.inject_peer(peer_id, Peer {
-		status: dummy_status(),
-		capabilities: dummy_capabilities(),
-	});
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
 
 	let header = Header::default();
 	let encoded = header.encoded();
@@ -495,3 +477,200 @@ fn fill_from_cache() {
 
 	assert!(recv.wait().is_ok());
 }
+
+#[test]
+fn quorum_agreement() {
+	let mut harness = Harness::create();
+	harness.service.set_quorum(basic_request::Kind::TransactionIndex, 2);
+
+	let peer_ids = (10101, 12345);
+	let req_ids = (ReqId(14426), ReqId(555));
+
+	harness.inject_peer(peer_ids.0, Peer::new(dummy_status(), dummy_capabilities()));
+	harness.inject_peer(peer_ids.1, Peer::new(dummy_status(), dummy_capabilities()));
+
+	let recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::TransactionIndex(H256::default().into()).into()]
+	).unwrap();
+
+	assert_eq!(harness.service.pending.read().len(), 2);
+
+	// each member of the quorum goes to a different peer.
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.0, req_ids.0));
+	assert_eq!(harness.service.pending.read().len(), 1);
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.0, req_ids.0));
+	assert_eq!(harness.service.pending.read().len(), 1);
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.1, req_ids.1));
+	assert_eq!(harness.service.pending.read().len(), 0);
+
+	let index = basic_request::TransactionIndexResponse { num: 5, hash: H256::from(5), index: 1 };
+	for &(peer_id, req_id) in &[(peer_ids.0, req_ids.0), (peer_ids.1, req_ids.1)] {
+		harness.service.on_responses(
+			&Context::WithPeer(peer_id),
+			req_id,
+			&[Response::TransactionIndex(index.clone())]
+		);
+	}
+
+	assert_eq!(recv.wait().unwrap(), vec![request::Response::TransactionIndex(index)]);
+}
+
+#[test]
+fn quorum_disagreement() {
+	let mut harness = Harness::create();
+	harness.service.set_quorum(basic_request::Kind::TransactionIndex, 2);
+
+	let peer_ids = (10101, 12345);
+	let req_ids = (ReqId(14426), ReqId(555));
+
+	harness.inject_peer(peer_ids.0, Peer::new(dummy_status(), dummy_capabilities()));
+	harness.inject_peer(peer_ids.1, Peer::new(dummy_status(), dummy_capabilities()));
+
+	let recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![request::TransactionIndex(H256::default().into()).into()]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.0, req_ids.0));
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_ids.1, req_ids.1));
+	assert_eq!(harness.service.pending.read().len(), 0);
+
+	for &(peer_id, req_id, num) in &[(peer_ids.0, req_ids.0, 5), (peer_ids.1, req_ids.1, 6)] {
+		harness.service.on_responses(
+			&Context::WithPeer(peer_id),
+			req_id,
+			&[Response::TransactionIndex(basic_request::TransactionIndexResponse {
+				num: num,
+				hash: H256::from(num),
+				index: 1,
+			})]
+		);
+	}
+
+	assert!(recv.wait().is_err());
+}
+
+#[test]
+fn storage_of_missing_account() {
+	let harness = Harness::create();
+
+	let peer_id = 10101;
+	let req_id = ReqId(14426);
+
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
+
+	// the state is empty, so every account is missing.
+	let mut header = Header::default();
+	header.set_state_root(KECCAK_NULL_RLP);
+	let encoded = header.encoded();
+	let address = Address::from(5);
+
+	let recv = harness.service.request_raw(
+		&Context::NoOp,
+		vec![
+			request::HeaderByHash(header.hash().into()).into(),
+			request::Account { header: HeaderRef::Unresolved(0, header.hash().into()), address: address }.into(),
+			request::Storage {
+				header: HeaderRef::Unresolved(0, header.hash().into()),
+				storage_root: Field::back_ref(1, 1),
+				address: address,
+				key: H256::from(1),
+			}.into(),
+		]
+	).unwrap();
+
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_id));
+	assert_eq!(harness.service.pending.read().len(), 0);
+
+	// the peer proves the account doesn't exist and doesn't serve its storage.
+	harness.service.on_responses(
+		&Context::WithPeer(peer_id),
+		req_id,
+		&[
+			Response::Headers(basic_request::HeadersResponse { headers: vec![encoded] }),
+			Response::Account(basic_request::AccountResponse {
+				proof: vec![],
+				nonce: 0.into(),
+				balance: 0.into(),
+				code_hash: H256::from(2),
+				storage_root: H256::from(3),
+			}),
+		]
+	);
+
+	assert_eq!(harness.service.pending.read().len(), 0);
+	let responses = recv.wait().unwrap();
+	assert_eq!(responses[1], request::Response::Account(None));
+	assert_eq!(responses[2], request::Response::Storage(H256::zero()));
+}
+
+#[test]
+fn partial_response_without_credits() {
+	let harness = Harness::create();
+
+	let peer_id = 111;
+	let req_ids = (ReqId(14426), ReqId(555));
+
+	harness.inject_peer(peer_id, Peer::new(dummy_status(), dummy_capabilities()));
+
+	let make = |num| {
+		let mut hdr = Header::default();
+		hdr.set_number(num);
+
+		let encoded = hdr.encoded();
+		(hdr, encoded)
+	};
+
+	let (header1, encoded1) = make(5);
+	let (header2, _) = make(23452);
+	let (header3, encoded3) = make(7);
+
+	let requests = |first: &Header, second: &Header| -> Vec<Request> {
+		vec![
+			request::HeaderByHash(first.hash().into()).into(),
+			request::HeaderByHash(second.hash().into()).into(),
+		]
+	};
+
+	// the peer answers only the first request as it ran out of credits.
+	let _recv = harness.service.request_raw(&Context::NoOp, requests(&header1, &header2)).unwrap();
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_ids.0));
+	harness.service.on_responses(
+		&Context::OutOfCredits(peer_id),
+		req_ids.0,
+		&[Response::Headers(basic_request::HeadersResponse { headers: vec![encoded1] })]
+	);
+
+	assert_eq!(harness.service.peers.read()[&peer_id].failures(), 0);
+
+	// the peer had the credits to answer the second request but didn't.
+	harness.service.pending.write().clear();
+	let _recv = harness.service.request_raw(&Context::NoOp, requests(&header3, &header2)).unwrap();
+	harness.service.dispatch_pending(&Context::RequestFrom(peer_id, req_ids.1));
+	harness.service.on_responses(
+		&Context::WithPeer(peer_id),
+		req_ids.1,
+		&[Response::Headers(basic_request::HeadersResponse { headers: vec![encoded3] })]
+	);
+
+	assert_eq!(harness.service.peers.read()[&peer_id].failures(), 1);
+}
+
+#[test]
+fn failures_decay() {
+	let mut peer = Peer::new(dummy_status(), dummy_capabilities());
+	assert_eq!(peer.failures(), 0);
+
+	for _ in 0..4 {
+		peer.note_failure();
+	}
+	assert_eq!(peer.failures(), 4);
+
+	// halved for every half-life since the last failure.
+	peer.last_failure = Some(Instant::now() - super::FAILURE_HALF_LIFE);
+	assert_eq!(peer.failures(), 2);
+
+	peer.note_failure();
+	assert_eq!(peer.failures(), 3);
+}
@@ -136,6 +136,25 @@ pub trait Provider: Send + Sync {
 
 	/// Provide epoch signal data at given block hash. This should be just the
 	fn epoch_signal(&self, req: request::CompleteSignalRequest) -> Option<request::SignalResponse>;
+
+	/// Provide a range of bloom groups covered by a single CHT, along with
+	/// a proof of them against the root of the CHT's bloom trie.
+	fn bloom_groups(&self, req: request::CompleteBloomGroupsRequest) -> Option<request::BloomGroupsResponse> {
+		if req.count == 0 || req.count > request::bloom_groups::MAX_COUNT {
+			return None
+		}
+
+		let cht_number = cht::bloom_group_to_cht_number(req.start);
+		let blooms = (cht::start_number(cht_number)..cht::start_number(cht_number + 1))
+			.map(|num| self.block_header(BlockId::Number(num)).map(|hdr| hdr.log_bloom()))
+			.collect::<Option<Vec<_>>>()?; // incomplete CHT.
+
+		cht::prove_bloom_groups(cht_number, blooms, req.start, req.count)
+			.map(|(blooms, proof)| request::BloomGroupsResponse {
+				proof: proof,
+				blooms: blooms,
+			})
+	}
 }
 
 // Implementation of a light client data provider for a client.
@@ -396,6 +415,7 @@ impl<L: AsLightClient> AsLightClient for LightProvider<L> {
 #[cfg(test)]
 mod tests {
 	use ethcore::client::{EachBlockWith, TestBlockChainClient};
+	use ethcore::ids::BlockId;
 	use super::Provider;
 
 	#[test]
@@ -413,4 +433,26 @@ mod tests {
 
 		assert!(client.header_proof(req.clone()).is_some());
 	}
+
+	#[test]
+	fn bloom_groups_proof() {
+		let client = TestBlockChainClient::new();
+		client.add_blocks(2048, EachBlockWith::Nothing);
+
+		let req = ::request::CompleteBloomGroupsRequest {
+			start: 10,
+			count: 100,
+		};
+
+		let res = client.bloom_groups(req).unwrap();
+		assert_eq!(res.blooms.len(), 100);
+
+		let blooms = (1..2049).map(|num| client.block_header(BlockId::Number(num)).unwrap().log_bloom());
+		let root = ::cht::compute_bloom_root(0, blooms).unwrap();
+		assert_eq!(::cht::check_bloom_proof(&res.proof, 10, 100, root), Some(res.blooms));
+
+		// groups beyond the CHT, and CHTs which aren't complete yet, can't be proved.
+		assert!(client.bloom_groups(::request::CompleteBloomGroupsRequest { start: 100, count: 100 }).is_none());
+		assert!(client.bloom_groups(::request::CompleteBloomGroupsRequest { start: 128, count: 1 }).is_none());
+	}
 }
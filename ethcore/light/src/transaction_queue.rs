@@ -17,14 +17,16 @@
 //! Light Transaction Queue.
 //!
 //! Manages local transactions,
-//! but stores all local transactions, removing only on invalidated nonce.
+//! but stores all local transactions, removing only on invalidated nonce
+//! or inclusion in a block. The statuses of a few recently removed transactions
+//! are kept around.
 //!
 //! Under the assumption that light nodes will have a relatively limited set of
 //! accounts for which they create transactions, this queue is structured in an
 //! address-wise manner.
 
 use std::fmt;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::collections::hash_map::Entry;
 
 use transaction::{self, Condition, PendingTransaction, SignedTransaction};
@@ -132,6 +134,25 @@ pub enum ImportDestination {
 	Future,
 }
 
+/// Status of a local transaction which has been removed from the queue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+	/// The transaction was included in a block.
+	Mined(SignedTransaction),
+	/// The transaction was replaced by another one with the same sender and nonce.
+	Replaced {
+		/// The replaced transaction.
+		old: SignedTransaction,
+		/// The transaction which replaced it.
+		new: SignedTransaction,
+	},
+	/// Another transaction with the same sender and nonce was included in a block.
+	Invalid(SignedTransaction),
+}
+
+// maximum number of statuses of removed transactions to keep.
+const MAX_OLD: usize = 10;
+
 type Listener = Box<Fn(&[H256]) + Send + Sync>;
 
 /// Light transaction queue. See module docs for more details.
@@ -139,6 +160,7 @@ type Listener = Box<Fn(&[H256]) + Send + Sync>;
 pub struct TransactionQueue {
 	by_account: HashMap<Address, AccountTransactions>,
 	by_hash: H256FastMap<PendingTransaction>,
+	old: VecDeque<(H256, Status)>,
 	listeners: Vec<Listener>,
 }
 
@@ -147,6 +169,7 @@ impl fmt::Debug for TransactionQueue {
 		fmt.debug_struct("TransactionQueue")
 			.field("by_account", &self.by_account)
 			.field("by_hash", &self.by_hash)
+			.field("old", &self.old)
 			.field("listeners", &self.listeners.len())
 			.finish()
 	}
@@ -162,6 +185,7 @@ impl TransactionQueue {
 
 		if self.by_hash.contains_key(&hash) { return Err(transaction::Error::AlreadyImported) }
 
+		let mut replaced = None;
 		let (res, promoted) = match self.by_account.entry(sender) {
 			Entry::Vacant(entry) => {
 				entry.insert(AccountTransactions {
@@ -190,7 +214,7 @@ impl TransactionQueue {
 							sender, nonce);
 
 						let old = ::std::mem::replace(&mut acct_txs.current[idx], tx_info);
-						self.by_hash.remove(&old.hash);
+						replaced = self.by_hash.remove(&old.hash);
 
 						(ImportDestination::Current, vec![hash])
 					}
@@ -218,7 +242,9 @@ impl TransactionQueue {
 						} else if idx == cur_len && acct_txs.current.last().map_or(false, |f| f.nonce + 1.into() != nonce) {
 							trace!(target: "txqueue", "Queued future transaction for {}, nonce={}", sender, nonce);
 							let future_nonce = nonce;
-							acct_txs.future.insert(future_nonce, tx_info);
+							if let Some(old) = acct_txs.future.insert(future_nonce, tx_info) {
+								replaced = self.by_hash.remove(&old.hash);
+							}
 
 							(ImportDestination::Future, vec![])
 						} else {
@@ -236,6 +262,11 @@ impl TransactionQueue {
 			}
 		};
 
+		if let Some(old) = replaced {
+			let old_hash = old.hash();
+			self.note_removed(old_hash, Status::Replaced { old: old.transaction, new: tx.transaction.clone() });
+		}
+
 		self.by_hash.insert(hash, tx);
 		self.notify(&promoted);
 		Ok(res)
@@ -309,7 +340,32 @@ impl TransactionQueue {
 	}
 
 	/// Cull out all transactions by the given address which are invalidated by the given nonce.
+	///
+	/// As the transactions which used up the nonces are unknown, culled transactions
+	/// are assumed to have been mined.
 	pub fn cull(&mut self, address: Address, cur_nonce: U256) {
+		self.cull_with(address, cur_nonce, None)
+	}
+
+	/// Note that the given transactions were included in a block.
+	///
+	/// Queued transactions are removed, along with all other transactions from the same
+	/// senders which are invalidated by them. Unknown hashes are ignored.
+	pub fn mined(&mut self, hashes: &[H256]) {
+		let mut mined: Vec<_> = hashes.iter()
+			.filter_map(|hash| self.by_hash.get(hash).map(|tx| (tx.sender(), tx.nonce, *hash)))
+			.collect();
+
+		// cull in ascending nonce order so earlier mined transactions aren't taken for invalid ones.
+		mined.sort();
+		for (sender, nonce, hash) in mined {
+			self.cull_with(sender, nonce + 1.into(), Some(hash));
+		}
+	}
+
+	// cull transactions by the given address up to the given nonce.
+	// if given, `mined` is the hash of the only culled transaction which was included in a block.
+	fn cull_with(&mut self, address: Address, cur_nonce: U256, mined: Option<H256>) {
 		let mut removed_hashes = vec![];
 		if let Entry::Occupied(mut entry) = self.by_account.entry(address) {
 			{
@@ -350,7 +406,48 @@ impl TransactionQueue {
 			removed_hashes.len(), address, cur_nonce);
 
 		for hash in removed_hashes {
-			self.by_hash.remove(&hash);
+			let tx = match self.by_hash.remove(&hash) {
+				Some(tx) => tx.transaction,
+				None => continue,
+			};
+
+			let status = match mined {
+				Some(mined) if mined != hash => {
+					warn!(target: "own_tx", "Transaction invalidated by a mined transaction (hash {:?})", hash);
+					Status::Invalid(tx)
+				}
+				_ => {
+					info!(target: "own_tx", "Transaction mined (hash {:?})", hash);
+					Status::Mined(tx)
+				}
+			};
+
+			self.note_removed(hash, status);
+		}
+	}
+
+	/// Whether there are no queued transactions.
+	pub fn is_empty(&self) -> bool {
+		self.by_hash.is_empty()
+	}
+
+	/// Get all queued transactions, including those not ready to be propagated.
+	pub fn queued_transactions(&self) -> Vec<PendingTransaction> {
+		self.by_hash.values().cloned().collect()
+	}
+
+	/// Get the statuses of recently removed transactions, oldest first.
+	pub fn old_transactions(&self) -> &VecDeque<(H256, Status)> {
+		&self.old
+	}
+
+	// keep the status of a removed transaction, forgetting the oldest ones.
+	fn note_removed(&mut self, hash: H256, status: Status) {
+		self.old.retain(|&(ref h, _)| h != &hash);
+		self.old.push_back((hash, status));
+
+		while self.old.len() > MAX_OLD {
+			self.old.pop_front();
 		}
 	}
 
@@ -374,7 +471,7 @@ impl TransactionQueue {
 
 #[cfg(test)]
 mod tests {
-	use super::TransactionQueue;
+	use super::{Status, TransactionQueue};
 	use ethereum_types::Address;
 	use transaction::{Transaction, PendingTransaction, Condition};
 
@@ -545,6 +642,41 @@ mod tests {
 		txq.import(tx_b).unwrap();
 
 		assert!(txq.transaction(&hash).is_none());
+		assert_eq!(txq.old_transactions().len(), 1);
+		match txq.old_transactions()[0] {
+			(ref old_hash, Status::Replaced { .. }) => assert_eq!(old_hash, &hash),
+			ref other => panic!("unexpected status: {:?}", other),
+		}
+	}
+
+	#[test]
+	fn mined_and_invalidated() {
+		let sender = Address::default();
+		let mut txq = TransactionQueue::default();
+
+		let hashes: Vec<_> = (0..4).map(|i| {
+			let mut tx = Transaction::default();
+			tx.nonce = i.into();
+
+			let tx: PendingTransaction = tx.fake_sign(sender).into();
+			let hash = tx.hash();
+			txq.import(tx).unwrap();
+			hash
+		}).collect();
+
+		// nonce 0 was used by another transaction, and nonces 1 and 2 by ours.
+		txq.mined(&[hashes[2], hashes[1], 5.into()]);
+
+		assert_eq!(txq.ready_transactions(0, 0).len(), 1);
+		assert_eq!(txq.next_nonce(&sender).unwrap(), 4.into());
+
+		let statuses: Vec<_> = txq.old_transactions().iter().map(|&(hash, ref status)| match *status {
+			Status::Mined(_) => (hash, "mined"),
+			Status::Invalid(_) => (hash, "invalid"),
+			Status::Replaced { .. } => (hash, "replaced"),
+		}).collect();
+
+		assert_eq!(statuses, vec![(hashes[0], "invalid"), (hashes[1], "mined"), (hashes[2], "mined")]);
 	}
 
 	#[test]
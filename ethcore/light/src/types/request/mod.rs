@@ -72,6 +72,11 @@ pub use self::epoch_signal::{
 	Incomplete as IncompleteSignalRequest,
 	Response as SignalResponse,
 };
+pub use self::bloom_groups::{
+	Complete as CompleteBloomGroupsRequest,
+	Incomplete as IncompleteBloomGroupsRequest,
+	Response as BloomGroupsResponse,
+};
 
 pub use self::batch::{Batch, Builder};
 
@@ -267,6 +272,8 @@ pub enum Request {
 	Execution(IncompleteExecutionRequest),
 	/// A request for an epoch signal.
 	Signal(IncompleteSignalRequest),
+	/// A request for proved bloom groups.
+	BloomGroups(IncompleteBloomGroupsRequest),
 }
 
 /// All request types, in an answerable state.
@@ -292,6 +299,8 @@ pub enum CompleteRequest {
 	Execution(CompleteExecutionRequest),
 	/// A request for an epoch signal.
 	Signal(CompleteSignalRequest),
+	/// A request for proved bloom groups.
+	BloomGroups(CompleteBloomGroupsRequest),
 }
 
 impl CompleteRequest {
@@ -308,6 +317,7 @@ impl CompleteRequest {
 			CompleteRequest::Code(_) => Kind::Code,
 			CompleteRequest::Execution(_) => Kind::Execution,
 			CompleteRequest::Signal(_) => Kind::Signal,
+			CompleteRequest::BloomGroups(_) => Kind::BloomGroups,
 		}
 	}
 }
@@ -326,6 +336,7 @@ impl Request {
 			Request::Code(_) => Kind::Code,
 			Request::Execution(_) => Kind::Execution,
 			Request::Signal(_) => Kind::Signal,
+			Request::BloomGroups(_) => Kind::BloomGroups,
 		}
 	}
 }
@@ -343,6 +354,7 @@ impl Decodable for Request {
 			Kind::Code => Ok(Request::Code(rlp.val_at(1)?)),
 			Kind::Execution => Ok(Request::Execution(rlp.val_at(1)?)),
 			Kind::Signal => Ok(Request::Signal(rlp.val_at(1)?)),
+			Kind::BloomGroups => Ok(Request::BloomGroups(rlp.val_at(1)?)),
 		}
 	}
 }
@@ -365,6 +377,7 @@ impl Encodable for Request {
 			Request::Code(ref req) => s.append(req),
 			Request::Execution(ref req) => s.append(req),
 			Request::Signal(ref req) => s.append(req),
+			Request::BloomGroups(ref req) => s.append(req),
 		};
 	}
 }
@@ -387,6 +400,7 @@ impl IncompleteRequest for Request {
 			Request::Code(ref req) => req.check_outputs(f),
 			Request::Execution(ref req) => req.check_outputs(f),
 			Request::Signal(ref req) => req.check_outputs(f),
+			Request::BloomGroups(ref req) => req.check_outputs(f),
 		}
 	}
 
@@ -402,6 +416,7 @@ impl IncompleteRequest for Request {
 			Request::Code(ref req) => req.note_outputs(f),
 			Request::Execution(ref req) => req.note_outputs(f),
 			Request::Signal(ref req) => req.note_outputs(f),
+			Request::BloomGroups(ref req) => req.note_outputs(f),
 		}
 	}
 
@@ -417,6 +432,7 @@ impl IncompleteRequest for Request {
 			Request::Code(ref mut req) => req.fill(oracle),
 			Request::Execution(ref mut req) => req.fill(oracle),
 			Request::Signal(ref mut req) => req.fill(oracle),
+			Request::BloomGroups(ref mut req) => req.fill(oracle),
 		}
 	}
 
@@ -432,6 +448,7 @@ impl IncompleteRequest for Request {
 			Request::Code(req) => req.complete().map(CompleteRequest::Code),
 			Request::Execution(req) => req.complete().map(CompleteRequest::Execution),
 			Request::Signal(req) => req.complete().map(CompleteRequest::Signal),
+			Request::BloomGroups(req) => req.complete().map(CompleteRequest::BloomGroups),
 		}
 	}
 
@@ -447,6 +464,7 @@ impl IncompleteRequest for Request {
 			Request::Code(ref mut req) => req.adjust_refs(mapping),
 			Request::Execution(ref mut req) => req.adjust_refs(mapping),
 			Request::Signal(ref mut req) => req.adjust_refs(mapping),
+			Request::BloomGroups(ref mut req) => req.adjust_refs(mapping),
 		}
 	}
 }
@@ -490,6 +508,18 @@ pub enum Kind {
 	Execution = 8,
 	/// A request for epoch transition signal.
 	Signal = 9,
+	/// A request for bloom groups proved from a CHT's bloom trie.
+	BloomGroups = 10,
+}
+
+impl Kind {
+	/// The lowest protocol version supporting requests of this kind.
+	pub fn min_protocol_version(&self) -> u8 {
+		match *self {
+			Kind::BloomGroups => 2,
+			_ => 1,
+		}
+	}
 }
 
 impl Decodable for Kind {
@@ -505,6 +535,7 @@ impl Decodable for Kind {
 			7 => Ok(Kind::Code),
 			8 => Ok(Kind::Execution),
 			9 => Ok(Kind::Signal),
+			10 => Ok(Kind::BloomGroups),
 			_ => Err(DecoderError::Custom("Unknown PIP request ID.")),
 		}
 	}
@@ -539,6 +570,8 @@ pub enum Response {
 	Execution(ExecutionResponse),
 	/// A response for epoch change signal.
 	Signal(SignalResponse),
+	/// A response for bloom groups proved from a CHT's bloom trie.
+	BloomGroups(BloomGroupsResponse),
 }
 
 impl ResponseLike for Response {
@@ -555,6 +588,7 @@ impl ResponseLike for Response {
 			Response::Code(ref res) => res.fill_outputs(f),
 			Response::Execution(ref res) => res.fill_outputs(f),
 			Response::Signal(ref res) => res.fill_outputs(f),
+			Response::BloomGroups(ref res) => res.fill_outputs(f),
 		}
 	}
 }
@@ -573,6 +607,7 @@ impl Response {
 			Response::Code(_) => Kind::Code,
 			Response::Execution(_) => Kind::Execution,
 			Response::Signal(_) => Kind::Signal,
+			Response::BloomGroups(_) => Kind::BloomGroups,
 		}
 	}
 }
@@ -590,6 +625,7 @@ impl Decodable for Response {
 			Kind::Code => Ok(Response::Code(rlp.val_at(1)?)),
 			Kind::Execution => Ok(Response::Execution(rlp.val_at(1)?)),
 			Kind::Signal => Ok(Response::Signal(rlp.val_at(1)?)),
+			Kind::BloomGroups => Ok(Response::BloomGroups(rlp.val_at(1)?)),
 		}
 	}
 }
@@ -612,6 +648,7 @@ impl Encodable for Response {
 			Response::Code(ref res) => s.append(res),
 			Response::Execution(ref res) => s.append(res),
 			Response::Signal(ref res) => s.append(res),
+			Response::BloomGroups(ref res) => s.append(res),
 		};
 	}
 }
@@ -1633,6 +1670,94 @@ pub mod epoch_signal {
 	}
 }
 
+/// A request for proved bloom groups.
+///
+/// A bloom group aggregates the log blooms of `cht::BLOOM_GROUP_SIZE` consecutive blocks
+/// and is proved against the root of the bloom trie kept alongside each CHT.
+pub mod bloom_groups {
+	use super::{NoSuchOutput, OutputKind, Output};
+	use ethereum_types::Bloom;
+	use rlp::{Encodable, Decodable, DecoderError, RlpStream, Rlp};
+	use bytes::Bytes;
+
+	/// Maximum number of bloom groups which may be requested at once:
+	/// all of those covered by a single CHT.
+	pub const MAX_COUNT: u64 = ::cht::SIZE / ::cht::BLOOM_GROUP_SIZE;
+
+	/// Potentially incomplete bloom groups request.
+	#[derive(Debug, Clone, PartialEq, Eq, RlpEncodable, RlpDecodable)]
+	pub struct Incomplete {
+		/// Number of the first bloom group.
+		pub start: u64,
+		/// Number of bloom groups requested.
+		pub count: u64,
+	}
+
+	impl super::IncompleteRequest for Incomplete {
+		type Complete = Complete;
+		type Response = Response;
+
+		fn check_outputs<F>(&self, _: F) -> Result<(), NoSuchOutput>
+			where F: FnMut(usize, usize, OutputKind) -> Result<(), NoSuchOutput>
+		{
+			Ok(())
+		}
+
+		fn note_outputs<F>(&self, _: F) where F: FnMut(usize, OutputKind) {}
+
+		fn fill<F>(&mut self, _: F) where F: Fn(usize, usize) -> Result<Output, NoSuchOutput> {}
+
+		fn complete(self) -> Result<Self::Complete, NoSuchOutput> {
+			Ok(Complete {
+				start: self.start,
+				count: self.count,
+			})
+		}
+
+		fn adjust_refs<F>(&mut self, _: F) where F: FnMut(usize) -> usize {}
+	}
+
+	/// A complete bloom groups request.
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct Complete {
+		/// Number of the first bloom group.
+		pub start: u64,
+		/// Number of bloom groups requested.
+		pub count: u64,
+	}
+
+	/// The output of a request for bloom groups.
+	#[derive(Debug, Clone, PartialEq, Eq)]
+	pub struct Response {
+		/// Nodes of the bloom trie proving the bloom groups.
+		pub proof: Vec<Bytes>,
+		/// The bloom groups, in ascending order.
+		pub blooms: Vec<Bloom>,
+	}
+
+	impl super::ResponseLike for Response {
+		/// Fill reusable outputs by providing them to the function.
+		fn fill_outputs<F>(&self, _: F) where F: FnMut(usize, Output) {}
+	}
+
+	impl Decodable for Response {
+		fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+			Ok(Response {
+				proof: rlp.list_at(0)?,
+				blooms: rlp.list_at(1)?,
+			})
+		}
+	}
+
+	impl Encodable for Response {
+		fn rlp_append(&self, s: &mut RlpStream) {
+			s.begin_list(2)
+				.append_list::<Vec<u8>,_>(&self.proof[..])
+				.append_list(&self.blooms[..]);
+		}
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -1926,6 +2051,26 @@ mod tests {
 		assert_eq!(::rlp::decode_list::<Response>(&raw), reqs);
 	}
 
+	#[test]
+	fn bloom_groups_roundtrip() {
+		let req = IncompleteBloomGroupsRequest {
+			start: 256,
+			count: 64,
+		};
+
+		let full_req = Request::BloomGroups(req.clone());
+		let res = BloomGroupsResponse {
+			proof: vec![vec![1, 2, 3], vec![4, 5, 6]],
+			blooms: vec![Default::default(), Default::default()],
+		};
+		let full_res = Response::BloomGroups(res.clone());
+
+		check_roundtrip(req);
+		check_roundtrip(full_req);
+		check_roundtrip(res);
+		check_roundtrip(full_res);
+	}
+
 	#[test]
 	fn epoch_signal_roundtrip() {
 		let req = IncompleteSignalRequest {
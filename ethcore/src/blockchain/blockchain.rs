@@ -16,13 +16,13 @@
 
 //! Blockchain database.
 
-use std::collections::{HashMap, HashSet, hash_map};
+use std::collections::{BTreeSet, HashMap, HashSet, hash_map};
 use std::sync::Arc;
 use std::mem;
 use itertools::Itertools;
 use bloomchain as bc;
 use heapsize::HeapSizeOf;
-use ethereum_types::{H256, Bloom, U256};
+use ethereum_types::{H256, Bloom, U256, Address};
 use parking_lot::{Mutex, RwLock};
 use bytes::Bytes;
 use rlp::RlpStream;
@@ -35,12 +35,12 @@ use receipt::Receipt;
 use blooms::{BloomGroup, GroupPosition};
 use blockchain::best_block::{BestBlock, BestAncientBlock};
 use blockchain::block_info::{BlockInfo, BlockLocation, BranchBecomingCanonChainData};
-use blockchain::extras::{BlockReceipts, BlockDetails, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions};
+use blockchain::extras::{BlockReceipts, BlockDetails, TransactionAddress, EPOCH_KEY_PREFIX, EpochTransitions, LogIndexItem, LOG_INDEX_FROM_KEY, SenderNonce, AccountTransaction};
 use types::blockchain_info::BlockChainInfo;
 use types::tree_route::TreeRoute;
 use blockchain::update::ExtrasUpdate;
 use blockchain::{CacheSize, ImportRoute, Config};
-use db::{self, Writable, Readable, CacheUpdatePolicy};
+use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use cache_manager::CacheManager;
 use encoded;
 use engines::epoch::{Transition as EpochTransition, PendingTransition as PendingEpochTransition};
@@ -204,6 +204,15 @@ pub struct BlockChain {
 	blocks_blooms: RwLock<HashMap<GroupPosition, BloomGroup>>,
 	block_receipts: RwLock<HashMap<H256, BlockReceipts>>,
 
+	// Whether the log index is maintained.
+	log_index: bool,
+	// Whether the index of transactions by sender and nonce is maintained.
+	sender_nonce_index: bool,
+	// Whether the index of transactions by touched account is maintained.
+	account_transactions_index: bool,
+	// First block from which the log index is complete. `None` if the log index is not maintained.
+	log_index_from: RwLock<Option<BlockNumber>>,
+
 	db: Arc<KeyValueDB>,
 
 	cache_man: Mutex<CacheManager<CacheId>>,
@@ -485,6 +494,10 @@ impl BlockChain {
 			transaction_addresses: RwLock::new(HashMap::new()),
 			blocks_blooms: RwLock::new(HashMap::new()),
 			block_receipts: RwLock::new(HashMap::new()),
+			log_index: config.log_index,
+			sender_nonce_index: config.sender_nonce_index,
+			account_transactions_index: config.account_transactions_index,
+			log_index_from: RwLock::new(None),
 			db: db.clone(),
 			cache_man: Mutex::new(cache_man),
 			pending_best_block: RwLock::new(None),
@@ -593,6 +606,29 @@ impl BlockChain {
 			}
 		}
 
+		{
+			let raw_from = bc.db.get(db::COL_LOG_INDEX, LOG_INDEX_FROM_KEY).expect("Low level database error. Some issue with disk?");
+			let mut batch = DBTransaction::new();
+			let log_index_from = match (bc.log_index, raw_from) {
+				(true, Some(raw_from)) => Some(::rlp::decode(&raw_from).expect("decode db value failed")),
+				(true, None) => {
+					// only blocks imported from now on are indexed.
+					let best_block_number = bc.best_block_number();
+					let from = if best_block_number == 0 { 0 } else { best_block_number + 1 };
+					batch.put(db::COL_LOG_INDEX, LOG_INDEX_FROM_KEY, &::rlp::encode(&from));
+					Some(from)
+				},
+				(false, Some(_)) => {
+					// blocks imported from now on won't be indexed, so the index is no longer complete.
+					batch.delete(db::COL_LOG_INDEX, LOG_INDEX_FROM_KEY);
+					None
+				},
+				(false, None) => None,
+			};
+			bc.db.write(batch).expect("Low level database error. Some issue with disk?");
+			*bc.log_index_from.write() = log_index_from;
+		}
+
 		bc
 	}
 
@@ -720,6 +756,14 @@ impl BlockChain {
 		batch.put(db::COL_HEADERS, &hash, &compressed_header);
 		batch.put(db::COL_BODIES, &hash, &compressed_body);
 
+		// unordered blocks are not executed, so only the senders and recipients
+		// of their transactions can be indexed.
+		self.update_account_transactions(batch, bytes, &ImportRoute {
+			retracted: Vec::new(),
+			enacted: vec![hash],
+			omitted: Vec::new(),
+		}, |_| Vec::new());
+
 		let maybe_parent = self.block_details(&header.parent_hash());
 
 		if let Some(parent_details) = maybe_parent {
@@ -734,9 +778,11 @@ impl BlockChain {
 			self.prepare_update(batch, ExtrasUpdate {
 				block_hashes: self.prepare_block_hashes_update(bytes, &info),
 				block_details: self.prepare_block_details_update(bytes, &info),
+				log_index: self.prepare_log_index_update(&receipts, &info),
 				block_receipts: self.prepare_block_receipts_update(receipts, &info),
 				blocks_blooms: self.prepare_block_blooms_update(bytes, &info),
 				transactions_addresses: self.prepare_transaction_addresses_update(bytes, &info),
+				sender_nonces: self.prepare_sender_nonces_update(bytes, &info),
 				info: info,
 				block: bytes
 			}, is_best);
@@ -782,9 +828,11 @@ impl BlockChain {
 			self.prepare_update(batch, ExtrasUpdate {
 				block_hashes: self.prepare_block_hashes_update(bytes, &info),
 				block_details: update,
+				log_index: self.prepare_log_index_update(&receipts, &info),
 				block_receipts: self.prepare_block_receipts_update(receipts, &info),
 				blocks_blooms: self.prepare_block_blooms_update(bytes, &info),
 				transactions_addresses: self.prepare_transaction_addresses_update(bytes, &info),
+				sender_nonces: self.prepare_sender_nonces_update(bytes, &info),
 				info: info,
 				block: bytes,
 			}, is_best);
@@ -931,9 +979,11 @@ impl BlockChain {
 		self.prepare_update(batch, ExtrasUpdate {
 			block_hashes: self.prepare_block_hashes_update(bytes, &info),
 			block_details: self.prepare_block_details_update(bytes, &info),
+			log_index: self.prepare_log_index_update(&receipts, &info),
 			block_receipts: self.prepare_block_receipts_update(receipts, &info),
 			blocks_blooms: self.prepare_block_blooms_update(bytes, &info),
 			transactions_addresses: self.prepare_transaction_addresses_update(bytes, &info),
+			sender_nonces: self.prepare_sender_nonces_update(bytes, &info),
 			info: info.clone(),
 			block: bytes,
 		}, true);
@@ -989,6 +1039,21 @@ impl BlockChain {
 			batch.extend_with_cache(db::COL_EXTRA, &mut *write_receipts, update.block_receipts, CacheUpdatePolicy::Remove);
 		}
 
+		for (sender_nonce, address) in update.sender_nonces {
+			match address {
+				Some(address) => batch.write(db::COL_EXTRA, &sender_nonce, &address),
+				None => batch.delete(db::COL_EXTRA, &sender_nonce.key()),
+			}
+		}
+
+		for ((item, number), indexed) in update.log_index {
+			if indexed {
+				batch.put(db::COL_LOG_INDEX, &item.key(number), &[]);
+			} else {
+				batch.delete(db::COL_LOG_INDEX, &item.key(number));
+			}
+		}
+
 		{
 			let mut write_blocks_blooms = self.blocks_blooms.write();
 			// update best block
@@ -1250,6 +1315,82 @@ impl BlockChain {
 		}
 	}
 
+	/// This function returns modified transaction addresses by sender and nonce.
+	fn prepare_sender_nonces_update(&self, block_bytes: &[u8], info: &BlockInfo) -> HashMap<SenderNonce, Option<TransactionAddress>> {
+		if !self.sender_nonce_index {
+			return HashMap::new();
+		}
+
+		// transactions with invalid signatures can't be part of the chain.
+		let addresses = |block_hash: H256, transactions: Vec<UnverifiedTransaction>| {
+			transactions.into_iter()
+				.enumerate()
+				.filter_map(|(i, tx)| SignedTransaction::new(tx).ok().map(|tx| (i, tx)))
+				.map(|(i, tx)| (SenderNonce { sender: tx.sender(), nonce: tx.nonce }, TransactionAddress {
+					block_hash: block_hash,
+					index: i,
+				}))
+				.collect::<Vec<_>>()
+		};
+
+		match info.location {
+			BlockLocation::CanonChain => {
+				addresses(info.hash, view!(BlockView, block_bytes).transactions()).into_iter()
+					.map(|(sender_nonce, address)| (sender_nonce, Some(address)))
+					.collect()
+			},
+			BlockLocation::BranchBecomingCanonChain(ref data) => {
+				let enacted = data.enacted.iter().flat_map(|hash| {
+					let body = self.block_body(hash).expect("Enacted block must be in database.");
+					addresses(*hash, body.transactions())
+				});
+
+				let current = addresses(info.hash, view!(BlockView, block_bytes).transactions());
+
+				let retracted = data.retracted.iter().flat_map(|hash| {
+					let body = self.block_body(hash).expect("Retracted block must be in database.");
+					addresses(*hash, body.transactions()).into_iter().map(|(sender_nonce, _)| (sender_nonce, None))
+				});
+
+				// The order here is important! Don't remove transaction if it was part of enacted blocks as well.
+				retracted
+					.chain(enacted.chain(current).map(|(sender_nonce, address)| (sender_nonce, Some(address))))
+					.collect()
+			},
+			BlockLocation::Branch => HashMap::new(),
+		}
+	}
+
+	/// This function returns modified log index entries.
+	fn prepare_log_index_update(&self, receipts: &[Receipt], info: &BlockInfo) -> HashMap<(LogIndexItem, BlockNumber), bool> {
+		if !self.log_index {
+			return HashMap::new();
+		}
+
+		let entries = |hash: &H256, indexed: bool| {
+			let number = self.block_number(hash).expect("Route block must be in database.");
+			let receipts = self.block_receipts(hash).expect("Route block receipts must be in database.").receipts;
+			LogIndexItem::from_receipts(&receipts).into_iter()
+				.map(|item| ((item, number), indexed))
+				.collect::<Vec<_>>()
+		};
+
+		let current = LogIndexItem::from_receipts(receipts).into_iter()
+			.map(|item| ((item, info.number), true));
+
+		match info.location {
+			BlockLocation::CanonChain => current.collect(),
+			BlockLocation::BranchBecomingCanonChain(ref data) => {
+				let retracted = data.retracted.iter().flat_map(|hash| entries(hash, false));
+				let enacted = data.enacted.iter().flat_map(|hash| entries(hash, true));
+
+				// The order here is important! Don't remove entries which are part of enacted blocks as well.
+				retracted.chain(enacted).chain(current).collect()
+			},
+			BlockLocation::Branch => HashMap::new(),
+		}
+	}
+
 	/// This functions returns modified blocks blooms.
 	///
 	/// To accelerate blooms lookups, blomms are stored in multiple
@@ -1303,6 +1444,187 @@ impl BlockChain {
 			.collect()
 	}
 
+	/// Updates the index of transactions by touched account: removes entries of blocks retracted
+	/// by the import `route` and adds entries of the enacted ones, including the imported block.
+	///
+	/// Transaction senders and recipients are always indexed, `traced_accounts` returns accounts
+	/// touched by internal calls of every transaction of a block.
+	pub fn update_account_transactions<F>(&self, batch: &mut DBTransaction, block_bytes: &[u8], route: &ImportRoute, traced_accounts: F)
+		where F: Fn(&H256) -> Vec<Vec<Address>>
+	{
+		if !self.account_transactions_index {
+			return;
+		}
+
+		let block = view!(BlockView, block_bytes);
+		let block_hash = block.hash();
+
+		let entries = |hash: &H256| {
+			// the imported block is not in the database yet.
+			let (number, transactions) = if *hash == block_hash {
+				(block.header_view().number(), block.transactions())
+			} else {
+				let number = self.block_number(hash).expect("Route blocks must be in database.");
+				let body = self.block_body(hash).expect("Route blocks must be in database.");
+				(number, body.transactions())
+			};
+			let traced = traced_accounts(hash);
+
+			transactions.into_iter()
+				.enumerate()
+				.flat_map(|(index, tx)| {
+					let mut accounts = HashSet::new();
+					if let Action::Call(ref to) = tx.action {
+						accounts.insert(*to);
+					}
+					if let Ok(tx) = SignedTransaction::new(tx) {
+						accounts.insert(tx.sender());
+					}
+					accounts.extend(traced.get(index).into_iter().flat_map(|accounts| accounts.iter().cloned()));
+
+					accounts.into_iter().map(move |account| (AccountTransaction {
+						account: account,
+						block_number: number,
+						index: index,
+					}, TransactionAddress {
+						block_hash: *hash,
+						index: index,
+					}))
+				})
+				.collect::<Vec<_>>()
+		};
+
+		// The order here is important! Entries of enacted blocks may replace the retracted ones.
+		for (position, _) in route.retracted.iter().flat_map(&entries) {
+			batch.delete(db::COL_EXTRA, &position.key());
+		}
+
+		for (position, address) in route.enacted.iter().flat_map(&entries) {
+			batch.write(db::COL_EXTRA, &position, &address);
+		}
+	}
+
+	/// Returns addresses of canonical transactions touching given account within given range of blocks,
+	/// starting with the transaction following `after`. Returns `None` if the index is not maintained.
+	/// Only transactions imported while the index was maintained can be found.
+	pub fn account_transactions(&self, account: &Address, from_block: BlockNumber, to_block: BlockNumber, after: Option<&TransactionAddress>, count: usize) -> Option<Vec<TransactionAddress>> {
+		if !self.account_transactions_index {
+			return None;
+		}
+
+		let after = match after {
+			Some(address) => Some((self.block_number(&address.block_hash)?, address.index)),
+			None => None,
+		};
+
+		// keys are ordered by block number, so seek to the first block of the range.
+		let start = AccountTransaction {
+			account: *account,
+			block_number: after.map_or(from_block, |(number, _)| ::std::cmp::max(from_block, number)),
+			index: 0,
+		};
+		let start_key = start.key();
+		let prefix = AccountTransaction::prefix(account);
+		let addresses = self.db.iter_from_prefix(db::COL_EXTRA, &start_key)
+			.take_while(|&(ref key, _)| key.starts_with(&prefix))
+			.filter_map(|(key, value)| AccountTransaction::from_key(&key).map(|position| (position, value)))
+			.take_while(|&(ref position, _)| position.block_number <= to_block)
+			.filter(|&(ref position, _)| after.map_or(true, |after| (position.block_number, position.index) > after))
+			.map(|(_, value)| ::rlp::decode::<TransactionAddress>(&value).expect("decode db value failed"))
+			// entries of retracted blocks might be left if the index was disabled for a while.
+			.filter(|address| self.is_canon(&address.block_hash))
+			.take(count)
+			.collect();
+
+		Some(addresses)
+	}
+
+	/// Get the address of a canonical transaction with given sender and nonce.
+	/// Only transactions imported while the index was maintained can be found.
+	pub fn transaction_address_by_sender_and_nonce(&self, sender: &Address, nonce: &U256) -> Option<TransactionAddress> {
+		let key = SenderNonce { sender: *sender, nonce: *nonce };
+		let address: TransactionAddress = self.db.read(db::COL_EXTRA, &key)?;
+
+		// entries of retracted blocks might be left if the index was disabled for a while.
+		if self.is_canon(&address.block_hash) {
+			Some(address)
+		} else {
+			None
+		}
+	}
+
+	/// Returns the first block from which the log index is complete,
+	/// or `None` if the log index is not maintained.
+	pub fn log_index_from(&self) -> Option<BlockNumber> {
+		*self.log_index_from.read()
+	}
+
+	/// Adds log index entries of given canonical block. Used to build the log index
+	/// for blocks imported before it was enabled.
+	/// Returns false if the block or its receipts are not in the database.
+	pub fn insert_log_index(&self, batch: &mut DBTransaction, number: BlockNumber) -> bool {
+		let receipts = match self.block_hash(number).and_then(|hash| self.block_receipts(&hash)) {
+			Some(receipts) => receipts.receipts,
+			None => return false,
+		};
+
+		for item in LogIndexItem::from_receipts(&receipts) {
+			batch.put(db::COL_LOG_INDEX, &item.key(number), &[]);
+		}
+		true
+	}
+
+	/// Updates the first block from which the log index is complete.
+	/// Does nothing if the log index is not maintained.
+	pub fn set_log_index_from(&self, batch: &mut DBTransaction, number: BlockNumber) {
+		let mut log_index_from = self.log_index_from.write();
+		if log_index_from.is_some() {
+			batch.put(db::COL_LOG_INDEX, LOG_INDEX_FROM_KEY, &::rlp::encode(&number));
+			*log_index_from = Some(number);
+		}
+	}
+
+	/// Returns numbers of blocks which may contain logs of given addresses and topics.
+	///
+	/// Returns `None` if the log index is not complete for given range or if there are
+	/// no addresses nor topics to look up, in which case blooms should be used instead.
+	pub fn blocks_with_log_index(&self, addresses: Option<&[Address]>, topics: &[Option<Vec<H256>>], from_block: BlockNumber, to_block: BlockNumber) -> Option<Vec<BlockNumber>> {
+		match self.log_index_from() {
+			Some(index_from) if index_from <= from_block => (),
+			_ => return None,
+		}
+
+		let addresses = addresses.into_iter()
+			.filter(|addresses| !addresses.is_empty())
+			.map(|addresses| addresses.iter().cloned().map(LogIndexItem::Address).collect::<Vec<_>>());
+		let topics = topics.iter()
+			.filter_map(|topics| topics.as_ref())
+			.filter(|topics| !topics.is_empty())
+			.map(|topics| topics.iter().cloned().map(LogIndexItem::Topic).collect::<Vec<_>>());
+
+		// block must contain one of the items of every group.
+		addresses.chain(topics)
+			.map(|items| items.iter()
+				.flat_map(|item| self.log_index_blocks(item, from_block, to_block))
+				.collect::<BTreeSet<_>>())
+			.fold(None, |acc: Option<BTreeSet<BlockNumber>>, blocks| match acc {
+				Some(acc) => Some(acc.intersection(&blocks).cloned().collect()),
+				None => Some(blocks),
+			})
+			.map(|blocks| blocks.into_iter().collect())
+	}
+
+	/// Returns numbers of indexed blocks of given log index item, in ascending order.
+	fn log_index_blocks(&self, item: &LogIndexItem, from_block: BlockNumber, to_block: BlockNumber) -> Vec<BlockNumber> {
+		// keys are ordered by block number, so seek to the first block of the range.
+		let prefix = item.prefix();
+		self.db.iter_from_prefix(db::COL_LOG_INDEX, &item.key(from_block))
+			.take_while(|&(ref key, _)| key.starts_with(&prefix))
+			.filter_map(|(key, _)| item.block_number(&key))
+			.take_while(|number| *number <= to_block)
+			.collect()
+	}
+
 	/// Get best block hash.
 	pub fn best_block_hash(&self) -> H256 {
 		self.best_block.read().header.hash()
@@ -1619,6 +1941,204 @@ mod tests {
 		assert_eq!(bc.transaction_address(&t1_hash), None);
 	}
 
+	#[test]
+	fn test_fork_sender_nonce_index() {
+		use db::Readable;
+		use blockchain::extras::SenderNonce;
+
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+		let t2 = Transaction {
+			nonce: 0.into(),
+			gas_price: 1.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+		let t3 = Transaction {
+			nonce: 1.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+
+		let sender = t1.sender();
+
+		let genesis = BlockBuilder::genesis();
+		let b1a = genesis.add_block_with_transactions(iter::once(t1));
+		let b2a = b1a.add_block_with_transactions(iter::once(t3));
+		let b1b = genesis.add_block_with_difficulty(9);
+		let b2b = b1b.add_block_with_transactions(iter::once(t2));
+		let b3b = b2b.add_block();
+
+		let b1a_hash = b1a.last().hash();
+		let b2a_hash = b2a.last().hash();
+		let b2b_hash = b2b.last().hash();
+		let b3b_hash = b3b.last().hash();
+
+		let db = new_db();
+		let config = Config { sender_nonce_index: true, ..Default::default() };
+		let bc = BlockChain::new(config, &genesis.last().encoded(), db.clone());
+
+		insert_block(&db, &bc, &b1a.last().encoded(), vec![]);
+		insert_block(&db, &bc, &b2a.last().encoded(), vec![]);
+		insert_block(&db, &bc, &b1b.last().encoded(), vec![]);
+		insert_block(&db, &bc, &b2b.last().encoded(), vec![]);
+
+		assert_eq!(bc.best_block_hash(), b2a_hash);
+		assert_eq!(bc.transaction_address_by_sender_and_nonce(&sender, &0.into()), Some(TransactionAddress {
+			block_hash: b1a_hash,
+			index: 0,
+		}));
+		assert_eq!(bc.transaction_address_by_sender_and_nonce(&sender, &1.into()), Some(TransactionAddress {
+			block_hash: b2a_hash,
+			index: 0,
+		}));
+		assert_eq!(bc.transaction_address_by_sender_and_nonce(&sender, &2.into()), None);
+
+		// the first transaction is replaced by the forked chain and the second one is retracted.
+		insert_block(&db, &bc, &b3b.last().encoded(), vec![]);
+
+		assert_eq!(bc.best_block_hash(), b3b_hash);
+		assert_eq!(bc.transaction_address_by_sender_and_nonce(&sender, &0.into()), Some(TransactionAddress {
+			block_hash: b2b_hash,
+			index: 0,
+		}));
+		assert_eq!(bc.transaction_address_by_sender_and_nonce(&sender, &1.into()), None);
+		let retracted: Option<TransactionAddress> = db.read(::db::COL_EXTRA, &SenderNonce { sender: sender, nonce: 1.into() });
+		assert_eq!(retracted, None);
+	}
+
+	#[test]
+	fn test_account_transactions_index() {
+		let recipient = Address::from(5);
+		let traced = Address::from(6);
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+		let t2 = Transaction {
+			nonce: 1.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Call(recipient),
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+		let t3 = Transaction {
+			nonce: 1.into(),
+			gas_price: 1.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+
+		let sender = t1.sender();
+
+		let genesis = BlockBuilder::genesis();
+		let b1 = genesis.add_block_with_transactions(iter::once(t1));
+		let b2a = b1.add_block_with_transactions(iter::once(t2));
+		let b2b = b1.add_block_with_difficulty(9);
+		let b3 = b2b.add_block_with_transactions(iter::once(t3));
+
+		let b1_hash = b1.last().hash();
+		let b2a_hash = b2a.last().hash();
+		let b3_hash = b3.last().hash();
+
+		let db = new_db();
+		let config = Config { account_transactions_index: true, ..Default::default() };
+		let bc = BlockChain::new(config, &genesis.last().encoded(), db.clone());
+
+		let insert = |bytes: &[u8]| {
+			let mut batch = db.transaction();
+			let route = bc.insert_block(&mut batch, bytes, vec![]);
+			// pretend the first transaction of every block touched `traced` internally.
+			bc.update_account_transactions(&mut batch, bytes, &route, |_| vec![vec![traced]]);
+			db.write(batch).unwrap();
+			bc.commit();
+		};
+
+		insert(&b1.last().encoded());
+		insert(&b2a.last().encoded());
+
+		let address = |block_hash| TransactionAddress { block_hash: block_hash, index: 0 };
+
+		assert_eq!(bc.account_transactions(&sender, 0, 10, None, 10), Some(vec![address(b1_hash), address(b2a_hash)]));
+		assert_eq!(bc.account_transactions(&sender, 2, 10, None, 10), Some(vec![address(b2a_hash)]));
+		assert_eq!(bc.account_transactions(&sender, 0, 10, None, 1), Some(vec![address(b1_hash)]));
+		assert_eq!(bc.account_transactions(&sender, 0, 10, Some(&address(b1_hash)), 10), Some(vec![address(b2a_hash)]));
+		assert_eq!(bc.account_transactions(&recipient, 0, 10, None, 10), Some(vec![address(b2a_hash)]));
+		assert_eq!(bc.account_transactions(&traced, 0, 10, None, 10), Some(vec![address(b1_hash), address(b2a_hash)]));
+
+		// the call to `recipient` is retracted by the forked chain
+		insert(&b2b.last().encoded());
+		insert(&b3.last().encoded());
+
+		assert_eq!(bc.best_block_hash(), b3_hash);
+		assert_eq!(bc.account_transactions(&sender, 0, 10, None, 10), Some(vec![address(b1_hash), address(b3_hash)]));
+		assert_eq!(bc.account_transactions(&recipient, 0, 10, None, 10), Some(vec![]));
+	}
+
+	#[test]
+	fn test_account_transactions_index_unordered() {
+		let recipient = Address::from(5);
+		let t1 = Transaction {
+			nonce: 0.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Call(recipient),
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+		let t2 = Transaction {
+			nonce: 1.into(),
+			gas_price: 0.into(),
+			gas: 100_000.into(),
+			action: Action::Create,
+			value: 100.into(),
+			data: vec![],
+		}.sign(&secret(), None);
+
+		let sender = t1.sender();
+
+		let genesis = BlockBuilder::genesis();
+		let b1 = genesis.add_block_with_transactions(iter::once(t1));
+		let b2 = b1.add_block();
+		let b3 = b2.add_block_with_transactions(iter::once(t2));
+
+		let db = new_db();
+		let config = Config { account_transactions_index: true, ..Default::default() };
+		let bc = BlockChain::new(config, &genesis.last().encoded(), db.clone());
+
+		let mut batch = db.transaction();
+		for block in &[&b1, &b2, &b3] {
+			bc.insert_unordered_block(&mut batch, &block.last().encoded(), vec![], None, true, true);
+			bc.commit();
+		}
+		db.write(batch).unwrap();
+
+		let address = |block_hash| TransactionAddress { block_hash: block_hash, index: 0 };
+
+		assert_eq!(bc.account_transactions(&sender, 0, 10, None, 10), Some(vec![address(b1.last().hash()), address(b3.last().hash())]));
+		// there are no entries at the first block of the range.
+		assert_eq!(bc.account_transactions(&sender, 2, 10, None, 10), Some(vec![address(b3.last().hash())]));
+		assert_eq!(bc.account_transactions(&recipient, 0, 10, None, 10), Some(vec![address(b1.last().hash())]));
+	}
+
 	#[test]
 	fn test_overwriting_transaction_addresses() {
 		let t1 = Transaction {
@@ -2083,6 +2603,63 @@ mod tests {
 		]);
 	}
 
+	#[test]
+	fn test_log_index() {
+		let receipt = |address: u64, topic: u64| Receipt {
+			outcome: TransactionOutcome::StateRoot(H256::default()),
+			gas_used: 10_000.into(),
+			log_bloom: Default::default(),
+			logs: vec![
+				LogEntry { address: address.into(), topics: vec![topic.into()], data: vec![], },
+			],
+		};
+
+		let genesis = BlockBuilder::genesis();
+		let b1 = genesis.add_block();
+		let b2 = b1.add_block();
+		let b2_fork = b1.add_block_with_difficulty(20u64);
+
+		let db = new_db();
+		let config = Config { log_index: true, ..Default::default() };
+		let bc = BlockChain::new(config.clone(), &genesis.last().encoded(), db.clone());
+		assert_eq!(bc.log_index_from(), Some(0));
+
+		insert_block(&db, &bc, &b1.last().encoded(), vec![receipt(1, 10)]);
+		insert_block(&db, &bc, &b2.last().encoded(), vec![receipt(2, 10)]);
+
+		let address = |a: u64| Some(vec![Address::from(a)]);
+		let topic = |t: u64| vec![Some(vec![H256::from(t)]), None, None, None];
+
+		assert_eq!(bc.blocks_with_log_index(address(1).as_ref().map(|a| &a[..]), &[], 0, 5), Some(vec![1]));
+		assert_eq!(bc.blocks_with_log_index(None, &topic(10), 0, 5), Some(vec![1, 2]));
+		assert_eq!(bc.blocks_with_log_index(None, &topic(10), 2, 5), Some(vec![2]));
+		assert_eq!(bc.blocks_with_log_index(address(2).as_ref().map(|a| &a[..]), &topic(10), 0, 5), Some(vec![2]));
+		assert_eq!(bc.blocks_with_log_index(None, &[None, None, None, None], 0, 5), None);
+
+		// reorganization replaces entries of the retracted block.
+		insert_block(&db, &bc, &b2_fork.last().encoded(), vec![receipt(3, 11)]);
+		assert_eq!(bc.blocks_with_log_index(None, &topic(10), 0, 5), Some(vec![1]));
+		assert_eq!(bc.blocks_with_log_index(address(2).as_ref().map(|a| &a[..]), &[], 0, 5), Some(vec![]));
+		assert_eq!(bc.blocks_with_log_index(address(3).as_ref().map(|a| &a[..]), &topic(11), 0, 5), Some(vec![2]));
+
+		// disabling the index makes it incomplete.
+		let bc = BlockChain::new(Config::default(), &genesis.last().encoded(), db.clone());
+		assert_eq!(bc.log_index_from(), None);
+		assert_eq!(bc.blocks_with_log_index(None, &topic(11), 0, 5), None);
+
+		// enabling it again covers new blocks only, until older ones are indexed.
+		let bc = BlockChain::new(config, &genesis.last().encoded(), db.clone());
+		assert_eq!(bc.log_index_from(), Some(3));
+		assert_eq!(bc.blocks_with_log_index(None, &topic(11), 0, 5), None);
+
+		let mut batch = db.transaction();
+		assert!(bc.insert_log_index(&mut batch, 2));
+		assert!(bc.insert_log_index(&mut batch, 1));
+		bc.set_log_index_from(&mut batch, 0);
+		db.write(batch).unwrap();
+		assert_eq!(bc.blocks_with_log_index(None, &topic(11), 0, 5), Some(vec![2]));
+	}
+
 	#[test]
 	fn test_bloom_filter_simple() {
 		let bloom_b1: Bloom = "00000020000000000000000000000000000000000000000002000000000000000000000000000000020000000000000000000000000000000000000000000000000000000000000000000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000040000000000000010000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000008000400000000000000000000002000".into();
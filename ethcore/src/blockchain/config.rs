@@ -23,6 +23,12 @@ pub struct Config {
 	pub pref_cache_size: usize,
 	/// Maximum cache size in bytes.
 	pub max_cache_size: usize,
+	/// Maintain the address and topic log index.
+	pub log_index: bool,
+	/// Maintain the index of transactions by sender and nonce.
+	pub sender_nonce_index: bool,
+	/// Maintain the index of transactions by touched account.
+	pub account_transactions_index: bool,
 }
 
 impl Default for Config {
@@ -30,6 +36,9 @@ impl Default for Config {
 		Config {
 			pref_cache_size: 1 << 14,
 			max_cache_size: 1 << 20,
+			log_index: false,
+			sender_nonce_index: false,
+			account_transactions_index: false,
 		}
 	}
 }
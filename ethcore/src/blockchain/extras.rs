@@ -17,6 +17,7 @@
 //! Blockchain DB extras.
 
 use std::ops;
+use std::collections::HashSet;
 use std::io::Write;
 use blooms::{GroupPosition, BloomGroup};
 use db::Key;
@@ -25,7 +26,7 @@ use header::BlockNumber;
 use receipt::Receipt;
 
 use heapsize::HeapSizeOf;
-use ethereum_types::{H256, H264, U256};
+use ethereum_types::{H256, H264, U256, Address};
 use kvdb::PREFIX_LEN as DB_PREFIX_LEN;
 
 /// Represents index of extra data in database
@@ -45,6 +46,10 @@ pub enum ExtrasIndex {
 	EpochTransitions = 5,
 	/// Pending epoch transition data index.
 	PendingEpochTransition = 6,
+	/// Transaction address by sender and nonce index.
+	SenderNonce = 7,
+	/// Transactions by account index.
+	AccountTransaction = 8,
 }
 
 fn with_index(hash: &H256, i: ExtrasIndex) -> H264 {
@@ -119,6 +124,101 @@ impl Key<TransactionAddress> for H256 {
 	}
 }
 
+/// Sender and nonce of a transaction.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct SenderNonce {
+	/// Transaction sender.
+	pub sender: Address,
+	/// Transaction nonce.
+	pub nonce: U256,
+}
+
+pub struct SenderNonceKey([u8; 53]);
+
+impl ops::Deref for SenderNonceKey {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl Key<TransactionAddress> for SenderNonce {
+	type Target = SenderNonceKey;
+
+	fn key(&self) -> Self::Target {
+		let mut result = [0u8; 53];
+		result[0] = ExtrasIndex::SenderNonce as u8;
+		result[1..21].copy_from_slice(&self.sender);
+		self.nonce.to_big_endian(&mut result[21..]);
+		SenderNonceKey(result)
+	}
+}
+
+/// Position of a transaction touching an account.
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+pub struct AccountTransaction {
+	/// Account touched by the transaction.
+	pub account: Address,
+	/// Number of the block containing the transaction.
+	pub block_number: BlockNumber,
+	/// Index of the transaction within the block.
+	pub index: usize,
+}
+
+/// Length of account transaction keys.
+pub const ACCOUNT_TRANSACTION_KEY_LEN: usize = 33;
+
+impl AccountTransaction {
+	/// Returns the common prefix of keys of all transactions touching given account.
+	/// Keys of an account are ordered by block number and transaction index.
+	pub fn prefix(account: &Address) -> [u8; 21] {
+		let mut result = [0u8; 21];
+		result[0] = ExtrasIndex::AccountTransaction as u8;
+		result[1..].copy_from_slice(account);
+		result
+	}
+
+	/// Decodes position of the transaction from the key.
+	pub fn from_key(key: &[u8]) -> Option<Self> {
+		if key.len() != ACCOUNT_TRANSACTION_KEY_LEN || key[0] != ExtrasIndex::AccountTransaction as u8 {
+			return None;
+		}
+
+		Some(AccountTransaction {
+			account: Address::from_slice(&key[1..21]),
+			block_number: key[21..29].iter().fold(0, |n, b| (n << 8) | *b as BlockNumber),
+			index: key[29..].iter().fold(0, |n, b| (n << 8) | *b as usize),
+		})
+	}
+}
+
+pub struct AccountTransactionKey([u8; ACCOUNT_TRANSACTION_KEY_LEN]);
+
+impl ops::Deref for AccountTransactionKey {
+	type Target = [u8];
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl Key<TransactionAddress> for AccountTransaction {
+	type Target = AccountTransactionKey;
+
+	fn key(&self) -> Self::Target {
+		let mut result = [0u8; ACCOUNT_TRANSACTION_KEY_LEN];
+		result[..21].copy_from_slice(&AccountTransaction::prefix(&self.account));
+		for i in 0..8 {
+			result[21 + i] = (self.block_number >> ((7 - i) * 8)) as u8;
+		}
+		for i in 0..4 {
+			result[29 + i] = (self.index >> ((3 - i) * 8)) as u8;
+		}
+		AccountTransactionKey(result)
+	}
+}
+
 impl Key<BlockReceipts> for H256 {
 	type Target = H264;
 
@@ -166,6 +266,72 @@ impl Key<EpochTransitions> for u64 {
 	}
 }
 
+/// Key of the log index entry which marks the first block
+/// from which the log index is known to be complete.
+pub const LOG_INDEX_FROM_KEY: &'static [u8] = b"from";
+
+/// Item of the log index.
+///
+/// The log index maps every address and topic to the numbers of canonical
+/// blocks containing logs emitted by that address or with that topic.
+#[derive(Copy, Debug, Hash, Eq, PartialEq, Clone)]
+pub enum LogIndexItem {
+	/// Address of the contract which emitted a log.
+	Address(Address),
+	/// One of the log topics, regardless of its position.
+	Topic(H256),
+}
+
+impl LogIndexItem {
+	/// Returns the common prefix of all entries of this item.
+	pub fn prefix(&self) -> Vec<u8> {
+		match *self {
+			LogIndexItem::Address(ref address) => {
+				let mut prefix = vec![0u8];
+				prefix.extend_from_slice(address);
+				prefix
+			},
+			LogIndexItem::Topic(ref topic) => {
+				let mut prefix = vec![1u8];
+				prefix.extend_from_slice(topic);
+				prefix
+			},
+		}
+	}
+
+	/// Returns the key of the entry of this item at given block.
+	///
+	/// Block numbers are big-endian, so entries of a single item are ordered by block number.
+	pub fn key(&self, number: BlockNumber) -> Vec<u8> {
+		let mut key = self.prefix();
+		for i in (0..8).rev() {
+			key.push((number >> (i * 8)) as u8);
+		}
+		key
+	}
+
+	/// Decodes block number from the key of an entry of this item.
+	pub fn block_number(&self, key: &[u8]) -> Option<BlockNumber> {
+		let prefix_len = self.prefix().len();
+		if key.len() != prefix_len + 8 {
+			return None;
+		}
+
+		Some(key[prefix_len..].iter().fold(0, |n, b| (n << 8) | *b as BlockNumber))
+	}
+
+	/// Returns all items of the log index found in given receipts.
+	pub fn from_receipts(receipts: &[Receipt]) -> Vec<LogIndexItem> {
+		receipts.iter()
+			.flat_map(|receipt| receipt.logs.iter())
+			.flat_map(|log| ::std::iter::once(LogIndexItem::Address(log.address))
+				.chain(log.topics.iter().cloned().map(LogIndexItem::Topic)))
+			.collect::<HashSet<_>>()
+			.into_iter()
+			.collect()
+	}
+}
+
 /// Familial details concerning a block
 #[derive(Debug, Clone, RlpEncodable, RlpDecodable)]
 pub struct BlockDetails {
@@ -228,7 +394,8 @@ pub struct EpochTransitions {
 #[cfg(test)]
 mod tests {
 	use rlp::*;
-	use super::BlockReceipts;
+	use db::Key;
+	use super::{BlockReceipts, LogIndexItem, AccountTransaction, TransactionAddress};
 
 	#[test]
 	fn encode_block_receipts() {
@@ -241,4 +408,30 @@ mod tests {
 		assert!(s.is_finished(), "List should be finished now");
 		s.out();
 	}
+
+	#[test]
+	fn log_index_keys_are_ordered_by_block_number() {
+		let item = LogIndexItem::Topic(5.into());
+		let first = item.key(255);
+		let second = item.key(256);
+
+		assert!(first.starts_with(&item.prefix()));
+		assert!(first < second);
+		assert_eq!(item.block_number(&first), Some(255));
+		assert_eq!(item.block_number(&second), Some(256));
+		assert_eq!(LogIndexItem::Address(5.into()).block_number(&first), None);
+	}
+
+	#[test]
+	fn account_transaction_key_roundtrip() {
+		let position = AccountTransaction {
+			account: 5.into(),
+			block_number: 258,
+			index: 3,
+		};
+		let key = Key::<TransactionAddress>::key(&position);
+
+		assert!(key.starts_with(&AccountTransaction::prefix(&position.account)));
+		assert_eq!(AccountTransaction::from_key(&key), Some(position));
+	}
 }
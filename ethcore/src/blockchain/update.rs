@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use ethereum_types::H256;
 use header::BlockNumber;
 use blockchain::block_info::BlockInfo;
-use blockchain::extras::{BlockDetails, BlockReceipts, TransactionAddress};
+use blockchain::extras::{BlockDetails, BlockReceipts, TransactionAddress, LogIndexItem, SenderNonce};
 use blooms::{BloomGroup, GroupPosition};
 
 /// Block extras update info.
@@ -21,4 +21,8 @@ pub struct ExtrasUpdate<'a> {
 	pub blocks_blooms: HashMap<GroupPosition, BloomGroup>,
 	/// Modified transaction addresses (None signifies removed transactions).
 	pub transactions_addresses: HashMap<H256, Option<TransactionAddress>>,
+	/// Modified log index entries (false signifies removed entries).
+	pub log_index: HashMap<(LogIndexItem, BlockNumber), bool>,
+	/// Modified transaction addresses by sender and nonce (None signifies removed transactions).
+	pub sender_nonces: HashMap<SenderNonce, Option<TransactionAddress>>,
 }
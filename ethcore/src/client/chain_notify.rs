@@ -150,4 +150,9 @@ pub trait ChainNotify : Send + Sync {
 	) {
 		// does nothing by default
 	}
+
+	/// fires when transactions received from peers are rejected by the transaction pool
+	fn transactions_rejected(&self, _hashes: &[H256]) {
+		// does nothing by default
+	}
 }
@@ -92,6 +92,8 @@ const MAX_ANCIENT_BLOCKS_QUEUE_SIZE: usize = 4096;
 const MAX_ANCIENT_BLOCKS_TO_IMPORT: usize = 4;
 const MAX_QUEUE_SIZE_TO_SLEEP_ON: usize = 2;
 const MIN_HISTORY_SIZE: u64 = 8;
+// Number of re-traced blocks written to the database at once.
+const RETRACE_FLUSH_INTERVAL: u64 = 128;
 
 /// Report on the status of a client.
 #[derive(Default, Clone, Debug, Eq, PartialEq)]
@@ -503,6 +505,9 @@ impl Importer {
 			retracted: route.retracted.len()
 		});
 
+		// traces of the imported block are already available.
+		chain.update_account_transactions(&mut batch, block_data, &route, |hash| client.tracedb.read().transactions_accounts(hash));
+
 		let is_canon = route.enacted.last().map_or(false, |h| h == hash);
 		state.sync_cache(&route.enacted, &route.retracted, is_canon);
 		// Final commit to the DB
@@ -1114,6 +1119,105 @@ impl Client {
 		self.history
 	}
 
+	// re-execute a canonical block on top of its parent state and write the
+	// resulting traces into the trace database.
+	fn retrace_block(&self, number: BlockNumber) -> Result<(), String> {
+		let _import_lock = self.importer.import_lock.lock();
+
+		let (hash, block, parent, is_epoch_begin) = {
+			let chain = self.chain.read();
+			let hash = chain.block_hash(number).ok_or_else(|| format!("Block #{} not found", number))?;
+			let block = chain.block(&hash).ok_or_else(|| format!("Block #{} not found", number))?;
+			let parent = chain.block_header_data(&block.parent_hash())
+				.ok_or_else(|| format!("Parent of block #{} not found", number))?
+				.decode()
+				.map_err(|e| format!("Invalid parent header of block #{}: {}", number, e))?;
+			let is_epoch_begin = chain.epoch_transition(parent.number(), parent.hash()).is_some();
+
+			(hash, block, parent, is_epoch_begin)
+		};
+
+		let db = self.state_db.read().boxed_clone_canon(&parent.hash());
+		if !db.journal_db().contains(parent.state_root()) {
+			return Err(format!("State of block #{} is not available", parent.number()));
+		}
+
+		let transactions = block.transactions()
+			.into_iter()
+			.map(SignedTransaction::new)
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(|e| format!("Invalid transaction in block #{}: {}", number, e))?;
+
+		let header = block.decode_header();
+		let block_bytes = block.into_inner();
+		let preverified = PreverifiedBlock {
+			header: header,
+			transactions: transactions,
+			bytes: block_bytes.clone(),
+		};
+
+		let locked_block = enact_verified(
+			preverified,
+			&*self.engine,
+			true,
+			db,
+			&parent,
+			self.build_last_hashes(&parent.hash()),
+			self.factories.clone(),
+			is_epoch_begin,
+		).map_err(|e| format!("Failed to re-execute block #{}: {}", number, e))?;
+
+		let traces = locked_block.traces().clone().drain();
+		let mut batch = DBTransaction::new();
+		self.tracedb.read().backfill(&mut batch, TraceImportRequest {
+			traces: traces.into(),
+			block_hash: hash,
+			block_number: number,
+			enacted: vec![hash],
+			retracted: 0,
+		});
+
+		// index internal calls of the block as well.
+		let route = ImportRoute {
+			retracted: Vec::new(),
+			enacted: vec![hash],
+			omitted: Vec::new(),
+		};
+		self.chain.read().update_account_transactions(&mut batch, &block_bytes, &route, |hash| self.tracedb.read().transactions_accounts(hash));
+		self.db.read().write_buffered(batch);
+
+		Ok(())
+	}
+
+	/// Extend the log index to at most `count` blocks imported before it was enabled.
+	/// Blocks are indexed from the newest to the oldest one.
+	/// Returns the first block from which the log index is complete.
+	pub fn build_log_index(&self, count: u64) -> Result<BlockNumber, String> {
+		let _import_lock = self.importer.import_lock.lock();
+
+		let chain = self.chain.read();
+		let from = chain.log_index_from().ok_or_else(|| "Log index is not enabled".to_owned())?;
+		// genesis has no receipts.
+		let first = ::std::cmp::max(chain.first_block_number().unwrap_or(0), 1);
+		let mut indexed = from;
+
+		let mut batch = DBTransaction::new();
+		while indexed > first && from - indexed < count {
+			if !chain.insert_log_index(&mut batch, indexed - 1) {
+				return Err(format!("Receipts of block #{} not found", indexed - 1));
+			}
+			indexed -= 1;
+		}
+
+		if indexed == 1 {
+			indexed = 0;
+		}
+
+		chain.set_log_index_from(&mut batch, indexed);
+		self.db.read().write(batch).map_err(|e| format!("Failed to write log index: {}", e))?;
+		Ok(indexed)
+	}
+
 	fn block_hash(chain: &BlockChain, id: BlockId) -> Option<H256> {
 		match id {
 			BlockId::Hash(hash) => Some(hash),
@@ -1727,6 +1831,36 @@ impl BlockChainClient for Client {
 		self.transaction_address(id).and_then(|address| self.chain.read().transaction(&address))
 	}
 
+	fn transaction_by_sender_and_nonce(&self, sender: &Address, nonce: &U256) -> Option<LocalizedTransaction> {
+		let chain = self.chain.read();
+		chain.transaction_address_by_sender_and_nonce(sender, nonce).and_then(|address| chain.transaction(&address))
+	}
+
+	fn sender_nonce_index_enabled(&self) -> bool {
+		self.config.blockchain.sender_nonce_index
+	}
+
+	fn list_account_transactions(&self, account: &Address, from: BlockId, to: BlockId, after: Option<&H256>, count: u64) -> Option<Vec<H256>> {
+		let from = self.block_number_ref(&from)?;
+		let to = self.block_number_ref(&to)?;
+
+		let chain = self.chain.read();
+		let after = match after {
+			Some(hash) => Some(chain.transaction_address(hash)?),
+			None => None,
+		};
+
+		let addresses = chain.account_transactions(account, from, to, after.as_ref(), count as usize)?;
+		Some(addresses.into_iter()
+			.filter_map(|address| chain.transaction(&address))
+			.map(|tx| tx.hash())
+			.collect())
+	}
+
+	fn account_transactions_index_enabled(&self) -> bool {
+		self.config.blockchain.account_transactions_index
+	}
+
 	fn uncle(&self, id: UncleId) -> Option<encoded::Header> {
 		let index = id.position;
 		self.block_body(id.block).and_then(|body| body.view().uncle_rlp_at(index))
@@ -1810,14 +1944,21 @@ impl BlockChainClient for Client {
 				let from = self.block_number_ref(&filter.from_block)?;
 				let to = self.block_number_ref(&filter.to_block)?;
 
-				filter.bloom_possibilities().iter()
-					.map(|bloom| {
-						chain.blocks_with_bloom(bloom, from, to)
-					})
-					.flat_map(|m| m)
-					// remove duplicate elements
-					.collect::<BTreeSet<u64>>()
-					.into_iter()
+				// Prefer the log index if it covers the whole range.
+				let numbers = match chain.blocks_with_log_index(filter.address.as_ref().map(|a| &a[..]), &filter.topics, from, to) {
+					Some(numbers) => numbers,
+					None => filter.bloom_possibilities().iter()
+						.map(|bloom| {
+							chain.blocks_with_bloom(bloom, from, to)
+						})
+						.flat_map(|m| m)
+						// remove duplicate elements
+						.collect::<BTreeSet<u64>>()
+						.into_iter()
+						.collect(),
+				};
+
+				numbers.into_iter()
 					.filter_map(|n| chain.block_hash(n))
 					.collect::<Vec<H256>>()
 
@@ -1908,6 +2049,24 @@ impl BlockChainClient for Client {
 			.and_then(|number| self.tracedb.read().block_traces(number))
 	}
 
+	fn retrace_blocks(&self, from: BlockId, to: BlockId) -> Result<u64, String> {
+		let from = self.block_number(from).ok_or("From block could not be found")?;
+		let to = self.block_number(to).ok_or("To block could not be found")?;
+
+		// genesis traces are written when the trace database is created.
+		let mut retraced = 0;
+		for number in ::std::cmp::max(from, 1)..(to + 1) {
+			self.retrace_block(number)?;
+			retraced += 1;
+			if retraced % RETRACE_FLUSH_INTERVAL == 0 {
+				self.db.read().flush().map_err(|e| format!("Failed to flush traces: {}", e))?;
+			}
+		}
+
+		self.db.read().flush().map_err(|e| format!("Failed to flush traces: {}", e))?;
+		Ok(retraced)
+	}
+
 	fn last_hashes(&self) -> LastHashes {
 		(*self.build_last_hashes(&self.chain.read().best_block_hash())).clone()
 	}
@@ -1916,6 +2075,10 @@ impl BlockChainClient for Client {
 		self.importer.miner.ready_transactions(self, max_len, ::miner::PendingOrdering::Priority)
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.importer.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> {
 		self.engine.signing_chain_id(&self.latest_env_info())
 	}
@@ -1990,7 +2153,21 @@ impl IoClient for Client {
 				notify.transactions_received(&txs, peer_id);
 			});
 
-			client.importer.miner.import_external_transactions(client, txs);
+			let hashes: Vec<H256> = txs.iter().map(|tx| tx.hash()).collect();
+			let results = client.importer.miner.import_external_transactions(client, txs);
+			let rejected: Vec<H256> = hashes.into_iter()
+				.zip(results)
+				.filter(|&(_, ref result)| match *result {
+					Ok(_) | Err(transaction::Error::AlreadyImported) => false,
+					Err(_) => true,
+				})
+				.map(|(hash, _)| hash)
+				.collect();
+			if !rejected.is_empty() {
+				client.notify(|notify| {
+					notify.transactions_rejected(&rejected);
+				});
+			}
 		}).unwrap_or_else(|e| {
 			debug!(target: "client", "Ignoring {} transactions: {}", len, e);
 		});
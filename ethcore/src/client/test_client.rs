@@ -114,6 +114,10 @@ pub struct TestBlockChainClient {
 	pub history: RwLock<Option<u64>>,
 	/// Is disabled
 	pub disabled: AtomicBool,
+	/// Whether the index of transactions by sender and nonce is reported as maintained.
+	pub sender_nonce_index: AtomicBool,
+	/// Whether the index of transactions by touched account is reported as maintained.
+	pub account_transactions_index: AtomicBool,
 }
 
 /// Used for generating test client blocks.
@@ -180,6 +184,8 @@ impl TestBlockChainClient {
 			traces: RwLock::new(None),
 			history: RwLock::new(None),
 			disabled: AtomicBool::new(false),
+			sender_nonce_index: AtomicBool::new(true),
+			account_transactions_index: AtomicBool::new(true),
 		};
 
 		// insert genesis hash.
@@ -661,6 +667,22 @@ impl BlockChainClient for TestBlockChainClient {
 		None	// Simple default.
 	}
 
+	fn transaction_by_sender_and_nonce(&self, _sender: &Address, _nonce: &U256) -> Option<LocalizedTransaction> {
+		None	// Simple default.
+	}
+
+	fn sender_nonce_index_enabled(&self) -> bool {
+		self.sender_nonce_index.load(AtomicOrder::Relaxed)
+	}
+
+	fn list_account_transactions(&self, _account: &Address, _from: BlockId, _to: BlockId, _after: Option<&H256>, _count: u64) -> Option<Vec<H256>> {
+		None
+	}
+
+	fn account_transactions_index_enabled(&self) -> bool {
+		self.account_transactions_index.load(AtomicOrder::Relaxed)
+	}
+
 	fn uncle(&self, _id: UncleId) -> Option<encoded::Header> {
 		None	// Simple default.
 	}
@@ -817,10 +839,20 @@ impl BlockChainClient for TestBlockChainClient {
 		self.traces.read().clone()
 	}
 
+	fn retrace_blocks(&self, from: BlockId, to: BlockId) -> Result<u64, String> {
+		let from = self.block_number(from).ok_or("From block could not be found")?;
+		let to = self.block_number(to).ok_or("To block could not be found")?;
+		Ok((from..(to + 1)).count() as u64)
+	}
+
 	fn ready_transactions(&self, max_len: usize) -> Vec<Arc<VerifiedTransaction>> {
 		self.miner.ready_transactions(self, max_len, miner::PendingOrdering::Priority)
 	}
 
+	fn queued_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>> {
+		self.miner.transaction(hash)
+	}
+
 	fn signing_chain_id(&self) -> Option<u64> { None }
 
 	fn mode(&self) -> Mode { Mode::Active }
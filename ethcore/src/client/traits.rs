@@ -268,6 +268,21 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Get transaction with given hash.
 	fn transaction(&self, id: TransactionId) -> Option<LocalizedTransaction>;
 
+	/// Get canonical transaction with given sender and nonce,
+	/// if the index of transactions by sender and nonce is maintained.
+	fn transaction_by_sender_and_nonce(&self, sender: &Address, nonce: &U256) -> Option<LocalizedTransaction>;
+
+	/// Whether the index of transactions by sender and nonce is maintained.
+	fn sender_nonce_index_enabled(&self) -> bool;
+
+	/// Get hashes of canonical transactions touching given account within given range of blocks,
+	/// if the index of transactions by account is maintained, otherwise `None`.
+	/// If `after` is set the list starts with the following transaction.
+	fn list_account_transactions(&self, account: &Address, from: BlockId, to: BlockId, after: Option<&H256>, count: u64) -> Option<Vec<H256>>;
+
+	/// Whether the index of transactions by touched account is maintained.
+	fn account_transactions_index_enabled(&self) -> bool;
+
 	/// Get uncle with given id.
 	fn uncle(&self, id: UncleId) -> Option<encoded::Header>;
 
@@ -317,12 +332,21 @@ pub trait BlockChainClient : Sync + Send + AccountData + BlockChain + CallContra
 	/// Returns traces created by transaction from block.
 	fn block_traces(&self, trace: BlockId) -> Option<Vec<LocalizedTrace>>;
 
+	/// Re-executes blocks in the given range and writes their traces into the trace database,
+	/// even if tracing is disabled, so that it can be enabled later on.
+	/// State of the parent of the first block must be available.
+	/// Returns the number of blocks which have been traced.
+	fn retrace_blocks(&self, from: BlockId, to: BlockId) -> Result<u64, String>;
+
 	/// Get last hashes starting from best block.
 	fn last_hashes(&self) -> LastHashes;
 
 	/// List all transactions that are allowed into the next block.
 	fn ready_transactions(&self, max_len: usize) -> Vec<Arc<VerifiedTransaction>>;
 
+	/// Get a transaction from the pool given its hash.
+	fn queued_transaction(&self, hash: &H256) -> Option<Arc<VerifiedTransaction>>;
+
 	/// Sorted list of transaction gas prices from at least last sample_size blocks.
 	fn gas_price_corpus(&self, sample_size: usize) -> ::stats::Corpus<U256> {
 		let mut h = self.chain_info().best_block_hash;
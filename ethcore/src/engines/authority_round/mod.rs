@@ -864,6 +864,16 @@ impl Engine<EthereumMachine> for AuthorityRound {
 		}
 	}
 
+	fn fork_transitions(&self) -> Vec<BlockNumber> {
+		vec![
+			self.validate_score_transition,
+			self.validate_step_transition,
+			self.block_reward_contract_transition,
+			self.maximum_uncle_count_transition,
+			self.empty_steps_transition,
+		]
+	}
+
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
 		let parent_step = header_step(parent, self.empty_steps_transition).expect("Header has been verified; qed");
 		let current_step = self.step.inner.load();
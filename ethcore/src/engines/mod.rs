@@ -331,6 +331,9 @@ pub trait Engine<M: Machine>: Sync + Send {
 		None
 	}
 
+	/// Block numbers of engine-specific hard forks.
+	fn fork_transitions(&self) -> Vec<BlockNumber> { Vec::new() }
+
 	/// Whether this engine supports warp sync.
 	fn supports_warp(&self) -> bool {
 		self.snapshot_components().is_some()
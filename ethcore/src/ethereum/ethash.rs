@@ -216,6 +216,21 @@ impl Engine<EthereumMachine> for Arc<Ethash> {
 
 	fn maximum_uncle_count(&self, _block: BlockNumber) -> usize { 2 }
 
+	fn fork_transitions(&self) -> Vec<BlockNumber> {
+		let p = &self.ethash_params;
+		vec![
+			p.homestead_transition,
+			p.difficulty_hardfork_transition,
+			p.bomb_defuse_transition,
+			p.eip100b_transition,
+			p.ecip1010_pause_transition,
+			p.ecip1010_continue_transition,
+			p.mcip3_transition,
+			p.eip649_transition,
+			p.expip2_transition,
+		]
+	}
+
 	fn populate_from_parent(&self, header: &mut Header, parent: &Header) {
 		let difficulty = self.calculate_difficulty(header, parent);
 		header.set_difficulty(difficulty);
@@ -197,4 +197,11 @@ mod tests {
 
 		let _ = frontier.engine;
 	}
+
+	#[test]
+	fn frontier_fork_transitions() {
+		let frontier = new_foundation(&::std::env::temp_dir());
+
+		assert_eq!(frontier.fork_transitions(), vec![1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000]);
+	}
 }
@@ -77,6 +77,20 @@ impl From<::ethjson::spec::EthashParams> for EthashExtensions {
 	}
 }
 
+impl EthashExtensions {
+	/// Block numbers of all transitions configured in these extensions.
+	pub fn transitions(&self) -> Vec<BlockNumber> {
+		vec![
+			self.homestead_transition,
+			self.eip150_transition,
+			self.eip160_transition,
+			self.eip161abc_transition,
+			self.eip161d_transition,
+			self.dao_hardfork_transition,
+		]
+	}
+}
+
 /// Special rules to be applied to the schedule.
 pub type ScheduleCreationRules = Fn(&mut Schedule, BlockNumber) + Sync + Send;
 
@@ -97,6 +97,11 @@ const DEFAULT_MINIMAL_GAS_PRICE: u64 = 20_000_000_000;
 /// in case we have only a fraction of available block gas limit left.
 const MAX_SKIPPED_TRANSACTIONS: usize = 128;
 
+/// Maximal time spent on packing transactions into a block
+/// when `PrioritizationStrategy::GasPriceAndGasUsage` is used.
+/// Transactions are pushed in priority order if packing takes longer.
+const PACKING_TIME_BUDGET: Duration = Duration::from_millis(50);
+
 /// Configures the behaviour of the miner.
 #[derive(Debug, PartialEq)]
 pub struct MinerOptions {
@@ -211,6 +216,7 @@ pub struct Miner {
 	engine: Arc<EthEngine>,
 	accounts: Option<Arc<AccountProvider>>,
 	io_channel: RwLock<Option<IoChannel<ClientIoMessage>>>,
+	packing_stats: Mutex<pool::packing::PackingStats>,
 }
 
 impl Miner {
@@ -225,6 +231,11 @@ impl Miner {
 		self.transaction_queue.add_listener(f);
 	}
 
+	/// Set a callback to be notified about transactions being replaced or mined.
+	pub fn add_transaction_events_listener(&self, f: Box<Fn(&[pool::TransactionEvent]) + Send + Sync>) {
+		self.transaction_queue.add_event_listener(f);
+	}
+
 	/// Creates new instance of miner Arc.
 	pub fn new(
 		options: MinerOptions,
@@ -255,6 +266,7 @@ impl Miner {
 			accounts,
 			engine: spec.engine.clone(),
 			io_channel: RwLock::new(None),
+			packing_stats: Default::default(),
 		}
 	}
 
@@ -284,6 +296,32 @@ impl Miner {
 		self.sealing.lock().queue.reset();
 	}
 
+	/// Returns all non-local transactions in the queue in the order they were inserted,
+	/// along with a flag indicating whether their sender is currently penalized.
+	pub fn queue_snapshot(&self) -> Vec<(Arc<VerifiedTransaction>, bool)> {
+		self.transaction_queue.snapshot()
+			.into_iter()
+			.filter(|&(ref tx, _)| !tx.is_local())
+			.collect()
+	}
+
+	/// Imports transactions restored from a snapshot of the queue.
+	///
+	/// Transactions are verified again like any other external transactions
+	/// and given senders are penalized the same way they were before the snapshot was taken.
+	pub fn import_queue_snapshot<C: miner::BlockChainClient>(
+		&self,
+		chain: &C,
+		transactions: Vec<UnverifiedTransaction>,
+		penalized: &[Address],
+	) -> Vec<Result<(), transaction::Error>> {
+		use miner::MinerService;
+
+		let results = self.import_external_transactions(chain, transactions);
+		self.transaction_queue.penalize(penalized);
+		results
+	}
+
 	/// Updates transaction queue verification limits.
 	///
 	/// Limits consist of current block gas limit and minimal gas price.
@@ -405,6 +443,20 @@ impl Miner {
 			}
 		);
 
+		let pending = match self.options.tx_queue_strategy {
+			PrioritizationStrategy::GasPriceAndGasUsage if !self.options.infinite_pending_block => {
+				let gas_left = {
+					let header = open_block.block().header();
+					*header.gas_limit() - *header.gas_used()
+				};
+				let packing = pool::packing::pack(pending, gas_left, PACKING_TIME_BUDGET);
+				debug!(target: "miner", "Packed transactions with estimated fees of {} (priority order: {}).", packing.fees, packing.simple_fees);
+				self.packing_stats.lock().note(&packing);
+				packing.transactions
+			},
+			_ => pending,
+		};
+
 		let took_ms = |elapsed: &Duration| {
 			elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000
 		};
@@ -976,6 +1028,10 @@ impl miner::MinerService for Miner {
 		self.transaction_queue.status()
 	}
 
+	fn packing_stats(&self) -> pool::packing::PackingStats {
+		self.packing_stats.lock().clone()
+	}
+
 	fn pending_receipt(&self, best_block: BlockNumber, hash: &H256) -> Option<RichReceipt> {
 		self.map_existing_pending_block(|pending| {
 			let txs = pending.transactions();
@@ -33,7 +33,7 @@ use std::collections::{BTreeSet, BTreeMap};
 
 use bytes::Bytes;
 use ethereum_types::{H256, U256, Address};
-use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, local_transactions};
+use ethcore_miner::pool::{VerifiedTransaction, QueueStatus, local_transactions, packing};
 
 use block::{Block, SealedBlock};
 use client::{
@@ -190,6 +190,11 @@ pub trait MinerService : Send + Sync {
 	/// Status includes verification thresholds and current pool utilization and limits.
 	fn queue_status(&self) -> QueueStatus;
 
+	/// Get statistics comparing fees of blocks packed with `PrioritizationStrategy::GasPriceAndGasUsage`
+	/// to fees of the same blocks with transactions pushed in priority order.
+	/// Fees are estimated from gas limits of the transactions.
+	fn packing_stats(&self) -> packing::PackingStats;
+
 	// Misc
 
 	/// Suggested gas price.
@@ -166,6 +166,25 @@ impl CommonParams {
 		}
 	}
 
+	/// Block numbers of all transitions configured in these params.
+	pub fn transitions(&self) -> Vec<BlockNumber> {
+		vec![
+			self.eip98_transition,
+			self.eip658_transition,
+			self.eip155_transition,
+			self.validate_receipts_transition,
+			self.validate_chain_id_transition,
+			self.eip86_transition,
+			self.eip140_transition,
+			self.eip210_transition,
+			self.eip211_transition,
+			self.eip214_transition,
+			self.dust_protection_transition,
+			self.wasm_activation_transition,
+			self.max_code_size_transition,
+		]
+	}
+
 	/// Whether these params contain any bug-fix hard forks.
 	pub fn contains_bugfix_hard_fork(&self) -> bool {
 		self.eip98_transition != 0 && self.eip155_transition != 0 &&
@@ -365,6 +384,7 @@ impl Clone for Spec {
 }
 
 /// Part of `Spec`. Describes the hardcoded synchronization parameters.
+#[derive(Debug, Clone)]
 pub struct SpecHardcodedSync {
 	/// Header of the block to jump to for hardcoded sync, and total difficulty.
 	pub header: encoded::Header,
@@ -373,6 +393,8 @@ pub struct SpecHardcodedSync {
 	/// List of hardcoded CHTs, in order. If `hardcoded_sync` is set, the CHTs should include the
 	/// header of `hardcoded_sync`.
 	pub chts: Vec<H256>,
+	/// Header and proof of the last epoch transition at or before `header`, if any.
+	pub epoch_transition: Option<(encoded::Header, Bytes)>,
 }
 
 impl SpecHardcodedSync {
@@ -380,16 +402,29 @@ impl SpecHardcodedSync {
 	pub fn to_json(self) -> ethjson::spec::HardcodedSync {
 		self.into()
 	}
-}
 
-#[cfg(test)]
-impl Clone for SpecHardcodedSync {
-	fn clone(&self) -> SpecHardcodedSync {
-		SpecHardcodedSync {
-			header: self.header.clone(),
-			total_difficulty: self.total_difficulty.clone(),
-			chts: self.chts.clone(),
-		}
+	/// Loads hardcoded sync parameters from a JSON file, such as one generated by `to_json`.
+	pub fn load<R: Read>(reader: R) -> Result<Self, String> {
+		ethjson::spec::HardcodedSync::load(reader)
+			.map_err(fmt_err)
+			.and_then(SpecHardcodedSync::from_json)
+	}
+
+	fn from_json(hs: ethjson::spec::HardcodedSync) -> Result<Self, String> {
+		let epoch_transition = match hs.epoch_transition {
+			Some(transition) => Some((
+				encoded::Header::new(transition.header.from_hex().map_err(fmt_err)?),
+				transition.proof.from_hex().map_err(fmt_err)?,
+			)),
+			None => None,
+		};
+
+		Ok(SpecHardcodedSync {
+			header: encoded::Header::new(hs.header.from_hex().map_err(fmt_err)?),
+			total_difficulty: hs.total_difficulty.into(),
+			chts: hs.chts.into_iter().map(Into::into).collect(),
+			epoch_transition: epoch_transition,
+		})
 	}
 }
 
@@ -399,6 +434,10 @@ impl From<SpecHardcodedSync> for ethjson::spec::HardcodedSync {
 			header: sync.header.into_inner().to_hex(),
 			total_difficulty: ethjson::uint::Uint(sync.total_difficulty),
 			chts: sync.chts.into_iter().map(Into::into).collect(),
+			epoch_transition: sync.epoch_transition.map(|(header, proof)| ethjson::spec::EpochTransition {
+				header: header.into_inner().to_hex(),
+				proof: proof.to_hex(),
+			}),
 		}
 	}
 }
@@ -421,22 +460,7 @@ fn load_from(spec_params: SpecParams, s: ethjson::spec::Spec) -> Result<Spec, Er
 	let GenericSeal(seal_rlp) = g.seal.into();
 	let params = CommonParams::from(s.params);
 
-	let hardcoded_sync = if let Some(ref hs) = s.hardcoded_sync {
-		if let Ok(header) = hs.header.from_hex() {
-			Some(SpecHardcodedSync {
-				header: encoded::Header::new(header),
-				total_difficulty: hs.total_difficulty.into(),
-				chts: s.hardcoded_sync
-					.as_ref()
-					.map(|s| s.chts.iter().map(|c| c.clone().into()).collect())
-					.unwrap_or(Vec::new()),
-			})
-		} else {
-			None
-		}
-	} else {
-		None
-	};
+	let hardcoded_sync = s.hardcoded_sync.and_then(|hs| SpecHardcodedSync::from_json(hs).ok());
 
 	let mut s = Spec {
 		name: s.name.clone().into(),
@@ -648,6 +672,23 @@ impl Spec {
 		self.params().fork_block
 	}
 
+	/// Get the sorted block numbers of all hard forks of the chain, excluding the ones
+	/// active from genesis and the disabled ones. Used to compute the fork identifier.
+	pub fn fork_transitions(&self) -> Vec<BlockNumber> {
+		let machine = self.engine.machine();
+		let mut transitions = self.params().transitions();
+		transitions.extend(machine.ethash_extensions().map_or_else(Vec::new, |e| e.transitions()));
+		transitions.extend(self.engine.fork_transitions());
+
+		// chain specs disable a transition by setting it to a far-future block,
+		// usually `0x7fffffffffffff`, `0x7fffffffffffffff` or `u64::max_value()`.
+		const DISABLED_TRANSITION: BlockNumber = 0x7fffffffffffff;
+		transitions.retain(|n| *n != 0 && *n < DISABLED_TRANSITION);
+		transitions.sort();
+		transitions.dedup();
+		transitions
+	}
+
 	/// Get the header of the genesis block.
 	pub fn genesis_header(&self) -> Header {
 		let mut header: Header = Default::default();
@@ -84,13 +84,13 @@ pub type ApplyResult<T, V> = Result<ApplyOutcome<T, V>, Error>;
 
 /// Return type of proof validity check.
 #[derive(Debug, Clone)]
-pub enum ProvedExecution {
+pub enum ProvedExecution<T = FlatTrace, V = VMTrace> {
 	/// Proof wasn't enough to complete execution.
 	BadProof,
 	/// The transaction failed, but not due to a bad proof.
 	Failed(ExecutionError),
 	/// The transaction successfully completd with the given proof.
-	Complete(Executed),
+	Complete(Executed<T, V>),
 }
 
 #[derive(Eq, PartialEq, Clone, Copy, Debug)]
@@ -205,29 +205,62 @@ pub fn check_proof(
 	machine: &Machine,
 	env_info: &EnvInfo,
 ) -> ProvedExecution {
-	let backend = self::backend::ProofCheck::new(proof);
-	let mut factories = Factories::default();
-	factories.accountdb = ::account_db::Factory::Plain;
+	let options = TransactOptions::with_no_tracing().save_output_from_contract();
+	trace!(target: "iolite_exec_trace", "[check_proof] at {path}", path="ethcore/src/state/mod.rs:line 194");
+	check_proof_with_options(proof, root, transaction, machine, env_info, options, false)
+}
 
-	let res = State::from_existing(
-		backend,
-		root,
-		machine.account_start_nonce(env_info.number),
-		factories
-	);
+/// Check the given proof of execution, executing with the given tracers.
+/// When `state_diff` is set, the diff is computed against the proved state,
+/// so it fails with `BadProof` if the proof doesn't cover every touched account.
+pub fn check_proof_with_options<T, V>(
+	proof: &[DBValue],
+	root: H256,
+	transaction: &SignedTransaction,
+	machine: &Machine,
+	env_info: &EnvInfo,
+	options: TransactOptions<T, V>,
+	state_diff: bool,
+) -> ProvedExecution<T::Output, V::Output> where
+	T: trace::Tracer,
+	V: trace::VMTracer,
+{
+	let open = || {
+		let mut factories = Factories::default();
+		factories.accountdb = ::account_db::Factory::Plain;
+
+		State::from_existing(
+			self::backend::ProofCheck::new(proof),
+			root,
+			machine.account_start_nonce(env_info.number),
+			factories
+		)
+	};
 
-	let mut state = match res {
+	let mut state = match open() {
 		Ok(state) => state,
 		Err(_) => return ProvedExecution::BadProof,
 	};
 
-	let options = TransactOptions::with_no_tracing().save_output_from_contract();
-	trace!(target: "iolite_exec_trace", "[check_proof] at {path}", path="ethcore/src/state/mod.rs:line 194");
-	match state.execute(env_info, machine, transaction, options, true) {
-		Ok(executed) => ProvedExecution::Complete(executed),
-		Err(ExecutionError::Internal(_)) => ProvedExecution::BadProof,
-		Err(e) => ProvedExecution::Failed(e),
+	let mut executed = match state.execute(env_info, machine, transaction, options, true) {
+		Ok(executed) => executed,
+		Err(ExecutionError::Internal(_)) => return ProvedExecution::BadProof,
+		Err(e) => return ProvedExecution::Failed(e),
+	};
+
+	if state_diff {
+		let original = match open() {
+			Ok(original) => original,
+			Err(_) => return ProvedExecution::BadProof,
+		};
+
+		match state.diff_from(original) {
+			Ok(diff) => executed.state_diff = Some(diff),
+			Err(_) => return ProvedExecution::BadProof,
+		}
 	}
+
+	ProvedExecution::Complete(executed)
 }
 
 /// Prove a transaction on the given state.
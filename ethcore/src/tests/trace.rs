@@ -206,3 +206,47 @@ fn can_trace_block_and_uncle_reward() {
 	let traces = client.block_traces(BlockId::Number(3));
 	assert_eq!(traces.unwrap().len(), 3);
 }
+
+#[test]
+fn can_retrace_imported_blocks() {
+	let tempdir = TempDir::new("").unwrap();
+	let spec = Spec::new_test_with_reward();
+	let engine = &*spec.engine;
+
+	let db_config = DatabaseConfig::with_columns(::db::NUM_COLUMNS);
+	let client_config = ClientConfig::default();
+	assert!(!client_config.tracing.enabled);
+	let client_db = Arc::new(Database::open(&db_config, tempdir.path().to_str().unwrap()).unwrap());
+	let client = Client::new(
+		client_config,
+		&spec,
+		client_db,
+		Arc::new(Miner::new_for_tests(&spec, None)),
+		IoChannel::disconnected(),
+	).unwrap();
+
+	for number in 1..3 {
+		let mut block = client.prepare_open_block(Address::default(), (3141562.into(), 31415620.into()), vec![]);
+		block.set_timestamp(number * 10);
+		let block = block.close_and_lock().seal(engine, vec![]).unwrap();
+
+		if let Err(e) = client.import_block(block.rlp_bytes()) {
+			panic!("error importing block which is valid by definition: {:?}", e);
+		}
+		client.flush_queue();
+		client.import_verified_blocks();
+	}
+
+	assert_eq!(client.block_traces(BlockId::Number(1)), None);
+	assert_eq!(client.block_traces(BlockId::Number(2)), None);
+
+	assert_eq!(client.retrace_blocks(BlockId::Earliest, BlockId::Latest), Ok(2));
+
+	// the block reward of each block
+	for number in 1..3 {
+		let traces = client.block_traces(BlockId::Number(number)).unwrap();
+		assert_eq!(traces.len(), 1);
+		assert_eq!(traces[0].block_number, number);
+		assert_eq!(traces[0].block_hash, client.block_hash(BlockId::Number(number)).unwrap());
+	}
+}
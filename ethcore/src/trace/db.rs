@@ -21,7 +21,7 @@ use std::sync::Arc;
 use bloomchain::{Number, Config as BloomConfig};
 use bloomchain::group::{BloomGroupDatabase, BloomGroupChain, GroupPosition, BloomGroup};
 use heapsize::HeapSizeOf;
-use ethereum_types::{H256, H264};
+use ethereum_types::{H256, H264, Address};
 use kvdb::{KeyValueDB, DBTransaction};
 use parking_lot::RwLock;
 use header::BlockNumber;
@@ -29,6 +29,7 @@ use trace::{LocalizedTrace, Config, Filter, Database as TraceDatabase, ImportReq
 use db::{self, Key, Writable, Readable, CacheUpdatePolicy};
 use blooms;
 use super::flat::{FlatTrace, FlatBlockTraces, FlatTransactionTraces};
+use super::trace::{Action, Res};
 use cache_manager::CacheManager;
 
 const TRACE_DB_VER: &'static [u8] = b"1.0";
@@ -186,6 +187,51 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		});
 	}
 
+	/// Writes the traces and rebuilds the blooms of the enacted blocks.
+	fn insert_traces(&self, batch: &mut DBTransaction, request: ImportRequest) {
+		// now let's rebuild the blooms
+		if !request.enacted.is_empty() {
+			let range_start = request.block_number as Number + 1 - request.enacted.len();
+			let range_end = range_start + request.retracted;
+			let replaced_range = range_start..range_end;
+			let enacted_blooms = request.enacted
+				.iter()
+				// all traces are expected to be found here. That's why `expect` has been used
+				// instead of `filter_map`. If some traces haven't been found, it meens that
+				// traces database is corrupted or incomplete.
+				.map(|block_hash| if block_hash == &request.block_hash {
+					request.traces.bloom()
+				} else {
+					self.traces(block_hash).expect("Traces database is incomplete.").bloom()
+				})
+				.collect();
+
+			let chain = BloomGroupChain::new(self.bloom_config, self);
+			let trace_blooms = chain.replace(&replaced_range, enacted_blooms);
+			let blooms_to_insert = trace_blooms.into_iter()
+				.map(|p| (From::from(p.0), From::from(p.1)))
+				.collect::<HashMap<TraceGroupPosition, blooms::BloomGroup>>();
+
+			let blooms_keys: Vec<_> = blooms_to_insert.keys().cloned().collect();
+			let mut blooms = self.blooms.write();
+			batch.extend_with_cache(db::COL_TRACE, &mut *blooms, blooms_to_insert, CacheUpdatePolicy::Remove);
+			// note_used must be called after locking blooms to avoid cache/traces deadlock on garbage collection
+			for key in blooms_keys {
+				self.note_used(CacheId::Bloom(key));
+			}
+		}
+
+		// insert new block traces into the cache and the database
+		{
+			let mut traces = self.traces.write();
+			// it's important to use overwrite here,
+			// cause this value might be queried by hash later
+			batch.write_with_cache(db::COL_TRACE, &mut *traces, request.block_hash, request.traces, CacheUpdatePolicy::Overwrite);
+			// note_used must be called after locking traces to avoid cache/traces deadlock on garbage collection
+			self.note_used(CacheId::Trace(request.block_hash.clone()));
+		}
+	}
+
 	/// Returns traces for block with hash.
 	fn traces(&self, block_hash: &H256) -> Option<FlatBlockTraces> {
 		let result = self.tracesdb.read_with_cache(db::COL_TRACE, &self.traces, block_hash);
@@ -198,6 +244,32 @@ impl<T> TraceDB<T> where T: DatabaseExtras {
 		self.traces(block_hash).map(Into::into)
 	}
 
+	/// Returns accounts touched by internal calls of every transaction of the block with given hash.
+	/// The result is empty if the block has not been traced.
+	pub fn transactions_accounts(&self, block_hash: &H256) -> Vec<Vec<Address>> {
+		self.transactions_traces(block_hash)
+			.unwrap_or_default()
+			.into_iter()
+			.map(|traces| {
+				let traces: Vec<FlatTrace> = traces.into();
+				traces.into_iter()
+					.flat_map(|trace| {
+						let mut accounts = match trace.action {
+							Action::Call(call) => vec![call.from, call.to],
+							Action::Create(create) => vec![create.from],
+							Action::Suicide(suicide) => vec![suicide.address, suicide.refund_address],
+							Action::Reward(reward) => vec![reward.author],
+						};
+						if let Res::Create(result) = trace.result {
+							accounts.push(result.address);
+						}
+						accounts
+					})
+					.collect()
+			})
+			.collect()
+	}
+
 	fn matching_block_traces(
 		&self,
 		filter: &Filter,
@@ -269,47 +341,11 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 			return;
 		}
 
-		// now let's rebuild the blooms
-		if !request.enacted.is_empty() {
-			let range_start = request.block_number as Number + 1 - request.enacted.len();
-			let range_end = range_start + request.retracted;
-			let replaced_range = range_start..range_end;
-			let enacted_blooms = request.enacted
-				.iter()
-				// all traces are expected to be found here. That's why `expect` has been used
-				// instead of `filter_map`. If some traces haven't been found, it meens that
-				// traces database is corrupted or incomplete.
-				.map(|block_hash| if block_hash == &request.block_hash {
-					request.traces.bloom()
-				} else {
-					self.traces(block_hash).expect("Traces database is incomplete.").bloom()
-				})
-				.collect();
-
-			let chain = BloomGroupChain::new(self.bloom_config, self);
-			let trace_blooms = chain.replace(&replaced_range, enacted_blooms);
-			let blooms_to_insert = trace_blooms.into_iter()
-				.map(|p| (From::from(p.0), From::from(p.1)))
-				.collect::<HashMap<TraceGroupPosition, blooms::BloomGroup>>();
-
-			let blooms_keys: Vec<_> = blooms_to_insert.keys().cloned().collect();
-			let mut blooms = self.blooms.write();
-			batch.extend_with_cache(db::COL_TRACE, &mut *blooms, blooms_to_insert, CacheUpdatePolicy::Remove);
-			// note_used must be called after locking blooms to avoid cache/traces deadlock on garbage collection
-			for key in blooms_keys {
-				self.note_used(CacheId::Bloom(key));
-			}
-		}
+		self.insert_traces(batch, request);
+	}
 
-		// insert new block traces into the cache and the database
-		{
-			let mut traces = self.traces.write();
-			// it's important to use overwrite here,
-			// cause this value might be queried by hash later
-			batch.write_with_cache(db::COL_TRACE, &mut *traces, request.block_hash, request.traces, CacheUpdatePolicy::Overwrite);
-			// note_used must be called after locking traces to avoid cache/traces deadlock on garbage collection
-			self.note_used(CacheId::Trace(request.block_hash.clone()));
-		}
+	fn backfill(&self, batch: &mut DBTransaction, request: ImportRequest) {
+		self.insert_traces(batch, request);
 	}
 
 	fn trace(&self, block_number: BlockNumber, tx_position: usize, trace_position: Vec<usize>) -> Option<LocalizedTrace> {
@@ -403,9 +439,11 @@ impl<T> TraceDatabase for TraceDB<T> where T: DatabaseExtras {
 				let number = n as BlockNumber;
 				let hash = self.extras.block_hash(number)
 					.expect("Expected to find block hash. Extras db is probably corrupted");
-				let traces = self.traces(&hash)
-					.expect("Expected to find a trace. Db is probably corrupted.");
-				self.matching_block_traces(filter, traces, hash, number)
+				// blocks imported before tracing was enabled might not have been traced yet.
+				match self.traces(&hash) {
+					Some(traces) => self.matching_block_traces(filter, traces, hash, number),
+					None => Vec::new(),
+				}
 			})
 			.collect()
 	}
@@ -140,6 +140,9 @@ pub trait Database {
 	/// Imports new block traces.
 	fn import(&self, batch: &mut DBTransaction, request: ImportRequest);
 
+	/// Imports traces of a re-executed block, even if tracing is disabled.
+	fn backfill(&self, batch: &mut DBTransaction, request: ImportRequest);
+
 	/// Returns localized trace at given position.
 	fn trace(&self, block_number: BlockNumber, tx_position: usize, trace_position: Vec<usize>) -> Option<LocalizedTrace>;
 
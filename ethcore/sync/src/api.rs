@@ -17,11 +17,11 @@
 use std::sync::Arc;
 use std::collections::{HashMap, BTreeMap};
 use std::io;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use bytes::Bytes;
 use devp2p::{NetworkService, ConnectionFilter};
 use network::{NetworkProtocolHandler, NetworkContext, HostInfo, PeerId, ProtocolId,
-	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, Error, ErrorKind};
+	NetworkConfiguration as BasicNetworkConfiguration, NonReservedPeerMode, Error, ErrorKind, SessionTraffic};
 use ethereum_types::{H256, H512, U256};
 use io::{TimerToken};
 use ethcore::ethstore::ethkey::Secret;
@@ -32,9 +32,11 @@ use sync_io::NetSyncIo;
 use chain::{ChainSync, SyncStatus as EthSyncStatus};
 use std::net::{SocketAddr, AddrParseError};
 use std::str::FromStr;
-use parking_lot::RwLock;
-use chain::{ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_62,
-	PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3};
+use parking_lot::{Mutex, RwLock};
+use rlp::RlpStream;
+use fork_id::ForkId;
+use chain::{ETH_PROTOCOL_VERSION_65, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_62,
+	PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4, PAR_PROTOCOL_VERSION_5};
 use light::client::AsLightClient;
 use light::Provider;
 use light::net::{self as light_net, LightProtocol, Params as LightParams, Capabilities, Handler as LightHandler, EventContext};
@@ -84,8 +86,24 @@ impl WarpSync {
 	}
 }
 
+/// Policy of propagating new transactions to peers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionPropagation {
+	/// Full transactions are sent to a subset of peers, their hashes are announced to the others.
+	Mixed,
+	/// Only hashes are announced to peers accepting announcements, they request the transactions they lack.
+	/// Local transactions are still sent in full.
+	AnnounceOnly,
+}
+
+impl Default for TransactionPropagation {
+	fn default() -> Self {
+		TransactionPropagation::Mixed
+	}
+}
+
 /// Sync configuration
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct SyncConfig {
 	/// Max blocks to download ahead
 	pub max_download_ahead_blocks: usize,
@@ -99,10 +117,20 @@ pub struct SyncConfig {
 	pub light_subprotocol_name: [u8; 3],
 	/// Fork block to check
 	pub fork_block: Option<(BlockNumber, H256)>,
+	/// Sorted block numbers of hard forks, used to compute the fork identifier.
+	pub forks: Vec<BlockNumber>,
 	/// Enable snapshot sync
 	pub warp_sync: WarpSync,
 	/// Enable light client server.
 	pub serve_light: bool,
+	/// Resources which may be spent serving all light peers each second.
+	pub light_serve_budget: light_net::ServingBudget,
+	/// Resources which may be spent serving each non-reserved light peer each second.
+	pub light_serve_peer_budget: light_net::ServingBudget,
+	/// Transactions propagation policy.
+	pub transaction_propagation: TransactionPropagation,
+	/// Number of peers receiving full transactions, square root of the number of peers if `None`.
+	pub transaction_fanout: Option<usize>,
 }
 
 impl Default for SyncConfig {
@@ -114,8 +142,13 @@ impl Default for SyncConfig {
 			subprotocol_name: ETH_PROTOCOL,
 			light_subprotocol_name: LIGHT_PROTOCOL,
 			fork_block: None,
+			forks: Vec::new(),
 			warp_sync: WarpSync::Disabled,
 			serve_light: false,
+			light_serve_budget: Default::default(),
+			light_serve_peer_budget: Default::default(),
+			transaction_propagation: TransactionPropagation::Mixed,
+			transaction_fanout: None,
 		}
 	}
 }
@@ -133,6 +166,12 @@ pub trait SyncProvider: Send + Sync {
 
 	/// Returns propagation count for pending transactions.
 	fn transactions_stats(&self) -> BTreeMap<H256, TransactionStats>;
+
+	/// Returns statistics of transactions received from peers.
+	fn received_transactions_stats(&self) -> ReceivedTransactionsStats;
+
+	/// Returns the load of serving light peers, if serving light peers.
+	fn light_serving_load(&self) -> Option<light_net::ServingLoad>;
 }
 
 /// Transaction stats
@@ -144,6 +183,30 @@ pub struct TransactionStats {
 	pub propagated_to: BTreeMap<H512, usize>,
 }
 
+/// Statistics of transactions received from peers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReceivedTransactionsStats {
+	/// Number of transactions received.
+	pub received: u64,
+	/// Number of received transactions which were already known.
+	pub duplicates: u64,
+	/// Number of received transactions rejected by the transaction pool.
+	pub rejected: u64,
+	/// Number of transaction hashes announced by peers.
+	pub announced: u64,
+	/// Number of announced transactions requested from peers.
+	pub requested: u64,
+}
+
+/// Peer banned for bad behaviour
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BannedPeerInfo {
+	/// Public node id
+	pub id: H512,
+	/// Time the ban expires
+	pub until: SystemTime,
+}
+
 /// Peer connection information
 #[derive(Debug)]
 pub struct PeerInfo {
@@ -157,6 +220,8 @@ pub struct PeerInfo {
 	pub remote_address: String,
 	/// Local endpoint address
 	pub local_address: String,
+	/// Payload traffic of the session
+	pub traffic: SessionTraffic,
 	/// Eth protocol info.
 	pub eth_info: Option<EthProtocolInfo>,
 	/// Light protocol info.
@@ -172,6 +237,8 @@ pub struct EthProtocolInfo {
 	pub head: H256,
 	/// Peer total difficulty if known
 	pub difficulty: Option<U256>,
+	/// Measured block download throughput in blocks per second, if any blocks were downloaded from the peer
+	pub throughput: Option<f64>,
 }
 
 /// PIP protocol info.
@@ -252,6 +319,8 @@ pub struct EthSync {
 	subprotocol_name: [u8; 3],
 	/// Light subprotocol name.
 	light_subprotocol_name: [u8; 3],
+	/// Fork identifier currently advertised in the node record.
+	advertised_fork_id: Mutex<Option<ForkId>>,
 }
 
 impl EthSync {
@@ -283,6 +352,8 @@ impl EthSync {
 
 				let max_peers = ::std::cmp::min(params.network_config.max_peers, 1);
 				light_params.config.load_share = MAX_LIGHTSERV_LOAD / max_peers as f64;
+				light_params.config.total_budget = params.config.light_serve_budget;
+				light_params.config.peer_budget = params.config.light_serve_peer_budget;
 
 				let mut light_proto = LightProtocol::new(params.provider, light_params);
 				light_proto.add_handler(Arc::new(TxRelay(params.chain.clone())));
@@ -291,7 +362,7 @@ impl EthSync {
 			})
 		};
 
-		let chain_sync = ChainSync::new(params.config, &*params.chain, params.private_tx_handler.clone());
+		let chain_sync = ChainSync::new(params.config.clone(), &*params.chain, params.private_tx_handler.clone());
 		let service = NetworkService::new(params.network_config.clone().into_basic()?, connection_filter)?;
 
 		let sync = Arc::new(EthSync {
@@ -306,10 +377,29 @@ impl EthSync {
 			subprotocol_name: params.config.subprotocol_name,
 			light_subprotocol_name: params.config.light_subprotocol_name,
 			attached_protos: params.attached_protos,
+			advertised_fork_id: Mutex::new(None),
 		});
 
 		Ok(sync)
 	}
+
+	/// Advertise the current fork identifier in the `eth` entry of the node record.
+	fn update_node_record(&self) {
+		let best_block = self.eth_handler.chain.chain_info().best_block_number;
+		let fork_id = self.eth_handler.sync.read().fork_id(best_block);
+		let mut advertised = self.advertised_fork_id.lock();
+		if *advertised == Some(fork_id) {
+			return;
+		}
+
+		let mut entry = RlpStream::new_list(1);
+		entry.append(&fork_id);
+		let key = String::from_utf8_lossy(&self.subprotocol_name).into_owned();
+		match self.network.set_node_record_entry(&key, entry.out()) {
+			Ok(()) => *advertised = Some(fork_id),
+			Err(e) => warn!("Error updating node record: {:?}", e),
+		}
+	}
 }
 
 impl SyncProvider for EthSync {
@@ -337,6 +427,7 @@ impl SyncProvider for EthSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					traffic: session_info.traffic,
 					eth_info: eth_sync.peer_info(&peer_id),
 					pip_info: light_proto.as_ref().and_then(|lp| lp.peer_status(&peer_id)).map(Into::into),
 				})
@@ -355,6 +446,15 @@ impl SyncProvider for EthSync {
 			.map(|(hash, stats)| (*hash, stats.into()))
 			.collect()
 	}
+
+	fn received_transactions_stats(&self) -> ReceivedTransactionsStats {
+		self.eth_handler.sync.read().received_transactions_stats()
+	}
+
+	fn light_serving_load(&self) -> Option<light_net::ServingLoad> {
+		let light_proto = self.light_proto.as_ref()?;
+		self.network.with_context_eval(self.light_subprotocol_name, |ctx| light_proto.serving_load(&ctx))
+	}
 }
 
 const PEERS_TIMER: TimerToken = 0;
@@ -439,6 +539,7 @@ impl ChainNotify for EthSync {
 				&sealed,
 				&proposed);
 		});
+		self.update_node_record();
 
 		self.network.with_context(self.light_subprotocol_name, |context| {
 			let light_proto = match self.light_proto.as_ref() {
@@ -466,11 +567,12 @@ impl ChainNotify for EthSync {
 			Err(err) => warn!("Error starting network: {}", err),
 			_ => {},
 		}
-		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63])
+		self.network.register_protocol(self.eth_handler.clone(), self.subprotocol_name, &[ETH_PROTOCOL_VERSION_62, ETH_PROTOCOL_VERSION_63, ETH_PROTOCOL_VERSION_64, ETH_PROTOCOL_VERSION_65])
 			.unwrap_or_else(|e| warn!("Error registering ethereum protocol: {:?}", e));
 		// register the warp sync subprotocol
-		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3])
+		self.network.register_protocol(self.eth_handler.clone(), WARP_SYNC_PROTOCOL_ID, &[PAR_PROTOCOL_VERSION_1, PAR_PROTOCOL_VERSION_2, PAR_PROTOCOL_VERSION_3, PAR_PROTOCOL_VERSION_4, PAR_PROTOCOL_VERSION_5])
 			.unwrap_or_else(|e| warn!("Error registering snapshot sync protocol: {:?}", e));
+		self.update_node_record();
 
 		// register the light protocol.
 		if let Some(light_proto) = self.light_proto.as_ref().map(|x| x.clone()) {
@@ -484,6 +586,7 @@ impl ChainNotify for EthSync {
 
 	fn stop(&self) {
 		self.eth_handler.snapshot_service.abort_restore();
+		*self.advertised_fork_id.lock() = None;
 		self.network.stop().unwrap_or_else(|e| warn!("Error stopping network: {:?}", e));
 	}
 
@@ -502,6 +605,10 @@ impl ChainNotify for EthSync {
 		let mut sync = self.eth_handler.sync.write();
 		sync.transactions_received(txs, peer_id);
 	}
+
+	fn transactions_rejected(&self, hashes: &[H256]) {
+		self.eth_handler.sync.write().transactions_rejected(hashes);
+	}
 }
 
 /// PIP event handler.
@@ -533,6 +640,12 @@ pub trait ManageNetwork : Send + Sync {
 	fn network_config(&self) -> NetworkConfiguration;
 	/// Get network context for protocol.
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut FnMut(&NetworkContext));
+	/// Peers currently banned for bad behaviour
+	fn banned_peers(&self) -> Vec<BannedPeerInfo>;
+	/// Lift the ban of a peer. Returns `false` if the peer was not banned
+	fn unban_peer(&self, id: H512) -> bool;
+	/// Lift all bans
+	fn clear_banned_peers(&self);
 }
 
 
@@ -577,6 +690,18 @@ impl ManageNetwork for EthSync {
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut FnMut(&NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
+
+	fn banned_peers(&self) -> Vec<BannedPeerInfo> {
+		self.network.banned_peers().into_iter().map(|(id, until)| BannedPeerInfo { id, until }).collect()
+	}
+
+	fn unban_peer(&self, id: H512) -> bool {
+		self.network.unban_peer(&id)
+	}
+
+	fn clear_banned_peers(&self) {
+		self.network.clear_banned_peers();
+	}
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -596,6 +721,8 @@ pub struct NetworkConfiguration {
 	pub nat_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Topics advertised in the node record
+	pub discovery_topics: Vec<String>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -639,6 +766,7 @@ impl NetworkConfiguration {
 			udp_port: self.udp_port,
 			nat_enabled: self.nat_enabled,
 			discovery_enabled: self.discovery_enabled,
+			discovery_topics: self.discovery_topics,
 			boot_nodes: self.boot_nodes,
 			use_secret: self.use_secret,
 			max_peers: self.max_peers,
@@ -663,6 +791,7 @@ impl From<BasicNetworkConfiguration> for NetworkConfiguration {
 			udp_port: other.udp_port,
 			nat_enabled: other.nat_enabled,
 			discovery_enabled: other.discovery_enabled,
+			discovery_topics: other.discovery_topics,
 			boot_nodes: other.boot_nodes,
 			use_secret: other.use_secret,
 			max_peers: other.max_peers,
@@ -854,6 +983,18 @@ impl ManageNetwork for LightSync {
 	fn with_proto_context(&self, proto: ProtocolId, f: &mut FnMut(&NetworkContext)) {
 		self.network.with_context_eval(proto, f);
 	}
+
+	fn banned_peers(&self) -> Vec<BannedPeerInfo> {
+		self.network.banned_peers().into_iter().map(|(id, until)| BannedPeerInfo { id, until }).collect()
+	}
+
+	fn unban_peer(&self, id: H512) -> bool {
+		self.network.unban_peer(&id)
+	}
+
+	fn clear_banned_peers(&self) {
+		self.network.clear_banned_peers();
+	}
 }
 
 impl LightSyncProvider for LightSync {
@@ -884,6 +1025,7 @@ impl LightSyncProvider for LightSync {
 					capabilities: session_info.peer_capabilities.into_iter().map(|c| c.to_string()).collect(),
 					remote_address: session_info.remote_address,
 					local_address: session_info.local_address,
+					traffic: session_info.traffic,
 					eth_info: None,
 					pip_info: self.proto.peer_status(&peer_id).map(Into::into),
 				})
@@ -20,6 +20,7 @@
 
 use std::collections::{HashSet, VecDeque};
 use std::cmp;
+use std::time::Duration;
 use heapsize::HeapSizeOf;
 use ethereum_types::H256;
 use rlp::Rlp;
@@ -33,10 +34,17 @@ use blocks::BlockCollection;
 
 const MAX_HEADERS_TO_REQUEST: usize = 128;
 const MAX_BODIES_TO_REQUEST: usize = 32;
+const MIN_BODIES_TO_REQUEST: usize = 4;
 const MAX_RECEPITS_TO_REQUEST: usize = 128;
+const MIN_RECEIPTS_TO_REQUEST: usize = 8;
 const SUBCHAIN_SIZE: u64 = 256;
 const MAX_ROUND_PARENTS: usize = 16;
-const MAX_PARALLEL_SUBCHAIN_DOWNLOAD: usize = 5;
+/// Time in seconds a peer should take to serve a body or receipt request, given its throughput.
+const TARGET_RESPONSE_TIME: f64 = 2.0;
+/// Weight of the latest response in the throughput average.
+const THROUGHPUT_SMOOTHING: f64 = 0.3;
+/// A request is retried with another peer once it took this many times longer than expected.
+const SLOW_RESPONSE_FACTOR: f64 = 4.0;
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// Downloader state
@@ -66,6 +74,56 @@ pub enum BlockRequest {
 	},
 }
 
+/// Download throughput of a peer, measured on block body and receipt responses.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Throughput {
+	/// Moving average of blocks per second, unknown until the first response.
+	blocks_per_second: Option<f64>,
+}
+
+impl Throughput {
+	/// Account a response with `count` items received `elapsed` after the request was sent.
+	pub fn record(&mut self, count: usize, elapsed: Duration) {
+		let seconds = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000f64;
+		let sample = count as f64 / seconds.max(0.001);
+		self.blocks_per_second = Some(match self.blocks_per_second {
+			Some(average) => average + THROUGHPUT_SMOOTHING * (sample - average),
+			None => sample,
+		});
+	}
+
+	/// Account a request the peer did not serve in time.
+	pub fn record_timeout(&mut self) {
+		self.blocks_per_second = Some(self.blocks_per_second.unwrap_or(0.0) / 2.0);
+	}
+
+	/// Measured blocks per second.
+	pub fn blocks_per_second(&self) -> Option<f64> {
+		self.blocks_per_second
+	}
+
+	/// Number of items to request so that the peer serves them in about `TARGET_RESPONSE_TIME`.
+	fn request_size(&self, min: usize, max: usize) -> usize {
+		match self.blocks_per_second {
+			// ask peers we know nothing about for a moderate amount.
+			None => cmp::max(min, max / 2),
+			Some(rate) => cmp::max(min, cmp::min(max, (rate * TARGET_RESPONSE_TIME) as usize)),
+		}
+	}
+
+	/// Time after which a request for `count` items should be given to another peer.
+	pub fn retry_timeout(&self, count: usize, min: Duration, max: Duration) -> Duration {
+		match self.blocks_per_second {
+			Some(rate) if rate > 0.0 => {
+				let expected = count as f64 / rate * SLOW_RESPONSE_FACTOR;
+				cmp::max(min, cmp::min(max, Duration::from_millis((expected * 1000.0) as u64)))
+			},
+			Some(_) => min,
+			None => max,
+		}
+	}
+}
+
 /// Indicates sync action
 pub enum DownloadAction {
 	/// Do nothing
@@ -414,17 +472,19 @@ impl BlockDownloader {
 		self.imported_this_round = None;
 	}
 
-	/// Find some headers or blocks to download for a peer.
-	pub fn request_blocks(&mut self, io: &mut SyncIo, num_active_peers: usize) -> Option<BlockRequest> {
+	/// Find some headers or blocks to download for a peer. The skeleton of subchain heads is only
+	/// downloaded from the best peer, bodies and receipts are requested in amounts matching the
+	/// throughput of the peer.
+	pub fn request_blocks(&mut self, io: &mut SyncIo, best_peer: bool, throughput: &Throughput) -> Option<BlockRequest> {
 		match self.state {
 			State::Idle => {
 				self.start_sync_round(io);
 				if self.state == State::ChainHead {
-					return self.request_blocks(io, num_active_peers);
+					return self.request_blocks(io, best_peer, throughput);
 				}
 			},
 			State::ChainHead => {
-				if num_active_peers < MAX_PARALLEL_SUBCHAIN_DOWNLOAD {
+				if best_peer {
 					// Request subchain headers
 					trace!(target: "sync", "Starting sync with better chain");
 					// Request MAX_HEADERS_TO_REQUEST - 2 headers apart so that
@@ -438,7 +498,8 @@ impl BlockDownloader {
 			},
 			State::Blocks => {
 				// check to see if we need to download any block bodies first
-				let needed_bodies = self.blocks.needed_bodies(MAX_BODIES_TO_REQUEST, false);
+				let count = throughput.request_size(MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST);
+				let needed_bodies = self.blocks.needed_bodies(count, false);
 				if !needed_bodies.is_empty() {
 					return Some(BlockRequest::Bodies {
 						hashes: needed_bodies,
@@ -446,7 +507,8 @@ impl BlockDownloader {
 				}
 
 				if self.download_receipts {
-					let needed_receipts = self.blocks.needed_receipts(MAX_RECEPITS_TO_REQUEST, false);
+					let count = throughput.request_size(MIN_RECEIPTS_TO_REQUEST, MAX_RECEPITS_TO_REQUEST);
+					let needed_receipts = self.blocks.needed_receipts(count, false);
 					if !needed_receipts.is_empty() {
 						return Some(BlockRequest::Receipts {
 							hashes: needed_receipts,
@@ -559,4 +621,43 @@ impl BlockDownloader {
 	}
 }
 
-//TODO: module tests
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn request_size_follows_throughput() {
+		let mut throughput = Throughput::default();
+		assert_eq!(throughput.request_size(MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST), MAX_BODIES_TO_REQUEST / 2);
+
+		// 10 blocks per second
+		throughput.record(20, Duration::from_secs(2));
+		assert_eq!(throughput.blocks_per_second(), Some(10.0));
+		assert_eq!(throughput.request_size(MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST), 20);
+
+		// a much faster response moves the average towards it
+		throughput.record(100, Duration::from_secs(1));
+		assert_eq!(throughput.blocks_per_second(), Some(37.0));
+		assert_eq!(throughput.request_size(MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST), MAX_BODIES_TO_REQUEST);
+
+		throughput.record_timeout();
+		throughput.record_timeout();
+		throughput.record_timeout();
+		assert_eq!(throughput.request_size(MIN_BODIES_TO_REQUEST, MAX_BODIES_TO_REQUEST), 9);
+	}
+
+	#[test]
+	fn slow_requests_are_retried() {
+		let (min, max) = (Duration::from_secs(2), Duration::from_secs(20));
+		let mut throughput = Throughput::default();
+		assert_eq!(throughput.retry_timeout(16, min, max), max);
+
+		throughput.record(16, Duration::from_secs(1));
+		assert_eq!(throughput.retry_timeout(16, min, max), Duration::from_secs(4));
+		assert_eq!(throughput.retry_timeout(1, min, max), min);
+
+		throughput.record_timeout();
+		throughput.record_timeout();
+		assert_eq!(throughput.retry_timeout(160, min, max), max);
+	}
+}
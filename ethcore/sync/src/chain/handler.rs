@@ -15,6 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use api::WARP_SYNC_PROTOCOL_ID;
+use fork_id::ForkId;
 use block_sync::{BlockDownloaderImportError as DownloaderImportError, DownloadAction};
 use bytes::Bytes;
 use ethcore::client::{BlockStatus, BlockId, BlockImportError, BlockImportErrorKind};
@@ -23,7 +24,7 @@ use ethcore::header::{BlockNumber, Header as BlockHeader};
 use ethcore::snapshot::{ManifestData, RestorationStatus};
 use ethereum_types::{H256, U256};
 use hash::keccak;
-use network::PeerId;
+use network::{PeerId, PeerBehaviour};
 use rlp::Rlp;
 use snapshot::ChunkType;
 use std::cmp;
@@ -41,15 +42,18 @@ use super::{
 	SyncRequester,
 	SyncState,
 	ETH_PROTOCOL_VERSION_62,
-	ETH_PROTOCOL_VERSION_63,
+	ETH_PROTOCOL_VERSION_65,
 	MAX_NEW_BLOCK_AGE,
 	MAX_NEW_HASHES,
+	MAX_TRANSACTIONS_TO_REQUEST,
 	PAR_PROTOCOL_VERSION_1,
-	PAR_PROTOCOL_VERSION_3,
+	PAR_PROTOCOL_VERSION_5,
 	BLOCK_BODIES_PACKET,
 	BLOCK_HEADERS_PACKET,
 	NEW_BLOCK_HASHES_PACKET,
 	NEW_BLOCK_PACKET,
+	NEW_POOLED_TRANSACTION_HASHES_PACKET,
+	POOLED_TRANSACTIONS_PACKET,
 	PRIVATE_TRANSACTION_PACKET,
 	RECEIPTS_PACKET,
 	SIGNED_PRIVATE_TRANSACTION_PACKET,
@@ -72,7 +76,8 @@ impl SyncHandler {
 		let rlp = Rlp::new(data);
 		let result = match packet_id {
 			STATUS_PACKET => SyncHandler::on_peer_status(sync, io, peer, &rlp),
-			TRANSACTIONS_PACKET => SyncHandler::on_peer_transactions(sync, io, peer, &rlp),
+			TRANSACTIONS_PACKET | POOLED_TRANSACTIONS_PACKET => SyncHandler::on_peer_transactions(sync, io, peer, &rlp),
+			NEW_POOLED_TRANSACTION_HASHES_PACKET => SyncHandler::on_peer_new_pooled_transaction_hashes(sync, io, peer, &rlp),
 			BLOCK_HEADERS_PACKET => SyncHandler::on_peer_block_headers(sync, io, peer, &rlp),
 			BLOCK_BODIES_PACKET => SyncHandler::on_peer_block_bodies(sync, io, peer, &rlp),
 			RECEIPTS_PACKET => SyncHandler::on_peer_block_receipts(sync, io, peer, &rlp),
@@ -89,6 +94,7 @@ impl SyncHandler {
 		};
 		result.unwrap_or_else(|e| {
 			debug!(target:"sync", "{} -> Malformed packet {} : {}", peer, packet_id, e);
+			io.report_peer(peer, PeerBehaviour::BadProtocol);
 		})
 	}
 
@@ -165,6 +171,7 @@ impl SyncHandler {
 		let last_imported_number = sync.new_blocks.last_imported_block_number();
 		if last_imported_number > header.number() && last_imported_number - header.number() > MAX_NEW_BLOCK_AGE {
 			trace!(target: "sync", "Ignored ancient new block {:?}", h);
+			io.report_peer(peer_id, PeerBehaviour::Spam);
 			io.disable_peer(peer_id);
 			return Ok(());
 		}
@@ -187,6 +194,7 @@ impl SyncHandler {
 			},
 			Err(e) => {
 				debug!(target: "sync", "Bad new block {:?} : {:?}", h, e);
+				io.report_peer(peer_id, PeerBehaviour::InvalidData);
 				io.disable_peer(peer_id);
 			}
 		};
@@ -241,6 +249,7 @@ impl SyncHandler {
 			}
 			if last_imported_number > number && last_imported_number - number > MAX_NEW_BLOCK_AGE {
 				trace!(target: "sync", "Ignored ancient new block hash {:?}", hash);
+				io.report_peer(peer_id, PeerBehaviour::Spam);
 				io.disable_peer(peer_id);
 				continue;
 			}
@@ -263,6 +272,7 @@ impl SyncHandler {
 				},
 				BlockStatus::Bad => {
 					debug!(target: "sync", "Bad new block hash {:?}", hash);
+					io.report_peer(peer_id, PeerBehaviour::InvalidData);
 					io.disable_peer(peer_id);
 					return Ok(());
 				}
@@ -282,7 +292,9 @@ impl SyncHandler {
 	fn on_peer_block_bodies(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), PacketDecodeError> {
 		sync.clear_peer_download(peer_id);
 		let block_set = sync.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
-		if !sync.reset_peer_asking(peer_id, PeerAsking::BlockBodies) {
+		// the request of an expired peer has already been given to another peer.
+		let expired = sync.peers.get(&peer_id).map_or(false, |p| p.expired);
+		if !sync.reset_peer_asking(peer_id, PeerAsking::BlockBodies) || expired {
 			trace!(target: "sync", "{}: Ignored unexpected bodies", peer_id);
 			sync.continue_sync(io);
 			return Ok(());
@@ -311,9 +323,13 @@ impl SyncHandler {
 				};
 				downloader.import_bodies(io, r)
 			};
+			if result.is_ok() {
+				sync.record_throughput(peer_id, item_count);
+			}
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					io.report_peer(peer_id, PeerBehaviour::InvalidData);
 					io.disable_peer(peer_id);
 					sync.deactivate_peer(io, peer_id);
 					sync.continue_sync(io);
@@ -322,7 +338,7 @@ impl SyncHandler {
 				Err(DownloaderImportError::Useless) => {
 					sync.deactivate_peer(io, peer_id);
 				},
-				Ok(()) => (),
+				Ok(()) => io.report_peer(peer_id, PeerBehaviour::UsefulResponse),
 			}
 
 			sync.collect_blocks(io, block_set);
@@ -420,12 +436,14 @@ impl SyncHandler {
 				sync.deactivate_peer(io, peer_id);
 			},
 			Err(DownloaderImportError::Invalid) => {
+				io.report_peer(peer_id, PeerBehaviour::InvalidData);
 				io.disable_peer(peer_id);
 				sync.deactivate_peer(io, peer_id);
 				sync.continue_sync(io);
 				return Ok(());
 			},
 			Ok(DownloadAction::Reset) => {
+				io.report_peer(peer_id, PeerBehaviour::UsefulResponse);
 				// mark all outstanding requests as expired
 				trace!("Resetting downloads for {:?}", block_set);
 				for (_, ref mut p) in sync.peers.iter_mut().filter(|&(_, ref p)| p.block_set == Some(block_set)) {
@@ -433,7 +451,7 @@ impl SyncHandler {
 				}
 
 			}
-			Ok(DownloadAction::None) => {},
+			Ok(DownloadAction::None) => io.report_peer(peer_id, PeerBehaviour::UsefulResponse),
 		}
 
 		sync.collect_blocks(io, block_set);
@@ -448,7 +466,9 @@ impl SyncHandler {
 	fn on_peer_block_receipts(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), PacketDecodeError> {
 		sync.clear_peer_download(peer_id);
 		let block_set = sync.peers.get(&peer_id).and_then(|p| p.block_set).unwrap_or(BlockSet::NewBlocks);
-		if !sync.reset_peer_asking(peer_id, PeerAsking::BlockReceipts) {
+		// the request of an expired peer has already been given to another peer.
+		let expired = sync.peers.get(&peer_id).map_or(false, |p| p.expired);
+		if !sync.reset_peer_asking(peer_id, PeerAsking::BlockReceipts) || expired {
 			trace!(target: "sync", "{}: Ignored unexpected receipts", peer_id);
 			sync.continue_sync(io);
 			return Ok(());
@@ -477,9 +497,13 @@ impl SyncHandler {
 				};
 				downloader.import_receipts(io, r)
 			};
+			if result.is_ok() {
+				sync.record_throughput(peer_id, item_count);
+			}
 
 			match result {
 				Err(DownloaderImportError::Invalid) => {
+					io.report_peer(peer_id, PeerBehaviour::InvalidData);
 					io.disable_peer(peer_id);
 					sync.deactivate_peer(io, peer_id);
 					sync.continue_sync(io);
@@ -488,7 +512,7 @@ impl SyncHandler {
 				Err(DownloaderImportError::Useless) => {
 					sync.deactivate_peer(io, peer_id);
 				},
-				Ok(()) => (),
+				Ok(()) => io.report_peer(peer_id, PeerBehaviour::UsefulResponse),
 			}
 
 			sync.collect_blocks(io, block_set);
@@ -515,6 +539,7 @@ impl SyncHandler {
 		let manifest = match ManifestData::from_rlp(manifest_rlp.as_raw()) {
 			Err(e) => {
 				trace!(target: "sync", "{}: Ignored bad manifest: {:?}", peer_id, e);
+				io.report_peer(peer_id, PeerBehaviour::InvalidData);
 				io.disable_peer(peer_id);
 				sync.continue_sync(io);
 				return Ok(());
@@ -593,6 +618,7 @@ impl SyncHandler {
 			}
 			Err(()) => {
 				trace!(target: "sync", "{}: Got bad snapshot chunk", peer_id);
+				io.report_peer(peer_id, PeerBehaviour::InvalidData);
 				io.disconnect_peer(peer_id);
 				sync.continue_sync(io);
 				return Ok(());
@@ -626,16 +652,22 @@ impl SyncHandler {
 			asking_hash: None,
 			ask_time: Instant::now(),
 			last_sent_transactions: HashSet::new(),
+			transaction_announcements: ChainSync::has_transaction_announcements(warp_protocol, protocol_version),
 			expired: false,
+			throughput: Default::default(),
 			confirmation: if sync.fork_block.is_none() { ForkConfirmation::Confirmed } else { ForkConfirmation::Unconfirmed },
 			asking_snapshot_data: None,
 			snapshot_hash: if warp_protocol { Some(r.val_at(5)?) } else { None },
 			snapshot_number: if warp_protocol { Some(r.val_at(6)?) } else { None },
 			block_set: None,
 		};
+		let fork_id: Option<ForkId> = match ChainSync::has_fork_id(warp_protocol, protocol_version) {
+			true => Some(r.val_at(if warp_protocol { 7 } else { 5 })?),
+			false => None,
+		};
 
-		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{}, snapshot:{:?})",
-			peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis, peer.snapshot_number);
+		trace!(target: "sync", "New peer {} (protocol: {}, network: {:?}, difficulty: {:?}, latest:{}, genesis:{}, snapshot:{:?}, fork id:{:?})",
+			peer_id, peer.protocol_version, peer.network_id, peer.difficulty, peer.latest_hash, peer.genesis, peer.snapshot_number, fork_id);
 		if io.is_expired() {
 			trace!(target: "sync", "Status packet from expired session {}:{}", peer_id, io.peer_info(peer_id));
 			return Ok(());
@@ -656,10 +688,17 @@ impl SyncHandler {
 			trace!(target: "sync", "Peer {} network id mismatch (ours: {}, theirs: {})", peer_id, sync.network_id, peer.network_id);
 			return Ok(());
 		}
+		if let Some(fork_id) = fork_id {
+			if let Err(e) = sync.fork_filter.validate(chain_info.best_block_number, &fork_id) {
+				io.disable_peer(peer_id);
+				trace!(target: "sync", "Peer {} fork id mismatch (ours: {:?}, theirs: {:?}): {:?}", peer_id, sync.fork_filter.current(chain_info.best_block_number), fork_id, e);
+				return Ok(());
+			}
+		}
 
 		if false
-			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_3.0))
-			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_62.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_63.0))
+			|| (warp_protocol && (peer.protocol_version < PAR_PROTOCOL_VERSION_1.0 || peer.protocol_version > PAR_PROTOCOL_VERSION_5.0))
+			|| (!warp_protocol && (peer.protocol_version < ETH_PROTOCOL_VERSION_62.0 || peer.protocol_version > ETH_PROTOCOL_VERSION_65.0))
 		{
 			io.disable_peer(peer_id);
 			trace!(target: "sync", "Peer {} unsupported eth protocol ({})", peer_id, peer.protocol_version);
@@ -704,16 +743,67 @@ impl SyncHandler {
 
 		let item_count = r.item_count()?;
 		trace!(target: "sync", "{:02} -> Transactions ({} entries)", peer_id, item_count);
+		let block_number = io.chain().chain_info().best_block_number;
 		let mut transactions = Vec::with_capacity(item_count);
 		for i in 0 .. item_count {
 			let rlp = r.at(i)?;
 			let tx = rlp.as_raw().to_vec();
-			transactions.push(tx);
+			let hash = keccak(&tx);
+			sync.requested_transactions.remove(&hash);
+			if let Some(peer) = sync.peers.get_mut(&peer_id) {
+				peer.last_sent_transactions.insert(hash);
+			}
+			let known = io.chain().queued_transaction(&hash).is_some();
+			if sync.transactions_stats.received(&hash, known, block_number) {
+				transactions.push(tx);
+			}
+		}
+		if transactions.len() < item_count {
+			trace!(target: "sync", "{:02} -> {} duplicate transactions", peer_id, item_count - transactions.len());
 		}
 		io.chain().queue_transactions(transactions, peer_id);
 		Ok(())
 	}
 
+	/// Called when peer announces hashes of new transactions
+	fn on_peer_new_pooled_transaction_hashes(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), PacketDecodeError> {
+		// Accept transactions only when fully synced
+		if !io.is_chain_queue_empty() || (sync.state != SyncState::Idle && sync.state != SyncState::NewBlocks) {
+			trace!(target: "sync", "{} Ignoring transaction hashes while syncing", peer_id);
+			return Ok(());
+		}
+		if !sync.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
+			trace!(target: "sync", "{} Ignoring transaction hashes from unconfirmed/unknown peer", peer_id);
+			return Ok(());
+		}
+
+		let item_count = r.item_count()?;
+		trace!(target: "sync", "{:02} -> NewPooledTransactionHashes ({} entries)", peer_id, item_count);
+		let now = Instant::now();
+		let mut to_request = Vec::new();
+		for i in 0 .. item_count {
+			let hash: H256 = r.val_at(i)?;
+			if let Some(peer) = sync.peers.get_mut(&peer_id) {
+				peer.last_sent_transactions.insert(hash);
+			}
+			if to_request.len() >= MAX_TRANSACTIONS_TO_REQUEST
+				|| sync.requested_transactions.contains_key(&hash)
+				|| sync.transactions_stats.is_known(&hash)
+				|| io.chain().queued_transaction(&hash).is_some()
+			{
+				continue;
+			}
+			sync.requested_transactions.insert(hash, now);
+			to_request.push(hash);
+		}
+		sync.transactions_stats.announced(item_count, to_request.len());
+
+		if !to_request.is_empty() {
+			SyncRequester::request_pooled_transactions(io, peer_id, &to_request);
+		}
+		Ok(())
+	}
+
 	/// Called when peer sends us signed private transaction packet
 	fn on_signed_private_transaction(sync: &ChainSync, _io: &mut SyncIo, peer_id: PeerId, r: &Rlp) -> Result<(), PacketDecodeError> {
 		if !sync.peers.get(&peer_id).map_or(false, |p| p.can_sync()) {
@@ -850,4 +940,18 @@ mod tests {
 
 		assert!(result.is_ok());
 	}
+
+	#[test]
+	fn reports_malformed_packets() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Uncle);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(5), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		SyncHandler::on_packet(&mut sync, &mut io, 0, NEW_BLOCK_PACKET, &[0xc0]);
+
+		assert_eq!(io.reports, vec![(0, PeerBehaviour::BadProtocol)]);
+	}
 }
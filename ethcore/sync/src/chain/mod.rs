@@ -103,18 +103,19 @@ use plain_hasher::H256FastMap;
 use parking_lot::RwLock;
 use bytes::Bytes;
 use rlp::{Rlp, RlpStream, DecoderError};
-use network::{self, PeerId, PacketId};
+use network::{self, PeerId, PacketId, PeerBehaviour};
 use ethcore::header::{BlockNumber};
 use ethcore::client::{BlockChainClient, BlockStatus, BlockId, BlockChainInfo, BlockQueueInfo};
 use ethcore::snapshot::{RestorationStatus};
 use sync_io::SyncIo;
-use super::{WarpSync, SyncConfig};
-use block_sync::{BlockDownloader, BlockDownloaderImportError as DownloaderImportError};
+use super::{WarpSync, SyncConfig, TransactionPropagation, ReceivedTransactionsStats};
+use block_sync::{BlockDownloader, BlockDownloaderImportError as DownloaderImportError, Throughput};
 use rand::Rng;
 use snapshot::{Snapshot};
 use api::{EthProtocolInfo as PeerInfoDigest, WARP_SYNC_PROTOCOL_ID};
 use private_tx::PrivateTxHandler;
 use transactions_stats::{TransactionsStats, Stats as TransactionStats};
+use fork_id::{ForkFilter, ForkId};
 use transaction::UnverifiedTransaction;
 
 use self::handler::SyncHandler;
@@ -126,6 +127,10 @@ known_heap_size!(0, PeerInfo);
 
 pub type PacketDecodeError = DecoderError;
 
+/// 65 version of Ethereum protocol (transaction hash announcements added).
+pub const ETH_PROTOCOL_VERSION_65: (u8, u8) = (65, 0x11);
+/// 64 version of Ethereum protocol (fork identifier added to status).
+pub const ETH_PROTOCOL_VERSION_64: (u8, u8) = (64, 0x11);
 /// 63 version of Ethereum protocol.
 pub const ETH_PROTOCOL_VERSION_63: (u8, u8) = (63, 0x11);
 /// 62 version of Ethereum protocol.
@@ -136,6 +141,10 @@ pub const PAR_PROTOCOL_VERSION_1: (u8, u8) = (1, 0x15);
 pub const PAR_PROTOCOL_VERSION_2: (u8, u8) = (2, 0x16);
 /// 3 version of Parity protocol (private transactions messages added).
 pub const PAR_PROTOCOL_VERSION_3: (u8, u8) = (3, 0x18);
+/// 4 version of Parity protocol (fork identifier added to status).
+pub const PAR_PROTOCOL_VERSION_4: (u8, u8) = (4, 0x18);
+/// 5 version of Parity protocol (transaction hash announcements added).
+pub const PAR_PROTOCOL_VERSION_5: (u8, u8) = (5, 0x18);
 
 pub const MAX_BODIES_TO_SEND: usize = 256;
 pub const MAX_HEADERS_TO_SEND: usize = 512;
@@ -155,9 +164,15 @@ const MAX_TRANSACTION_PACKET_SIZE: usize = 8 * 1024 * 1024;
 const MAX_TRANSACTIONS_TO_QUERY: usize = 4096;
 // Maximal number of transactions in sent in single packet.
 const MAX_TRANSACTIONS_TO_PROPAGATE: usize = 64;
+// Maximal number of transactions requested from a peer at once.
+const MAX_TRANSACTIONS_TO_REQUEST: usize = 256;
+// Maximal number of pooled transactions sent in response to a single request.
+pub const MAX_POOLED_TRANSACTIONS_TO_SEND: usize = 256;
 // Min number of blocks to be behind for a snapshot sync
 const SNAPSHOT_RESTORE_THRESHOLD: BlockNumber = 30000;
 const SNAPSHOT_MIN_PEERS: usize = 3;
+// Weight of the latest sample in the smoothed block import rate.
+const IMPORT_RATE_SMOOTHING: f64 = 0.2;
 
 const STATUS_PACKET: u8 = 0x00;
 const NEW_BLOCK_HASHES_PACKET: u8 = 0x01;
@@ -167,6 +182,9 @@ pub const BLOCK_HEADERS_PACKET: u8 = 0x04;
 pub const GET_BLOCK_BODIES_PACKET: u8 = 0x05;
 const BLOCK_BODIES_PACKET: u8 = 0x06;
 const NEW_BLOCK_PACKET: u8 = 0x07;
+const NEW_POOLED_TRANSACTION_HASHES_PACKET: u8 = 0x08;
+pub const GET_POOLED_TRANSACTIONS_PACKET: u8 = 0x09;
+pub const POOLED_TRANSACTIONS_PACKET: u8 = 0x0a;
 
 pub const GET_NODE_DATA_PACKET: u8 = 0x0d;
 pub const NODE_DATA_PACKET: u8 = 0x0e;
@@ -188,7 +206,11 @@ const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
 const HEADERS_TIMEOUT: Duration = Duration::from_secs(15);
 const BODIES_TIMEOUT: Duration = Duration::from_secs(20);
 const RECEIPTS_TIMEOUT: Duration = Duration::from_secs(10);
+// Body and receipt requests taking longer than expected for the peer are given to other peers after at least this time.
+const MIN_DOWNLOAD_RETRY_TIMEOUT: Duration = Duration::from_secs(3);
 const FORK_HEADER_TIMEOUT: Duration = Duration::from_secs(3);
+// Announced transactions are requested from another peer if not received in time.
+const POOLED_TRANSACTIONS_TIMEOUT: Duration = Duration::from_secs(5);
 const SNAPSHOT_MANIFEST_TIMEOUT: Duration = Duration::from_secs(5);
 const SNAPSHOT_DATA_TIMEOUT: Duration = Duration::from_secs(120);
 
@@ -244,6 +266,8 @@ pub struct SyncStatus {
 	pub snapshot_chunks_done: usize,
 	/// Last fully downloaded and imported ancient block number (if any).
 	pub last_imported_old_block_number: Option<BlockNumber>,
+	/// Smoothed number of blocks imported per second (if measured).
+	pub import_rate: Option<f64>,
 }
 
 impl SyncStatus {
@@ -327,8 +351,10 @@ pub struct PeerInfo {
 	asking_snapshot_data: Option<H256>,
 	/// Request timestamp
 	ask_time: Instant,
-	/// Holds a set of transactions recently sent to this peer to avoid spamming.
+	/// Holds a set of transactions recently sent to or received from this peer to avoid spamming.
 	last_sent_transactions: HashSet<H256>,
+	/// Peer accepts transaction hash announcements.
+	transaction_announcements: bool,
 	/// Pending request is expired and result should be ignored
 	expired: bool,
 	/// Peer fork confirmation status
@@ -339,6 +365,8 @@ pub struct PeerInfo {
 	snapshot_number: Option<BlockNumber>,
 	/// Block set requested
 	block_set: Option<BlockSet>,
+	/// Measured block download throughput
+	throughput: Throughput,
 }
 
 impl PeerInfo {
@@ -350,6 +378,15 @@ impl PeerInfo {
 		self.confirmation != ForkConfirmation::Unconfirmed && !self.expired
 	}
 
+	/// Time after which the pending body or receipt request should be given to another peer.
+	fn download_retry_timeout(&self) -> Duration {
+		let max = match self.asking {
+			PeerAsking::BlockReceipts => RECEIPTS_TIMEOUT,
+			_ => BODIES_TIMEOUT,
+		};
+		self.throughput.retry_timeout(self.asking_blocks.len(), MIN_DOWNLOAD_RETRY_TIMEOUT, max)
+	}
+
 	fn reset_asking(&mut self) {
 		self.asking_blocks.clear();
 		self.asking_hash = None;
@@ -358,6 +395,15 @@ impl PeerInfo {
 			self.expired = true;
 		}
 	}
+
+	/// Give up on the pending request, which has been given to other peers.
+	/// The peer keeps asking, so that it gets no new requests until it responds
+	/// or the request times out, and its response is ignored.
+	fn expire_request(&mut self) {
+		self.asking_blocks.clear();
+		self.asking_hash = None;
+		self.expired = true;
+	}
 }
 
 #[cfg(not(test))]
@@ -374,6 +420,38 @@ pub mod random {
 pub type RlpResponseResult = Result<Option<(PacketId, RlpStream)>, PacketDecodeError>;
 pub type Peers = HashMap<PeerId, PeerInfo>;
 
+/// Smoothed rate of blocks being imported into the chain.
+#[derive(Default)]
+struct ImportRate {
+	/// Time and best block number of the last sample.
+	last: Option<(Instant, BlockNumber)>,
+	blocks_per_second: Option<f64>,
+}
+
+impl ImportRate {
+	/// Sample the best block number at given time.
+	fn record(&mut self, now: Instant, best_block: BlockNumber) {
+		if let Some((time, number)) = self.last {
+			let elapsed = now.duration_since(time);
+			let elapsed = elapsed.as_secs() as f64 + elapsed.subsec_nanos() as f64 / 1_000_000_000.0;
+			if elapsed <= 0.0 {
+				return;
+			}
+			// the chain might have been reorganised or restored from a snapshot.
+			let sample = best_block.saturating_sub(number) as f64 / elapsed;
+			self.blocks_per_second = Some(match self.blocks_per_second {
+				Some(rate) => rate + IMPORT_RATE_SMOOTHING * (sample - rate),
+				None => sample,
+			});
+		}
+		self.last = Some((now, best_block));
+	}
+
+	fn blocks_per_second(&self) -> Option<f64> {
+		self.blocks_per_second
+	}
+}
+
 /// Blockchain sync handler.
 /// See module documentation for more details.
 pub struct ChainSync {
@@ -397,6 +475,8 @@ pub struct ChainSync {
 	network_id: u64,
 	/// Optional fork block to check
 	fork_block: Option<(BlockNumber, H256)>,
+	/// Fork identifier of the chain.
+	fork_filter: ForkFilter,
 	/// Snapshot downloader.
 	snapshot: Snapshot,
 	/// Connected peers pending Status message.
@@ -406,12 +486,20 @@ pub struct ChainSync {
 	sync_start_time: Option<Instant>,
 	/// Transactions propagation statistics
 	transactions_stats: TransactionsStats,
+	/// Announced transactions requested from peers, with request timestamp.
+	requested_transactions: H256FastMap<Instant>,
+	/// Transactions propagation policy.
+	transaction_propagation: TransactionPropagation,
+	/// Number of peers receiving full transactions, square root of the number of peers if `None`.
+	transaction_fanout: Option<usize>,
 	/// Enable ancient block downloading
 	download_old_blocks: bool,
 	/// Shared private tx service.
 	private_tx_handler: Arc<PrivateTxHandler>,
 	/// Enable warp sync.
 	warp_sync: WarpSync,
+	/// Block import rate.
+	import_rate: ImportRate,
 }
 
 impl ChainSync {
@@ -433,12 +521,17 @@ impl ChainSync {
 			last_sent_block_number: 0,
 			network_id: config.network_id,
 			fork_block: config.fork_block,
+			fork_filter: ForkFilter::new(&chain_info.genesis_hash, config.forks),
 			download_old_blocks: config.download_old_blocks,
 			snapshot: Snapshot::new(),
 			sync_start_time: None,
 			transactions_stats: TransactionsStats::default(),
+			requested_transactions: Default::default(),
+			transaction_propagation: config.transaction_propagation,
+			transaction_fanout: config.transaction_fanout,
 			private_tx_handler,
 			warp_sync: config.warp_sync,
+			import_rate: ImportRate::default(),
 		};
 		sync.update_targets(chain);
 		sync
@@ -458,7 +551,7 @@ impl ChainSync {
 		let last_imported_number = self.new_blocks.last_imported_block_number();
 		SyncStatus {
 			state: self.state.clone(),
-			protocol_version: ETH_PROTOCOL_VERSION_63.0,
+			protocol_version: ETH_PROTOCOL_VERSION_64.0,
 			network_id: self.network_id,
 			start_block_number: self.starting_block,
 			last_imported_block_number: Some(last_imported_number),
@@ -470,6 +563,7 @@ impl ChainSync {
 			num_active_peers: self.peers.values().filter(|p| p.is_allowed() && p.asking != PeerAsking::Nothing).count(),
 			num_snapshot_chunks: self.snapshot.total_chunks(),
 			snapshot_chunks_done: self.snapshot.done_chunks(),
+			import_rate: self.import_rate.blocks_per_second(),
 			mem_used:
 				self.new_blocks.heap_size()
 				+ self.old_blocks.as_ref().map_or(0, |d| d.heap_size())
@@ -477,6 +571,11 @@ impl ChainSync {
 		}
 	}
 
+	/// Returns the fork identifier of the chain at given best block
+	pub fn fork_id(&self, best_block: BlockNumber) -> ForkId {
+		self.fork_filter.current(best_block)
+	}
+
 	/// Returns information on peers connections
 	pub fn peer_info(&self, peer_id: &PeerId) -> Option<PeerInfoDigest> {
 		self.peers.get(peer_id).map(|peer_data| {
@@ -484,6 +583,7 @@ impl ChainSync {
 				version: peer_data.protocol_version as u32,
 				difficulty: peer_data.difficulty,
 				head: peer_data.latest_hash,
+				throughput: peer_data.throughput.blocks_per_second(),
 			}
 		})
 	}
@@ -493,6 +593,11 @@ impl ChainSync {
 		self.transactions_stats.stats()
 	}
 
+	/// Returns statistics of transactions received from peers.
+	pub fn received_transactions_stats(&self) -> ReceivedTransactionsStats {
+		self.transactions_stats.received_stats()
+	}
+
 	/// Updates transactions were received by a peer
 	pub fn transactions_received(&mut self, txs: &[UnverifiedTransaction], peer_id: PeerId) {
 		if let Some(peer_info) = self.peers.get_mut(&peer_id) {
@@ -500,6 +605,11 @@ impl ChainSync {
 		}
 	}
 
+	/// Forgets transactions rejected by the pool, so that they can be received again.
+	pub fn transactions_rejected(&mut self, hashes: &[H256]) {
+		self.transactions_stats.rejected(hashes);
+	}
+
 	/// Abort all sync activity
 	pub fn abort(&mut self, io: &mut SyncIo) {
 		self.reset_and_continue(io);
@@ -726,7 +836,8 @@ impl ChainSync {
 		};
 		let chain_info = io.chain().chain_info();
 		let syncing_difficulty = chain_info.pending_total_difficulty;
-		let num_active_peers = self.peers.values().filter(|p| p.asking != PeerAsking::Nothing).count();
+		let best_peer = force || self.is_best_peer(io, peer_id);
+		let throughput = self.peers.get(&peer_id).map_or_else(Throughput::default, |p| p.throughput);
 
 		let higher_difficulty = peer_difficulty.map_or(true, |pd| pd > syncing_difficulty);
 		if force || higher_difficulty || self.old_blocks.is_some() {
@@ -752,7 +863,7 @@ impl ChainSync {
 					if !have_latest && (higher_difficulty || force || self.state == SyncState::NewBlocks) {
 						// check if got new blocks to download
 						trace!(target: "sync", "Syncing with peer {}, force={}, td={:?}, our td={}, state={:?}", peer_id, force, peer_difficulty, syncing_difficulty, self.state);
-						if let Some(request) = self.new_blocks.request_blocks(io, num_active_peers) {
+						if let Some(request) = self.new_blocks.request_blocks(io, best_peer, &throughput) {
 							SyncRequester::request_blocks(self, io, peer_id, request, BlockSet::NewBlocks);
 							if self.state == SyncState::Idle {
 								self.state = SyncState::Blocks;
@@ -763,7 +874,7 @@ impl ChainSync {
 
 					// Only ask for old blocks if the peer has a higher difficulty
 					if force || higher_difficulty {
-						if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, num_active_peers)) {
+						if let Some(request) = self.old_blocks.as_mut().and_then(|d| d.request_blocks(io, best_peer, &throughput)) {
 							SyncRequester::request_blocks(self, io, peer_id, request, BlockSet::OldBlocks);
 							return;
 						}
@@ -806,6 +917,32 @@ impl ChainSync {
 		}
 	}
 
+	/// Whether no other peer we could sync with has a better chain we don't know yet.
+	/// Better peers busy with anything but headers are passed over, so the skeleton
+	/// download falls back to the next-best idle peer instead of waiting for them.
+	fn is_best_peer(&self, io: &SyncIo, peer_id: PeerId) -> bool {
+		let difficulty = match self.peers.get(&peer_id) {
+			Some(peer) => peer.difficulty,
+			None => return false,
+		};
+		!self.peers.iter().any(|(id, peer)|
+			*id != peer_id &&
+			self.active_peers.contains(id) &&
+			peer.can_sync() &&
+			(peer.asking == PeerAsking::Nothing || peer.asking == PeerAsking::BlockHeaders) &&
+			(peer.difficulty > difficulty || (peer.difficulty == difficulty && *id < peer_id)) &&
+			io.chain().block_status(BlockId::Hash(peer.latest_hash)) == BlockStatus::Unknown
+		)
+	}
+
+	/// Account a response to the pending body or receipt request of a peer.
+	fn record_throughput(&mut self, peer_id: PeerId, count: usize) {
+		if let Some(peer) = self.peers.get_mut(&peer_id) {
+			let elapsed = peer.ask_time.elapsed();
+			peer.throughput.record(count, elapsed);
+		}
+	}
+
 	/// Clear all blocks/headers marked as being downloaded by a peer.
 	fn clear_peer_download(&mut self, peer_id: PeerId) {
 		if let Some(ref peer) = self.peers.get(&peer_id) {
@@ -880,9 +1017,15 @@ impl ChainSync {
 	fn send_status(&mut self, io: &mut SyncIo, peer: PeerId) -> Result<(), network::Error> {
 		let warp_protocol_version = io.protocol_version(&WARP_SYNC_PROTOCOL_ID, peer);
 		let warp_protocol = warp_protocol_version != 0;
-		let protocol = if warp_protocol { warp_protocol_version } else { ETH_PROTOCOL_VERSION_63.0 };
+		let protocol = match warp_protocol {
+			true => warp_protocol_version,
+			false if io.eth_protocol_version(peer) >= ETH_PROTOCOL_VERSION_65.0 => ETH_PROTOCOL_VERSION_65.0,
+			false if io.eth_protocol_version(peer) >= ETH_PROTOCOL_VERSION_64.0 => ETH_PROTOCOL_VERSION_64.0,
+			false => ETH_PROTOCOL_VERSION_63.0,
+		};
+		let fork_id = ChainSync::has_fork_id(warp_protocol, protocol);
 		trace!(target: "sync", "Sending status to {}, protocol version {}", peer, protocol);
-		let mut packet = RlpStream::new_list(if warp_protocol { 7 } else { 5 });
+		let mut packet = RlpStream::new_list(if warp_protocol { 7 } else { 5 } + if fork_id { 1 } else { 0 });
 		let chain = io.chain().chain_info();
 		packet.append(&(protocol as u32));
 		packet.append(&self.network_id);
@@ -896,14 +1039,41 @@ impl ChainSync {
 			packet.append(&manifest_hash);
 			packet.append(&block_number);
 		}
+		if fork_id {
+			packet.append(&self.fork_filter.current(chain.best_block_number));
+		}
 		io.respond(STATUS_PACKET, packet.out())
 	}
 
+	/// Whether status packets of given protocol version carry the fork identifier.
+	fn has_fork_id(warp_protocol: bool, protocol_version: u8) -> bool {
+		match warp_protocol {
+			true => protocol_version >= PAR_PROTOCOL_VERSION_4.0,
+			false => protocol_version >= ETH_PROTOCOL_VERSION_64.0,
+		}
+	}
+
+	/// Whether peers of given protocol version accept transaction hash announcements.
+	fn has_transaction_announcements(warp_protocol: bool, protocol_version: u8) -> bool {
+		match warp_protocol {
+			true => protocol_version >= PAR_PROTOCOL_VERSION_5.0,
+			false => protocol_version >= ETH_PROTOCOL_VERSION_65.0,
+		}
+	}
+
 	pub fn maintain_peers(&mut self, io: &mut SyncIo) {
 		let tick = Instant::now();
 		let mut aborting = Vec::new();
+		let mut retrying = Vec::new();
 		for (peer_id, peer) in &self.peers {
 			let elapsed = tick - peer.ask_time;
+			let slow_download = match peer.asking {
+				PeerAsking::BlockBodies | PeerAsking::BlockReceipts => !peer.expired && elapsed > peer.download_retry_timeout(),
+				_ => false,
+			};
+			if slow_download {
+				retrying.push(*peer_id);
+			}
 			let timeout = match peer.asking {
 				PeerAsking::BlockHeaders => elapsed > HEADERS_TIMEOUT,
 				PeerAsking::BlockBodies => elapsed > BODIES_TIMEOUT,
@@ -915,6 +1085,7 @@ impl ChainSync {
 			};
 			if timeout {
 				debug!(target:"sync", "Timeout {}", peer_id);
+				io.report_peer(*peer_id, PeerBehaviour::Timeout);
 				io.disconnect_peer(*peer_id);
 				aborting.push(*peer_id);
 			}
@@ -923,6 +1094,22 @@ impl ChainSync {
 			SyncHandler::on_peer_aborting(self, io, p);
 		}
 
+		// Give requests of slow peers to other peers. The peer is disconnected if it doesn't respond at all.
+		for p in &retrying {
+			debug!(target: "sync", "Slow download from {}, retrying with other peers", p);
+			self.clear_peer_download(*p);
+			if let Some(peer) = self.peers.get_mut(p) {
+				peer.throughput.record_timeout();
+				peer.expire_request();
+			}
+		}
+		if !retrying.is_empty() {
+			self.continue_sync(io);
+		}
+
+		let best_block = io.chain().chain_info().best_block_number;
+		self.transactions_stats.prune_received(best_block);
+
 		// Check for handshake timeouts
 		for (peer, &ask_time) in &self.handshaking_peers {
 			let elapsed = (tick - ask_time) / 1_000_000_000;
@@ -931,6 +1118,9 @@ impl ChainSync {
 				io.disconnect_peer(*peer);
 			}
 		}
+
+		// Forget timed out transaction requests so that they can be made to other peers.
+		self.requested_transactions.retain(|_, ask_time| tick - *ask_time < POOLED_TRANSACTIONS_TIMEOUT);
 	}
 
 	fn check_resume(&mut self, io: &mut SyncIo) {
@@ -1057,6 +1247,8 @@ impl ChainSync {
 
 	/// Maintain other peers. Send out any new blocks and transactions
 	pub fn maintain_sync(&mut self, io: &mut SyncIo) {
+		let best_block = io.chain().chain_info().best_block_number;
+		self.import_rate.record(Instant::now(), best_block);
 		self.maybe_start_snapshot_sync(io);
 		self.check_resume(io);
 	}
@@ -1217,9 +1409,26 @@ pub mod tests {
 			num_snapshot_chunks: 0,
 			snapshot_chunks_done: 0,
 			last_imported_old_block_number: None,
+			import_rate: None,
 		}
 	}
 
+	#[test]
+	fn import_rate_is_smoothed() {
+		let mut rate = ImportRate::default();
+		let start = Instant::now();
+
+		rate.record(start, 100);
+		assert_eq!(rate.blocks_per_second(), None);
+
+		rate.record(start + Duration::from_secs(2), 120);
+		assert_eq!(rate.blocks_per_second(), Some(10.0));
+
+		// no blocks imported
+		rate.record(start + Duration::from_secs(4), 120);
+		assert_eq!(rate.blocks_per_second(), Some(8.0));
+	}
+
 	#[test]
 	fn is_still_verifying() {
 		assert!(!sync_status(SyncState::Idle).is_syncing(queue_info(2, 1)));
@@ -1251,7 +1460,9 @@ pub mod tests {
 				asking_hash: None,
 				ask_time: Instant::now(),
 				last_sent_transactions: HashSet::new(),
+				transaction_announcements: false,
 				expired: false,
+				throughput: Throughput::default(),
 				confirmation: super::ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
@@ -1261,6 +1472,70 @@ pub mod tests {
 
 	}
 
+	#[test]
+	fn best_peer_falls_back_to_next_idle_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(H256::from(1), &client);
+		insert_dummy_peer(&mut sync, 1, H256::from(2));
+		for (id, difficulty) in vec![(0, 200u64), (1, 100)] {
+			sync.peers.get_mut(&id).unwrap().difficulty = Some(U256::from(difficulty));
+			sync.active_peers.insert(id);
+		}
+		let ss = TestSnapshotService::new();
+		let io = TestIo::new(&mut client, &ss, &queue, None);
+
+		assert!(sync.is_best_peer(&io, 0));
+		assert!(!sync.is_best_peer(&io, 1));
+
+		// the best peer is busy downloading bodies.
+		sync.peers.get_mut(&0).unwrap().asking = PeerAsking::BlockBodies;
+		assert!(sync.is_best_peer(&io, 1));
+
+		// the best peer is already downloading headers.
+		sync.peers.get_mut(&0).unwrap().asking = PeerAsking::BlockHeaders;
+		assert!(!sync.is_best_peer(&io, 1));
+	}
+
+	#[test]
+	fn should_ignore_late_response_of_slow_peer() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(10, EachBlockWith::Nothing);
+		let queue = RwLock::new(VecDeque::new());
+		let mut sync = dummy_sync_with_peer(client.block_hash_delta_minus(1), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+		{
+			let peer = sync.peers.get_mut(&0).unwrap();
+			peer.asking = PeerAsking::BlockBodies;
+			peer.asking_blocks = vec![H256::from(1)];
+			peer.ask_time = Instant::now() - MIN_DOWNLOAD_RETRY_TIMEOUT * 2;
+			// the peer is known to be slow.
+			peer.throughput.record_timeout();
+		}
+
+		sync.maintain_peers(&mut io);
+
+		{
+			let peer = &sync.peers[&0];
+			assert!(peer.expired);
+			assert_eq!(peer.asking, PeerAsking::BlockBodies);
+			assert!(peer.asking_blocks.is_empty());
+		}
+
+		// the late response is dropped without punishing the peer.
+		let mut bodies = RlpStream::new_list(1);
+		bodies.begin_list(2);
+		bodies.begin_list(0);
+		bodies.begin_list(0);
+		SyncHandler::on_packet(&mut sync, &mut io, 0, BLOCK_BODIES_PACKET, &bodies.out());
+
+		assert!(io.reports.is_empty());
+		assert!(io.to_disconnect.is_empty());
+		assert!(!sync.peers[&0].expired);
+	}
+
 	#[test]
 	fn finds_lagging_peers() {
 		let mut client = TestBlockChainClient::new();
@@ -29,6 +29,7 @@ use transaction::SignedTransaction;
 use super::{
 	random,
 	ChainSync,
+	TransactionPropagation,
 	MAX_PEER_LAG_PROPAGATION,
 	MAX_PEERS_PROPAGATION,
 	MAX_TRANSACTION_PACKET_SIZE,
@@ -38,6 +39,7 @@ use super::{
 	CONSENSUS_DATA_PACKET,
 	NEW_BLOCK_HASHES_PACKET,
 	NEW_BLOCK_PACKET,
+	NEW_POOLED_TRANSACTION_HASHES_PACKET,
 	PRIVATE_TRANSACTION_PACKET,
 	SIGNED_PRIVATE_TRANSACTION_PACKET,
 	TRANSACTIONS_PACKET,
@@ -120,7 +122,13 @@ impl SyncPropagator {
 			return 0;
 		}
 
-		let (transactions, service_transactions): (Vec<_>, Vec<_>) = transactions.iter()
+		// local transactions go first, so they are never cut off by packet limits
+		let (local, remote): (Vec<_>, Vec<_>) = transactions.iter().partition(|tx| tx.is_local());
+		let local_hashes = local.iter()
+			.map(|tx| tx.signed().hash())
+			.collect::<HashSet<H256>>();
+		let (transactions, service_transactions): (Vec<_>, Vec<_>) = local.into_iter()
+			.chain(remote)
 			.map(|tx| tx.signed())
 			.partition(|tx| !tx.gas_price.is_zero());
 
@@ -128,21 +136,21 @@ impl SyncPropagator {
 		let mut affected_peers = HashSet::new();
 		if !transactions.is_empty() {
 			let peers = SyncPropagator::select_peers_for_transactions(sync, |_| true);
-			affected_peers = SyncPropagator::propagate_transactions_to_peers(sync, io, peers, transactions);
+			affected_peers = SyncPropagator::propagate_transactions_to_peers(sync, io, peers, transactions, &local_hashes);
 		}
 
 		// most of times service_transactions will be empty
 		// => there's no need to merge packets
 		if !service_transactions.is_empty() {
 			let service_transactions_peers = SyncPropagator::select_peers_for_transactions(sync, |peer_id| accepts_service_transaction(&io.peer_info(*peer_id)));
-			let service_transactions_affected_peers = SyncPropagator::propagate_transactions_to_peers(sync, io, service_transactions_peers, service_transactions);
+			let service_transactions_affected_peers = SyncPropagator::propagate_transactions_to_peers(sync, io, service_transactions_peers, service_transactions, &local_hashes);
 			affected_peers.extend(&service_transactions_affected_peers);
 		}
 
 		affected_peers.len()
 	}
 
-	fn propagate_transactions_to_peers(sync: &mut ChainSync, io: &mut SyncIo, peers: Vec<PeerId>, transactions: Vec<&SignedTransaction>) -> HashSet<PeerId> {
+	fn propagate_transactions_to_peers(sync: &mut ChainSync, io: &mut SyncIo, peers: Vec<PeerId>, transactions: Vec<&SignedTransaction>, local: &HashSet<H256>) -> HashSet<PeerId> {
 		let all_transactions_hashes = transactions.iter()
 			.map(|tx| tx.hash())
 			.collect::<HashSet<H256>>();
@@ -155,90 +163,106 @@ impl SyncPropagator {
 		// Clear old transactions from stats
 		sync.transactions_stats.retain(&all_transactions_hashes);
 
-		// sqrt(x)/x scaled to max u32
 		let block_number = io.chain().chain_info().best_block_number;
+		let full_peers = SyncPropagator::select_full_transactions_peers(sync, &peers);
+		let announce_only = sync.transaction_propagation == TransactionPropagation::AnnounceOnly;
 
-		let lucky_peers = {
-			peers.into_iter()
-				.filter_map(|peer_id| {
-					let stats = &mut sync.transactions_stats;
-					let peer_info = sync.peers.get_mut(&peer_id)
-						.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
-
-					// Send all transactions
-					if peer_info.last_sent_transactions.is_empty() {
-						// update stats
-						for hash in &all_transactions_hashes {
-							let id = io.peer_session_info(peer_id).and_then(|info| info.id);
-							stats.propagated(hash, id, block_number);
-						}
-						peer_info.last_sent_transactions = all_transactions_hashes.clone();
-						return Some((peer_id, all_transactions_hashes.len(), all_transactions_rlp.clone()));
-					}
+		let mut lucky_peers = Vec::new();
+		for peer_id in peers {
+			let stats = &mut sync.transactions_stats;
+			let peer_info = sync.peers.get_mut(&peer_id)
+				.expect("peer_id is form peers; peers is result of select_peers_for_transactions; select_peers_for_transactions selects peers from self.peers; qed");
+
+			// Peers which don't receive full transactions get only the local ones, and hashes of the others if they accept announcements.
+			let announcements = peer_info.transaction_announcements;
+			let full = full_peers.contains(&peer_id) && !(announce_only && announcements);
+			// Send all transactions to new peers, only a limited number of new ones otherwise.
+			let limit = match peer_info.last_sent_transactions.is_empty() {
+				true => transactions.len(),
+				false => MAX_TRANSACTIONS_TO_PROPAGATE,
+			};
+			let (bodies, hashes): (Vec<_>, Vec<_>) = transactions.iter()
+				.filter(|tx| !peer_info.last_sent_transactions.contains(&tx.hash()))
+				.filter(|tx| full || announcements || local.contains(&tx.hash()))
+				.take(limit)
+				.partition(|tx| full || local.contains(&tx.hash()));
+			if bodies.is_empty() && hashes.is_empty() {
+				continue;
+			}
 
-					// Get hashes of all transactions to send to this peer
-					let to_send = all_transactions_hashes.difference(&peer_info.last_sent_transactions)
-						.take(MAX_TRANSACTIONS_TO_PROPAGATE)
-						.cloned()
-						.collect::<HashSet<_>>();
-					if to_send.is_empty() {
-						return None;
+			// Construct RLP
+			let (bodies_packet, sent) = if bodies.len() == transactions.len() {
+				(all_transactions_rlp.clone(), all_transactions_hashes.clone())
+			} else {
+				let mut sent = HashSet::new();
+				let mut packet = RlpStream::new();
+				packet.begin_unbounded_list();
+				for tx in &bodies {
+					let mut transaction = RlpStream::new();
+					tx.rlp_append(&mut transaction);
+					let appended = packet.append_raw_checked(&transaction.drain(), 1, MAX_TRANSACTION_PACKET_SIZE);
+					if !appended {
+						// Maximal packet size reached just proceed with sending
+						debug!("Transaction packet size limit reached. Sending incomplete set of {}/{} transactions.", sent.len(), bodies.len());
+						break;
 					}
+					sent.insert(tx.hash());
+				}
+				packet.complete_unbounded_list();
+				(packet.out(), sent)
+			};
+			let hashes_packet = {
+				let mut packet = RlpStream::new_list(hashes.len());
+				for tx in &hashes {
+					packet.append(&tx.hash());
+				}
+				packet.out()
+			};
 
-					// Construct RLP
-					let (packet, to_send) = {
-						let mut to_send = to_send;
-						let mut packet = RlpStream::new();
-						packet.begin_unbounded_list();
-						let mut pushed = 0;
-						for tx in &transactions {
-							let hash = tx.hash();
-							if to_send.contains(&hash) {
-								let mut transaction = RlpStream::new();
-								tx.rlp_append(&mut transaction);
-								let appended = packet.append_raw_checked(&transaction.drain(), 1, MAX_TRANSACTION_PACKET_SIZE);
-								if !appended {
-									// Maximal packet size reached just proceed with sending
-									debug!("Transaction packet size limit reached. Sending incomplete set of {}/{} transactions.", pushed, to_send.len());
-									to_send = to_send.into_iter().take(pushed).collect();
-									break;
-								}
-								pushed += 1;
-							}
-						}
-						packet.complete_unbounded_list();
-						(packet, to_send)
-					};
-
-					// Update stats
-					let id = io.peer_session_info(peer_id).and_then(|info| info.id);
-					for hash in &to_send {
-						// update stats
-						stats.propagated(hash, id, block_number);
-					}
+			// Update stats
+			let id = io.peer_session_info(peer_id).and_then(|info| info.id);
+			let announced = hashes.iter().map(|tx| tx.hash()).collect::<HashSet<_>>();
+			for hash in sent.iter().chain(&announced) {
+				stats.propagated(hash, id, block_number);
+			}
 
-					peer_info.last_sent_transactions = all_transactions_hashes
-						.intersection(&peer_info.last_sent_transactions)
-						.chain(&to_send)
-						.cloned()
-						.collect();
-					Some((peer_id, to_send.len(), packet.out()))
-				})
-				.collect::<Vec<_>>()
-		};
+			peer_info.last_sent_transactions = all_transactions_hashes
+				.intersection(&peer_info.last_sent_transactions)
+				.chain(&sent)
+				.chain(&announced)
+				.cloned()
+				.collect();
+			let bodies = match sent.is_empty() {
+				true => None,
+				false => Some((sent.len(), bodies_packet)),
+			};
+			let hashes = match announced.is_empty() {
+				true => None,
+				false => Some((announced.len(), hashes_packet)),
+			};
+			lucky_peers.push((peer_id, bodies, hashes));
+		}
 
 		// Send RLPs
 		let mut peers = HashSet::new();
 		if lucky_peers.len() > 0 {
 			let mut max_sent = 0;
+			let mut max_announced = 0;
 			let lucky_peers_len = lucky_peers.len();
-			for (peer_id, sent, rlp) in lucky_peers {
+			for (peer_id, bodies, hashes) in lucky_peers {
 				peers.insert(peer_id);
-				SyncPropagator::send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp);
-				trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
-				max_sent = cmp::max(max_sent, sent);
+				if let Some((sent, rlp)) = bodies {
+					SyncPropagator::send_packet(io, peer_id, TRANSACTIONS_PACKET, rlp);
+					trace!(target: "sync", "{:02} <- Transactions ({} entries)", peer_id, sent);
+					max_sent = cmp::max(max_sent, sent);
+				}
+				if let Some((announced, rlp)) = hashes {
+					SyncPropagator::send_packet(io, peer_id, NEW_POOLED_TRANSACTION_HASHES_PACKET, rlp);
+					trace!(target: "sync", "{:02} <- NewPooledTransactionHashes ({} entries)", peer_id, announced);
+					max_announced = cmp::max(max_announced, announced);
+				}
 			}
-			debug!(target: "sync", "Sent up to {} transactions to {} peers.", max_sent, lucky_peers_len);
+			debug!(target: "sync", "Sent up to {} transactions and announced up to {} hashes to {} peers.", max_sent, max_announced, lucky_peers_len);
 		}
 
 		peers
@@ -308,19 +332,35 @@ impl SyncPropagator {
 
 	fn select_peers_for_transactions<F>(sync: &ChainSync, filter: F) -> Vec<PeerId>
 		where F: Fn(&PeerId) -> bool {
-		// sqrt(x)/x scaled to max u32
-		let fraction = ((sync.peers.len() as f64).powf(-0.5) * (u32::max_value() as f64).round()) as u32;
-		let small = sync.peers.len() < MIN_PEERS_PROPAGATION;
-
-		let mut random = random::new();
 		sync.peers.keys()
 			.cloned()
 			.filter(filter)
-			.filter(|_| small || random.next_u32() < fraction)
-			.take(MAX_PEERS_PROPAGATION)
 			.collect()
 	}
 
+	/// Selects peers receiving full transactions, the other ones get only announcements.
+	fn select_full_transactions_peers(sync: &ChainSync, peers: &[PeerId]) -> HashSet<PeerId> {
+		let mut random = random::new();
+		match sync.transaction_fanout {
+			Some(fanout) => {
+				let mut peers = peers.to_vec();
+				random.shuffle(&mut peers);
+				peers.into_iter().take(cmp::min(fanout, MAX_PEERS_PROPAGATION)).collect()
+			},
+			None => {
+				// sqrt(x)/x scaled to max u32
+				let fraction = ((sync.peers.len() as f64).powf(-0.5) * (u32::max_value() as f64).round()) as u32;
+				let small = sync.peers.len() < MIN_PEERS_PROPAGATION;
+
+				peers.iter()
+					.cloned()
+					.filter(|_| small || random.next_u32() < fraction)
+					.take(MAX_PEERS_PROPAGATION)
+					.collect()
+			},
+		}
+	}
+
 	/// Generic packet sender
 	fn send_packet(sync: &mut SyncIo, peer_id: PeerId, packet_id: PacketId, packet: Bytes) {
 		if let Err(e) = sync.send(peer_id, packet_id, packet) {
@@ -424,7 +464,9 @@ mod tests {
 				asking_hash: None,
 				ask_time: Instant::now(),
 				last_sent_transactions: HashSet::new(),
+				transaction_announcements: false,
 				expired: false,
+				throughput: Default::default(),
 				confirmation: ForkConfirmation::Confirmed,
 				snapshot_number: None,
 				snapshot_hash: None,
@@ -562,6 +604,56 @@ mod tests {
 		assert_eq!(stats.len(), 1, "Should maintain stats for single transaction.")
 	}
 
+	#[test]
+	fn announces_hashes_to_peers_not_receiving_full_transactions() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut config = SyncConfig::default();
+		config.transaction_fanout = Some(0);
+		let mut sync = ChainSync::new(config, &client, Arc::new(NoopPrivateTxHandler));
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		// peer#1 accepts announcements, peer#2 doesn't
+		insert_dummy_peer(&mut sync, 1, block_hash);
+		sync.peers.get_mut(&1).unwrap().transaction_announcements = true;
+		insert_dummy_peer(&mut sync, 2, block_hash);
+
+		let peer_count = SyncPropagator::propagate_new_transactions(&mut sync, &mut io);
+
+		// only the hash is announced to peer#1
+		assert_eq!(1, peer_count);
+		assert_eq!(1, io.packets.len());
+		assert_eq!(1, io.packets[0].recipient);
+		assert_eq!(NEW_POOLED_TRANSACTION_HASHES_PACKET, io.packets[0].packet_id);
+		assert_eq!(Ok(1), Rlp::new(&io.packets[0].data).item_count());
+	}
+
+	#[test]
+	fn limits_full_transactions_to_fanout() {
+		let mut client = TestBlockChainClient::new();
+		client.add_blocks(100, EachBlockWith::Uncle);
+		client.insert_transaction_to_queue();
+		let block_hash = client.block_hash_delta_minus(1);
+		let mut config = SyncConfig::default();
+		config.transaction_fanout = Some(2);
+		let mut sync = ChainSync::new(config, &client, Arc::new(NoopPrivateTxHandler));
+		let queue = RwLock::new(VecDeque::new());
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		for peer_id in 0..5 {
+			insert_dummy_peer(&mut sync, peer_id, block_hash);
+		}
+
+		SyncPropagator::propagate_new_transactions(&mut sync, &mut io);
+
+		assert_eq!(2, io.packets.iter().filter(|p| p.packet_id == TRANSACTIONS_PACKET).count());
+	}
+
 	#[test]
 	fn should_propagate_service_transaction_to_selected_peers_only() {
 		let mut client = TestBlockChainClient::new();
@@ -31,6 +31,7 @@ use super::{
 	ETH_PROTOCOL_VERSION_63,
 	GET_BLOCK_BODIES_PACKET,
 	GET_BLOCK_HEADERS_PACKET,
+	GET_POOLED_TRANSACTIONS_PACKET,
 	GET_RECEIPTS_PACKET,
 	GET_SNAPSHOT_DATA_PACKET,
 	GET_SNAPSHOT_MANIFEST_PACKET,
@@ -132,6 +133,19 @@ impl SyncRequester {
 		SyncRequester::send_request(sync, io, peer_id, PeerAsking::SnapshotData, GET_SNAPSHOT_DATA_PACKET, rlp.out());
 	}
 
+	/// Request announced transactions from a peer. Doesn't affect any ongoing block or snapshot request.
+	pub fn request_pooled_transactions(io: &mut SyncIo, peer_id: PeerId, hashes: &[H256]) {
+		trace!(target: "sync", "{} <- GetPooledTransactions: {} entries", peer_id, hashes.len());
+		let mut rlp = RlpStream::new_list(hashes.len());
+		for h in hashes {
+			rlp.append(h);
+		}
+		if let Err(e) = io.send(peer_id, GET_POOLED_TRANSACTIONS_PACKET, rlp.out()) {
+			debug!(target:"sync", "Error sending request: {:?}", e);
+			io.disconnect_peer(peer_id);
+		}
+	}
+
 	/// Generic request sender
 	fn send_request(sync: &mut ChainSync, io: &mut SyncIo, peer_id: PeerId, asking: PeerAsking,  packet_id: PacketId, packet: Bytes) {
 		if let Some(ref mut peer) = sync.peers.get_mut(&peer_id) {
@@ -20,7 +20,7 @@ use ethcore::header::BlockNumber;
 use ethereum_types::H256;
 use network::{self, PeerId};
 use parking_lot::RwLock;
-use rlp::{Rlp, RlpStream};
+use rlp::{Encodable, Rlp, RlpStream};
 use std::cmp;
 use sync_io::SyncIo;
 
@@ -34,15 +34,19 @@ use super::{
 	GET_BLOCK_BODIES_PACKET,
 	GET_BLOCK_HEADERS_PACKET,
 	GET_NODE_DATA_PACKET,
+	GET_POOLED_TRANSACTIONS_PACKET,
 	GET_RECEIPTS_PACKET,
 	GET_SNAPSHOT_DATA_PACKET,
 	GET_SNAPSHOT_MANIFEST_PACKET,
 	MAX_BODIES_TO_SEND,
 	MAX_HEADERS_TO_SEND,
 	MAX_NODE_DATA_TO_SEND,
+	MAX_POOLED_TRANSACTIONS_TO_SEND,
 	MAX_RECEIPTS_HEADERS_TO_SEND,
 	MAX_RECEIPTS_TO_SEND,
+	MAX_TRANSACTION_PACKET_SIZE,
 	NODE_DATA_PACKET,
+	POOLED_TRANSACTIONS_PACKET,
 	RECEIPTS_PACKET,
 	SNAPSHOT_DATA_PACKET,
 	SNAPSHOT_MANIFEST_PACKET,
@@ -72,6 +76,10 @@ impl SyncSupplier {
 				SyncSupplier::return_node_data,
 				|e| format!("Error sending nodes: {:?}", e)),
 
+			GET_POOLED_TRANSACTIONS_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
+				SyncSupplier::return_pooled_transactions,
+				|e| format!("Error sending pooled transactions: {:?}", e)),
+
 			GET_SNAPSHOT_MANIFEST_PACKET => SyncSupplier::return_rlp(io, &rlp, peer,
 				SyncSupplier::return_snapshot_manifest,
 				|e| format!("Error sending snapshot manifest: {:?}", e)),
@@ -213,6 +221,33 @@ impl SyncSupplier {
 		Ok(Some((NODE_DATA_PACKET, rlp)))
 	}
 
+	/// Respond to GetPooledTransactions request
+	fn return_pooled_transactions(io: &SyncIo, r: &Rlp, peer_id: PeerId) -> RlpResponseResult {
+		let mut count = r.item_count().unwrap_or(0);
+		trace!(target: "sync", "{} -> GetPooledTransactions: {} entries", peer_id, count);
+		if count == 0 {
+			debug!(target: "sync", "Empty GetPooledTransactions request, ignoring.");
+			return Ok(None);
+		}
+		count = cmp::min(count, MAX_POOLED_TRANSACTIONS_TO_SEND);
+		let mut added = 0usize;
+		let mut rlp = RlpStream::new();
+		rlp.begin_unbounded_list();
+		for i in 0..count {
+			if let Some(tx) = io.chain().queued_transaction(&r.val_at::<H256>(i)?) {
+				let mut transaction = RlpStream::new();
+				tx.signed().rlp_append(&mut transaction);
+				if !rlp.append_raw_checked(&transaction.drain(), 1, MAX_TRANSACTION_PACKET_SIZE) {
+					break;
+				}
+				added += 1;
+			}
+		}
+		rlp.complete_unbounded_list();
+		trace!(target: "sync", "{} -> GetPooledTransactions: return {} entries", peer_id, added);
+		Ok(Some((POOLED_TRANSACTIONS_PACKET, rlp)))
+	}
+
 	fn return_receipts(io: &SyncIo, rlp: &Rlp, peer_id: PeerId) -> RlpResponseResult {
 		let mut count = rlp.item_count().unwrap_or(0);
 		trace!(target: "sync", "{} -> GetReceipts: {} entries", peer_id, count);
@@ -304,6 +339,7 @@ mod test {
 	use ethereum_types::{H256};
 	use parking_lot::RwLock;
 	use bytes::Bytes;
+	use hash::keccak;
 	use rlp::{Rlp, RlpStream};
 	use super::{*, super::tests::*};
 	use ethcore::client::{BlockChainClient, EachBlockWith, TestBlockChainClient};
@@ -406,6 +442,37 @@ mod test {
 		assert_eq!(1, io.packets.len());
 	}
 
+	#[test]
+	fn return_pooled_transactions() {
+		let mut client = TestBlockChainClient::new();
+		let tx_hash = client.insert_transaction_to_queue();
+		let queue = RwLock::new(VecDeque::new());
+		let sync = dummy_sync_with_peer(H256::new(), &client);
+		let ss = TestSnapshotService::new();
+		let mut io = TestIo::new(&mut client, &ss, &queue, None);
+
+		let mut hash_list = RlpStream::new_list(2);
+		hash_list.append(&tx_hash);
+		hash_list.append(&H256::from(1));
+
+		let request = hash_list.out();
+		// unknown transactions are skipped
+		let result = SyncSupplier::return_pooled_transactions(&io, &Rlp::new(&request), 0);
+
+		assert!(result.is_ok());
+		let (packet_id, rlp) = result.unwrap().expect("known transaction is returned");
+		assert_eq!(POOLED_TRANSACTIONS_PACKET, packet_id);
+		let rlp = rlp.out();
+		let rlp = Rlp::new(&rlp);
+		assert_eq!(Ok(1), rlp.item_count());
+		assert_eq!(tx_hash, keccak(rlp.at(0).unwrap().as_raw()));
+
+		io.sender = Some(2usize);
+
+		ChainSync::dispatch_packet(&RwLock::new(sync), &mut io, 0usize, GET_POOLED_TRANSACTIONS_PACKET, &request);
+		assert_eq!(1, io.packets.len());
+	}
+
 	#[test]
 	fn return_receipts_empty() {
 		let mut client = TestBlockChainClient::new();
@@ -0,0 +1,194 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fork identifiers as specified in EIP-2124, used to tell apart peers
+//! following other forks of the chain during the handshake.
+
+use crc::crc32;
+use ethcore::header::BlockNumber;
+use ethereum_types::H256;
+use rlp::{Encodable, Decodable, DecoderError, RlpStream, Rlp};
+
+/// Identifier of the fork a node is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForkId {
+	/// CRC32 checksum of the genesis hash and all the passed fork block numbers.
+	pub hash: u32,
+	/// Block number of the next upcoming fork, or 0 if none is known.
+	pub next: BlockNumber,
+}
+
+impl Encodable for ForkId {
+	fn rlp_append(&self, s: &mut RlpStream) {
+		let hash = [(self.hash >> 24) as u8, (self.hash >> 16) as u8, (self.hash >> 8) as u8, self.hash as u8];
+		s.begin_list(2);
+		s.append(&&hash[..]);
+		s.append(&self.next);
+	}
+}
+
+impl Decodable for ForkId {
+	fn decode(rlp: &Rlp) -> Result<Self, DecoderError> {
+		let hash: Vec<u8> = rlp.val_at(0)?;
+		if hash.len() != 4 {
+			return Err(DecoderError::Custom("Invalid fork hash length"));
+		}
+
+		Ok(ForkId {
+			hash: hash.iter().fold(0, |acc, b| (acc << 8) | *b as u32),
+			next: rlp.val_at(1)?,
+		})
+	}
+}
+
+/// Reason of a remote fork identifier being rejected.
+#[derive(Debug, PartialEq)]
+pub enum ForkIdError {
+	/// Remote node is on an old fork and does not know about the one we have already passed.
+	RemoteStale,
+	/// Remote node is on another fork, or we have not upgraded for a fork it has already passed.
+	LocalIncompatibleOrStale,
+}
+
+/// Computes the local fork identifier and validates remote ones.
+#[derive(Debug, Clone)]
+pub struct ForkFilter {
+	/// Sorted fork block numbers.
+	forks: Vec<BlockNumber>,
+	/// Checksums of the genesis hash and the first `i` forks.
+	hashes: Vec<u32>,
+}
+
+impl ForkFilter {
+	/// Create a new filter for the chain with given genesis and sorted fork block numbers.
+	pub fn new(genesis: &H256, forks: Vec<BlockNumber>) -> Self {
+		let mut hashes = Vec::with_capacity(forks.len() + 1);
+		let mut hash = crc32::update(0, &crc32::IEEE_TABLE, &**genesis);
+		hashes.push(hash);
+		for fork in &forks {
+			let number = [
+				(fork >> 56) as u8, (fork >> 48) as u8, (fork >> 40) as u8, (fork >> 32) as u8,
+				(fork >> 24) as u8, (fork >> 16) as u8, (fork >> 8) as u8, *fork as u8,
+			];
+			hash = crc32::update(hash, &crc32::IEEE_TABLE, &number);
+			hashes.push(hash);
+		}
+
+		ForkFilter {
+			forks: forks,
+			hashes: hashes,
+		}
+	}
+
+	/// Number of forks passed at given block.
+	fn passed(&self, head: BlockNumber) -> usize {
+		self.forks.iter().take_while(|fork| **fork <= head).count()
+	}
+
+	/// Fork identifier of the chain at given best block.
+	pub fn current(&self, head: BlockNumber) -> ForkId {
+		let passed = self.passed(head);
+		ForkId {
+			hash: self.hashes[passed],
+			next: self.forks.get(passed).cloned().unwrap_or(0),
+		}
+	}
+
+	/// Check whether a peer announcing given fork identifier is compatible with
+	/// the chain at given best block.
+	pub fn validate(&self, head: BlockNumber, remote: &ForkId) -> Result<(), ForkIdError> {
+		let passed = self.passed(head);
+
+		// same forks passed: the peer might only announce a fork we don't know about yet.
+		if remote.hash == self.hashes[passed] {
+			return match remote.next {
+				0 => Ok(()),
+				next if head >= next => Err(ForkIdError::LocalIncompatibleOrStale),
+				_ => Ok(()),
+			};
+		}
+
+		// the peer is behind: it has to know about the next fork it hasn't passed yet.
+		if let Some(index) = self.hashes[..passed].iter().position(|hash| *hash == remote.hash) {
+			return match remote.next == self.forks[index] {
+				true => Ok(()),
+				false => Err(ForkIdError::RemoteStale),
+			};
+		}
+
+		// the peer is ahead of us, we're still syncing.
+		if self.hashes[passed + 1..].contains(&remote.hash) {
+			return Ok(());
+		}
+
+		Err(ForkIdError::LocalIncompatibleOrStale)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use rlp;
+
+	fn mainnet() -> ForkFilter {
+		let genesis = "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".into();
+		ForkFilter::new(&genesis, vec![1_150_000, 1_920_000, 2_463_000, 2_675_000, 4_370_000, 7_280_000])
+	}
+
+	#[test]
+	fn current_fork_id() {
+		let filter = mainnet();
+
+		assert_eq!(filter.current(0), ForkId { hash: 0xfc64ec04, next: 1_150_000 });
+		assert_eq!(filter.current(1_149_999), ForkId { hash: 0xfc64ec04, next: 1_150_000 });
+		assert_eq!(filter.current(1_150_000), ForkId { hash: 0x97c2c34c, next: 1_920_000 });
+		assert_eq!(filter.current(1_920_000), ForkId { hash: 0x91d1f948, next: 2_463_000 });
+		assert_eq!(filter.current(2_463_000), ForkId { hash: 0x7a64da13, next: 2_675_000 });
+		assert_eq!(filter.current(2_675_000), ForkId { hash: 0x3edd5b10, next: 4_370_000 });
+		assert_eq!(filter.current(4_370_000), ForkId { hash: 0xa00bc324, next: 7_280_000 });
+		assert_eq!(filter.current(7_280_000), ForkId { hash: 0x668db0af, next: 0 });
+	}
+
+	#[test]
+	fn validate_fork_id() {
+		let filter = mainnet();
+
+		// same fork, remote knows about the next one too.
+		assert_eq!(filter.validate(7_279_999, &ForkId { hash: 0xa00bc324, next: 7_280_000 }), Ok(()));
+		// same fork, remote doesn't know about the next one.
+		assert_eq!(filter.validate(7_279_999, &ForkId { hash: 0xa00bc324, next: 0 }), Ok(()));
+		// same fork, remote announces a fork we've already passed without upgrading.
+		let byzantium = ForkFilter::new(&"d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3".into(), filter.forks[..5].to_vec());
+		assert_eq!(byzantium.validate(7_987_396, &ForkId { hash: 0xa00bc324, next: 7_280_000 }), Err(ForkIdError::LocalIncompatibleOrStale));
+		// remote is syncing, but knows about our fork.
+		assert_eq!(filter.validate(7_280_000, &ForkId { hash: 0xa00bc324, next: 7_280_000 }), Ok(()));
+		// remote is syncing and doesn't know about the fork we've passed.
+		assert_eq!(filter.validate(7_280_000, &ForkId { hash: 0xa00bc324, next: 0 }), Err(ForkIdError::RemoteStale));
+		// we are syncing.
+		assert_eq!(filter.validate(4_369_999, &ForkId { hash: 0x668db0af, next: 0 }), Ok(()));
+		// remote is on another chain.
+		assert_eq!(filter.validate(7_280_000, &ForkId { hash: 0xafec6b27, next: 0 }), Err(ForkIdError::LocalIncompatibleOrStale));
+	}
+
+	#[test]
+	fn fork_id_rlp() {
+		let fork_id = ForkId { hash: 0xa00bc324, next: 7_280_000 };
+		let encoded = rlp::encode(&fork_id);
+
+		assert_eq!(&*encoded, &[0xc9, 0x84, 0xa0, 0x0b, 0xc3, 0x24, 0x83, 0x6f, 0x15, 0x80][..]);
+		assert_eq!(rlp::decode::<ForkId>(&encoded).unwrap(), fork_id);
+	}
+}
@@ -17,7 +17,7 @@
 #![warn(missing_docs)]
 
 //! Blockchain sync module
-//! Implements ethereum protocol version 63 as specified here:
+//! Implements ethereum protocol version 64 as specified here:
 //! https://github.com/ethereum/wiki/wiki/Ethereum-Wire-Protocol
 //!
 
@@ -40,6 +40,7 @@ extern crate ipnetwork;
 extern crate keccak_hash as hash;
 extern crate triehash;
 extern crate kvdb;
+extern crate crc;
 
 extern crate ethcore_light as light;
 
@@ -64,6 +65,7 @@ mod sync_io;
 mod private_tx;
 mod snapshot;
 mod transactions_stats;
+mod fork_id;
 
 pub mod light_sync;
 
@@ -75,5 +77,5 @@ mod api;
 pub use api::*;
 pub use chain::{SyncStatus, SyncState};
 pub use devp2p::{validate_node_url, ConnectionFilter, ConnectionDirection};
-pub use network::{NonReservedPeerMode, Error, ErrorKind};
+pub use network::{NonReservedPeerMode, Error, ErrorKind, SessionTraffic};
 pub use private_tx::{PrivateTxHandler, NoopPrivateTxHandler, SimplePrivateTxHandler};
@@ -29,7 +29,7 @@ use kvdb_memorydb;
 use light::client::fetch::{self, Unavailable};
 use light::net::{LightProtocol, IoContext, Capabilities, Params as LightParams};
 use light::provider::LightProvider;
-use network::{NodeId, PeerId};
+use network::{NodeId, PeerId, PeerBehaviour};
 use parking_lot::RwLock;
 
 use std::time::Duration;
@@ -65,9 +65,11 @@ impl<'a> IoContext for TestIoContext<'a> {
 	}
 
 	fn disable_peer(&self, peer: PeerId) { self.disconnect_peer(peer) }
+	fn report_peer(&self, _peer: PeerId, _behaviour: PeerBehaviour) {}
 	fn protocol_version(&self, _peer: PeerId) -> Option<u8> { Some(::light::net::MAX_PROTOCOL_VERSION) }
 
 	fn persistent_peer_id(&self, _peer: PeerId) -> Option<NodeId> { unimplemented!() }
+	fn is_reserved_peer(&self, _peer: PeerId) -> bool { false }
 }
 
 // peer-specific data.
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
-use network::{NetworkContext, PeerId, PacketId, Error, SessionInfo, ProtocolId};
+use network::{NetworkContext, PeerId, PacketId, Error, SessionInfo, ProtocolId, PeerBehaviour};
 use bytes::Bytes;
 use ethcore::client::BlockChainClient;
 use ethcore::header::BlockNumber;
@@ -24,12 +24,13 @@ use parking_lot::RwLock;
 
 /// IO interface for the syncing handler.
 /// Provides peer connection management and an interface to the blockchain client.
-// TODO: ratings
 pub trait SyncIo {
 	/// Disable a peer
 	fn disable_peer(&mut self, peer_id: PeerId);
 	/// Disconnect peer
 	fn disconnect_peer(&mut self, peer_id: PeerId);
+	/// Report peer behaviour, affecting its reputation
+	fn report_peer(&mut self, peer_id: PeerId, behaviour: PeerBehaviour);
 	/// Respond to current request with a packet. Can be called from an IO handler for incoming packet.
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), Error>;
 	/// Send a packet to a peer.
@@ -92,6 +93,10 @@ impl<'s> SyncIo for NetSyncIo<'s> {
 		self.network.disconnect_peer(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, behaviour: PeerBehaviour) {
+		self.network.report_peer(peer_id, behaviour);
+	}
+
 	fn respond(&mut self, packet_id: PacketId, data: Vec<u8>) -> Result<(), Error>{
 		self.network.respond(packet_id, data)
 	}
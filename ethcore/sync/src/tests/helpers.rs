@@ -20,7 +20,7 @@ use std::time::Duration;
 use ethereum_types::H256;
 use parking_lot::{RwLock, Mutex};
 use bytes::Bytes;
-use network::{self, PeerId, ProtocolId, PacketId, SessionInfo};
+use network::{self, PeerId, ProtocolId, PacketId, SessionInfo, PeerBehaviour};
 use tests::snapshot::*;
 use ethcore::client::{TestBlockChainClient, BlockChainClient, Client as EthcoreClient,
 	ClientConfig, ChainNotify, ChainRoute, ChainMessageType, ClientIoMessage};
@@ -54,6 +54,7 @@ pub struct TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 	pub queue: &'p RwLock<VecDeque<TestPacket>>,
 	pub sender: Option<PeerId>,
 	pub to_disconnect: HashSet<PeerId>,
+	pub reports: Vec<(PeerId, PeerBehaviour)>,
 	pub packets: Vec<TestPacket>,
 	pub peers_info: HashMap<PeerId, String>,
 	overlay: RwLock<HashMap<BlockNumber, Bytes>>,
@@ -67,6 +68,7 @@ impl<'p, C> TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 			queue: queue,
 			sender: sender,
 			to_disconnect: HashSet::new(),
+			reports: Vec::new(),
 			overlay: RwLock::new(HashMap::new()),
 			packets: Vec::new(),
 			peers_info: HashMap::new(),
@@ -89,6 +91,10 @@ impl<'p, C> SyncIo for TestIo<'p, C> where C: FlushingBlockChainClient, C: 'p {
 		self.to_disconnect.insert(peer_id);
 	}
 
+	fn report_peer(&mut self, peer_id: PeerId, behaviour: PeerBehaviour) {
+		self.reports.push((peer_id, behaviour));
+	}
+
 	fn is_expired(&self) -> bool {
 		false
 	}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use api::TransactionStats;
+use api::{TransactionStats, ReceivedTransactionsStats};
 use std::collections::{HashSet, HashMap};
 use ethereum_types::{H256, H512};
 use plain_hasher::H256FastMap;
@@ -22,6 +22,9 @@ use plain_hasher::H256FastMap;
 type NodeId = H512;
 type BlockNumber = u64;
 
+/// Number of blocks received transactions are remembered for to detect duplicates.
+const RECEIVED_TRANSACTIONS_MEMORY: BlockNumber = 16;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Stats {
 	first_seen: BlockNumber,
@@ -52,6 +55,9 @@ impl<'a> From<&'a Stats> for TransactionStats {
 #[derive(Debug, Default)]
 pub struct TransactionsStats {
 	pending_transactions: H256FastMap<Stats>,
+	/// Recently received transactions with the block number they were first received at.
+	received_transactions: H256FastMap<BlockNumber>,
+	received_stats: ReceivedTransactionsStats,
 }
 
 impl TransactionsStats {
@@ -63,6 +69,49 @@ impl TransactionsStats {
 		*count = count.saturating_add(1);
 	}
 
+	/// Records a transaction received from a peer. `known` indicates the transaction is already in the pool.
+	/// Returns `false` if the transaction is a duplicate.
+	pub fn received(&mut self, hash: &H256, known: bool, current_block_num: BlockNumber) -> bool {
+		self.received_stats.received = self.received_stats.received.saturating_add(1);
+		let duplicate = known
+			|| self.pending_transactions.contains_key(hash)
+			|| self.received_transactions.insert(*hash, current_block_num).is_some();
+		if duplicate {
+			self.received_stats.duplicates = self.received_stats.duplicates.saturating_add(1);
+		}
+		!duplicate
+	}
+
+	/// Whether the transaction was recently propagated or received.
+	pub fn is_known(&self, hash: &H256) -> bool {
+		self.pending_transactions.contains_key(hash) || self.received_transactions.contains_key(hash)
+	}
+
+	/// Records received transactions rejected by the pool.
+	/// They are forgotten, so that they can be received again.
+	pub fn rejected(&mut self, hashes: &[H256]) {
+		self.received_stats.rejected = self.received_stats.rejected.saturating_add(hashes.len() as u64);
+		for hash in hashes {
+			self.received_transactions.remove(hash);
+		}
+	}
+
+	/// Records transaction hashes announced by a peer and how many of them were requested.
+	pub fn announced(&mut self, announced: usize, requested: usize) {
+		self.received_stats.announced = self.received_stats.announced.saturating_add(announced as u64);
+		self.received_stats.requested = self.received_stats.requested.saturating_add(requested as u64);
+	}
+
+	/// Returns statistics of transactions received from peers.
+	pub fn received_stats(&self) -> ReceivedTransactionsStats {
+		self.received_stats
+	}
+
+	/// Forgets transactions received before the recent blocks.
+	pub fn prune_received(&mut self, current_block_num: BlockNumber) {
+		self.received_transactions.retain(|_, number| *number + RECEIVED_TRANSACTIONS_MEMORY > current_block_num);
+	}
+
 	/// Returns propagation stats for given hash or `None` if hash is not known.
 	#[cfg(test)]
 	pub fn get(&self, hash: &H256) -> Option<&Stats> {
@@ -90,7 +139,8 @@ impl TransactionsStats {
 mod tests {
 
 	use std::collections::{HashMap, HashSet};
-	use super::{Stats, TransactionsStats};
+	use api::ReceivedTransactionsStats;
+	use super::{Stats, TransactionsStats, RECEIVED_TRANSACTIONS_MEMORY};
 
 	#[test]
 	fn should_keep_track_of_propagations() {
@@ -131,4 +181,50 @@ mod tests {
 		let stats = stats.get(&hash);
 		assert_eq!(stats, None);
 	}
+
+	#[test]
+	fn should_count_duplicate_transactions() {
+		// given
+		let mut stats = TransactionsStats::default();
+		let hash1 = 1.into();
+		let hash2 = 2.into();
+		let hash3 = 3.into();
+		stats.propagated(&hash3, None, 10);
+
+		// when
+		assert!(stats.received(&hash1, false, 10));
+		assert!(!stats.received(&hash1, false, 11));
+		assert!(!stats.received(&hash2, true, 11));
+		assert!(!stats.received(&hash3, false, 11));
+		stats.announced(4, 1);
+
+		// then
+		assert_eq!(stats.received_stats(), ReceivedTransactionsStats {
+			received: 4,
+			duplicates: 3,
+			rejected: 0,
+			announced: 4,
+			requested: 1,
+		});
+
+		// and the transaction is forgotten after a while
+		stats.prune_received(10 + RECEIVED_TRANSACTIONS_MEMORY);
+		assert!(stats.received(&hash1, false, 30));
+	}
+
+	#[test]
+	fn should_forget_rejected_transactions() {
+		// given
+		let mut stats = TransactionsStats::default();
+		let hash = 1.into();
+		assert!(stats.received(&hash, false, 10));
+
+		// when
+		stats.rejected(&[hash]);
+
+		// then
+		assert_eq!(stats.received_stats().rejected, 1);
+		assert!(!stats.is_known(&hash));
+		assert!(stats.received(&hash, false, 11));
+	}
 }
@@ -16,6 +16,9 @@
 
 //! Spec hardcoded synchronization deserialization for the light client.
 
+use std::io::Read;
+use serde_json;
+use serde_json::Error;
 use hash::H256;
 use uint::Uint;
 
@@ -30,6 +33,26 @@ pub struct HardcodedSync {
 	/// Ordered trie roots of blocks before and including `header`.
 	#[serde(rename="CHTs")]
 	pub chts: Vec<H256>,
+	/// Last epoch transition at or before `header`. Required for chains with changing validator sets.
+	#[serde(rename="epochTransition")]
+	#[serde(skip_serializing_if="Option::is_none")]
+	pub epoch_transition: Option<EpochTransition>,
+}
+
+impl HardcodedSync {
+	/// Loads hardcoded sync from json.
+	pub fn load<R>(reader: R) -> Result<Self, Error> where R: Read {
+		serde_json::from_reader(reader)
+	}
+}
+
+/// Epoch transition of a hardcoded sync.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct EpochTransition {
+	/// Hexadecimal of the RLP encoding of the header of the transition block.
+	pub header: String,
+	/// Hexadecimal of the epoch transition proof.
+	pub proof: String,
 }
 
 #[cfg(test)]
@@ -38,7 +61,7 @@ mod tests {
 	use uint::Uint;
 	use ethereum_types::{U256, H256 as Eth256};
 	use hash::H256;
-	use spec::hardcoded_sync::HardcodedSync;
+	use spec::hardcoded_sync::{HardcodedSync, EpochTransition};
 
 	#[test]
 	fn hardcoded_sync_deserialization() {
@@ -57,7 +80,26 @@ mod tests {
 			chts: vec![
 				H256(Eth256::from("0x11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa")),
 				H256(Eth256::from("0xd7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544")),
-			]
+			],
+			epoch_transition: None,
 		});
 	}
+
+	#[test]
+	fn hardcoded_sync_with_epoch_transition_deserialization() {
+		let s = r#"{
+			"header": "f901f9",
+			"totalDifficulty": "0x20000",
+			"CHTs": [],
+			"epochTransition": {
+				"header": "f901f8",
+				"proof": "c0"
+			}
+		}"#;
+		let deserialized: HardcodedSync = serde_json::from_str(s).unwrap();
+		assert_eq!(deserialized.epoch_transition, Some(EpochTransition {
+			header: "f901f8".into(),
+			proof: "c0".into(),
+		}));
+	}
 }
@@ -46,4 +46,4 @@ pub use self::basic_authority::{BasicAuthority, BasicAuthorityParams};
 pub use self::authority_round::{AuthorityRound, AuthorityRoundParams};
 pub use self::tendermint::{Tendermint, TendermintParams};
 pub use self::null_engine::{NullEngine, NullEngineParams};
-pub use self::hardcoded_sync::HardcodedSync;
+pub use self::hardcoded_sync::{HardcodedSync, EpochTransition};
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Manages local node data: pending local transactions, sync security level
+//! Manages local node data: pending local transactions, transaction queue snapshots, sync security level
 
 use std::sync::Arc;
 use std::fmt;
@@ -28,6 +28,7 @@ use ethcore::client::ClientIoMessage;
 use io::IoHandler;
 use rlp::Rlp;
 use kvdb::KeyValueDB;
+use serde::Serialize;
 
 extern crate ethcore;
 extern crate ethcore_transaction as transaction;
@@ -49,6 +50,7 @@ extern crate ethkey;
 extern crate kvdb_memorydb;
 
 const LOCAL_TRANSACTIONS_KEY: &'static [u8] = &*b"LOCAL_TXS";
+const QUEUED_TRANSACTIONS_KEY: &'static [u8] = &*b"QUEUED_TXS";
 
 const UPDATE_TIMER: ::io::TimerToken = 0;
 const UPDATE_TIMEOUT: Duration = Duration::from_secs(15 * 60); // once every 15 minutes.
@@ -131,10 +133,68 @@ impl From<PendingTransaction> for TransactionEntry {
 	}
 }
 
+/// A transaction taken from a snapshot of the transaction queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedTransaction {
+	/// The transaction itself.
+	pub transaction: SignedTransaction,
+	/// Id of the transaction insertion into the queue.
+	pub insertion_id: usize,
+	/// Whether the sender of the transaction was penalized.
+	pub penalized: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct QueuedEntry {
+	rlp_bytes: Vec<u8>,
+	insertion_id: usize,
+	penalized: bool,
+}
+
+impl QueuedEntry {
+	fn into_queued(self) -> Option<QueuedTransaction> {
+		let tx: UnverifiedTransaction = match Rlp::new(&self.rlp_bytes).as_val() {
+			Err(e) => {
+				warn!(target: "local_store", "Invalid queued transaction stored: {}", e);
+				return None
+			}
+			Ok(tx) => tx,
+		};
+
+		let hash = tx.hash();
+		match SignedTransaction::new(tx) {
+			Ok(tx) => Some(QueuedTransaction {
+				transaction: tx,
+				insertion_id: self.insertion_id,
+				penalized: self.penalized,
+			}),
+			Err(_) => {
+				warn!(target: "local_store", "Bad signature on queued transaction: {}", hash);
+				return None
+			}
+		}
+	}
+}
+
+impl From<QueuedTransaction> for QueuedEntry {
+	fn from(queued: QueuedTransaction) -> Self {
+		QueuedEntry {
+			rlp_bytes: ::rlp::encode(&queued.transaction).into_vec(),
+			insertion_id: queued.insertion_id,
+			penalized: queued.penalized,
+		}
+	}
+}
+
 /// Something which can provide information about the local node.
 pub trait NodeInfo: Send + Sync {
 	/// Get all pending transactions of local origin.
 	fn pending_transactions(&self) -> Vec<PendingTransaction>;
+
+	/// Get a snapshot of the whole transaction queue.
+	///
+	/// Returns `None` if the queue contents should not be persisted.
+	fn queued_transactions(&self) -> Option<Vec<QueuedTransaction>> { None }
 }
 
 /// Create a new local data store, given a database, a column to write to, and a node.
@@ -173,6 +233,24 @@ impl<T: NodeInfo> LocalDataStore<T> {
 		}
 	}
 
+	/// Attempt to read the transaction queue snapshot out of the local store.
+	///
+	/// Transactions are returned in the order they were inserted into the queue.
+	pub fn queued_transactions(&self) -> Result<Vec<QueuedTransaction>, Error> {
+		if let Some(val) = self.db.get(self.col, QUEUED_TRANSACTIONS_KEY).map_err(Error::Database)? {
+			let mut queued: Vec<_> = ::serde_json::from_slice::<Vec<QueuedEntry>>(&val)
+				.map_err(Error::Json)?
+				.into_iter()
+				.filter_map(QueuedEntry::into_queued)
+				.collect();
+
+			queued.sort_by_key(|tx| tx.insertion_id);
+			Ok(queued)
+		} else {
+			Ok(Vec::new())
+		}
+	}
+
 	/// Update the entries in the database.
 	pub fn update(&self) -> Result<(), Error> {
 		trace!(target: "local_store", "Updating local store entries.");
@@ -182,28 +260,39 @@ impl<T: NodeInfo> LocalDataStore<T> {
 			.map(Into::into)
 			.collect();
 
-		self.write_txs(&local_entries)
+		let queued_entries: Option<Vec<QueuedEntry>> = self.node.queued_transactions()
+			.map(|queued| queued.into_iter().map(Into::into).collect());
+
+		self.write_txs(&local_entries, queued_entries.as_ref().map(|entries| &entries[..]))
 	}
 
 	/// Clear data in this column.
 	pub fn clear(&self) -> Result<(), Error> {
 		trace!(target: "local_store", "Clearing local store entries.");
 
-		self.write_txs(&[])
+		self.write_txs(&[], None)
 	}
 
-	// helper for writing a vector of transaction entries to disk.
-	fn write_txs(&self, txs: &[TransactionEntry]) -> Result<(), Error> {
+	// helper for writing local transaction entries and the queue snapshot to disk.
+	// the snapshot is removed if none is given.
+	fn write_txs(&self, txs: &[TransactionEntry], queued: Option<&[QueuedEntry]>) -> Result<(), Error> {
 		let mut batch = self.db.transaction();
 
-		let local_json = ::serde_json::to_value(txs).map_err(Error::Json)?;
-		let json_str = format!("{}", local_json);
+		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, to_json_bytes(txs)?);
+		match queued {
+			Some(queued) => batch.put_vec(self.col, QUEUED_TRANSACTIONS_KEY, to_json_bytes(queued)?),
+			None => batch.delete(self.col, QUEUED_TRANSACTIONS_KEY),
+		}
 
-		batch.put_vec(self.col, LOCAL_TRANSACTIONS_KEY, json_str.into_bytes());
 		self.db.write(batch).map_err(Error::Database)
 	}
 }
 
+fn to_json_bytes<T: Serialize>(entries: &[T]) -> Result<Vec<u8>, Error> {
+	let json = ::serde_json::to_value(entries).map_err(Error::Json)?;
+	Ok(format!("{}", json).into_bytes())
+}
+
 impl<T: NodeInfo> IoHandler<ClientIoMessage> for LocalDataStore<T> {
 	fn initialize(&self, io: &::io::IoContext<ClientIoMessage>) {
 		if let Err(e) = io.register_timer(UPDATE_TIMER, UPDATE_TIMEOUT) {
@@ -230,7 +319,7 @@ impl<T: NodeInfo> Drop for LocalDataStore<T> {
 
 #[cfg(test)]
 mod tests {
-	use super::NodeInfo;
+	use super::{NodeInfo, QueuedTransaction};
 
 	use std::sync::Arc;
 	use transaction::{Transaction, Condition, PendingTransaction};
@@ -244,6 +333,12 @@ mod tests {
 		fn pending_transactions(&self) -> Vec<PendingTransaction> { self.0.clone() }
 	}
 
+	struct DummyQueue(Option<Vec<QueuedTransaction>>);
+	impl NodeInfo for DummyQueue {
+		fn pending_transactions(&self) -> Vec<PendingTransaction> { Vec::new() }
+		fn queued_transactions(&self) -> Option<Vec<QueuedTransaction>> { self.0.clone() }
+	}
+
 	#[test]
 	fn twice_empty() {
 		let db = Arc::new(::kvdb_memorydb::create(0));
@@ -328,4 +423,37 @@ mod tests {
 			assert_eq!(loaded, transactions);
 		}
 	}
+
+	#[test]
+	fn queue_snapshot() {
+		let keypair = Brain::new("abcd".into()).generate().unwrap();
+		let mut queued: Vec<_> = (0..5u64).map(|nonce| {
+			let mut tx = Transaction::default();
+			tx.nonce = nonce.into();
+
+			QueuedTransaction {
+				transaction: tx.sign(keypair.secret(), None),
+				insertion_id: 10 - nonce as usize,
+				penalized: nonce == 3,
+			}
+		}).collect();
+
+		let db = Arc::new(::kvdb_memorydb::create(0));
+		{
+			// nothing written yet, will write the snapshot.
+			let store = super::create(db.clone(), None, DummyQueue(Some(queued.clone())));
+			assert_eq!(store.queued_transactions().unwrap(), vec![])
+		}
+		{
+			// snapshot written and sorted by insertion id, persistence disabled, will remove it.
+			let store = super::create(db.clone(), None, DummyQueue(None));
+			queued.reverse();
+			assert_eq!(store.queued_transactions().unwrap(), queued)
+		}
+		{
+			// snapshot removed.
+			let store = super::create(db.clone(), None, DummyQueue(None));
+			assert_eq!(store.queued_transactions().unwrap(), vec![])
+		}
+	}
 }
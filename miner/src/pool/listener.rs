@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-//! Notifier for new transaction hashes.
+//! Notifiers for new transaction hashes and transaction events.
 
 use std::fmt;
 use std::sync::Arc;
@@ -64,6 +64,70 @@ impl txpool::Listener<Transaction> for Notifier {
 	}
 }
 
+/// Event about a transaction leaving the pool.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionEvent {
+	/// Transaction was replaced by another one with the same sender and nonce.
+	Replaced {
+		/// Hash of the replaced transaction.
+		old: H256,
+		/// Hash of the transaction that replaced it.
+		new: H256,
+	},
+	/// Transaction was mined.
+	Mined(H256),
+}
+
+type EventListener = Box<Fn(&[TransactionEvent]) + Send + Sync>;
+
+/// Manages notifications to transaction event listeners.
+#[derive(Default)]
+pub struct EventNotifier {
+	listeners: Vec<EventListener>,
+	events: Vec<TransactionEvent>,
+}
+
+impl fmt::Debug for EventNotifier {
+	fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+		fmt.debug_struct("EventNotifier")
+			.field("listeners", &self.listeners.len())
+			.field("events", &self.events)
+			.finish()
+	}
+}
+
+impl EventNotifier {
+	/// Add new listener to receive notifications.
+	pub fn add(&mut self, f: EventListener) {
+		self.listeners.push(f)
+	}
+
+	/// Notify listeners about all events since the last notification.
+	pub fn notify(&mut self) {
+		if self.events.is_empty() {
+			return;
+		}
+
+		for l in &self.listeners {
+			(l)(&self.events);
+		}
+
+		self.events.clear();
+	}
+}
+
+impl txpool::Listener<Transaction> for EventNotifier {
+	fn added(&mut self, tx: &Arc<Transaction>, old: Option<&Arc<Transaction>>) {
+		if let Some(old) = old {
+			self.events.push(TransactionEvent::Replaced { old: *old.hash(), new: *tx.hash() });
+		}
+	}
+
+	fn mined(&mut self, tx: &Arc<Transaction>) {
+		self.events.push(TransactionEvent::Mined(*tx.hash()));
+	}
+}
+
 
 /// Transaction pool logger.
 #[derive(Default, Debug)]
@@ -146,6 +210,37 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn should_notify_event_listeners() {
+		// given
+		let received = Arc::new(Mutex::new(vec![]));
+		let r = received.clone();
+		let listener = Box::new(move |events: &[TransactionEvent]| {
+			r.lock().extend(events.iter().cloned());
+		});
+
+		let mut tx_listener = EventNotifier::default();
+		tx_listener.add(listener);
+
+		// when
+		let old = new_tx();
+		let new = Arc::new(Transaction::from_pending_block_transaction(transaction::Transaction {
+			gas_price: 6.into(),
+			..old.signed().as_unsigned().clone()
+		}.fake_sign(5.into())));
+		tx_listener.added(&old, None);
+		tx_listener.added(&new, Some(&old));
+		tx_listener.mined(&new);
+		assert_eq!(*received.lock(), vec![]);
+
+		// then
+		tx_listener.notify();
+		assert_eq!(*received.lock(), vec![
+			TransactionEvent::Replaced { old: *old.hash(), new: *new.hash() },
+			TransactionEvent::Mined(*new.hash()),
+		]);
+	}
+
 	fn new_tx() -> Arc<Transaction> {
 		let signed = transaction::Transaction {
 			action: transaction::Action::Create,
@@ -28,12 +28,15 @@ mod scoring;
 
 pub mod client;
 pub mod local_transactions;
+pub mod packing;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests;
 
+pub use self::listener::TransactionEvent;
 pub use self::queue::{TransactionQueue, Status as QueueStatus};
+pub use self::scoring::bump_gas_price;
 pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options};
 
 /// How to prioritize transactions in the pool
@@ -43,6 +46,9 @@ pub use self::txpool::{VerifiedTransaction as PoolVerifiedTransaction, Options};
 pub enum PrioritizationStrategy {
 	/// Simple gas-price based prioritization.
 	GasPriceOnly,
+	/// Gas-price based prioritization in the pool, but blocks are packed
+	/// to maximize total fees given the block gas limit (see `packing`).
+	GasPriceAndGasUsage,
 }
 
 /// Transaction ordering when requesting pending set.
@@ -143,10 +149,15 @@ impl VerifiedTransaction {
 	}
 
 	/// Gets transaction insertion id.
-	pub(crate) fn insertion_id(&self) -> usize {
+	pub fn insertion_id(&self) -> usize {
 		self.insertion_id
 	}
 
+	/// Whether the transaction was submitted locally.
+	pub fn is_local(&self) -> bool {
+		self.priority.is_local()
+	}
+
 	/// Gets wrapped `SignedTransaction`
 	pub fn signed(&self) -> &transaction::SignedTransaction {
 		&self.transaction
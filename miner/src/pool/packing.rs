@@ -0,0 +1,394 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Block packing.
+//!
+//! Selects a subset of pending transactions that maximizes total fees collected in a block.
+//! Since transactions of every sender have to be included in nonce order, this is a group knapsack
+//! problem: for every sender we pick a prefix of its transactions (possibly empty).
+//! The problem is solved with dynamic programming over block gas split into `GAS_RESOLUTION` units.
+//!
+//! Local and service (zero gas price) transactions are not packed, they always come first
+//! in priority order, together with all other transactions of their senders.
+//!
+//! NOTE Fees are estimated from the gas limit of each transaction, the actual gas used is only
+//! known after execution.
+
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use ethereum_types::{U256, Address};
+
+use super::VerifiedTransaction;
+
+/// Number of units the available block gas is split into.
+///
+/// Gas of every transaction is rounded up to a full unit, so the higher the resolution
+/// the closer to optimal the packing is, at the cost of time and memory.
+const GAS_RESOLUTION: u64 = 512;
+
+/// Transactions packed into a block.
+#[derive(Debug)]
+pub struct Packing {
+	/// All pending transactions in the order they should be pushed to the block.
+	///
+	/// Selected transactions come first (prioritized ones first, then in priority order),
+	/// followed by the rest of them, which may still fit if selected transactions use less gas
+	/// than they declare.
+	pub transactions: Vec<Arc<VerifiedTransaction>>,
+	/// Expected fees of selected transactions.
+	pub fees: U256,
+	/// Expected fees of transactions that would be selected in priority order
+	/// (after prioritized ones).
+	pub simple_fees: U256,
+}
+
+/// Statistics comparing fees estimated for packing to the simple priority order.
+///
+/// Fees are estimated from gas limits of selected transactions (see module docs),
+/// so they are an upper bound of the fees actually collected.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PackingStats {
+	/// Number of packed blocks.
+	pub blocks: u64,
+	/// Number of blocks where packing selected transactions with higher estimated fees than the priority order.
+	pub improved_blocks: u64,
+	/// Total estimated fees of packed blocks.
+	pub estimated_fees: U256,
+	/// Total estimated fees the same blocks would have with transactions in priority order.
+	pub estimated_simple_fees: U256,
+}
+
+impl PackingStats {
+	/// Record results of packing a block.
+	pub fn note(&mut self, packing: &Packing) {
+		self.blocks += 1;
+		if packing.fees > packing.simple_fees {
+			self.improved_blocks += 1;
+		}
+		self.estimated_fees = self.estimated_fees + packing.fees;
+		self.estimated_simple_fees = self.estimated_simple_fees + packing.simple_fees;
+	}
+}
+
+/// Pack pending transactions (ordered by priority) into a block with given amount of gas left.
+///
+/// Falls back to the priority order if an optimal packing can't be found within `budget`
+/// or if it doesn't give higher fees.
+pub fn pack(pending: Vec<Arc<VerifiedTransaction>>, gas_limit: U256, budget: Duration) -> Packing {
+	let deadline = Instant::now() + budget;
+
+	let (prioritized, pending) = split_prioritized(pending);
+	let prioritized_selected = select_in_order(&prioritized, gas_limit);
+	let prioritized_fees = total_fees(&prioritized, &prioritized_selected);
+	let gas_limit = gas_limit - total_gas(&prioritized, &prioritized_selected);
+
+	let simple = select_in_order(&pending, gas_limit);
+	let simple_fees = prioritized_fees + total_fees(&pending, &simple);
+
+	let (selected, fees) = match select_max_fees(&pending, gas_limit, deadline) {
+		Some(packed) => {
+			let packed_fees = prioritized_fees + total_fees(&pending, &packed);
+			if packed_fees > simple_fees {
+				(packed, packed_fees)
+			} else {
+				(simple, simple_fees)
+			}
+		},
+		None => {
+			debug!(target: "miner", "Packing {} transactions took longer than {:?}. Using priority order.", pending.len(), budget);
+			(simple, simple_fees)
+		},
+	};
+
+	let (mut transactions, mut rest): (Vec<_>, Vec<_>) = prioritized
+		.into_iter()
+		.zip(prioritized_selected)
+		.partition(|&(_, is_selected)| is_selected);
+	let (packed, packed_rest): (Vec<_>, Vec<_>) = pending
+		.into_iter()
+		.zip(selected)
+		.partition(|&(_, is_selected)| is_selected);
+	transactions.extend(packed);
+	rest.extend(packed_rest);
+	transactions.extend(rest);
+
+	Packing {
+		transactions: transactions.into_iter().map(|(tx, _)| tx).collect(),
+		fees,
+		simple_fees,
+	}
+}
+
+// Splits off transactions of senders having any local or service (zero gas price) transactions,
+// keeping the priority order of both parts.
+fn split_prioritized(pending: Vec<Arc<VerifiedTransaction>>) -> (Vec<Arc<VerifiedTransaction>>, Vec<Arc<VerifiedTransaction>>) {
+	let senders: HashSet<Address> = pending.iter()
+		.filter(|tx| tx.is_local() || tx.transaction.gas_price.is_zero())
+		.map(|tx| tx.sender)
+		.collect();
+
+	pending.into_iter().partition(|tx| senders.contains(&tx.sender))
+}
+
+fn fee(tx: &VerifiedTransaction) -> U256 {
+	tx.transaction.gas * tx.transaction.gas_price
+}
+
+fn total_fees(pending: &[Arc<VerifiedTransaction>], selected: &[bool]) -> U256 {
+	pending.iter()
+		.zip(selected)
+		.filter(|&(_, is_selected)| *is_selected)
+		.fold(U256::zero(), |acc, (tx, _)| acc + fee(tx))
+}
+
+fn total_gas(pending: &[Arc<VerifiedTransaction>], selected: &[bool]) -> U256 {
+	pending.iter()
+		.zip(selected)
+		.filter(|&(_, is_selected)| *is_selected)
+		.fold(U256::zero(), |acc, (tx, _)| acc + tx.transaction.gas)
+}
+
+// Mirrors the way transactions are pushed to the block in priority order:
+// a transaction is skipped if it doesn't fit, and so are all subsequent transactions of its sender.
+fn select_in_order(pending: &[Arc<VerifiedTransaction>], gas_limit: U256) -> Vec<bool> {
+	let mut gas_used = U256::zero();
+	let mut skipped_senders = Vec::new();
+
+	pending.iter().map(|tx| {
+		if skipped_senders.contains(&tx.sender) {
+			return false;
+		}
+
+		let gas = gas_used + tx.transaction.gas;
+		if gas > gas_limit {
+			skipped_senders.push(tx.sender);
+			false
+		} else {
+			gas_used = gas;
+			true
+		}
+	}).collect()
+}
+
+fn select_max_fees(pending: &[Arc<VerifiedTransaction>], gas_limit: U256, deadline: Instant) -> Option<Vec<bool>> {
+	if gas_limit.is_zero() {
+		return Some(vec![false; pending.len()]);
+	}
+
+	// Round gas up, so that selected transactions never exceed the limit.
+	let unit = cmp::max(U256::one(), gas_limit / U256::from(GAS_RESOLUTION));
+	let capacity = (gas_limit / unit).low_u64() as usize;
+	let weight = |tx: &VerifiedTransaction| {
+		let units = (tx.transaction.gas + unit - U256::one()) / unit;
+		if units > U256::from(capacity) { capacity + 1 } else { units.low_u64() as usize }
+	};
+
+	// Group transactions by sender, pending set is already ordered by nonce for every sender.
+	let mut chains: Vec<Vec<usize>> = Vec::new();
+	{
+		let mut chain_ids: HashMap<Address, usize> = HashMap::new();
+		for (idx, tx) in pending.iter().enumerate() {
+			let id = *chain_ids.entry(tx.sender).or_insert_with(|| {
+				chains.push(Vec::new());
+				chains.len() - 1
+			});
+			chains[id].push(idx);
+		}
+	}
+
+	// best[w] - highest fees of transactions weighing at most `w` units.
+	let mut best = vec![U256::zero(); capacity + 1];
+	// choices[chain][w] - length of the chain prefix giving `best[w]` after considering that chain.
+	let mut choices = Vec::with_capacity(chains.len());
+	for chain in &chains {
+		if Instant::now() >= deadline {
+			return None;
+		}
+
+		let mut prefixes = Vec::new();
+		let mut prefix_weight = 0;
+		let mut prefix_fees = U256::zero();
+		for &idx in chain.iter().take(u16::max_value() as usize) {
+			prefix_weight += weight(&pending[idx]);
+			if prefix_weight > capacity {
+				break;
+			}
+			prefix_fees = prefix_fees + fee(&pending[idx]);
+			prefixes.push((prefix_weight, prefix_fees));
+		}
+
+		let mut next = best.clone();
+		let mut choice = vec![0u16; capacity + 1];
+		for w in 0..capacity + 1 {
+			for (len, &(prefix_weight, prefix_fees)) in prefixes.iter().enumerate() {
+				if prefix_weight > w {
+					break;
+				}
+				let candidate = best[w - prefix_weight] + prefix_fees;
+				if candidate > next[w] {
+					next[w] = candidate;
+					choice[w] = len as u16 + 1;
+				}
+			}
+		}
+		best = next;
+		choices.push(choice);
+	}
+
+	let mut selected = vec![false; pending.len()];
+	let mut w = capacity;
+	for (chain, choice) in chains.iter().zip(choices).rev() {
+		for &idx in &chain[..choice[w] as usize] {
+			selected[idx] = true;
+			w -= weight(&pending[idx]);
+		}
+	}
+
+	Some(selected)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use ethkey::{Random, Generator};
+	use pool::Priority;
+	use pool::tests::tx::{Tx, TxExt};
+
+	fn verified(txs: Vec<Tx>) -> Vec<Arc<VerifiedTransaction>> {
+		let keypair = Random.generate().unwrap();
+		txs.into_iter()
+			.map(|tx| Arc::new(tx.unsigned().sign(keypair.secret(), None).verified()))
+			.collect()
+	}
+
+	fn verified_local(txs: Vec<Tx>) -> Vec<Arc<VerifiedTransaction>> {
+		let keypair = Random.generate().unwrap();
+		txs.into_iter()
+			.map(|tx| {
+				let mut tx = tx.unsigned().sign(keypair.secret(), None).verified();
+				tx.priority = Priority::Local;
+				Arc::new(tx)
+			})
+			.collect()
+	}
+
+	fn hashes(txs: &[Arc<VerifiedTransaction>]) -> Vec<::ethereum_types::H256> {
+		txs.iter().map(|tx| tx.hash).collect()
+	}
+
+	#[test]
+	fn should_prefer_smaller_transactions_with_higher_total_fees() {
+		// given
+		let big = verified(vec![Tx { nonce: 0, gas: 60_000, gas_price: 10 }]);
+		let small1 = verified(vec![Tx { nonce: 0, gas: 45_000, gas_price: 9 }]);
+		let small2 = verified(vec![Tx { nonce: 0, gas: 45_000, gas_price: 9 }]);
+		let pending = vec![big[0].clone(), small1[0].clone(), small2[0].clone()];
+
+		// when
+		let packing = pack(pending, 100_000.into(), Duration::from_secs(10));
+
+		// then
+		assert_eq!(packing.simple_fees, 600_000.into());
+		assert_eq!(packing.fees, 810_000.into());
+		assert_eq!(hashes(&packing.transactions), vec![small1[0].hash, small2[0].hash, big[0].hash]);
+	}
+
+	#[test]
+	fn should_respect_nonce_order_of_senders() {
+		// given
+		let big = verified(vec![Tx { nonce: 0, gas: 60_000, gas_price: 10 }]);
+		// the second transaction alone would give higher fees, but requires the first one.
+		let chain = verified(vec![
+			Tx { nonce: 0, gas: 50_000, gas_price: 1 },
+			Tx { nonce: 1, gas: 40_000, gas_price: 20 },
+		]);
+		let pending = vec![big[0].clone(), chain[0].clone(), chain[1].clone()];
+
+		// when
+		let packing = pack(pending, 100_000.into(), Duration::from_secs(10));
+
+		// then
+		assert_eq!(packing.simple_fees, 600_000.into());
+		assert_eq!(packing.fees, 850_000.into());
+		assert_eq!(hashes(&packing.transactions), vec![chain[0].hash, chain[1].hash, big[0].hash]);
+	}
+
+	#[test]
+	fn should_keep_local_and_service_transactions_first() {
+		// given
+		let big = verified(vec![Tx { nonce: 0, gas: 60_000, gas_price: 10 }]);
+		let small1 = verified(vec![Tx { nonce: 0, gas: 45_000, gas_price: 9 }]);
+		let local = verified_local(vec![Tx { nonce: 0, gas: 45_000, gas_price: 1 }]);
+		// the service transaction is followed by a regular one from the same sender.
+		let service = verified(vec![
+			Tx { nonce: 0, gas: 21_000, gas_price: 0 },
+			Tx { nonce: 1, gas: 21_000, gas_price: 2 },
+		]);
+		let pending = vec![big[0].clone(), small1[0].clone(), local[0].clone(), service[0].clone(), service[1].clone()];
+		// packing all of them together would give higher fees by leaving out the local transaction.
+
+		// when
+		let packing = pack(pending, 150_000.into(), Duration::from_secs(10));
+
+		// then
+		assert_eq!(packing.simple_fees, 687_000.into());
+		assert_eq!(packing.fees, 687_000.into());
+		assert_eq!(hashes(&packing.transactions), vec![local[0].hash, service[0].hash, service[1].hash, big[0].hash, small1[0].hash]);
+	}
+
+	#[test]
+	fn should_fall_back_to_priority_order_when_out_of_time() {
+		// given
+		let big = verified(vec![Tx { nonce: 0, gas: 60_000, gas_price: 10 }]);
+		let small1 = verified(vec![Tx { nonce: 0, gas: 50_000, gas_price: 9 }]);
+		let small2 = verified(vec![Tx { nonce: 0, gas: 50_000, gas_price: 9 }]);
+		let pending = vec![big[0].clone(), small1[0].clone(), small2[0].clone()];
+
+		// when
+		let packing = pack(pending, 100_000.into(), Duration::from_secs(0));
+
+		// then
+		assert_eq!(packing.fees, packing.simple_fees);
+		assert_eq!(hashes(&packing.transactions), vec![big[0].hash, small1[0].hash, small2[0].hash]);
+	}
+
+	#[test]
+	fn should_note_stats() {
+		// given
+		let mut stats = PackingStats::default();
+		let packing = Packing {
+			transactions: vec![],
+			fees: 10.into(),
+			simple_fees: 5.into(),
+		};
+
+		// when
+		stats.note(&packing);
+		stats.note(&Packing { simple_fees: 10.into(), ..packing });
+
+		// then
+		assert_eq!(stats, PackingStats {
+			blocks: 2,
+			improved_blocks: 1,
+			estimated_fees: 20.into(),
+			estimated_simple_fees: 15.into(),
+		});
+	}
+}
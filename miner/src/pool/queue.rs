@@ -19,7 +19,7 @@
 use std::{cmp, fmt};
 use std::sync::Arc;
 use std::sync::atomic::{self, AtomicUsize};
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 use ethereum_types::{H256, U256, Address};
 use parking_lot::RwLock;
@@ -32,7 +32,7 @@ use pool::{
 };
 use pool::local_transactions::LocalTransactionsList;
 
-type Listener = (LocalTransactionsList, (listener::Notifier, listener::Logger));
+type Listener = (LocalTransactionsList, (listener::Notifier, (listener::EventNotifier, listener::Logger)));
 type Pool = txpool::Pool<pool::VerifiedTransaction, scoring::NonceAndGasPrice, Listener>;
 
 /// Max cache time in milliseconds for pending transactions.
@@ -203,6 +203,7 @@ pub struct TransactionQueue {
 	options: RwLock<verifier::Options>,
 	cached_pending: RwLock<CachedPending>,
 	recently_rejected: RecentlyRejected,
+	penalized: RwLock<HashSet<Address>>,
 }
 
 impl TransactionQueue {
@@ -219,6 +220,7 @@ impl TransactionQueue {
 			options: RwLock::new(verification_options),
 			cached_pending: RwLock::new(CachedPending::none()),
 			recently_rejected: RecentlyRejected::new(cmp::max(MIN_REJECTED_CACHE_SIZE, max_count / 4)),
+			penalized: Default::default(),
 		}
 	}
 
@@ -291,8 +293,12 @@ impl TransactionQueue {
 			})
 			.collect::<Vec<_>>();
 
-		// Notify about imported transactions.
-		(self.pool.write().listener_mut().1).0.notify();
+		// Notify about imported and replaced transactions.
+		{
+			let mut pool = self.pool.write();
+			(pool.listener_mut().1).0.notify();
+			((pool.listener_mut().1).1).0.notify();
+		}
 
 		if results.iter().any(|r| r.is_ok()) {
 			self.cached_pending.write().clear();
@@ -307,6 +313,23 @@ impl TransactionQueue {
 		self.pool.read().unordered_pending(ready).collect()
 	}
 
+	/// Returns all transactions in the queue in the order they were inserted,
+	/// along with a flag indicating whether their sender is currently penalized.
+	pub fn snapshot(&self) -> Vec<(Arc<pool::VerifiedTransaction>, bool)> {
+		let transactions = self.all_transactions();
+		let penalized = self.penalized.read();
+		let mut transactions: Vec<_> = transactions
+			.into_iter()
+			.map(|tx| {
+				let is_penalized = penalized.contains(&tx.sender);
+				(tx, is_penalized)
+			})
+			.collect();
+
+		transactions.sort_by_key(|&(ref tx, _)| tx.insertion_id());
+		transactions
+	}
+
 	/// Computes unordered set of pending hashes.
 	///
 	/// Since strict nonce-checking is not required, you may get some false positive future transactions as well.
@@ -444,6 +467,16 @@ impl TransactionQueue {
 			let state_readiness = ready::State::new(client.clone(), stale_id, nonce_cap);
 			removed += self.pool.write().cull(Some(chunk), state_readiness);
 		}
+
+		// Forget penalties of senders that don't have any transactions left.
+		{
+			let pool = self.pool.read();
+			let senders: HashSet<_> = pool.senders().collect();
+			self.penalized.write().retain(|sender| senders.contains(sender));
+		}
+
+		// Notify about mined transactions.
+		((self.pool.write().listener_mut().1).1).0.notify();
 		debug!(target: "txqueue", "Removed {} stalled transactions. {}", removed, self.status());
 	}
 
@@ -507,13 +540,16 @@ impl TransactionQueue {
 	/// Clear the entire pool.
 	pub fn clear(&self) {
 		self.pool.write().clear();
+		self.penalized.write().clear();
 	}
 
 	/// Penalize given senders.
 	pub fn penalize<'a, T: IntoIterator<Item = &'a Address>>(&self, senders: T) {
 		let mut pool = self.pool.write();
+		let mut penalized = self.penalized.write();
 		for sender in senders {
 			pool.update_scores(sender, ());
+			penalized.insert(*sender);
 		}
 	}
 
@@ -561,6 +597,12 @@ impl TransactionQueue {
 		(pool.listener_mut().1).0.add(f);
 	}
 
+	/// Add a callback to be notified about transactions being replaced or mined.
+	pub fn add_event_listener(&self, f: Box<Fn(&[listener::TransactionEvent]) + Send + Sync>) {
+		let mut pool = self.pool.write();
+		((pool.listener_mut().1).1).0.add(f);
+	}
+
 	/// Check if pending set is cached.
 	#[cfg(test)]
 	pub fn is_pending_cached(&self) -> bool {
@@ -37,9 +37,9 @@ use super::{verifier, PrioritizationStrategy, VerifiedTransaction};
 /// `new_gas_price > old_gas_price + old_gas_price >> SHIFT`
 const GAS_PRICE_BUMP_SHIFT: usize = 3; // 2 = 25%, 3 = 12.5%, 4 = 6.25%
 
-/// Calculate minimal gas price requirement.
+/// Calculate minimal gas price required to replace a transaction with given gas price.
 #[inline]
-fn bump_gas_price(old_gp: U256) -> U256 {
+pub fn bump_gas_price(old_gp: U256) -> U256 {
 	old_gp.saturating_add(old_gp >> GAS_PRICE_BUMP_SHIFT)
 }
 
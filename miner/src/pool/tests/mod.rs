@@ -14,11 +14,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::Arc;
+
 use ethereum_types::U256;
+use parking_lot::Mutex;
 use transaction::{self, PendingTransaction};
 use txpool;
 
-use pool::{verifier, TransactionQueue, PrioritizationStrategy, PendingSettings, PendingOrdering};
+use pool::{verifier, TransactionQueue, TransactionEvent, PrioritizationStrategy, PendingSettings, PendingOrdering};
 
 pub mod tx;
 pub mod client;
@@ -423,6 +426,63 @@ fn should_remove_transaction() {
 	assert_eq!(txq.status().status.transaction_count, 0);
 }
 
+#[test]
+fn should_notify_about_replaced_and_mined_transactions() {
+	// given
+	let txq = new_queue();
+	let events = Arc::new(Mutex::new(Vec::new()));
+	let e = events.clone();
+	txq.add_event_listener(Box::new(move |new: &[TransactionEvent]| e.lock().extend(new.iter().cloned())));
+	let (tx, tx2) = Tx::gas_price(1).signed_replacement();
+	let (hash, hash2) = (tx.hash(), tx2.hash());
+	let client = TestClient::new().with_balance(1_000_000);
+
+	// when
+	let res = txq.import(client.clone(), vec![tx].local());
+	assert_eq!(res, vec![Ok(())]);
+	assert_eq!(*events.lock(), vec![]);
+	let res = txq.import(client.clone(), vec![tx2].local());
+	assert_eq!(res, vec![Ok(())]);
+	txq.cull(TestClient::new().with_nonce(124));
+
+	// then
+	assert_eq!(*events.lock(), vec![
+		TransactionEvent::Replaced { old: hash, new: hash2 },
+		TransactionEvent::Mined(hash2),
+	]);
+}
+
+#[test]
+fn should_snapshot_transactions_in_insertion_order_with_penalties() {
+	// given
+	let txq = new_queue();
+	let (tx, tx2, tx3) = Tx::default().signed_triple();
+	let sender = tx.sender();
+	let other = Tx::default().signed();
+	let (hash, hash2, other_hash) = (tx.hash(), tx2.hash(), other.hash());
+	let res = txq.import(TestClient::new(), vec![other, tx, tx2].unverified());
+	assert_eq!(res, vec![Ok(()), Ok(()), Ok(())]);
+
+	// when
+	txq.penalize(vec![&sender]);
+
+	// then
+	let snapshot = txq.snapshot().into_iter()
+		.map(|(tx, penalized)| (tx.hash, penalized))
+		.collect::<Vec<_>>();
+	assert_eq!(snapshot, vec![(other_hash, false), (hash, true), (hash2, true)]);
+
+	// when
+	txq.cull(TestClient::new().with_nonce(125));
+	let res = txq.import(TestClient::new().with_nonce(125), vec![tx3].unverified());
+	assert_eq!(res, vec![Ok(())]);
+
+	// then
+	let snapshot = txq.snapshot();
+	assert_eq!(snapshot.len(), 1);
+	assert_eq!(snapshot[0].1, false);
+}
+
 #[test]
 fn should_move_transactions_to_future_if_gap_introduced() {
 	// given
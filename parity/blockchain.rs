@@ -26,7 +26,7 @@ use ethereum_types::{U256, H256, Address};
 use bytes::ToPretty;
 use rlp::PayloadInfo;
 use ethcore::account_provider::AccountProvider;
-use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, Nonce, Balance, BlockChainClient, BlockId, BlockInfo, ImportBlock};
+use ethcore::client::{Mode, DatabaseCompactionProfile, VMType, BlockImportError, Nonce, Balance, BlockChainClient, BlockId, BlockInfo, ChainInfo, ImportBlock};
 use ethcore::error::{ImportErrorKind, BlockImportErrorKind};
 use ethcore::miner::Miner;
 use ethcore::verification::queue::VerifierSettings;
@@ -68,6 +68,8 @@ impl FromStr for DataFormat {
 #[derive(Debug, PartialEq)]
 pub enum BlockchainCmd {
 	Kill(KillBlockchain),
+	Trace(TraceBlockchain),
+	IndexLogs(IndexLogsBlockchain),
 	Import(ImportBlockchain),
 	Export(ExportBlockchain),
 	ExportState(ExportState),
@@ -80,6 +82,34 @@ pub struct KillBlockchain {
 	pub pruning: Pruning,
 }
 
+#[derive(Debug, PartialEq)]
+pub struct TraceBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub fat_db: Switch,
+	pub from_block: BlockId,
+	pub to_block: BlockId,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct IndexLogsBlockchain {
+	pub spec: SpecType,
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub pruning: Pruning,
+	pub pruning_history: u64,
+	pub pruning_memory: usize,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+	pub fat_db: Switch,
+}
+
 #[derive(Debug, PartialEq)]
 pub struct ImportBlockchain {
 	pub spec: SpecType,
@@ -144,6 +174,8 @@ pub struct ExportState {
 pub fn execute(cmd: BlockchainCmd) -> Result<(), String> {
 	match cmd {
 		BlockchainCmd::Kill(kill_cmd) => kill_db(kill_cmd),
+		BlockchainCmd::Trace(trace_cmd) => execute_trace(trace_cmd),
+		BlockchainCmd::IndexLogs(index_cmd) => execute_index_logs(index_cmd),
 		BlockchainCmd::Import(import_cmd) => {
 			if import_cmd.light {
 				execute_import_light(import_cmd)
@@ -202,6 +234,8 @@ fn execute_import_light(cmd: ImportBlockchain) -> Result<(), String> {
 		verify_full: true,
 		check_seal: cmd.check_seal,
 		no_hardcoded_sync: true,
+		checkpoint: None,
+		header_history: None,
 	};
 
 	config.queue.max_mem_use = cmd.cache_config.queue() as usize * 1024 * 1024;
@@ -366,6 +400,9 @@ fn execute_import(cmd: ImportBlockchain) -> Result<(), String> {
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.blockchain.log_index = user_defaults.log_index;
+	client_config.blockchain.sender_nonce_index = user_defaults.sender_nonce_index;
+	client_config.blockchain.account_transactions_index = user_defaults.account_transactions_index;
 
 	let client_db = db::open_client_db(&client_path, &client_config)?;
 	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
@@ -501,6 +538,7 @@ fn start_client(
 	wal: bool,
 	cache_config: CacheConfig,
 	require_fat_db: bool,
+	force_tracing: bool,
 ) -> Result<ClientService, String> {
 
 	// load spec file
@@ -524,7 +562,7 @@ fn start_client(
 	let algorithm = pruning.to_algorithm(&user_defaults);
 
 	// check if tracing is on
-	let tracing = tracing_switch_to_bool(tracing, &user_defaults)?;
+	let tracing = force_tracing || tracing_switch_to_bool(tracing, &user_defaults)?;
 
 	// check if fatdb is on
 	let fat_db = fatdb_switch_to_bool(fat_db, &user_defaults, algorithm)?;
@@ -543,7 +581,7 @@ fn start_client(
 	dirs.create_dirs(false, false, false)?;
 
 	// prepare client config
-	let client_config = to_client_config(
+	let mut client_config = to_client_config(
 		&cache_config,
 		spec.name.to_lowercase(),
 		Mode::Active,
@@ -559,6 +597,10 @@ fn start_client(
 		true,
 	);
 
+	client_config.blockchain.log_index = user_defaults.log_index;
+	client_config.blockchain.sender_nonce_index = user_defaults.sender_nonce_index;
+	client_config.blockchain.account_transactions_index = user_defaults.account_transactions_index;
+
 	let client_db = db::open_client_db(&client_path, &client_config)?;
 	let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
 
@@ -594,6 +636,7 @@ fn execute_export(cmd: ExportBlockchain) -> Result<(), String> {
 		cmd.wal,
 		cmd.cache_config,
 		false,
+		false,
 	)?;
 	let format = cmd.format.unwrap_or_default();
 
@@ -638,7 +681,8 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 		cmd.compaction,
 		cmd.wal,
 		cmd.cache_config,
-		true
+		true,
+		false,
 	)?;
 
 	let client = service.client();
@@ -713,6 +757,107 @@ fn execute_export_state(cmd: ExportState) -> Result<(), String> {
 	Ok(())
 }
 
+fn execute_trace(cmd: TraceBlockchain) -> Result<(), String> {
+	let user_defaults_path = {
+		let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+		let genesis_hash = spec.genesis_header().hash();
+		cmd.dirs.database(genesis_hash, None, spec.data_dir).user_defaults_path()
+	};
+
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		Switch::On,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config,
+		false,
+		true,
+	)?;
+
+	let client = service.client();
+
+	let from = client.block_number(cmd.from_block).ok_or("From block could not be found")?;
+	let to = client.block_number(cmd.to_block).ok_or("To block could not be found")?;
+
+	const BATCH_SIZE: u64 = 10000;
+
+	let mut start = from;
+	while start <= to {
+		let end = ::std::cmp::min(start + BATCH_SIZE - 1, to);
+		client.retrace_blocks(BlockId::Number(start), BlockId::Number(end))?;
+		info!("#{}", end);
+		start = end + 1;
+	}
+
+	// blocks imported from now on are traced as well, so tracing may be turned on
+	// once the whole canonical chain up to the best block has been traced.
+	// Blocks before `from` must be traced too, otherwise a reorg into them would hit an incomplete trace database.
+	let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+	if !user_defaults.tracing && to >= client.chain_info().best_block_number {
+		if from <= 1 {
+			user_defaults.tracing = true;
+			user_defaults.save(&user_defaults_path)?;
+			info!("Tracing enabled.");
+		} else {
+			info!("Tracing stays disabled, since blocks before #{} are not traced. Trace the chain from block #1 to enable it.", from);
+		}
+	}
+
+	info!("Trace completed.");
+	Ok(())
+}
+
+fn execute_index_logs(cmd: IndexLogsBlockchain) -> Result<(), String> {
+	// blocks imported from now on are indexed, so the log index is enabled
+	// before the client starts to keep the index of the best blocks.
+	{
+		let spec = cmd.spec.spec(&cmd.dirs.cache)?;
+		let genesis_hash = spec.genesis_header().hash();
+		let user_defaults_path = cmd.dirs.database(genesis_hash, None, spec.data_dir).user_defaults_path();
+		let mut user_defaults = UserDefaults::load(&user_defaults_path)?;
+		user_defaults.log_index = true;
+		user_defaults.save(&user_defaults_path)?;
+	}
+
+	let service = start_client(
+		cmd.dirs,
+		cmd.spec,
+		cmd.pruning,
+		cmd.pruning_history,
+		cmd.pruning_memory,
+		Switch::Auto,
+		cmd.fat_db,
+		cmd.compaction,
+		cmd.wal,
+		cmd.cache_config,
+		false,
+		false,
+	)?;
+
+	let client = service.client();
+
+	const BATCH_SIZE: u64 = 10000;
+
+	let mut from = client.build_log_index(BATCH_SIZE)?;
+	while from > 0 {
+		info!("#{}", from);
+		let indexed = client.build_log_index(BATCH_SIZE)?;
+		if indexed == from {
+			// the remaining blocks are not in the database.
+			break;
+		}
+		from = indexed;
+	}
+
+	info!("Log index complete for blocks #{} and newer.", from);
+	Ok(())
+}
+
 pub fn kill_db(cmd: KillBlockchain) -> Result<(), String> {
 	let spec = cmd.spec.spec(&cmd.dirs.cache)?;
 	let genesis_hash = spec.genesis_header().hash();
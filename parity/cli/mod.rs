@@ -230,12 +230,38 @@ usage! {
 			CMD cmd_db_kill {
 				"Clean the database",
 			}
+
+			CMD cmd_db_trace
+			{
+				"Re-execute a range of blocks and write their traces into the database. Tracing is enabled for the database if the range ends at the best block",
+
+				ARG arg_db_trace_from: (String) = "1",
+				"--from=[BLOCK]",
+				"Trace from block BLOCK, which may be an index or hash. State of its parent must be available.",
+
+				ARG arg_db_trace_to: (String) = "latest",
+				"--to=[BLOCK]",
+				"Trace to (including) block BLOCK, which may be an index, hash or latest.",
+			}
+
+			CMD cmd_db_index_logs {
+				"Build the log index for blocks imported before it was enabled. The log index is enabled for the database as well",
+			}
 		}
 
 		CMD cmd_export_hardcoded_sync
 		{
 			"Export the hardcoded sync JSON file from the existing light client database",
 		}
+
+		CMD cmd_light
+		{
+			"Inspect the light client database",
+
+			CMD cmd_light_verify_epochs {
+				"Replay the epoch transition proofs stored in the light client database through the engine and report their status",
+			}
+		}
 	}
 	{
 		// Global flags and arguments
@@ -260,6 +286,22 @@ usage! {
 			"--no-hardcoded-sync",
 			"By default, if there is no existing database the light client will automatically jump to a block hardcoded in the chain's specifications. This disables this feature.",
 
+			ARG arg_light_persistent_cache: (Option<u32>) = None, or |c: &Config| c.parity.as_ref()?.light_persistent_cache.clone(),
+			"--light-persistent-cache=[MB]",
+			"Keep up to MB megabytes of verified block bodies, receipts, accounts and code fetched by the light client in its database, so they don't have to be fetched from the network again after a restart.",
+
+			ARG arg_light_checkpoint: (Option<String>) = None, or |c: &Config| c.parity.as_ref()?.light_checkpoint.clone(),
+			"--light-checkpoint=[FILE]",
+			"Start the light client from the checkpoint in FILE instead of the block hardcoded in the chain's specifications, if there is no existing database. FILE is in the format generated by the export-hardcoded-sync command and must include the epoch transition for chains with changing validator sets.",
+
+			ARG arg_light_header_history: (Option<u64>) = None, or |c: &Config| c.parity.as_ref()?.light_header_history.clone(),
+			"--light-header-history=[ERAS]",
+			"Keep only the headers of the last ERAS blocks (at least 256) in the light client's database. Older headers are deleted in the background and fetched from the network when needed.",
+
+			ARG arg_light_quorum: (Option<String>) = None, or |c: &Config| c.parity.as_ref()?.light_quorum.clone(),
+			"--light-quorum=[KINDS]",
+			"Send light client requests of the given kinds to several peers and only accept responses they all agree on. KINDS is a comma-separated list of KIND:PEERS pairs, where KIND is one of: headers, header_proof, transaction_index, receipts, body, account, storage, code, execution, signal, bloom_groups (e.g. account:3,execution:2).",
+
 			FLAG flag_force_direct: (bool) = false, or |_| None,
 			"--force-direct",
 			"Run the originally installed version of Parity, ignoring any updates that have since been installed.",
@@ -430,6 +472,22 @@ usage! {
 			"--no-serve-light",
 			"Disable serving of light peers.",
 
+			ARG arg_light_serve_time: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.light_serve_time.clone(),
+			"--light-serve-time=[MS]",
+			"Spend at most MS milliseconds per second serving all light peers. Reserved peers are served regardless of the limit.",
+
+			ARG arg_light_serve_bandwidth: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.light_serve_bandwidth.clone(),
+			"--light-serve-bandwidth=[BYTES]",
+			"Send at most BYTES per second to all light peers. Reserved peers are served regardless of the limit.",
+
+			ARG arg_light_serve_peer_time: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.light_serve_peer_time.clone(),
+			"--light-serve-peer-time=[MS]",
+			"Spend at most MS milliseconds per second serving a single light peer. Doesn't apply to reserved peers.",
+
+			ARG arg_light_serve_peer_bandwidth: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.light_serve_peer_bandwidth.clone(),
+			"--light-serve-peer-bandwidth=[BYTES]",
+			"Send at most BYTES per second to a single light peer. Doesn't apply to reserved peers.",
+
 			ARG arg_warp_barrier: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.warp_barrier.clone(),
 			"--warp-barrier=[NUM]",
 			"When warp enabled never attempt regular sync before warping to block NUM.",
@@ -462,6 +520,14 @@ usage! {
 			"--max-pending-peers=[NUM]",
 			"Allow up to NUM pending connections.",
 
+			ARG arg_tx_propagation: (String) = "mixed", or |c: &Config| c.network.as_ref()?.tx_propagation.clone(),
+			"--tx-propagation=[POLICY]",
+			"Specify how new transactions are propagated to peers. POLICY must be one of: mixed - send full transactions to a subset of peers and announce hashes to the rest; announce - send full transactions only to peers which can't request them by hash.",
+
+			ARG arg_tx_propagation_fanout: (Option<usize>) = None, or |c: &Config| c.network.as_ref()?.tx_propagation_fanout.clone(),
+			"--tx-propagation-fanout=[NUM]",
+			"Send full transactions to at most NUM randomly chosen peers. Defaults to the square root of the number of peers.",
+
 			ARG arg_network_id: (Option<u64>) = None, or |c: &Config| c.network.as_ref()?.id.clone(),
 			"--network-id=[INDEX]",
 			"Override the network identifier from the chain we are on.",
@@ -470,6 +536,10 @@ usage! {
 			"--bootnodes=[NODES]",
 			"Override the bootnodes from our chain. NODES should be comma-delimited enodes.",
 
+			ARG arg_discovery_topics: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.discovery_topics.as_ref().map(|vec| vec.join(",")),
+			"--discovery-topics=[TOPICS]",
+			"Advertise comma-delimited TOPICS in the node record and prefer peers advertising any of them when connecting.",
+
 			ARG arg_node_key: (Option<String>) = None, or |c: &Config| c.network.as_ref()?.node_key.clone(),
 			"--node-key=[KEY]",
 			"Specify node secret key, either as 64-character hex string or input to SHA3 operation.",
@@ -681,6 +751,10 @@ usage! {
 			"--no-persistent-txqueue",
 			"Don't save pending local transactions to disk to be restored whenever the node restarts.",
 
+			FLAG flag_persistent_txqueue_all: (bool) = false, or |c: &Config| c.parity.as_ref()?.persistent_txqueue_all,
+			"--persistent-txqueue-all",
+			"Periodically save the whole transaction queue (including external transactions) to disk and re-import it whenever the node restarts. Ignored when --no-persistent-txqueue is set.",
+
 			FLAG flag_stratum: (bool) = false, or |c: &Config| Some(c.stratum.is_some()),
 			"--stratum",
 			"Run Stratum server for miner push notification.",
@@ -743,7 +817,7 @@ usage! {
 
 			ARG arg_tx_queue_strategy: (String) = "gas_price", or |c: &Config| c.mining.as_ref()?.tx_queue_strategy.clone(),
 			"--tx-queue-strategy=[S]",
-			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price",
+			"Prioritization strategy used to order transactions in the queue. S may be: gas_price - Prioritize txs with high gas price; gas_price_and_usage - Prioritize txs with high gas price, but pack blocks to maximize total fees within the block gas limit",
 
 			ARG arg_stratum_interface: (String) = "local", or |c: &Config| c.stratum.as_ref()?.interface.clone(),
 			"--stratum-interface=[IP]",
@@ -828,6 +902,14 @@ usage! {
 			"--scale-verifiers",
 			"Automatically scale amount of verifier threads based on workload. Not guaranteed to be faster.",
 
+			ARG arg_sender_nonce_index: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.sender_nonce_index.clone(),
+			"--sender-nonce-index=[BOOL]",
+			"Maintain an index of transactions by sender and nonce, used by parity_getTransactionBySenderAndNonce. Can only be enabled on a fresh database. BOOL may be one of on, off or auto. auto uses last used value of this option (off if it does not exist).",
+
+			ARG arg_account_transactions_index: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.account_transactions_index.clone(),
+			"--account-transactions-index=[BOOL]",
+			"Maintain an index of transactions touching each account, used by parity_listAccountTransactions. Internal calls are only indexed when tracing is enabled. Can only be enabled on a fresh database. BOOL may be one of on, off or auto. auto uses last used value of this option (off if it does not exist).",
+
 			ARG arg_tracing: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.tracing.clone(),
 			"--tracing=[BOOL]",
 			"Indicates if full transaction tracing should be enabled. Works only if client had been fully synced with tracing enabled. BOOL may be one of auto, on, off. auto uses last used value of this option (off if it does not exist).", // footprint option
@@ -868,6 +950,10 @@ usage! {
 			"--fat-db=[BOOL]",
 			"Build appropriate information to allow enumeration of all accounts and storage keys. Doubles the size of the state database. BOOL may be one of on, off or auto.",
 
+			ARG arg_log_index: (String) = "auto", or |c: &Config| c.footprint.as_ref()?.log_index.clone(),
+			"--log-index=[BOOL]",
+			"Maintain an index of log addresses and topics to speed up log filtering. Blocks imported before the index was enabled can be indexed with parity db index-logs. BOOL may be one of on, off or auto. auto uses last used value of this option (off if it does not exist).",
+
 			ARG arg_cache_size: (Option<u32>) = None, or |c: &Config| c.footprint.as_ref()?.cache_size.clone(),
 			"--cache-size=[MB]",
 			"Set total amount of discretionary memory to use for the entire system, overrides other cache and queue options.",
@@ -1080,7 +1166,12 @@ struct Operating {
 	identity: Option<String>,
 	light: Option<bool>,
 	no_persistent_txqueue: Option<bool>,
+	persistent_txqueue_all: Option<bool>,
 	no_hardcoded_sync: Option<bool>,
+	light_persistent_cache: Option<u32>,
+	light_checkpoint: Option<String>,
+	light_header_history: Option<u64>,
+	light_quorum: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1127,15 +1218,22 @@ struct Network {
 	max_peers: Option<u16>,
 	snapshot_peers: Option<u16>,
 	max_pending_peers: Option<u16>,
+	tx_propagation: Option<String>,
+	tx_propagation_fanout: Option<usize>,
 	nat: Option<String>,
 	allow_ips: Option<String>,
 	id: Option<u64>,
 	bootnodes: Option<Vec<String>>,
 	discovery: Option<bool>,
+	discovery_topics: Option<Vec<String>>,
 	node_key: Option<String>,
 	reserved_peers: Option<String>,
 	reserved_only: Option<bool>,
 	no_serve_light: Option<bool>,
+	light_serve_time: Option<u64>,
+	light_serve_bandwidth: Option<u64>,
+	light_serve_peer_time: Option<u64>,
+	light_serve_peer_bandwidth: Option<u64>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1277,6 +1375,9 @@ struct Footprint {
 	fat_db: Option<String>,
 	scale_verifiers: Option<bool>,
 	num_verifiers: Option<usize>,
+	log_index: Option<String>,
+	sender_nonce_index: Option<String>,
+	account_transactions_index: Option<String>,
 }
 
 #[derive(Default, Debug, PartialEq, Deserialize)]
@@ -1498,7 +1599,11 @@ mod tests {
 			cmd_tools_hash: false,
 			cmd_db: false,
 			cmd_db_kill: false,
+			cmd_db_trace: false,
+			cmd_db_index_logs: false,
 			cmd_export_hardcoded_sync: false,
+			cmd_light: false,
+			cmd_light_verify_epochs: false,
 
 			// Arguments
 			arg_daemon_pid_file: None,
@@ -1536,7 +1641,12 @@ mod tests {
 			arg_identity: "".into(),
 			flag_light: false,
 			flag_no_hardcoded_sync: false,
+			arg_light_persistent_cache: None,
+			arg_light_checkpoint: None,
+			arg_light_header_history: None,
+			arg_light_quorum: None,
 			flag_no_persistent_txqueue: false,
+			flag_persistent_txqueue_all: false,
 			flag_force_direct: false,
 
 			// -- Convenience Options
@@ -1576,16 +1686,23 @@ mod tests {
 			arg_max_peers: Some(50u16),
 			arg_max_pending_peers: 64u16,
 			arg_snapshot_peers: 0u16,
+			arg_tx_propagation: "mixed".into(),
+			arg_tx_propagation_fanout: None,
 			arg_allow_ips: "all".into(),
 			arg_nat: "any".into(),
 			arg_network_id: Some(1),
 			arg_bootnodes: Some("".into()),
 			flag_no_discovery: false,
+			arg_discovery_topics: Some("".into()),
 			arg_node_key: None,
 			arg_reserved_peers: Some("./path_to_file".into()),
 			flag_reserved_only: false,
 			flag_no_ancient_blocks: false,
 			flag_no_serve_light: false,
+			arg_light_serve_time: None,
+			arg_light_serve_bandwidth: None,
+			arg_light_serve_peer_time: None,
+			arg_light_serve_peer_bandwidth: None,
 
 			// -- API and Console Options
 			// RPC
@@ -1695,10 +1812,15 @@ mod tests {
 			arg_fat_db: "auto".into(),
 			flag_scale_verifiers: true,
 			arg_num_verifiers: Some(6),
+			arg_log_index: "auto".into(),
+			arg_sender_nonce_index: "auto".into(),
+			arg_account_transactions_index: "auto".into(),
 
 			// -- Import/Export Options
 			arg_export_blocks_from: "1".into(),
 			arg_export_blocks_to: "latest".into(),
+			arg_db_trace_from: "1".into(),
+			arg_db_trace_to: "latest".into(),
 			flag_no_seal_check: false,
 			flag_export_state_no_code: false,
 			flag_export_state_no_storage: false,
@@ -1807,7 +1929,12 @@ mod tests {
 				identity: None,
 				light: None,
 				no_hardcoded_sync: None,
+				light_persistent_cache: None,
+				light_checkpoint: None,
+				light_header_history: None,
+				light_quorum: None,
 				no_persistent_txqueue: None,
+				persistent_txqueue_all: None,
 			}),
 			account: Some(Account {
 				unlock: Some(vec!["0x1".into(), "0x2".into(), "0x3".into()]),
@@ -1833,15 +1960,22 @@ mod tests {
 				max_peers: Some(20),
 				max_pending_peers: Some(30),
 				snapshot_peers: Some(40),
+				tx_propagation: None,
+				tx_propagation_fanout: None,
 				allow_ips: Some("public".into()),
 				nat: Some("any".into()),
 				id: None,
 				bootnodes: None,
 				discovery: Some(true),
+				discovery_topics: None,
 				node_key: None,
 				reserved_peers: Some("./path/to/reserved_peers".into()),
 				reserved_only: Some(true),
 				no_serve_light: None,
+				light_serve_time: None,
+				light_serve_bandwidth: None,
+				light_serve_peer_time: None,
+				light_serve_peer_bandwidth: None,
 			}),
 			websockets: Some(Ws {
 				disable: Some(true),
@@ -1952,6 +2086,9 @@ mod tests {
 				fat_db: Some("off".into()),
 				scale_verifiers: Some(false),
 				num_verifiers: None,
+				log_index: None,
+				sender_nonce_index: None,
+				account_transactions_index: None,
 			}),
 			snapshots: Some(Snapshots {
 				disable_periodic: Some(true),
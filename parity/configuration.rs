@@ -28,6 +28,7 @@ use parity_version::{version_data, version};
 use bytes::Bytes;
 use ansi_term::Colour;
 use sync::{NetworkConfiguration, validate_node_url, self};
+use light::net::ServingBudget;
 use ethcore::ethstore::ethkey::{Secret, Public};
 use ethcore::client::{VMType};
 use ethcore::miner::{stratum, MinerOptions};
@@ -38,7 +39,7 @@ use rpc::{IpcConfiguration, HttpConfiguration, WsConfiguration, UiConfiguration}
 use rpc_apis::ApiSet;
 use parity_rpc::NetworkSettings;
 use cache::CacheConfig;
-use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization, passwords_from_files};
+use helpers::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_price, geth_ipc_path, parity_ipc_path, to_bootnodes, to_addresses, to_address, to_queue_strategy, to_queue_penalization, to_tx_propagation, to_light_quorum, passwords_from_files};
 use dir::helpers::{replace_home, replace_home_and_local};
 use params::{ResealPolicy, AccountsConfig, GasPricerConfig, MinerExtras, SpecType};
 use ethcore_logger::Config as LogConfig;
@@ -49,8 +50,9 @@ use ethcore_private_tx::{ProviderConfig, EncryptorConfig};
 use secretstore::{NodeSecretKey, Configuration as SecretStoreConfiguration, ContractAddress as SecretStoreContractAddress};
 use updater::{UpdatePolicy, UpdateFilter, ReleaseTrack};
 use run::RunCmd;
-use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, ExportState, DataFormat};
+use blockchain::{BlockchainCmd, ImportBlockchain, ExportBlockchain, KillBlockchain, TraceBlockchain, IndexLogsBlockchain, ExportState, DataFormat};
 use export_hardcoded_sync::ExportHsyncCmd;
+use light_epochs::VerifyEpochsCmd;
 use presale::ImportWallet;
 use account::{AccountCmd, NewAccount, ListAccounts, ImportAccounts, ImportFromGethAccounts};
 use snapshot::{self, SnapshotCommand};
@@ -85,6 +87,7 @@ pub enum Cmd {
 	Snapshot(SnapshotCommand),
 	Hash(Option<String>),
 	ExportHardcodedSync(ExportHsyncCmd),
+	LightVerifyEpochs(VerifyEpochsCmd),
 }
 
 pub struct Execute {
@@ -129,6 +132,9 @@ impl Configuration {
 		let cache_config = self.cache_config();
 		let tracing = self.args.arg_tracing.parse()?;
 		let fat_db = self.args.arg_fat_db.parse()?;
+		let log_index = self.args.arg_log_index.parse()?;
+		let sender_nonce_index = self.args.arg_sender_nonce_index.parse()?;
+		let account_transactions_index = self.args.arg_account_transactions_index.parse()?;
 		let compaction = self.args.arg_db_compaction.parse()?;
 		let wal = !self.args.flag_fast_and_loose;
 		let public_node = self.args.flag_public_node;
@@ -178,6 +184,32 @@ impl Configuration {
 				dirs: dirs,
 				pruning: pruning,
 			}))
+		} else if self.args.cmd_db && self.args.cmd_db_trace {
+			Cmd::Blockchain(BlockchainCmd::Trace(TraceBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				fat_db: fat_db,
+				from_block: to_block_id(&self.args.arg_db_trace_from)?,
+				to_block: to_block_id(&self.args.arg_db_trace_to)?,
+			}))
+		} else if self.args.cmd_db && self.args.cmd_db_index_logs {
+			Cmd::Blockchain(BlockchainCmd::IndexLogs(IndexLogsBlockchain {
+				spec: spec,
+				cache_config: cache_config,
+				dirs: dirs,
+				pruning: pruning,
+				pruning_history: pruning_history,
+				pruning_memory: self.args.arg_pruning_memory,
+				compaction: compaction,
+				wal: wal,
+				fat_db: fat_db,
+			}))
 		} else if self.args.cmd_account {
 			let account_cmd = if self.args.cmd_account_new {
 				let new_acc = NewAccount {
@@ -331,6 +363,15 @@ impl Configuration {
 				wal: wal,
 			};
 			Cmd::ExportHardcodedSync(export_hs_cmd)
+		} else if self.args.cmd_light && self.args.cmd_light_verify_epochs {
+			Cmd::LightVerifyEpochs(VerifyEpochsCmd {
+				cache_config: cache_config,
+				dirs: dirs,
+				spec: spec,
+				pruning: pruning,
+				compaction: compaction,
+				wal: wal,
+			})
 		} else {
 			let daemon = if self.args.cmd_daemon {
 				Some(self.args.arg_daemon_pid_file.clone().expect("CLI argument is required; qed"))
@@ -367,6 +408,9 @@ impl Configuration {
 				mode: mode,
 				tracing: tracing,
 				fat_db: fat_db,
+				log_index: log_index,
+				sender_nonce_index: sender_nonce_index,
+				account_transactions_index: account_transactions_index,
 				compaction: compaction,
 				wal: wal,
 				vm_type: vm_type,
@@ -391,10 +435,28 @@ impl Configuration {
 				download_old_blocks: !self.args.flag_no_ancient_blocks,
 				verifier_settings: verifier_settings,
 				serve_light: !self.args.flag_no_serve_light,
+				light_serve_budget: ServingBudget {
+					time: self.args.arg_light_serve_time.map(Duration::from_millis),
+					bytes: self.args.arg_light_serve_bandwidth,
+				},
+				light_serve_peer_budget: ServingBudget {
+					time: self.args.arg_light_serve_peer_time.map(Duration::from_millis),
+					bytes: self.args.arg_light_serve_peer_bandwidth,
+				},
+				tx_propagation: to_tx_propagation(&self.args.arg_tx_propagation)?,
+				tx_propagation_fanout: self.args.arg_tx_propagation_fanout,
 				light: self.args.flag_light,
 				no_persistent_txqueue: self.args.flag_no_persistent_txqueue,
+				persistent_txqueue_all: self.args.flag_persistent_txqueue_all,
 				whisper: whisper_config,
 				no_hardcoded_sync: self.args.flag_no_hardcoded_sync,
+				light_persistent_cache: self.args.arg_light_persistent_cache.map(|mb| mb as usize * 1024 * 1024),
+				light_checkpoint: self.args.arg_light_checkpoint.as_ref().map(|path| replace_home(&self.directories().base, path)),
+				light_header_history: self.args.arg_light_header_history,
+				light_quorum: match self.args.arg_light_quorum {
+					Some(ref quorum) => to_light_quorum(quorum)?,
+					None => Vec::new(),
+				},
 			};
 			Cmd::Run(run_cmd)
 		};
@@ -497,6 +559,10 @@ impl Configuration {
 		self.args.arg_snapshot_peers as u32
 	}
 
+	fn discovery_topics(&self) -> Vec<String> {
+		self.args.arg_discovery_topics.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.trim().to_owned()).filter(|s| !s.is_empty()).collect())
+	}
+
 	fn work_notify(&self) -> Vec<String> {
 		self.args.arg_notify_work.as_ref().map_or_else(Vec::new, |s| s.split(',').map(|s| s.to_owned()).collect())
 	}
@@ -787,6 +853,7 @@ impl Configuration {
 			Some(Err(err)) => return Err(err),
 		};
 		ret.discovery_enabled = !self.args.flag_no_discovery && !self.args.flag_nodiscover;
+		ret.discovery_topics = self.discovery_topics();
 		ret.max_peers = self.max_peers();
 		ret.min_peers = self.min_peers();
 		ret.snapshot_peers = self.snapshot_peers();
@@ -1378,6 +1445,56 @@ mod tests {
 		})));
 	}
 
+	#[test]
+	fn test_command_db_trace() {
+		let args = vec!["parity", "db", "trace", "--from", "100"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::Trace(TraceBlockchain {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			fat_db: Default::default(),
+			from_block: BlockId::Number(100),
+			to_block: BlockId::Latest,
+		})));
+	}
+
+	#[test]
+	fn test_command_db_index_logs() {
+		let args = vec!["parity", "db", "index-logs"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::Blockchain(BlockchainCmd::IndexLogs(IndexLogsBlockchain {
+			spec: Default::default(),
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			pruning: Default::default(),
+			pruning_history: 64,
+			pruning_memory: 32,
+			compaction: Default::default(),
+			wal: true,
+			fat_db: Default::default(),
+		})));
+	}
+
+	#[test]
+	fn test_command_light_verify_epochs() {
+		let args = vec!["parity", "light", "verify-epochs"];
+		let conf = parse(&args);
+		assert_eq!(conf.into_command().unwrap().cmd, Cmd::LightVerifyEpochs(VerifyEpochsCmd {
+			cache_config: Default::default(),
+			dirs: Default::default(),
+			spec: Default::default(),
+			pruning: Default::default(),
+			compaction: Default::default(),
+			wal: true,
+		}));
+	}
+
 	#[test]
 	fn test_command_state_export() {
 		let args = vec!["parity", "export", "state", "state.json"];
@@ -1528,15 +1645,27 @@ mod tests {
 			name: "".into(),
 			custom_bootnodes: false,
 			fat_db: Default::default(),
+			log_index: Default::default(),
+			sender_nonce_index: Default::default(),
+			account_transactions_index: Default::default(),
 			no_periodic_snapshot: false,
 			stratum: None,
 			check_seal: true,
 			download_old_blocks: true,
 			verifier_settings: Default::default(),
 			serve_light: true,
+			light_serve_budget: Default::default(),
+			light_serve_peer_budget: Default::default(),
+			tx_propagation: Default::default(),
+			tx_propagation_fanout: None,
 			light: false,
 			no_hardcoded_sync: false,
+			light_persistent_cache: None,
+			light_checkpoint: None,
+			light_header_history: None,
+			light_quorum: Vec::new(),
 			no_persistent_txqueue: false,
+			persistent_txqueue_all: false,
 			whisper: Default::default(),
 		};
 		expected.secretstore_conf.enabled = cfg!(feature = "secretstore");
@@ -1552,11 +1681,14 @@ mod tests {
 		// when
 		let conf0 = parse(&["parity"]);
 		let conf2 = parse(&["parity", "--tx-queue-strategy", "gas_price"]);
+		let conf3 = parse(&["parity", "--tx-queue-strategy", "gas_price_and_usage"]);
 
 		// then
 		assert_eq!(conf0.miner_options().unwrap(), mining_options);
 		mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceOnly;
 		assert_eq!(conf2.miner_options().unwrap(), mining_options);
+		mining_options.tx_queue_strategy = PrioritizationStrategy::GasPriceAndGasUsage;
+		assert_eq!(conf3.miner_options().unwrap(), mining_options);
 	}
 
 	#[test]
@@ -1626,6 +1758,15 @@ mod tests {
 		}));
 	}
 
+	#[test]
+	fn should_parse_discovery_topics() {
+		let conf0 = parse(&["parity"]);
+		let conf1 = parse(&["parity", "--discovery-topics", "private-net, staging"]);
+
+		assert_eq!(conf0.net_config().unwrap().discovery_topics, Vec::<String>::new());
+		assert_eq!(conf1.net_config().unwrap().discovery_topics, vec!["private-net".to_owned(), "staging".to_owned()]);
+	}
+
 	#[test]
 	fn should_parse_rpc_settings_with_geth_compatiblity() {
 		// given
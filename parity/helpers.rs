@@ -99,10 +99,46 @@ pub fn to_pending_set(s: &str) -> Result<PendingSet, String> {
 pub fn to_queue_strategy(s: &str) -> Result<PrioritizationStrategy, String> {
 	match s {
 		"gas_price" => Ok(PrioritizationStrategy::GasPriceOnly),
+		"gas_price_and_usage" => Ok(PrioritizationStrategy::GasPriceAndGasUsage),
 		other => Err(format!("Invalid queue strategy: {}", other)),
 	}
 }
 
+pub fn to_tx_propagation(s: &str) -> Result<sync::TransactionPropagation, String> {
+	match s {
+		"mixed" => Ok(sync::TransactionPropagation::Mixed),
+		"announce" => Ok(sync::TransactionPropagation::AnnounceOnly),
+		other => Err(format!("Invalid transaction propagation policy: {}", other)),
+	}
+}
+
+pub fn to_light_quorum(s: &str) -> Result<Vec<(::light::request::Kind, usize)>, String> {
+	use light::request::Kind;
+
+	s.split(',').filter(|s| !s.is_empty()).map(|entry| {
+		let mut parts = entry.splitn(2, ':');
+		let kind = match parts.next().unwrap_or("") {
+			"headers" => Kind::Headers,
+			"header_proof" => Kind::HeaderProof,
+			"transaction_index" => Kind::TransactionIndex,
+			"receipts" => Kind::Receipts,
+			"body" => Kind::Body,
+			"account" => Kind::Account,
+			"storage" => Kind::Storage,
+			"code" => Kind::Code,
+			"execution" => Kind::Execution,
+			"signal" => Kind::Signal,
+			"bloom_groups" => Kind::BloomGroups,
+			other => return Err(format!("Invalid light request kind: {}", other)),
+		};
+		let peers = parts.next()
+			.ok_or_else(|| format!("Missing quorum size for light request kind in: {}", entry))?
+			.parse::<usize>()
+			.map_err(|_| format!("Invalid quorum size in: {}", entry))?;
+		Ok((kind, peers))
+	}).collect()
+}
+
 pub fn to_queue_penalization(time: Option<u64>) -> Result<Penalization, String> {
 	Ok(match time {
 		Some(threshold_ms) => Penalization::Enabled {
@@ -189,6 +225,7 @@ pub fn default_network_config() -> ::sync::NetworkConfiguration {
 		udp_port: None,
 		nat_enabled: true,
 		discovery_enabled: true,
+		discovery_topics: Vec::new(),
 		boot_nodes: Vec::new(),
 		use_secret: None,
 		max_peers: 50,
@@ -330,7 +367,9 @@ mod tests {
 	use ethereum_types::U256;
 	use ethcore::client::{Mode, BlockId};
 	use ethcore::miner::PendingSet;
-	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file};
+	use sync::TransactionPropagation;
+	use miner::pool::PrioritizationStrategy;
+	use super::{to_duration, to_mode, to_block_id, to_u256, to_pending_set, to_queue_strategy, to_tx_propagation, to_light_quorum, to_address, to_addresses, to_price, geth_ipc_path, to_bootnodes, password_from_file};
 
 	#[test]
 	fn test_to_duration() {
@@ -360,6 +399,13 @@ mod tests {
 		assert!(to_mode("other", 20, 30).is_err());
 	}
 
+	#[test]
+	fn test_to_queue_strategy() {
+		assert_eq!(to_queue_strategy("gas_price").unwrap(), PrioritizationStrategy::GasPriceOnly);
+		assert_eq!(to_queue_strategy("gas_price_and_usage").unwrap(), PrioritizationStrategy::GasPriceAndGasUsage);
+		assert!(to_queue_strategy("gas_factor").is_err());
+	}
+
 	#[test]
 	fn test_to_block_id() {
 		assert_eq!(to_block_id("latest").unwrap(), BlockId::Latest);
@@ -380,6 +426,18 @@ mod tests {
 		assert!(to_u256("u").is_err())
 	}
 
+	#[test]
+	fn test_to_light_quorum() {
+		use light::request::Kind;
+
+		assert_eq!(to_light_quorum("").unwrap(), vec![]);
+		assert_eq!(to_light_quorum("account:3,execution:2").unwrap(), vec![(Kind::Account, 3), (Kind::Execution, 2)]);
+		assert_eq!(to_light_quorum("bloom_groups:2").unwrap(), vec![(Kind::BloomGroups, 2)]);
+		assert!(to_light_quorum("account").is_err());
+		assert!(to_light_quorum("account:x").is_err());
+		assert!(to_light_quorum("other:2").is_err());
+	}
+
 	#[test]
 	fn test_pending_set() {
 		assert_eq!(to_pending_set("cheap").unwrap(), PendingSet::AlwaysQueue);
@@ -388,6 +446,13 @@ mod tests {
 		assert!(to_pending_set("othe").is_err());
 	}
 
+	#[test]
+	fn test_tx_propagation() {
+		assert_eq!(to_tx_propagation("mixed").unwrap(), TransactionPropagation::Mixed);
+		assert_eq!(to_tx_propagation("announce").unwrap(), TransactionPropagation::AnnounceOnly);
+		assert!(to_tx_propagation("flood").is_err());
+	}
+
 	#[test]
 	fn test_to_address() {
 		assert_eq!(
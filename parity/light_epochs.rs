@@ -0,0 +1,65 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+use ethcore::client::DatabaseCompactionProfile;
+use light::client::EpochProofStatus;
+
+use params::{SpecType, Pruning};
+use dir::Directories;
+use cache::CacheConfig;
+use light_helpers::start_offline_client;
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyEpochsCmd {
+	pub cache_config: CacheConfig,
+	pub dirs: Directories,
+	pub spec: SpecType,
+	pub pruning: Pruning,
+	pub compaction: DatabaseCompactionProfile,
+	pub wal: bool,
+}
+
+/// Replay the epoch transition proofs stored in the light client database
+/// through the engine and report their status.
+pub fn execute(cmd: VerifyEpochsCmd) -> Result<String, String> {
+	let service = start_offline_client(&cmd.spec, &cmd.dirs, &cmd.pruning, &cmd.compaction, cmd.wal, &cmd.cache_config)?;
+
+	let transitions = service.client().verify_epoch_transitions();
+	let mut invalid = 0;
+	let mut report = Vec::with_capacity(transitions.len() + 1);
+
+	for t in &transitions {
+		let status = match t.status {
+			EpochProofStatus::Verified => "verified".to_owned(),
+			EpochProofStatus::Unconfirmed => "unconfirmed (previous epoch not stored)".to_owned(),
+			EpochProofStatus::Invalid(ref e) => {
+				invalid += 1;
+				format!("invalid: {}", e)
+			}
+		};
+
+		let canonical = if t.canonical { "" } else { " (non-canonical)" };
+		report.push(format!("#{} {:?}{}: {}", t.block_number, t.block_hash, canonical, status));
+	}
+
+	report.push(format!("{} epoch transitions, {} invalid", transitions.len(), invalid));
+	let report = report.join("\n");
+
+	match invalid {
+		0 => Ok(report),
+		_ => Err(report),
+	}
+}
@@ -17,7 +17,9 @@
 //! Utilities and helpers for the light client.
 
 mod epoch_fetch;
+mod offline_client;
 mod queue_cull;
 
 pub use self::epoch_fetch::EpochFetch;
+pub use self::offline_client::{start_offline_client, GAS_CORPUS_EXPIRATION_MINUTES};
 pub use self::queue_cull::QueueCull;
@@ -0,0 +1,97 @@
+// Copyright 2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Light client working on the local database only, used by commands inspecting it.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethcore::client::DatabaseCompactionProfile;
+use ethcore::spec::{SpecParams, OptimizeFor};
+use light::client::{self as light_client, Service};
+use light::client::fetch::Unavailable as UnavailableDataFetcher;
+use light::Cache as LightDataCache;
+use parking_lot::Mutex;
+
+use params::{SpecType, Pruning};
+use helpers::execute_upgrades;
+use dir::Directories;
+use cache::CacheConfig;
+use user_defaults::UserDefaults;
+use db;
+
+/// Number of minutes before a given gas price corpus should expire.
+pub const GAS_CORPUS_EXPIRATION_MINUTES: u64 = 60 * 6;
+
+/// Upgrade and open the light client database of the chain and start a client on it
+/// which doesn't fetch any data from the network.
+pub fn start_offline_client(
+	spec: &SpecType,
+	dirs: &Directories,
+	pruning: &Pruning,
+	compaction: &DatabaseCompactionProfile,
+	wal: bool,
+	cache_config: &CacheConfig,
+) -> Result<Service<UnavailableDataFetcher>, String> {
+	// load spec
+	let chain_spec = spec.spec(SpecParams::new(dirs.cache.as_ref(), OptimizeFor::Memory))?;
+
+	// load genesis hash
+	let genesis_hash = chain_spec.genesis_header().hash();
+
+	// database paths
+	let db_dirs = dirs.database(genesis_hash, spec.legacy_fork_name(), chain_spec.data_dir.clone());
+
+	// user defaults path
+	let user_defaults_path = db_dirs.user_defaults_path();
+
+	// load user defaults
+	let user_defaults = UserDefaults::load(&user_defaults_path)?;
+
+	// select pruning algorithm
+	let algorithm = pruning.to_algorithm(&user_defaults);
+
+	// execute upgrades
+	execute_upgrades(&dirs.base, &db_dirs, algorithm, compaction)?;
+
+	// create dirs used by parity
+	dirs.create_dirs(false, false, false)?;
+
+	// TODO: configurable cache size.
+	let cache = LightDataCache::new(Default::default(), Duration::from_secs(60 * GAS_CORPUS_EXPIRATION_MINUTES));
+	let cache = Arc::new(Mutex::new(cache));
+
+	let mut config = light_client::Config {
+		queue: Default::default(),
+		chain_column: ::ethcore::db::COL_LIGHT_CHAIN,
+		verify_full: true,
+		check_seal: true,
+		no_hardcoded_sync: true,
+		checkpoint: None,
+		header_history: None,
+	};
+
+	config.queue.max_mem_use = cache_config.queue() as usize * 1024 * 1024;
+
+	// initialize database.
+	let db = db::open_db(&db_dirs.client_path(algorithm).to_str().expect("DB path could not be converted to string."),
+						 cache_config,
+						 compaction,
+						 wal)?;
+
+	light_client::Service::start(config, &chain_spec, UnavailableDataFetcher, db, cache)
+		.map_err(|e| format!("Error starting light client: {}", e))
+}
@@ -15,15 +15,20 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Service for culling the light client's transaction queue.
+//!
+//! Queued transactions are culled periodically by checking the nonces of their senders,
+//! and as soon as they are found in the bodies of newly imported blocks.
 
 use std::sync::Arc;
 use std::time::Duration;
 
 use ethcore::client::ClientIoMessage;
+use ethcore::ids::BlockId;
+use ethereum_types::H256;
 use sync::LightSync;
 use io::{IoContext, IoHandler, TimerToken};
 
-use light::client::LightChainClient;
+use light::client::{LightChainClient, LightChainNotify};
 use light::on_demand::{request, OnDemand};
 use light::TransactionQueue;
 
@@ -40,7 +45,11 @@ const TIMEOUT: Duration = Duration::from_secs(60 * 10);
 // But make each attempt last only 9 minutes
 const PURGE_TIMEOUT: Duration = Duration::from_secs(60 * 9);
 
-/// Periodically culls the transaction queue of mined transactions.
+// Maximum number of newly imported blocks to fetch the bodies of.
+// Transactions included in older blocks are culled by nonce on the timer.
+const MAX_NEW_BLOCKS: usize = 16;
+
+/// Culls the transaction queue of mined transactions.
 pub struct QueueCull<T> {
 	/// A handle to the client, for getting the latest block header.
 	pub client: Arc<T>,
@@ -103,3 +112,37 @@ impl<T: LightChainClient + 'static> IoHandler<ClientIoMessage> for QueueCull<T>
 		}, PURGE_TIMEOUT, || {})
 	}
 }
+
+impl<T: LightChainClient + 'static> LightChainNotify for QueueCull<T> {
+	fn new_headers(&self, good: &[H256]) {
+		if self.txq.read().is_empty() || self.sync.is_major_importing() { return }
+
+		let headers: Vec<_> = good[good.len().saturating_sub(MAX_NEW_BLOCKS)..].iter()
+			.filter_map(|hash| self.client.block_header(BlockId::Hash(*hash)))
+			.collect();
+
+		let txq = self.txq.clone();
+		let maybe_fetching = self.sync.with_context(|ctx| {
+			// fetch the bodies of the new blocks to see which transactions they include.
+			// receipts aren't needed: an included transaction uses up its nonce even if it failed.
+			let body_reqs = headers.into_iter()
+				.map(|header| request::Body(header.into()))
+				.collect::<Vec<_>>();
+
+			self.on_demand.request(ctx, body_reqs)
+				.expect("No back-references; therefore all back-references are valid; qed")
+				.map(move |blocks| {
+					let mut txq = txq.write();
+					for block in blocks {
+						txq.mined(&block.transaction_hashes());
+					}
+				})
+				.map_err(|_| debug!(target: "cull", "OnDemand prematurely closed channel."))
+		});
+
+		match maybe_fetching {
+			Some(fut) => self.remote.spawn(fut),
+			None => debug!(target: "cull", "Unable to acquire network context; qed"),
+		}
+	}
+}
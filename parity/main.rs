@@ -107,6 +107,7 @@ mod ipfs;
 mod deprecated;
 mod helpers;
 mod informant;
+mod light_epochs;
 mod light_helpers;
 mod modules;
 mod params;
@@ -173,6 +174,7 @@ fn execute(command: Execute, can_restart: bool) -> Result<PostExecutionAction, S
 		Cmd::SignerReject { id, port, authfile } => rpc_cli::signer_reject(id, port, authfile).map(|s| PostExecutionAction::Print(s)),
 		Cmd::Snapshot(snapshot_cmd) => snapshot::execute(snapshot_cmd).map(|s| PostExecutionAction::Print(s)),
 		Cmd::ExportHardcodedSync(export_hs_cmd) => export_hardcoded_sync::execute(export_hs_cmd).map(|s| PostExecutionAction::Print(s)),
+		Cmd::LightVerifyEpochs(verify_cmd) => light_epochs::execute(verify_cmd).map(|s| PostExecutionAction::Print(s)),
 	}
 }
 
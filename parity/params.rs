@@ -320,6 +320,32 @@ pub fn tracing_switch_to_bool(switch: Switch, user_defaults: &UserDefaults) -> R
 	}
 }
 
+pub fn log_index_switch_to_bool(switch: Switch, user_defaults: &UserDefaults) -> bool {
+	match (switch, user_defaults.log_index) {
+		(Switch::On, _) => true,
+		(Switch::Off, _) => false,
+		(Switch::Auto, def) => def,
+	}
+}
+
+pub fn sender_nonce_index_switch_to_bool(switch: Switch, user_defaults: &UserDefaults) -> Result<bool, String> {
+	match (user_defaults.is_first_launch, switch, user_defaults.sender_nonce_index) {
+		(false, Switch::On, false) => Err("Sender/nonce index resync required".into()),
+		(_, Switch::On, _) => Ok(true),
+		(_, Switch::Off, _) => Ok(false),
+		(_, Switch::Auto, def) => Ok(def),
+	}
+}
+
+pub fn account_transactions_index_switch_to_bool(switch: Switch, user_defaults: &UserDefaults) -> Result<bool, String> {
+	match (user_defaults.is_first_launch, switch, user_defaults.account_transactions_index) {
+		(false, Switch::On, false) => Err("Account transactions index resync required".into()),
+		(_, Switch::On, _) => Ok(true),
+		(_, Switch::Off, _) => Ok(false),
+		(_, Switch::Auto, def) => Ok(def),
+	}
+}
+
 pub fn fatdb_switch_to_bool(switch: Switch, user_defaults: &UserDefaults, _algorithm: Algorithm) -> Result<bool, String> {
 	let result = match (user_defaults.is_first_launch, switch, user_defaults.fat_db) {
 		(false, Switch::On, false) => Err("FatDB resync required".into()),
@@ -338,7 +364,8 @@ pub fn mode_switch_to_bool(switch: Option<Mode>, user_defaults: &UserDefaults) -
 mod tests {
 	use journaldb::Algorithm;
 	use user_defaults::UserDefaults;
-	use super::{SpecType, Pruning, ResealPolicy, Switch, tracing_switch_to_bool};
+	use super::{SpecType, Pruning, ResealPolicy, Switch, tracing_switch_to_bool, sender_nonce_index_switch_to_bool,
+		account_transactions_index_switch_to_bool};
 
 	#[test]
 	fn test_spec_type_parsing() {
@@ -437,4 +464,40 @@ mod tests {
 		assert!(tracing_switch_to_bool(Switch::On, &user_defaults_with_tracing(false, true)).unwrap());
 		assert!(tracing_switch_to_bool(Switch::On, &user_defaults_with_tracing(false, false)).is_err());
 	}
+
+	#[test]
+	fn test_sender_nonce_index_switch_to_bool() {
+		let user_defaults = |first_launch, sender_nonce_index| {
+			let mut ud = UserDefaults::default();
+			ud.is_first_launch = first_launch;
+			ud.sender_nonce_index = sender_nonce_index;
+			ud
+		};
+
+		assert!(!sender_nonce_index_switch_to_bool(Switch::Off, &user_defaults(false, true)).unwrap());
+		assert!(!sender_nonce_index_switch_to_bool(Switch::Auto, &user_defaults(true, false)).unwrap());
+		assert!(!sender_nonce_index_switch_to_bool(Switch::Auto, &user_defaults(false, false)).unwrap());
+		assert!(sender_nonce_index_switch_to_bool(Switch::Auto, &user_defaults(false, true)).unwrap());
+
+		assert!(sender_nonce_index_switch_to_bool(Switch::On, &user_defaults(true, false)).unwrap());
+		assert!(sender_nonce_index_switch_to_bool(Switch::On, &user_defaults(false, true)).unwrap());
+		assert!(sender_nonce_index_switch_to_bool(Switch::On, &user_defaults(false, false)).is_err());
+	}
+
+	#[test]
+	fn test_account_transactions_index_switch_to_bool() {
+		let user_defaults = |first_launch, account_transactions_index| {
+			let mut ud = UserDefaults::default();
+			ud.is_first_launch = first_launch;
+			ud.account_transactions_index = account_transactions_index;
+			ud
+		};
+
+		assert!(!account_transactions_index_switch_to_bool(Switch::Off, &user_defaults(false, true)).unwrap());
+		assert!(account_transactions_index_switch_to_bool(Switch::Auto, &user_defaults(false, true)).unwrap());
+		assert!(!account_transactions_index_switch_to_bool(Switch::Auto, &user_defaults(true, false)).unwrap());
+
+		assert!(account_transactions_index_switch_to_bool(Switch::On, &user_defaults(true, false)).unwrap());
+		assert!(account_transactions_index_switch_to_bool(Switch::On, &user_defaults(false, false)).is_err());
+	}
 }
@@ -308,6 +308,10 @@ impl FullDependencies {
 						self.miner.add_transactions_listener(Box::new(move |hashes| if let Some(h) = h.upgrade() {
 							h.notify_new_transactions(hashes);
 						}));
+						let h = client.handler();
+						self.miner.add_transaction_events_listener(Box::new(move |events| if let Some(h) = h.upgrade() {
+							h.notify_transaction_events(events);
+						}));
 
 						if let Some(h) = client.handler().upgrade() {
 							self.client.add_notify(h);
@@ -330,6 +334,7 @@ impl FullDependencies {
 						self.client.clone(),
 						self.miner.clone(),
 						self.sync.clone(),
+						self.snapshot.clone(),
 						self.updater.clone(),
 						self.net_service.clone(),
 						self.health.clone(),
@@ -583,7 +588,7 @@ impl<C: LightChainClient + 'static> LightDependencies<C> {
 					).to_delegate())
 				},
 				Api::Traces => {
-					handler.extend_with(light::TracesClient.to_delegate())
+					handler.extend_with(light::TracesClient::new(Arc::new(dispatcher.clone())).to_delegate())
 				},
 				Api::Rpc => {
 					let modules = to_modules(&apis);
@@ -51,7 +51,8 @@ use parity_version::version;
 use ethcore_private_tx::{ProviderConfig, EncryptorConfig, SecretStoreEncryptor};
 use params::{
 	SpecType, Pruning, AccountsConfig, GasPricerConfig, MinerExtras, Switch,
-	tracing_switch_to_bool, fatdb_switch_to_bool, mode_switch_to_bool
+	tracing_switch_to_bool, fatdb_switch_to_bool, log_index_switch_to_bool, sender_nonce_index_switch_to_bool,
+	account_transactions_index_switch_to_bool, mode_switch_to_bool
 };
 use helpers::{to_client_config, execute_upgrades, passwords_from_files};
 use upgrade::upgrade_key_location;
@@ -74,10 +75,6 @@ const SNAPSHOT_PERIOD: u64 = 5000;
 // how many blocks to wait before starting a periodic snapshot.
 const SNAPSHOT_HISTORY: u64 = 100;
 
-// Number of minutes before a given gas price corpus should expire.
-// Light client only.
-const GAS_CORPUS_EXPIRATION_MINUTES: u64 = 60 * 6;
-
 // Pops along with error messages when a password is missing or invalid.
 const VERIFY_PASSWORD_HINT: &'static str = "Make sure valid password is present in files passed using `--password` or in the configuration file.";
 
@@ -110,6 +107,9 @@ pub struct RunCmd {
 	pub mode: Option<Mode>,
 	pub tracing: Switch,
 	pub fat_db: Switch,
+	pub log_index: Switch,
+	pub sender_nonce_index: Switch,
+	pub account_transactions_index: Switch,
 	pub compaction: DatabaseCompactionProfile,
 	pub wal: bool,
 	pub vm_type: VMType,
@@ -132,10 +132,19 @@ pub struct RunCmd {
 	pub download_old_blocks: bool,
 	pub verifier_settings: VerifierSettings,
 	pub serve_light: bool,
+	pub light_serve_budget: ::light::net::ServingBudget,
+	pub light_serve_peer_budget: ::light::net::ServingBudget,
+	pub tx_propagation: sync::TransactionPropagation,
+	pub tx_propagation_fanout: Option<usize>,
 	pub light: bool,
 	pub no_persistent_txqueue: bool,
+	pub persistent_txqueue_all: bool,
 	pub whisper: ::whisper::Config,
 	pub no_hardcoded_sync: bool,
+	pub light_persistent_cache: Option<usize>,
+	pub light_checkpoint: Option<String>,
+	pub light_header_history: Option<u64>,
+	pub light_quorum: Vec<(::light::request::Kind, usize)>,
 }
 
 pub fn open_ui(ws_conf: &rpc::WsConfiguration, ui_conf: &rpc::UiConfiguration, logger_config: &LogConfig) -> Result<(), String> {
@@ -163,6 +172,7 @@ pub fn open_dapp(dapps_conf: &dapps::Configuration, rpc_conf: &rpc::HttpConfigur
 // node info fetcher for the local store.
 struct FullNodeInfo {
 	miner: Option<Arc<Miner>>, // TODO: only TXQ needed, just use that after decoupling.
+	persist_queue: bool,
 }
 
 impl ::local_store::NodeInfo for FullNodeInfo {
@@ -180,6 +190,38 @@ impl ::local_store::NodeInfo for FullNodeInfo {
 			})
 			.collect()
 	}
+
+	fn queued_transactions(&self) -> Option<Vec<::local_store::QueuedTransaction>> {
+		if !self.persist_queue {
+			return None;
+		}
+
+		let miner = self.miner.as_ref()?;
+		let queued = miner.queue_snapshot()
+			.into_iter()
+			.map(|(tx, penalized)| ::local_store::QueuedTransaction {
+				transaction: tx.signed().clone(),
+				insertion_id: tx.insertion_id(),
+				penalized,
+			})
+			.collect();
+
+		Some(queued)
+	}
+}
+
+// node info fetcher for the local store of the light client.
+struct LightNodeInfo {
+	txq: Option<Arc<::parking_lot::RwLock<::light::TransactionQueue>>>,
+}
+
+impl ::local_store::NodeInfo for LightNodeInfo {
+	fn pending_transactions(&self) -> Vec<::transaction::PendingTransaction> {
+		match self.txq.as_ref() {
+			Some(txq) => txq.read().queued_transactions(),
+			None => Vec::new(),
+		}
+	}
 }
 
 type LightClient = ::light::client::Client<::light_helpers::EpochFetch>;
@@ -220,9 +262,21 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 	info!("Running in experimental {} mode.", Colour::Blue.bold().paint("Light Client"));
 
 	// TODO: configurable cache size.
-	let cache = LightDataCache::new(Default::default(), Duration::from_secs(60 * GAS_CORPUS_EXPIRATION_MINUTES));
+	let cache = LightDataCache::new(Default::default(), Duration::from_secs(60 * ::light_helpers::GAS_CORPUS_EXPIRATION_MINUTES));
 	let cache = Arc::new(Mutex::new(cache));
 
+	// load the checkpoint to start from.
+	let checkpoint = match cmd.light_checkpoint {
+		Some(ref path) => {
+			let file = ::std::fs::File::open(path)
+				.map_err(|e| format!("Error opening light checkpoint file {}: {}", path, e))?;
+			let checkpoint = ::ethcore::spec::SpecHardcodedSync::load(file)
+				.map_err(|e| format!("Invalid light checkpoint file {}: {}", path, e))?;
+			Some(checkpoint)
+		}
+		None => None,
+	};
+
 	// start client and create transaction queue.
 	let mut config = light_client::Config {
 		queue: Default::default(),
@@ -230,13 +284,19 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 		verify_full: true,
 		check_seal: cmd.check_seal,
 		no_hardcoded_sync: cmd.no_hardcoded_sync,
+		checkpoint: checkpoint,
+		header_history: cmd.light_header_history,
 	};
 
 	config.queue.max_mem_use = cmd.cache_config.queue() as usize * 1024 * 1024;
 	config.queue.verifier_settings = cmd.verifier_settings;
 
 	// start on_demand service.
-	let on_demand = Arc::new(::light::on_demand::OnDemand::new(cache.clone()));
+	let mut on_demand = ::light::on_demand::OnDemand::new(cache.clone());
+	for &(kind, peers) in &cmd.light_quorum {
+		on_demand.set_quorum(kind, peers);
+	}
+	let on_demand = Arc::new(on_demand);
 
 	let sync_handle = Arc::new(RwLock::new(Weak::new()));
 	let fetch = ::light_helpers::EpochFetch {
@@ -250,12 +310,55 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 						 &cmd.compaction,
 						 cmd.wal)?;
 
-	let service = light_client::Service::start(config, &spec, fetch, db, cache.clone())
+	if let Some(max_size) = cmd.light_persistent_cache {
+		let persistent = ::light::cache::PersistentCache::new(db.clone(), ::ethcore::db::COL_LIGHT_CHAIN, max_size);
+		cache.lock().set_persistent(persistent);
+	}
+
+	let service = light_client::Service::start(config, &spec, fetch, db.clone(), cache.clone())
 		.map_err(|e| format!("Error starting light client: {}", e))?;
 	let client = service.client().clone();
 	let txq = Arc::new(RwLock::new(::light::transaction_queue::TransactionQueue::default()));
 	let provider = ::light::provider::LightProvider::new(client.clone(), txq.clone());
 
+	// initialize the local node information store.
+	let store = {
+		let node_info = LightNodeInfo {
+			txq: match cmd.no_persistent_txqueue {
+				true => None,
+				false => Some(txq.clone()),
+			}
+		};
+
+		let store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
+
+		if cmd.no_persistent_txqueue {
+			info!("Running without a persistent transaction queue.");
+
+			if let Err(e) = store.clear() {
+				warn!("Error clearing persistent transaction queue: {}", e);
+			}
+		}
+
+		// re-queue pending transactions.
+		match store.pending_transactions() {
+			Ok(pending) => {
+				let mut txq = txq.write();
+				for pending_tx in pending {
+					if let Err(e) = txq.import(pending_tx) {
+						warn!("Error importing saved transaction: {}", e)
+					}
+				}
+			}
+			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
+		}
+
+		Arc::new(store)
+	};
+
+	// register it as an IO service to update periodically.
+	service.register_handler(store).map_err(|_| "Unable to register local store handler".to_owned())?;
+
 	// start network.
 	// set up bootnodes
 	let mut net_conf = cmd.net_conf;
@@ -298,7 +401,8 @@ fn execute_light_impl(cmd: RunCmd, logger: Arc<RotatingLogger>) -> Result<Runnin
 		remote: event_loop.remote(),
 	});
 
-	service.register_handler(queue_cull).map_err(|e| format!("Error attaching service: {:?}", e))?;
+	service.register_handler(queue_cull.clone()).map_err(|e| format!("Error attaching service: {:?}", e))?;
+	service.add_notify(queue_cull);
 
 	// start the network.
 	light_sync.start_network();
@@ -451,6 +555,15 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	// check if fatdb is on
 	let fat_db = fatdb_switch_to_bool(cmd.fat_db, &user_defaults, algorithm)?;
 
+	// check if log index is on
+	let log_index = log_index_switch_to_bool(cmd.log_index, &user_defaults);
+
+	// check if sender/nonce index is on
+	let sender_nonce_index = sender_nonce_index_switch_to_bool(cmd.sender_nonce_index, &user_defaults)?;
+
+	// check if account transactions index is on
+	let account_transactions_index = account_transactions_index_switch_to_bool(cmd.account_transactions_index, &user_defaults)?;
+
 	// get the mode
 	let mode = mode_switch_to_bool(cmd.mode, &user_defaults)?;
 	trace!(target: "mode", "mode is {:?}", mode);
@@ -509,6 +622,7 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	}
 
 	sync_config.fork_block = spec.fork_block();
+	sync_config.forks = spec.fork_transitions();
 	let mut warp_sync = spec.engine.supports_warp() && cmd.warp_sync;
 	if warp_sync {
 		// Logging is not initialized yet, so we print directly to stderr
@@ -530,6 +644,10 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	};
 	sync_config.download_old_blocks = cmd.download_old_blocks;
 	sync_config.serve_light = cmd.serve_light;
+	sync_config.light_serve_budget = cmd.light_serve_budget;
+	sync_config.light_serve_peer_budget = cmd.light_serve_peer_budget;
+	sync_config.transaction_propagation = cmd.tx_propagation;
+	sync_config.transaction_fanout = cmd.tx_propagation_fanout;
 
 	let passwords = passwords_from_files(&cmd.acc_conf.password_files)?;
 
@@ -584,6 +702,11 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 		warn!("The --no-hardcoded-sync flag has no effect if you don't use --light");
 	}
 
+	// display warning if using --light-checkpoint
+	if cmd.light_checkpoint.is_some() {
+		warn!("The --light-checkpoint option has no effect if you don't use --light");
+	}
+
 	// create client config
 	let mut client_config = to_client_config(
 		&cmd.cache_config,
@@ -602,6 +725,9 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	);
 
 	client_config.queue.verifier_settings = cmd.verifier_settings;
+	client_config.blockchain.log_index = log_index;
+	client_config.blockchain.sender_nonce_index = sender_nonce_index;
+	client_config.blockchain.account_transactions_index = account_transactions_index;
 	client_config.transaction_verification_queue_size = ::std::cmp::max(2048, txpool_size / 4);
 
 	// set up bootnodes
@@ -652,7 +778,8 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			miner: match cmd.no_persistent_txqueue {
 				true => None,
 				false => Some(miner.clone()),
-			}
+			},
+			persist_queue: cmd.persistent_txqueue_all,
 		};
 
 		let store = ::local_store::create(db, ::ethcore::db::COL_NODE_INFO, node_info);
@@ -677,6 +804,29 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 			Err(e) => warn!("Error loading cached pending transactions from disk: {}", e),
 		}
 
+		// re-queue the rest of the transaction queue.
+		if cmd.persistent_txqueue_all && !cmd.no_persistent_txqueue {
+			match store.queued_transactions() {
+				Ok(queued) => {
+					let total = queued.len();
+					let mut penalized: Vec<_> = queued.iter()
+						.filter(|tx| tx.penalized)
+						.map(|tx| tx.transaction.sender())
+						.collect();
+					penalized.sort();
+					penalized.dedup();
+
+					let transactions = queued.into_iter().map(|tx| tx.transaction.into()).collect();
+					let imported = miner.import_queue_snapshot(&*client, transactions, &penalized)
+						.into_iter()
+						.filter(Result::is_ok)
+						.count();
+					info!("Restored {} out of {} saved transactions to the transaction queue.", imported, total);
+				}
+				Err(e) => warn!("Error loading saved transaction queue from disk: {}", e),
+			}
+		}
+
 		Arc::new(store)
 	};
 
@@ -871,6 +1021,9 @@ fn execute_impl<Cr, Rr>(cmd: RunCmd, logger: Arc<RotatingLogger>, on_client_rq:
 	user_defaults.pruning = algorithm;
 	user_defaults.tracing = tracing;
 	user_defaults.fat_db = fat_db;
+	user_defaults.log_index = log_index;
+	user_defaults.sender_nonce_index = sender_nonce_index;
+	user_defaults.account_transactions_index = account_transactions_index;
 	user_defaults.mode = mode;
 	user_defaults.save(&user_defaults_path)?;
 
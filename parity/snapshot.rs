@@ -169,7 +169,7 @@ impl SnapshotCommand {
 		execute_upgrades(&self.dirs.base, &db_dirs, algorithm, &self.compaction)?;
 
 		// prepare client config
-		let client_config = to_client_config(
+		let mut client_config = to_client_config(
 			&self.cache_config,
 			spec.name.to_lowercase(),
 			Mode::Active,
@@ -185,6 +185,10 @@ impl SnapshotCommand {
 			true,
 		);
 
+		client_config.blockchain.log_index = user_defaults.log_index;
+		client_config.blockchain.sender_nonce_index = user_defaults.sender_nonce_index;
+		client_config.blockchain.account_transactions_index = user_defaults.account_transactions_index;
+
 		let client_db = db::open_client_db(&client_path, &client_config)?;
 		let restoration_db_handler = db::restoration_db_handler(&client_path, &client_config);
 
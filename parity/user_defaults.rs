@@ -34,6 +34,9 @@ pub struct UserDefaults {
 	pub pruning: Algorithm,
 	pub tracing: bool,
 	pub fat_db: bool,
+	pub log_index: bool,
+	pub sender_nonce_index: bool,
+	pub account_transactions_index: bool,
 	pub mode: Mode,
 }
 
@@ -45,6 +48,9 @@ impl Serialize for UserDefaults {
 		map.insert("pruning".into(), Value::String(self.pruning.as_str().into()));
 		map.insert("tracing".into(), Value::Bool(self.tracing));
 		map.insert("fat_db".into(), Value::Bool(self.fat_db));
+		map.insert("log_index".into(), Value::Bool(self.log_index));
+		map.insert("sender_nonce_index".into(), Value::Bool(self.sender_nonce_index));
+		map.insert("account_transactions_index".into(), Value::Bool(self.account_transactions_index));
 		let mode_str = match self.mode {
 			Mode::Off => "offline",
 			Mode::Dark(timeout) => {
@@ -89,6 +95,12 @@ impl<'a> Visitor<'a> for UserDefaultsVisitor {
 		let tracing = tracing.as_bool().ok_or_else(|| Error::custom("invalid tracing value"))?;
 		let fat_db: Value = map.remove("fat_db").unwrap_or_else(|| Value::Bool(false));
 		let fat_db = fat_db.as_bool().ok_or_else(|| Error::custom("invalid fat_db value"))?;
+		let log_index: Value = map.remove("log_index").unwrap_or_else(|| Value::Bool(false));
+		let log_index = log_index.as_bool().ok_or_else(|| Error::custom("invalid log_index value"))?;
+		let sender_nonce_index: Value = map.remove("sender_nonce_index").unwrap_or_else(|| Value::Bool(false));
+		let sender_nonce_index = sender_nonce_index.as_bool().ok_or_else(|| Error::custom("invalid sender_nonce_index value"))?;
+		let account_transactions_index: Value = map.remove("account_transactions_index").unwrap_or_else(|| Value::Bool(false));
+		let account_transactions_index = account_transactions_index.as_bool().ok_or_else(|| Error::custom("invalid account_transactions_index value"))?;
 
 		let mode: Value = map.remove("mode").unwrap_or_else(|| Value::String("active".to_owned()));
 		let mode = match mode.as_str().ok_or_else(|| Error::custom("invalid mode value"))? {
@@ -111,6 +123,9 @@ impl<'a> Visitor<'a> for UserDefaultsVisitor {
 			pruning: pruning,
 			tracing: tracing,
 			fat_db: fat_db,
+			log_index: log_index,
+			sender_nonce_index: sender_nonce_index,
+			account_transactions_index: account_transactions_index,
 			mode: mode,
 		};
 
@@ -125,6 +140,9 @@ impl Default for UserDefaults {
 			pruning: Algorithm::OverlayRecent,
 			tracing: false,
 			fat_db: false,
+			log_index: false,
+			sender_nonce_index: false,
+			account_transactions_index: false,
 			mode: Mode::Active,
 		}
 	}
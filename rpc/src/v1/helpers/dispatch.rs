@@ -38,7 +38,7 @@ use ethcore::client::BlockChainClient;
 use ethcore::miner::{self, MinerService};
 use ethcore::account_provider::AccountProvider;
 use crypto::DEFAULT_MAC;
-use transaction::{Action, SignedTransaction, PendingTransaction, Transaction};
+use transaction::{Action, SignedTransaction, PendingTransaction, Transaction, Error as TransactionError};
 
 use jsonrpc_core::{BoxFuture, Result, Error};
 use jsonrpc_core::futures::{future, Future, Poll, Async};
@@ -47,6 +47,7 @@ use v1::helpers::{errors, nonce, TransactionRequest, FilledTransactionRequest, C
 use v1::types::{
 	H256 as RpcH256, H520 as RpcH520, Bytes as RpcBytes,
 	RichRawTransaction as RpcRichRawTransaction,
+	TransactionRequest as RpcTransactionRequest,
 	ConfirmationPayload as RpcConfirmationPayload,
 	ConfirmationResponse,
 	SignRequest as RpcSignRequest,
@@ -77,6 +78,9 @@ pub trait Dispatcher: Send + Sync + Clone {
 	/// "Dispatch" a local transaction.
 	fn dispatch_transaction(&self, signed_transaction: PendingTransaction)
 		-> Result<H256>;
+
+	/// Get a local transaction which is still waiting in the queue.
+	fn queued_local_transaction(&self, hash: H256) -> Option<SignedTransaction>;
 }
 
 /// A dispatcher which uses references to a client and miner in order to sign
@@ -188,6 +192,13 @@ impl<C: miner::BlockChainClient + BlockChainClient, M: MinerService> Dispatcher
 	fn dispatch_transaction(&self, signed_transaction: PendingTransaction) -> Result<H256> {
 		Self::dispatch_transaction(&*self.client, &*self.miner, signed_transaction, true)
 	}
+
+	fn queued_local_transaction(&self, hash: H256) -> Option<SignedTransaction> {
+		match self.miner.local_transactions().remove(&hash) {
+			Some(::miner::pool::local_transactions::Status::Pending(tx)) => Some(tx.signed().clone()),
+			_ => None,
+		}
+	}
 }
 
 /// Get a recent gas price corpus.
@@ -421,6 +432,10 @@ impl Dispatcher for LightDispatcher {
 			.map_err(errors::transaction)
 			.map(|_| hash)
 	}
+
+	fn queued_local_transaction(&self, hash: H256) -> Option<SignedTransaction> {
+		self.transaction_queue.read().transaction(&hash)
+	}
 }
 
 fn sign_transaction(
@@ -766,6 +781,50 @@ pub fn default_gas_price<C, M>(client: &C, miner: &M, percentile: usize) -> U256
 	client.gas_price_corpus(100).percentile(percentile).cloned().unwrap_or_else(|| miner.sensible_gas_price())
 }
 
+/// Compose a request replacing a queued local transaction by one with the same sender and nonce.
+///
+/// Without a gas price the transaction is canceled: it's replaced by an empty transfer from
+/// the sender to itself with the lowest gas price the queue accepts as a replacement.
+/// Otherwise it's sped up: sent again with the given gas price.
+pub fn replacement_request<D: Dispatcher>(dispatcher: &D, hash: H256, gas_price: Option<U256>) -> Result<RpcTransactionRequest> {
+	let original = dispatcher.queued_local_transaction(hash)
+		.ok_or_else(errors::transaction_not_queued)?;
+
+	let min_gas_price = ::miner::pool::bump_gas_price(original.gas_price);
+	let sender = original.sender();
+
+	match gas_price {
+		None => Ok(RpcTransactionRequest {
+			from: Some(sender.into()),
+			to: Some(sender.into()),
+			gas_price: Some(min_gas_price.into()),
+			gas: Some(U256::from(21_000).into()),
+			value: Some(U256::zero().into()),
+			data: Some(Vec::new().into()),
+			nonce: Some(original.nonce.into()),
+			condition: None,
+			metadata: None,
+			metadata_limit: None,
+		}),
+		Some(gas_price) if gas_price < min_gas_price => Err(errors::transaction(TransactionError::TooCheapToReplace)),
+		Some(gas_price) => Ok(RpcTransactionRequest {
+			from: Some(sender.into()),
+			to: match original.action {
+				Action::Call(to) => Some(to.into()),
+				Action::Create => None,
+			},
+			gas_price: Some(gas_price.into()),
+			gas: Some(original.gas.into()),
+			value: Some(original.value.into()),
+			data: Some(original.data.clone().into()),
+			nonce: Some(original.nonce.into()),
+			condition: None,
+			metadata: Some(original.metadata.clone().into()),
+			metadata_limit: Some(original.metadataLimit.into()),
+		}),
+	}
+}
+
 /// Convert RPC confirmation payload to signer confirmation payload.
 /// May need to resolve in the future to fetch things like gas price.
 pub fn from_rpc<D>(payload: RpcConfirmationPayload, default_account: Address, dispatcher: &D) -> BoxFuture<ConfirmationPayload>
@@ -243,6 +243,22 @@ pub fn network_disabled() -> Error {
 	}
 }
 
+pub fn index_disabled(flag: &str) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: format!("The required index is not maintained. Run the node with {} to enable it.", flag),
+		data: None,
+	}
+}
+
+pub fn light_serving_disabled() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::UNSUPPORTED_REQUEST),
+		message: "Serving light peers is disabled.".into(),
+		data: None,
+	}
+}
+
 pub fn encryption<T: fmt::Debug>(error: T) -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::ENCRYPTION_ERROR),
@@ -361,6 +377,14 @@ pub fn transaction<T: Into<EthcoreError>>(error: T) -> Error {
 	}
 }
 
+pub fn transaction_not_queued() -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::TRANSACTION_ERROR),
+		message: "Transaction is not a local transaction waiting in the queue.".into(),
+		data: None,
+	}
+}
+
 pub fn decode<T: Into<EthcoreError>>(error: T) -> Error {
 	let error = error.into();
 	match *error.kind() {
@@ -415,6 +439,22 @@ pub fn unknown_block() -> Error {
 	}
 }
 
+pub fn logs_range_too_large(max_blocks: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: format!("Logs may be searched in at most {} blocks with pruned headers at once", max_blocks),
+		data: Some(Value::String("Narrow the block range of the filter.".into())),
+	}
+}
+
+pub fn retrace_range_too_large(max_blocks: u64) -> Error {
+	Error {
+		code: ErrorCode::ServerError(codes::REQUEST_REJECTED_LIMIT),
+		message: format!("At most {} blocks may be re-traced at once", max_blocks),
+		data: Some(Value::String("Split the block range into smaller ones.".into())),
+	}
+}
+
 pub fn no_light_peers() -> Error {
 	Error {
 		code: ErrorCode::ServerError(codes::NO_LIGHT_PEERS),
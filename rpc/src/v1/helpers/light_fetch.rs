@@ -19,6 +19,7 @@
 use std::sync::Arc;
 
 use ethcore::basic_account::BasicAccount;
+use ethcore::client::CallAnalytics;
 use ethcore::encoded;
 use ethcore::executed::{Executed, ExecutionError};
 use ethcore::ids::BlockId;
@@ -37,7 +38,7 @@ use light::on_demand::{request, OnDemand, HeaderRef, Request as OnDemandRequest,
 use light::request::Field;
 
 use sync::LightSync;
-use ethereum_types::{U256, Address};
+use ethereum_types::{U256, Address, Bloom};
 use hash::H256;
 use parking_lot::Mutex;
 use transaction::{Action, Transaction as EthTransaction, SignedTransaction, LocalizedTransaction};
@@ -47,6 +48,10 @@ use v1::types::{BlockNumber, CallRequest, Log, Transaction};
 
 const NO_INVALID_BACK_REFS: &'static str = "Fails only on invalid back-references; back-references here known to be valid; qed";
 
+/// The maximum number of blocks with pruned headers a single logs query may search.
+/// Their logs are found by fetching proved bloom groups from the network.
+const MAX_REMOTE_LOG_BLOCKS: u64 = 32 * cht::SIZE;
+
 /// Helper for fetching blockchain data either from the light client or the network
 /// as necessary.
 #[derive(Clone)]
@@ -102,6 +107,11 @@ fn extract_header(res: &[OnDemandResponse], header: HeaderRef) -> Option<encoded
 	}
 }
 
+// whether a log bloom contains any of the given blooms.
+fn bloom_matches(log_bloom: &Bloom, blooms: &[Bloom]) -> bool {
+	blooms.iter().any(|bloom| *log_bloom & *bloom == *bloom)
+}
+
 impl LightFetch {
 	// push the necessary requests onto the request chain to get the header by the given ID.
 	// yield a header reference which other requests can use.
@@ -112,6 +122,13 @@ impl LightFetch {
 
 		match id {
 			BlockId::Number(n) => {
+				// the header may have been pruned while its hash is still known.
+				if let Some(h) = self.client.block_hash(id) {
+					let idx = reqs.len();
+					reqs.push(request::HeaderByHash(h.into()).into());
+					return Ok(HeaderRef::Unresolved(idx, h.into()));
+				}
+
 				let cht_root = cht::block_to_cht_number(n).and_then(|cn| self.client.cht_root(cn as usize));
 				match cht_root {
 					None => Err(errors::unknown_block()),
@@ -171,6 +188,29 @@ impl LightFetch {
 		})
 	}
 
+	/// Helper for getting a storage value of an account at a given block.
+	pub fn storage(&self, address: Address, key: H256, id: BlockId) -> BoxFuture<H256> {
+		let mut reqs = Vec::new();
+		let header_ref = match self.make_header_requests(id, &mut reqs) {
+			Ok(r) => r,
+			Err(e) => return Box::new(future::err(e)),
+		};
+
+		reqs.push(request::Account { header: header_ref.clone(), address: address }.into());
+		let account_idx = reqs.len() - 1;
+		reqs.push(request::Storage {
+			header: header_ref,
+			storage_root: Field::back_ref(account_idx, 1),
+			address: address,
+			key: key,
+		}.into());
+
+		self.send_requests(reqs, |mut res| match res.pop() {
+			Some(OnDemandResponse::Storage(value)) => value,
+			_ => panic!("responses correspond directly with requests in amount and type; qed"),
+		})
+	}
+
 	/// Helper for getting account info at a given block.
 	/// `None` indicates the account doesn't exist at the given block.
 	pub fn account(&self, address: Address, id: BlockId) -> BoxFuture<Option<BasicAccount>> {
@@ -190,6 +230,11 @@ impl LightFetch {
 
 	/// Helper for getting proved execution.
 	pub fn proved_execution(&self, req: CallRequest, num: Trailing<BlockNumber>) -> BoxFuture<ExecutionResult> {
+		self.traced_execution(req, num, CallAnalytics::default())
+	}
+
+	/// Helper for getting proved execution, replayed locally with the given analytics.
+	pub fn traced_execution(&self, req: CallRequest, num: Trailing<BlockNumber>, analytics: CallAnalytics) -> BoxFuture<ExecutionResult> {
 		const DEFAULT_GAS_PRICE: u64 = 21_000;
 		// starting gas when gas not provided.
 		const START_GAS: u64 = 50_000;
@@ -288,6 +333,34 @@ impl LightFetch {
 				engine: client.engine().clone(),
 				on_demand: on_demand,
 				sync: sync,
+				analytics: analytics,
+			}))
+		}))
+	}
+
+	/// Helper for getting proved execution of a signed transaction on top of the state
+	/// of block `state`, in the environment of block `env`. The environment's header
+	/// must be known locally.
+	pub fn signed_execution(&self, tx: SignedTransaction, state: BlockId, env: BlockId, analytics: CallAnalytics)
+		-> BoxFuture<ExecutionResult>
+	{
+		let (sync, on_demand, client) = (self.sync.clone(), self.on_demand.clone(), self.client.clone());
+
+		Box::new(self.header(state).and_then(move |hdr| {
+			let env_info = match client.env_info(env) {
+				Some(env_info) => env_info,
+				_ => return Either::A(future::err(errors::unknown_block())),
+			};
+
+			Either::B(execute_tx(true, ExecuteParams {
+				from: tx.sender(),
+				tx: tx.as_unsigned().clone(),
+				hdr: hdr,
+				env_info: env_info,
+				engine: client.engine().clone(),
+				on_demand: on_demand,
+				sync: sync,
+				analytics: analytics,
 			}))
 		}))
 	}
@@ -326,8 +399,10 @@ impl LightFetch {
 
 	/// Get transaction logs
 	pub fn logs(&self, filter: EthcoreFilter) -> BoxFuture<Vec<Log>> {
+		use std::cmp;
 		use std::collections::BTreeMap;
 		use jsonrpc_core::futures::stream::{self, Stream};
+		use light::request::bloom_groups::MAX_COUNT as GROUPS_PER_CHT;
 
 		// early exit for "to" block before "from" block.
 		let best_number = self.client.chain_info().best_block_number;
@@ -338,39 +413,146 @@ impl LightFetch {
 			BlockId::Number(x) => Some(x),
 		};
 
-		match (block_number(filter.to_block), block_number(filter.from_block)) {
-			(Some(to), Some(from)) if to < from => return Box::new(future::ok(Vec::new())),
-			(Some(_), Some(_)) => {},
+		let (from, to) = match (block_number(filter.from_block), block_number(filter.to_block)) {
+			(Some(from), Some(to)) if to < from => return Box::new(future::ok(Vec::new())),
+			(Some(from), Some(to)) => (from, to),
 			_ => return Box::new(future::err(errors::unknown_block())),
+		};
+
+		// find all locally stored headers which match the filter.
+		let bit_combos = filter.bloom_possibilities();
+		let start_id = match filter.to_block {
+			BlockId::Number(n) if n > best_number => BlockId::Latest,
+			id => id,
+		};
+
+		let mut lowest_stored = cmp::min(to, best_number) + 1;
+		let mut local_matches = Vec::new();
+		for hdr in self.client.ancestry_iter(start_id).take_while(|hdr| hdr.number() >= from) {
+			lowest_stored = hdr.number();
+			if bloom_matches(&hdr.log_bloom(), &bit_combos) {
+				local_matches.push(hdr);
+			}
 		}
 
-		let maybe_future = self.sync.with_context(move |ctx| {
-			// find all headers which match the filter, and fetch the receipts for each one.
-			// match them with their numbers for easy sorting later.
-			let bit_combos = filter.bloom_possibilities();
-			let receipts_futures: Vec<_> = self.client.ancestry_iter(filter.to_block)
-				.take_while(|ref hdr| BlockId::Number(hdr.number()) != filter.from_block)
-				.take_while(|ref hdr| BlockId::Hash(hdr.hash()) != filter.from_block)
-				.filter(|ref hdr| {
-					let hdr_bloom = hdr.log_bloom();
-					bit_combos.iter().find(|&bloom| hdr_bloom & *bloom == *bloom).is_some()
-				})
-				.map(|hdr| (hdr.number(), request::BlockReceipts(hdr.into())))
-				.map(|(num, req)| self.on_demand.request(ctx, req).expect(NO_INVALID_BACK_REFS).map(move |x| (num, x)))
-				.collect();
+		// the headers of older blocks have been pruned. fetch those of blocks
+		// with known hashes, and search the rest by their bloom groups,
+		// each range proved against the bloom trie of the CHT covering it.
+		let (start, mut end) = (cmp::max(from, 1), lowest_stored.saturating_sub(1));
+		if end >= start && end - start + 1 > MAX_REMOTE_LOG_BLOCKS {
+			return Box::new(future::err(errors::logs_range_too_large(MAX_REMOTE_LOG_BLOCKS)));
+		}
+
+		let mut header_requests = Vec::new();
+		while end >= start {
+			match self.client.block_hash(BlockId::Number(end)) {
+				Some(hash) => header_requests.push(request::HeaderByHash(hash.into())),
+				None => break,
+			}
+			end -= 1;
+		}
+
+		let mut group_requests = Vec::new();
+		if end >= start {
+			let last_group = cht::block_to_bloom_group(end).expect("end >= start >= 1; qed");
+			let mut group = cht::block_to_bloom_group(start).expect("start >= 1; qed");
+			while group <= last_group {
+				let cht_num = cht::bloom_group_to_cht_number(group);
+				let count = cmp::min(last_group + 1, (cht_num + 1) * GROUPS_PER_CHT) - group;
+				let req = self.client.bloom_root(cht_num as usize)
+					.and_then(|root| request::BloomGroups::new(group, count, root));
+
+				match req {
+					Some(req) => group_requests.push(req),
+					None => return Box::new(future::err(errors::unknown_block())),
+				}
+
+				group += count;
+			}
+		}
+
+		let mut receipts_futures = vec![self.receipts_of(local_matches)];
+		let maybe_future = self.sync.with_context(|ctx| {
+			// fetch the receipts of the matching headers as they come in.
+			for req in header_requests {
+				let fetcher = self.clone();
+				let bit_combos = bit_combos.clone();
+				let receipts = self.on_demand.request(ctx, req)
+					.expect(NO_INVALID_BACK_REFS)
+					.map_err(errors::on_demand_cancel)
+					.and_then(move |hdr| fetcher.receipts_of(Some(hdr).into_iter()
+						.filter(|hdr| bloom_matches(&hdr.log_bloom(), &bit_combos))
+						.collect()));
+
+				receipts_futures.push(Box::new(receipts));
+			}
+
+			// fetch the headers of the blocks in the range within matching bloom groups,
+			// and then the receipts of those headers which match.
+			for req in group_requests {
+				let fetcher = self.clone();
+				let bit_combos = bit_combos.clone();
+				let first_group = req.start();
+				let receipts = self.on_demand.request(ctx, req)
+					.expect(NO_INVALID_BACK_REFS)
+					.map_err(errors::on_demand_cancel)
+					.and_then(move |blooms| {
+						let headers: Vec<_> = blooms.iter().enumerate()
+							.filter(|&(_, bloom)| bloom_matches(bloom, &bit_combos))
+							.flat_map(|(i, _)| {
+								let first_block = cht::bloom_group_start_number(first_group + i as u64);
+								first_block..first_block + cht::BLOOM_GROUP_SIZE
+							})
+							.filter(|num| *num >= start && *num <= end)
+							.map(|num| fetcher.header(BlockId::Number(num)))
+							.collect();
+
+						future::join_all(headers).and_then(move |headers| fetcher.receipts_of(headers.into_iter()
+							.filter(|hdr| bloom_matches(&hdr.log_bloom(), &bit_combos))
+							.collect()))
+					});
+
+				receipts_futures.push(Box::new(receipts));
+			}
 
 			// as the receipts come in, find logs within them which match the filter.
 			// insert them into a BTreeMap to maintain order by number and block index.
 			stream::futures_unordered(receipts_futures)
-				.fold(BTreeMap::new(), move |mut matches, (num, receipts)| {
-					for (block_index, log) in receipts.into_iter().flat_map(|r| r.logs).enumerate() {
-						if filter.matches(&log) {
-							matches.insert((num, block_index), log.into());
+				.fold(BTreeMap::new(), move |mut matches, receipts| {
+					for (num, receipts) in receipts {
+						for (block_index, log) in receipts.into_iter().flat_map(|r| r.logs).enumerate() {
+							if filter.matches(&log) {
+								matches.insert((num, block_index), log.into());
+							}
 						}
 					}
-					future::ok(matches)
+					future::ok::<_, ::jsonrpc_core::Error>(matches)
 				}) // and then collect them into a vector.
 				.map(|matches| matches.into_iter().map(|(_, v)| v).collect())
+		});
+
+		match maybe_future {
+			Some(fut) => Box::new(fut),
+			None => Box::new(future::err(errors::network_disabled())),
+		}
+	}
+
+	// fetch the receipts of the given headers, paired with their block numbers.
+	fn receipts_of(&self, headers: Vec<encoded::Header>) -> BoxFuture<Vec<(u64, Vec<Receipt>)>> {
+		use jsonrpc_core::futures::stream::{self, Stream};
+
+		if headers.is_empty() {
+			return Box::new(future::ok(Vec::new()));
+		}
+
+		let maybe_future = self.sync.with_context(|ctx| {
+			let receipts_futures: Vec<_> = headers.into_iter()
+				.map(|hdr| (hdr.number(), request::BlockReceipts(hdr.into())))
+				.map(|(num, req)| self.on_demand.request(ctx, req).expect(NO_INVALID_BACK_REFS).map(move |x| (num, x)))
+				.collect();
+
+			stream::futures_unordered(receipts_futures)
+				.collect()
 				.map_err(errors::on_demand_cancel)
 		});
 
@@ -466,6 +648,7 @@ struct ExecuteParams {
 	engine: Arc<::ethcore::engines::EthEngine>,
 	on_demand: Arc<OnDemand>,
 	sync: Arc<LightSync>,
+	analytics: CallAnalytics,
 }
 
 // has a peer execute the transaction with given params. If `gas_known` is false,
@@ -503,6 +686,7 @@ fn execute_tx(gas_known: bool, params: ExecuteParams) -> BoxFuture<ExecutionResu
 			header: params.hdr.into(),
 			env_info: params.env_info,
 			engine: params.engine,
+			analytics: params.analytics,
 		};
 
 		let on_demand = params.on_demand;
@@ -17,7 +17,7 @@
 //! Generic poll manager for Pub-Sub.
 
 use std::sync::Arc;
-use std::sync::atomic::{self, AtomicBool};
+use std::sync::atomic::{self, AtomicBool, AtomicUsize};
 use parking_lot::Mutex;
 
 use jsonrpc_core::futures::future::{self, Either};
@@ -29,22 +29,36 @@ use jsonrpc_pubsub::SubscriptionId;
 use v1::helpers::Subscribers;
 use v1::metadata::Metadata;
 
+/// Number of ticks between polls of the sync status.
+/// The status changes on every imported block, so subscribers are notified at most once per interval.
+const SYNC_STATUS_POLL_INTERVAL: usize = 5;
+
+/// Number of ticks between polls of given method.
+fn poll_interval(method: &str) -> usize {
+	match method {
+		"parity_syncStatus" => SYNC_STATUS_POLL_INTERVAL,
+		_ => 1,
+	}
+}
+
 #[derive(Debug)]
 struct Subscription {
 	metadata: Metadata,
 	method: String,
 	params: core::Params,
+	/// number of ticks between polls
+	interval: usize,
 	sink: mpsc::Sender<Result<core::Value, core::Error>>,
 	/// a flag if subscription is still active and last returned value
 	last_result: Arc<(AtomicBool, Mutex<Option<core::Output>>)>,
 }
 
 /// A struct managing all subscriptions.
-/// TODO [ToDr] Depending on the method decide on poll interval.
-/// For most of the methods it will be enough to poll on new block instead of time-interval.
+/// TODO [ToDr] For most of the methods it will be enough to poll on new block instead of time-interval.
 pub struct GenericPollManager<S: core::Middleware<Metadata>> {
 	subscribers: Subscribers<Subscription>,
 	rpc: MetaIoHandler<Metadata, S>,
+	ticks: AtomicUsize,
 }
 
 impl<S: core::Middleware<Metadata>> GenericPollManager<S> {
@@ -53,6 +67,7 @@ impl<S: core::Middleware<Metadata>> GenericPollManager<S> {
 		GenericPollManager {
 			subscribers: Default::default(),
 			rpc: rpc,
+			ticks: AtomicUsize::new(0),
 		}
 	}
 
@@ -71,6 +86,7 @@ impl<S: core::Middleware<Metadata>> GenericPollManager<S> {
 		let (sink, stream) = mpsc::channel(1);
 		let subscription = Subscription {
 			metadata,
+			interval: poll_interval(&method),
 			method,
 			params,
 			sink,
@@ -89,8 +105,13 @@ impl<S: core::Middleware<Metadata>> GenericPollManager<S> {
 
 	pub fn tick(&self) -> Box<Future<Item=(), Error=()> + Send> {
 		let mut futures = Vec::new();
-		// poll all subscriptions
+		let tick = self.ticks.fetch_add(1, atomic::Ordering::SeqCst);
+		// poll all subscriptions which are due
 		for (id, subscription) in self.subscribers.iter() {
+			if tick % subscription.interval != 0 {
+				continue;
+			}
+
 			let call = core::MethodCall {
 				jsonrpc: Some(core::Version::V2),
 				id: core::Id::Str(id.as_string()),
@@ -136,7 +157,7 @@ impl<S: core::Middleware<Metadata>> GenericPollManager<S> {
 
 #[cfg(test)]
 mod tests {
-	use std::sync::atomic::{self, AtomicBool};
+	use std::sync::atomic::{self, AtomicBool, AtomicUsize};
 
 	use jsonrpc_core::{MetaIoHandler, NoopMiddleware, Value, Params};
 	use jsonrpc_core::futures::{Future, Stream};
@@ -147,6 +168,10 @@ mod tests {
 
 	fn poll_manager() -> GenericPollManager<NoopMiddleware> {
 		let mut io = MetaIoHandler::default();
+		let status = AtomicUsize::new(0);
+		io.add_method("parity_syncStatus", move |_| {
+			Ok(Value::from(status.fetch_add(1, atomic::Ordering::SeqCst)))
+		});
 		let called = AtomicBool::new(false);
 		io.add_method("hello", move |_| {
 			if !called.load(atomic::Ordering::SeqCst) {
@@ -183,4 +208,28 @@ mod tests {
 		poll_manager.unsubscribe(&id);
 		assert_eq!(el.run(rx.into_future()).unwrap().0, None);
 	}
+
+	#[test]
+	fn should_throttle_sync_status() {
+		// given
+		let mut el = reactor::Core::new().unwrap();
+		let mut poll_manager = poll_manager();
+		let (id, rx) = poll_manager.subscribe(Default::default(), "parity_syncStatus".into(), Params::None);
+
+		// then
+		poll_manager.tick().wait().unwrap();
+		let (res, rx) = el.run(rx.into_future()).unwrap();
+		assert_eq!(res, Some(Ok(Value::from(0))));
+
+		// not polled again until the interval passes
+		for _ in 1..super::SYNC_STATUS_POLL_INTERVAL {
+			poll_manager.tick().wait().unwrap();
+		}
+		poll_manager.tick().wait().unwrap();
+		let (res, rx) = el.run(rx.into_future()).unwrap();
+		assert_eq!(res, Some(Ok(Value::from(1))));
+
+		poll_manager.unsubscribe(&id);
+		assert_eq!(el.run(rx.into_future()).unwrap().0, None);
+	}
 }
@@ -286,8 +286,9 @@ impl<T: LightChainClient + 'static> Eth for EthClient<T> {
 			.map(|acc| acc.map_or(0.into(), |a| a.balance).into()))
 	}
 
-	fn storage_at(&self, _address: RpcH160, _key: RpcU256, _num: Trailing<BlockNumber>) -> BoxFuture<RpcH256> {
-		Box::new(future::err(errors::unimplemented(None)))
+	fn storage_at(&self, address: RpcH160, pos: RpcU256, num: Trailing<BlockNumber>) -> BoxFuture<RpcH256> {
+		let position: U256 = RpcU256::into(pos);
+		Box::new(self.fetcher().storage(address.into(), position.into(), Self::num_to_id(num.unwrap_or_default())).map(Into::into))
 	}
 
 	fn block_by_hash(&self, hash: RpcH256, include_txs: bool) -> BoxFuture<Option<RichBlock>> {
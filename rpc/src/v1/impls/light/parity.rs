@@ -42,10 +42,10 @@ use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, U64, H160, H256, H512, CallRequest,
 	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
+	TransactionStats, ReceivedTransactionsStats, PackingStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
-	AccountInfo, HwAccountInfo, Header, RichHeader,
+	OperationsInfo, DappId, ChainStatus, SyncProgress, SyncStage, LightServingLoad, EpochTransition,
+	AncientBlocksStatus, AccountInfo, HwAccountInfo, Header, RichHeader,
 };
 use Host;
 
@@ -258,6 +258,10 @@ impl Parity for ParityClient {
 		Err(errors::light_unimplemented(None))
 	}
 
+	fn list_account_transactions(&self, _: H160, _: BlockNumber, _: BlockNumber, _: Option<H256>, _: u64) -> Result<Option<Vec<H256>>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -310,6 +314,21 @@ impl Parity for ParityClient {
 		)
 	}
 
+	fn received_transactions_stats(&self) -> Result<ReceivedTransactionsStats> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn light_serving_load(&self) -> Result<LightServingLoad> {
+		Err(errors::light_unimplemented(None))
+	}
+
+	fn light_epochs(&self) -> Result<Vec<EpochTransition>> {
+		Ok(self.light_dispatch.client.verify_epoch_transitions()
+			.into_iter()
+			.map(Into::into)
+			.collect())
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
 		let mut map = BTreeMap::new();
 		let chain_info = self.light_dispatch.client.chain_info();
@@ -324,11 +343,24 @@ impl Parity for ParityClient {
 			map.insert(future.hash().into(), LocalTransactionStatus::Future);
 		}
 
-		// TODO: other types?
+		for &(ref hash, ref status) in txq.old_transactions() {
+			let convert = |tx: &::transaction::SignedTransaction| Transaction::from_signed(tx.clone(), best_num, self.eip86_transition);
+			let status = match *status {
+				::light::transaction_queue::Status::Mined(ref tx) => LocalTransactionStatus::Mined(convert(tx)),
+				::light::transaction_queue::Status::Invalid(ref tx) => LocalTransactionStatus::Invalid(convert(tx)),
+				::light::transaction_queue::Status::Replaced { ref old, ref new } =>
+					LocalTransactionStatus::Replaced(convert(old), new.gas_price.into(), new.hash().into()),
+			};
+			map.insert(hash.clone().into(), status);
+		}
 
 		Ok(map)
 	}
 
+	fn packing_stats(&self) -> Result<PackingStats> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn dapps_url(&self) -> Result<String> {
 		helpers::to_url(&self.dapps_address)
 			.ok_or_else(|| errors::dapps_disabled())
@@ -382,6 +414,30 @@ impl Parity for ParityClient {
 		})
 	}
 
+	fn sync_status(&self) -> Result<SyncProgress> {
+		let sync = &self.light_dispatch.sync;
+		let chain_info = self.light_dispatch.client.chain_info();
+
+		let ancient = chain_info.ancient_block_number
+			.and_then(|ancient| chain_info.first_block_number.map(|first| AncientBlocksStatus {
+				current_block: U256::from(ancient),
+				target_block: U256::from(first),
+			}));
+
+		// light sync does not measure its import rate.
+		Ok(SyncProgress {
+			stage: if sync.is_major_importing() { SyncStage::Blocks } else { SyncStage::Idle },
+			starting_block: U256::from(sync.start_block()),
+			current_block: U256::from(chain_info.best_block_number),
+			highest_block: sync.highest_block().map(U256::from),
+			block_queue: self.light_dispatch.client.queue_info().into(),
+			snapshot: None,
+			ancient,
+			import_rate: None,
+			eta: None,
+		})
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -433,6 +489,10 @@ impl Parity for ParityClient {
 		Box::new(self.fetcher().header(id).and_then(from_encoded))
 	}
 
+	fn transaction_by_sender_and_nonce(&self, _: H160, _: U256) -> Result<Option<Transaction>> {
+		Err(errors::light_unimplemented(None))
+	}
+
 	fn ipfs_cid(&self, content: Bytes) -> Result<String> {
 		ipfs::cid(content)
 	}
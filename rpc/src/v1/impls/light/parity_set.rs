@@ -26,11 +26,11 @@ use futures_cpupool::CpuPool;
 use hash::keccak_buffer;
 
 use jsonrpc_core::{Result, BoxFuture};
-use jsonrpc_core::futures::Future;
+use jsonrpc_core::futures::{future, Future};
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, H512, U256, ReleaseInfo, Transaction, LocalDapp, BlockNumber, BannedPeer};
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<F> {
@@ -109,6 +109,19 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 		Ok(true)
 	}
 
+	fn banned_peers(&self) -> Result<Vec<BannedPeer>> {
+		Ok(self.net.banned_peers().into_iter().map(Into::into).collect())
+	}
+
+	fn unban_peer(&self, id: H512) -> Result<bool> {
+		Ok(self.net.unban_peer(id.into()))
+	}
+
+	fn clear_banned_peers(&self) -> Result<bool> {
+		self.net.clear_banned_peers();
+		Ok(true)
+	}
+
 	fn start_network(&self) -> Result<bool> {
 		self.net.start_network();
 		Ok(true)
@@ -159,4 +172,8 @@ impl<F: Fetch> ParitySet for ParitySetClient<F> {
 	fn remove_transaction(&self, _hash: H256) -> Result<Option<Transaction>> {
 		Err(errors::light_unimplemented(None))
 	}
+
+	fn retrace_blocks(&self, _from: BlockNumber, _to: BlockNumber) -> BoxFuture<U256> {
+		Box::new(future::err(errors::light_unimplemented(None)))
+	}
 }
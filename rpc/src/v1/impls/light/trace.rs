@@ -16,16 +16,54 @@
 
 //! Traces api implementation.
 
-use jsonrpc_core::Result;
+use std::sync::Arc;
+
+use ethcore::error::CallError;
+use ethcore::ids::BlockId;
+use rlp::Rlp;
+use transaction::SignedTransaction;
+
+use jsonrpc_core::{Result, BoxFuture};
+use jsonrpc_core::futures::{future, Future};
+use jsonrpc_core::futures::future::Either;
 use jsonrpc_macros::Trailing;
 use v1::Metadata;
 use v1::traits::Traces;
 use v1::helpers::errors;
+use v1::helpers::dispatch::LightDispatcher;
+use v1::helpers::light_fetch::{ExecutionResult, LightFetch};
+use v1::impls::traces::to_call_analytics;
 use v1::types::{TraceFilter, LocalizedTrace, BlockNumber, Index, CallRequest, Bytes, TraceResults, TraceOptions, H256};
 
 /// Traces api implementation.
-// TODO: all calling APIs should be possible w. proved remote TX execution.
-pub struct TracesClient;
+/// Calls are traced by replaying proved remote execution locally.
+pub struct TracesClient {
+	light_dispatch: Arc<LightDispatcher>,
+}
+
+impl TracesClient {
+	/// Create a new `TracesClient`.
+	pub fn new(light_dispatch: Arc<LightDispatcher>) -> Self {
+		TracesClient {
+			light_dispatch,
+		}
+	}
+
+	/// Create a light blockchain data fetcher.
+	fn fetcher(&self) -> LightFetch {
+		LightFetch {
+			client: self.light_dispatch.client.clone(),
+			on_demand: self.light_dispatch.on_demand.clone(),
+			sync: self.light_dispatch.sync.clone(),
+			cache: self.light_dispatch.cache.clone(),
+			gas_price_percentile: self.light_dispatch.gas_price_percentile,
+		}
+	}
+}
+
+fn to_trace_results(res: ExecutionResult) -> Result<TraceResults> {
+	res.map(TraceResults::from).map_err(errors::execution)
+}
 
 impl Traces for TracesClient {
 	type Metadata = Metadata;
@@ -46,20 +84,64 @@ impl Traces for TracesClient {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn call(&self, _meta: Self::Metadata, _request: CallRequest, _flags: TraceOptions, _block: Trailing<BlockNumber>) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn call(&self, _meta: Self::Metadata, request: CallRequest, flags: TraceOptions, block: Trailing<BlockNumber>) -> BoxFuture<TraceResults> {
+		Box::new(self.fetcher().traced_execution(request, block, to_call_analytics(flags)).and_then(to_trace_results))
 	}
 
 	fn call_many(&self, _meta: Self::Metadata, _request: Vec<(CallRequest, TraceOptions)>, _block: Trailing<BlockNumber>) -> Result<Vec<TraceResults>> {
 		Err(errors::light_unimplemented(None))
 	}
 
-	fn raw_transaction(&self, _raw_transaction: Bytes, _flags: TraceOptions, _block: Trailing<BlockNumber>) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn raw_transaction(&self, raw_transaction: Bytes, flags: TraceOptions, block: Trailing<BlockNumber>) -> BoxFuture<TraceResults> {
+		let tx = try_bf!(Rlp::new(&raw_transaction.into_vec()).as_val().map_err(|e| errors::invalid_params("Transaction is not valid RLP", e)));
+		let signed = try_bf!(SignedTransaction::new(tx).map_err(errors::transaction));
+
+		let id = match block.unwrap_or_default() {
+			BlockNumber::Num(num) => BlockId::Number(num),
+			BlockNumber::Earliest => BlockId::Earliest,
+			BlockNumber::Latest => BlockId::Latest,
+
+			BlockNumber::Pending => return Box::new(future::err(errors::invalid_params("`BlockNumber::Pending` is not supported", ()))),
+		};
+
+		Box::new(self.fetcher().signed_execution(signed, id, id, to_call_analytics(flags)).and_then(to_trace_results))
 	}
 
-	fn replay_transaction(&self, _transaction_hash: H256, _flags: TraceOptions) -> Result<TraceResults> {
-		Err(errors::light_unimplemented(None))
+	fn replay_transaction(&self, transaction_hash: H256, flags: TraceOptions) -> BoxFuture<TraceResults> {
+		let fetcher = self.fetcher();
+		let analytics = to_call_analytics(flags);
+		let eip86_transition = self.light_dispatch.client.eip86_transition();
+
+		Box::new(fetcher.transaction_by_hash(transaction_hash.into(), eip86_transition).and_then(move |tx| {
+			let (tx, index) = match tx {
+				Some(tx) => tx,
+				None => return Either::A(future::err(errors::call(CallError::TransactionNotFound))),
+			};
+
+			// light peers only prove the state at the end of a block, so the state
+			// a later transaction of the block was executed on isn't available.
+			if index != 0 {
+				return Either::A(future::err(errors::light_unimplemented(Some(
+					"Only the first transaction of a block can be replayed.".into()
+				))));
+			}
+
+			let block_hash = match tx.block_hash {
+				Some(hash) => hash.into(),
+				None => return Either::A(future::err(errors::unknown_block())),
+			};
+			let signed = match Rlp::new(&tx.raw.into_vec()).as_val().map_err(errors::rlp)
+				.and_then(|tx| SignedTransaction::new(tx).map_err(errors::transaction))
+			{
+				Ok(signed) => signed,
+				Err(e) => return Either::A(future::err(e)),
+			};
+
+			let env = BlockId::Hash(block_hash);
+			Either::B(fetcher.header(env).and_then(move |hdr| {
+				fetcher.signed_execution(signed, BlockId::Hash(hdr.parent_hash()), env, analytics)
+			}))
+		}).and_then(to_trace_results))
 	}
 
 	fn replay_block_transactions(&self,  _block_number: BlockNumber, _flags: TraceOptions) ->  Result<Vec<TraceResults>> {
@@ -16,6 +16,7 @@
 
 //! Parity-specific rpc implementation.
 use std::sync::Arc;
+use std::cmp;
 use std::str::FromStr;
 use std::collections::{BTreeMap, HashSet};
 
@@ -31,6 +32,7 @@ use ethcore::client::{BlockChainClient, StateClient, Call};
 use ethcore::ids::BlockId;
 use ethcore::miner::{self, MinerService};
 use ethcore::mode::Mode;
+use ethcore::snapshot::{SnapshotService, RestorationStatus};
 use ethcore::state::StateInfo;
 use ethcore_logger::RotatingLogger;
 use node_health::{NodeHealth, Health};
@@ -46,20 +48,25 @@ use v1::traits::Parity;
 use v1::types::{
 	Bytes, U256, U64, H160, H256, H512, CallRequest,
 	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
+	TransactionStats, ReceivedTransactionsStats, PackingStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
+	OperationsInfo, DappId, ChainStatus, SyncProgress, LightServingLoad, EpochTransition,
+	SnapshotSyncStatus, AncientBlocksStatus,
 	AccountInfo, HwAccountInfo, RichHeader,
 	block_number_to_id
 };
 use Host;
 
+/// Maximal number of transactions returned by a single `parity_listAccountTransactions` call.
+const MAX_ACCOUNT_TRANSACTIONS: u64 = 1000;
+
 /// Parity implementation.
 pub struct ParityClient<C, M, U>  {
 	client: Arc<C>,
 	miner: Arc<M>,
 	updater: Arc<U>,
 	sync: Arc<SyncProvider>,
+	snapshot: Arc<SnapshotService>,
 	net: Arc<ManageNetwork>,
 	health: NodeHealth,
 	accounts: Option<Arc<AccountProvider>>,
@@ -79,6 +86,7 @@ impl<C, M, U> ParityClient<C, M, U> where
 		client: Arc<C>,
 		miner: Arc<M>,
 		sync: Arc<SyncProvider>,
+		snapshot: Arc<SnapshotService>,
 		updater: Arc<U>,
 		net: Arc<ManageNetwork>,
 		health: NodeHealth,
@@ -94,6 +102,7 @@ impl<C, M, U> ParityClient<C, M, U> where
 			client,
 			miner,
 			sync,
+			snapshot,
 			updater,
 			net,
 			health,
@@ -307,6 +316,22 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 			.map(|a| a.into_iter().map(Into::into).collect()))
 	}
 
+	fn list_account_transactions(&self, address: H160, from_block: BlockNumber, to_block: BlockNumber, after: Option<H256>, count: u64) -> Result<Option<Vec<H256>>> {
+		if !self.client.account_transactions_index_enabled() {
+			return Err(errors::index_disabled("--account-transactions-index=on"));
+		}
+
+		// pending transactions are not mined yet.
+		let to_id = |number: BlockNumber| match number {
+			BlockNumber::Pending => BlockId::Latest,
+			num => block_number_to_id(num),
+		};
+
+		Ok(self.client
+			.list_account_transactions(&address.into(), to_id(from_block), to_id(to_block), after.map(Into::into).as_ref(), cmp::min(count, MAX_ACCOUNT_TRANSACTIONS))
+			.map(|a| a.into_iter().map(Into::into).collect()))
+	}
+
 	fn encrypt_message(&self, key: H512, phrase: Bytes) -> Result<Bytes> {
 		ecies::encrypt(&key.into(), &DEFAULT_MAC, &phrase.0)
 			.map_err(errors::encryption)
@@ -351,6 +376,20 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn received_transactions_stats(&self) -> Result<ReceivedTransactionsStats> {
+		Ok(self.sync.received_transactions_stats().into())
+	}
+
+	fn light_serving_load(&self) -> Result<LightServingLoad> {
+		self.sync.light_serving_load()
+			.map(Into::into)
+			.ok_or_else(errors::light_serving_disabled)
+	}
+
+	fn light_epochs(&self) -> Result<Vec<EpochTransition>> {
+		Err(errors::unsupported("This request is only available on light clients.", None))
+	}
+
 	fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>> {
 		// Return nothing if accounts are disabled (running as public node)
 		if self.accounts.is_none() {
@@ -366,6 +405,10 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		)
 	}
 
+	fn packing_stats(&self) -> Result<PackingStats> {
+		Ok(self.miner.packing_stats().into())
+	}
+
 	fn dapps_url(&self) -> Result<String> {
 		helpers::to_url(&self.dapps_address)
 			.ok_or_else(|| errors::dapps_disabled())
@@ -418,6 +461,52 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		})
 	}
 
+	fn sync_status(&self) -> Result<SyncProgress> {
+		let status = self.sync.status();
+		let chain_info = self.client.chain_info();
+
+		let restoration = match self.snapshot.status() {
+			RestorationStatus::Ongoing { state_chunks, block_chunks, state_chunks_done, block_chunks_done } =>
+				Some(((state_chunks + block_chunks) as usize, (state_chunks_done + block_chunks_done) as usize)),
+			_ => None,
+		};
+		let snapshot = match restoration {
+			Some((total, restored)) => Some(SnapshotSyncStatus {
+				chunks_total: cmp::max(total, status.num_snapshot_chunks),
+				chunks_downloaded: status.snapshot_chunks_done,
+				chunks_restored: restored,
+			}),
+			None if status.is_snapshot_syncing() => Some(SnapshotSyncStatus {
+				chunks_total: status.num_snapshot_chunks,
+				chunks_downloaded: status.snapshot_chunks_done,
+				chunks_restored: 0,
+			}),
+			None => None,
+		};
+
+		let ancient = chain_info.ancient_block_number
+			.and_then(|ancient| chain_info.first_block_number.map(|first| AncientBlocksStatus {
+				current_block: U256::from(ancient),
+				target_block: U256::from(first),
+			}));
+
+		let current = chain_info.best_block_number;
+		let highest = status.highest_block_number;
+		let mut progress = SyncProgress {
+			stage: status.state.into(),
+			starting_block: U256::from(status.start_block_number),
+			current_block: U256::from(current),
+			highest_block: highest.map(U256::from),
+			block_queue: self.client.queue_info().into(),
+			snapshot,
+			ancient,
+			import_rate: None,
+			eta: None,
+		};
+		progress.set_import_rate(current, highest, status.import_rate);
+		Ok(progress)
+	}
+
 	fn node_kind(&self) -> Result<::v1::types::NodeKind> {
 		use ::v1::types::{NodeKind, Availability, Capability};
 
@@ -461,6 +550,15 @@ impl<C, M, U, S> Parity for ParityClient<C, M, U> where
 		}))
 	}
 
+	fn transaction_by_sender_and_nonce(&self, sender: H160, nonce: U256) -> Result<Option<Transaction>> {
+		if !self.client.sender_nonce_index_enabled() {
+			return Err(errors::index_disabled("--sender-nonce-index=on"));
+		}
+
+		Ok(self.client.transaction_by_sender_and_nonce(&sender.into(), &nonce.into())
+			.map(|t| Transaction::from_localized(t, self.eip86_transition)))
+	}
+
 	fn ipfs_cid(&self, content: Bytes) -> Result<String> {
 		ipfs::cid(content)
 	}
@@ -18,7 +18,7 @@
 use std::io;
 use std::sync::Arc;
 
-use ethcore::client::BlockChainClient;
+use ethcore::client::{BlockChainClient, BlockId};
 use ethcore::miner::MinerService;
 use ethcore::mode::Mode;
 use sync::ManageNetwork;
@@ -28,11 +28,14 @@ use hash::keccak_buffer;
 use updater::{Service as UpdateService};
 
 use jsonrpc_core::{BoxFuture, Result};
-use jsonrpc_core::futures::Future;
+use jsonrpc_core::futures::{future, Future};
 use v1::helpers::dapps::DappsService;
 use v1::helpers::errors;
 use v1::traits::ParitySet;
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, H512, U256, ReleaseInfo, Transaction, LocalDapp, BlockNumber, BannedPeer, block_number_to_id};
+
+/// Maximal number of blocks re-traced by a single `parity_retraceBlocks` call.
+const MAX_RETRACE_BLOCKS: u64 = 1000;
 
 /// Parity-specific rpc interface for operations altering the settings.
 pub struct ParitySetClient<C, M, U, F = fetch::Client> {
@@ -147,6 +150,19 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		Ok(true)
 	}
 
+	fn banned_peers(&self) -> Result<Vec<BannedPeer>> {
+		Ok(self.net.banned_peers().into_iter().map(Into::into).collect())
+	}
+
+	fn unban_peer(&self, id: H512) -> Result<bool> {
+		Ok(self.net.unban_peer(id.into()))
+	}
+
+	fn clear_banned_peers(&self) -> Result<bool> {
+		self.net.clear_banned_peers();
+		Ok(true)
+	}
+
 	fn start_network(&self) -> Result<bool> {
 		self.net.start_network();
 		Ok(true)
@@ -210,4 +226,27 @@ impl<C, M, U, F> ParitySet for ParitySetClient<C, M, U, F> where
 		   .map(|t| Transaction::from_pending(t.pending().clone(), block_number + 1, self.eip86_transition))
 		)
 	}
+
+	fn retrace_blocks(&self, from: BlockNumber, to: BlockNumber) -> BoxFuture<U256> {
+		let (from, to) = match (from, to) {
+			(BlockNumber::Pending, _) | (_, BlockNumber::Pending) =>
+				return Box::new(future::err(errors::invalid_params("`BlockNumber::Pending` is not supported", ()))),
+			(from, to) => (block_number_to_id(from), block_number_to_id(to)),
+		};
+		let (from, to) = match (self.client.block_number(from), self.client.block_number(to)) {
+			(Some(from), Some(to)) => (from, to),
+			_ => return Box::new(future::err(errors::unknown_block())),
+		};
+		if to.saturating_sub(from) >= MAX_RETRACE_BLOCKS {
+			return Box::new(future::err(errors::retrace_range_too_large(MAX_RETRACE_BLOCKS)));
+		}
+
+		// blocks are re-executed in the background, to keep the RPC threads responsive.
+		let client = self.client.clone();
+		Box::new(self.pool.spawn_fn(move || {
+			client.retrace_blocks(BlockId::Number(from), BlockId::Number(to))
+				.map(Into::into)
+				.map_err(errors::database)
+		}))
+	}
 }
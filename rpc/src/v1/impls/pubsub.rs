@@ -79,6 +79,8 @@ impl<S: core::Middleware<Metadata>> PubSub for PubSubClient<S> {
 		let params = params.unwrap_or(core::Params::Array(vec![]));
 		// Make sure to get rid of PubSub session otherwise it will never be dropped.
 		meta.session = None;
+		// `syncStatus` is a throttled stream of the detailed sync progress.
+		let method = if method == "syncStatus" { "parity_syncStatus".into() } else { method };
 
 		let mut poll_manager = self.poll_manager.write();
 		let (id, receiver) = poll_manager.subscribe(meta, method, params);
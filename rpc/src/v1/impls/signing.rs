@@ -204,6 +204,16 @@ impl<D: Dispatcher + 'static> ParitySigning for SigningQueueClient<D> {
 			}
 		}))
 	}
+
+	fn cancel_transaction(&self, meta: Metadata, hash: RpcH256) -> BoxFuture<RpcH256> {
+		let request = try_bf!(dispatch::replacement_request(&self.dispatcher, hash.into(), None));
+		EthSigning::send_transaction(self, meta, request)
+	}
+
+	fn speed_up_transaction(&self, meta: Metadata, hash: RpcH256, gas_price: RpcU256) -> BoxFuture<RpcH256> {
+		let request = try_bf!(dispatch::replacement_request(&self.dispatcher, hash.into(), Some(gas_price.into())));
+		EthSigning::send_transaction(self, meta, request)
+	}
 }
 
 impl<D: Dispatcher + 'static> EthSigning for SigningQueueClient<D> {
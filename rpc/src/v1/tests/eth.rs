@@ -18,7 +18,7 @@
 use std::env;
 use std::sync::Arc;
 
-use ethereum_types::{H256, Address};
+use ethereum_types::{H256, U256, Address};
 use ethcore::account_provider::AccountProvider;
 use ethcore::block::Block;
 use ethcore::client::{BlockChainClient, Client, ClientConfig, ChainInfo, ImportBlock};
@@ -27,21 +27,28 @@ use ethcore::ids::BlockId;
 use ethcore::miner::Miner;
 use ethcore::spec::{Genesis, Spec};
 use ethcore::views::BlockView;
+use ethcore_logger::RotatingLogger;
 use ethjson::blockchain::BlockChain;
 use ethjson::state::test::ForkSpec;
 use io::IoChannel;
 use kvdb_memorydb;
 use miner::external::ExternalMiner;
+use node_health::{self, NodeHealth};
+use parity_reactor;
 use parking_lot::Mutex;
+use transaction::SignedTransaction;
 
 use jsonrpc_core::IoHandler;
+use v1::helpers::NetworkSettings;
 use v1::helpers::dispatch::FullDispatcher;
 use v1::helpers::nonce;
-use v1::impls::{EthClient, SigningUnsafeClient};
+use v1::impls::{EthClient, ParityClient, SigningUnsafeClient};
 use v1::metadata::Metadata;
-use v1::tests::helpers::{TestSnapshotService, TestSyncProvider, Config};
+use v1::tests::helpers::{TestSnapshotService, TestSyncProvider, TestUpdater, Config};
+use v1::tests::mocked::manage_network::TestManageNetwork;
 use v1::traits::eth::Eth;
 use v1::traits::eth_signing::EthSigning;
+use v1::traits::parity::Parity;
 use v1::types::U256 as NU256;
 
 fn account_provider() -> Arc<AccountProvider> {
@@ -73,17 +80,28 @@ fn make_spec(chain: &BlockChain) -> Spec {
 	spec
 }
 
+#[derive(Debug)]
+struct NoSync;
+impl node_health::SyncStatus for NoSync {
+	fn is_major_importing(&self) -> bool { false }
+	fn peers(&self) -> (usize, usize) { (0, 25) }
+}
+
 struct EthTester {
 	client: Arc<Client>,
-	_miner: Arc<Miner>,
-	_snapshot: Arc<TestSnapshotService>,
+	miner: Arc<Miner>,
+	snapshot: Arc<TestSnapshotService>,
 	accounts: Arc<AccountProvider>,
 	handler: IoHandler<Metadata>,
 }
 
 impl EthTester {
 	fn from_chain(chain: &BlockChain) -> Self {
-		let tester = Self::from_spec(make_spec(chain));
+		Self::from_chain_with_config(chain, ClientConfig::default())
+	}
+
+	fn from_chain_with_config(chain: &BlockChain, config: ClientConfig) -> Self {
+		let tester = Self::from_spec_with_config(make_spec(chain), config);
 
 		for b in &chain.blocks_rlp() {
 			if Block::is_good(&b) {
@@ -100,13 +118,17 @@ impl EthTester {
 	}
 
 	fn from_spec(spec: Spec) -> Self {
+		Self::from_spec_with_config(spec, ClientConfig::default())
+	}
+
+	fn from_spec_with_config(spec: Spec, config: ClientConfig) -> Self {
 		let account_provider = account_provider();
 		let opt_account_provider = Some(account_provider.clone());
 		let miner_service = miner_service(&spec, account_provider.clone());
 		let snapshot_service = snapshot_service();
 
 		let client = Client::new(
-			ClientConfig::default(),
+			config,
 			&spec,
 			Arc::new(kvdb_memorydb::create(::ethcore::db::NUM_COLUMNS.unwrap_or(0))),
 			miner_service.clone(),
@@ -138,13 +160,49 @@ impl EthTester {
 		handler.extend_with(eth_sign.to_delegate());
 
 		EthTester {
-			_miner: miner_service,
-			_snapshot: snapshot_service,
+			miner: miner_service,
+			snapshot: snapshot_service,
 			client: client,
 			accounts: account_provider,
 			handler: handler,
 		}
 	}
+
+	// serve the `parity` RPC APIs from the same client as well.
+	fn with_parity(mut self) -> Self {
+		let health = NodeHealth::new(
+			Arc::new(NoSync),
+			node_health::TimeChecker::new::<String>(&[], node_health::CpuPool::new(1)),
+			parity_reactor::Remote::new_sync(),
+		);
+		let settings = Arc::new(NetworkSettings {
+			name: "tester".to_owned(),
+			chain: "testchain".to_owned(),
+			network_port: 30303,
+			rpc_enabled: true,
+			rpc_interface: "all".to_owned(),
+			rpc_port: 8545,
+		});
+
+		let parity_client = ParityClient::new(
+			self.client.clone(),
+			self.miner.clone(),
+			sync_provider(),
+			self.snapshot.clone(),
+			Arc::new(TestUpdater::default()),
+			Arc::new(TestManageNetwork),
+			health,
+			Some(self.accounts.clone()),
+			Arc::new(RotatingLogger::new("rpc=trace".to_owned())),
+			settings,
+			None,
+			None,
+			None,
+		);
+
+		self.handler.extend_with(parity_client.to_delegate());
+		self
+	}
 }
 
 #[test]
@@ -461,3 +519,132 @@ fn starting_nonce_test() {
 register_test!(eth_transaction_count_1, verify_transaction_counts, "BlockchainTests/bcWalletTest/wallet2outOf3txs");
 register_test!(eth_transaction_count_2, verify_transaction_counts, "BlockchainTests/bcTotalDifficultyTest/sideChainWithMoreTransactions");
 register_test!(eth_transaction_count_3, verify_transaction_counts, "BlockchainTests/bcGasPricerTest/RPC_API_Test");
+
+fn verify_transactions_by_sender_and_nonce(name: String, chain: BlockChain) {
+	struct PanicHandler(String);
+	impl Drop for PanicHandler {
+		fn drop(&mut self) {
+			if ::std::thread::panicking() {
+				println!("Test failed: {}", self.0);
+			}
+		}
+	}
+
+	let _panic = PanicHandler(name);
+
+	fn by_sender_and_nonce(tester: &EthTester, sender: Address, nonce: U256) -> ::serde_json::Value {
+		let req = r#"{
+			"jsonrpc": "2.0",
+			"method": "parity_getTransactionBySenderAndNonce",
+			"params": [""#.to_owned() + format!("0x{:x}", sender).as_ref() + r#"", "#
+				+ &::serde_json::to_string(&NU256::from(nonce)).unwrap() + r#"],
+			"id": 1
+		}"#;
+
+		let res = tester.handler.handle_request_sync(&req).unwrap();
+		let res: ::serde_json::Value = ::serde_json::from_str(&res).unwrap();
+		res["result"].clone()
+	}
+
+	let mut config = ClientConfig::default();
+	config.blockchain.sender_nonce_index = true;
+	let tester = EthTester::from_chain_with_config(&chain, config).with_parity();
+
+	let mut found = 0;
+	for b in chain.blocks_rlp().iter().filter(|b| Block::is_good(b)).map(|b| view!(BlockView, b)) {
+		let number = b.header_view().number();
+
+		// only transactions of canonical blocks are indexed.
+		if tester.client.block_hash(BlockId::Number(number)) != Some(b.hash()) { continue }
+
+		for tx in b.transactions() {
+			let tx = SignedTransaction::new(tx).unwrap();
+			let res = by_sender_and_nonce(&tester, tx.sender(), tx.nonce);
+
+			assert_eq!(res["hash"], ::serde_json::Value::String(format!("0x{:x}", tx.hash())));
+			assert_eq!(res["blockNumber"], ::serde_json::to_value(NU256::from(number)).unwrap());
+
+			found += 1;
+		}
+	}
+
+	assert!(found > 0);
+	assert!(by_sender_and_nonce(&tester, Address::from(10), 0.into()).is_null());
+}
+
+register_test!(parity_transaction_by_sender_and_nonce_1, verify_transactions_by_sender_and_nonce, "BlockchainTests/bcWalletTest/wallet2outOf3txs");
+register_test!(parity_transaction_by_sender_and_nonce_2, verify_transactions_by_sender_and_nonce, "BlockchainTests/bcTotalDifficultyTest/sideChainWithMoreTransactions");
+
+fn verify_account_transactions(name: String, chain: BlockChain) {
+	use std::collections::BTreeMap;
+	use transaction::Action;
+
+	struct PanicHandler(String);
+	impl Drop for PanicHandler {
+		fn drop(&mut self) {
+			if ::std::thread::panicking() {
+				println!("Test failed: {}", self.0);
+			}
+		}
+	}
+
+	let _panic = PanicHandler(name);
+
+	fn list(tester: &EthTester, account: Address, after: Option<H256>, count: u64) -> ::serde_json::Value {
+		let after = after.map_or("null".to_owned(), |hash| format!("\"0x{:x}\"", hash));
+		let req = r#"{
+			"jsonrpc": "2.0",
+			"method": "parity_listAccountTransactions",
+			"params": [""#.to_owned() + format!("0x{:x}", account).as_ref() + r#"", "earliest", "latest", "#
+				+ &after + ", " + &count.to_string() + r#"],
+			"id": 1
+		}"#;
+
+		let res = tester.handler.handle_request_sync(&req).unwrap();
+		let res: ::serde_json::Value = ::serde_json::from_str(&res).unwrap();
+		res["result"].clone()
+	}
+
+	fn hashes(hashes: &[H256]) -> ::serde_json::Value {
+		::serde_json::Value::Array(hashes.iter().map(|hash| ::serde_json::Value::String(format!("0x{:x}", hash))).collect())
+	}
+
+	let mut config = ClientConfig::default();
+	config.blockchain.account_transactions_index = true;
+	let tester = EthTester::from_chain_with_config(&chain, config).with_parity();
+
+	// transactions of canonical blocks by the accounts sending or receiving them, in chain order.
+	let mut canonical: Vec<_> = chain.blocks_rlp().iter()
+		.filter(|b| Block::is_good(b))
+		.map(|b| view!(BlockView, b))
+		.filter(|b| tester.client.block_hash(BlockId::Number(b.header_view().number())) == Some(b.hash()))
+		.map(|b| (b.header_view().number(), b.transactions()))
+		.collect();
+	canonical.sort_by_key(|&(number, _)| number);
+
+	let mut expected: BTreeMap<Address, Vec<H256>> = BTreeMap::new();
+	for tx in canonical.into_iter().flat_map(|(_, transactions)| transactions) {
+		let tx = SignedTransaction::new(tx).unwrap();
+		expected.entry(tx.sender()).or_insert_with(Vec::new).push(tx.hash());
+		if let Action::Call(to) = tx.action {
+			if to != tx.sender() {
+				expected.entry(to).or_insert_with(Vec::new).push(tx.hash());
+			}
+		}
+	}
+
+	assert!(!expected.is_empty());
+	for (account, transactions) in expected {
+		assert_eq!(list(&tester, account, None, 1000), hashes(&transactions));
+
+		// page through the transactions one by one.
+		for (i, hash) in transactions.iter().enumerate().skip(1) {
+			assert_eq!(list(&tester, account, Some(transactions[i - 1]), 1), hashes(&[*hash]));
+		}
+	}
+
+	assert_eq!(list(&tester, Address::from(10), None, 1000), hashes(&[]));
+}
+
+register_test!(parity_list_account_transactions_1, verify_account_transactions, "BlockchainTests/bcWalletTest/wallet2outOf3txs");
+register_test!(parity_list_account_transactions_2, verify_account_transactions, "BlockchainTests/bcTotalDifficultyTest/sideChainWithMoreTransactions");
@@ -31,7 +31,7 @@ use ethcore::miner::{self, MinerService, AuthoringParams};
 use ethcore::receipt::{Receipt, RichReceipt};
 use ethereum_types::{H256, U256, Address};
 use miner::pool::local_transactions::Status as LocalTransactionStatus;
-use miner::pool::{verifier, VerifiedTransaction, QueueStatus};
+use miner::pool::{verifier, packing, VerifiedTransaction, QueueStatus};
 use parking_lot::{RwLock, Mutex};
 use transaction::{self, UnverifiedTransaction, SignedTransaction, PendingTransaction};
 use txpool;
@@ -279,6 +279,15 @@ impl MinerService for TestMinerService {
 		}
 	}
 
+	fn packing_stats(&self) -> packing::PackingStats {
+		packing::PackingStats {
+			blocks: 10,
+			improved_blocks: 3,
+			estimated_fees: 1_000.into(),
+			estimated_simple_fees: 900.into(),
+		}
+	}
+
 	/// Submit `seal` as a valid solution for the header of `pow_hash`.
 	/// Will check the seal, but not actually insert the block into the chain.
 	fn submit_seal(&self, _pow_hash: H256, _seal: Vec<Bytes>) -> Result<SealedBlock, Error> {
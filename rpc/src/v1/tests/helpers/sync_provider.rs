@@ -17,9 +17,11 @@
 //! Test implementation of SyncProvider.
 
 use std::collections::BTreeMap;
+use std::time::Duration;
 use ethereum_types::H256;
+use light::net::{ServingBudget, ServingLoad, PeerServingLoad};
 use parking_lot::RwLock;
-use sync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats};
+use sync::{SyncProvider, EthProtocolInfo, SyncStatus, SyncState, PeerInfo, TransactionStats, ReceivedTransactionsStats, SessionTraffic};
 
 /// TestSyncProvider config.
 pub struct Config {
@@ -54,6 +56,7 @@ impl TestSyncProvider {
 				num_snapshot_chunks: 0,
 				snapshot_chunks_done: 0,
 				last_imported_old_block_number: None,
+				import_rate: None,
 			}),
 		}
 	}
@@ -79,10 +82,17 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/62".to_owned(), "eth/63".to_owned()],
 				remote_address: "127.0.0.1:7777".to_owned(),
 				local_address: "127.0.0.1:8888".to_owned(),
+				traffic: SessionTraffic {
+					raw_sent: 1000,
+					compressed_sent: 400,
+					raw_received: 2000,
+					compressed_received: 500,
+				},
 				eth_info: Some(EthProtocolInfo {
 					version: 62,
 					difficulty: Some(40.into()),
 					head: 50.into(),
+					throughput: Some(12.5),
 				}),
 				pip_info: None,
 			},
@@ -92,10 +102,12 @@ impl SyncProvider for TestSyncProvider {
 				capabilities: vec!["eth/63".to_owned(), "eth/64".to_owned()],
 				remote_address: "Handshake".to_owned(),
 				local_address: "127.0.0.1:3333".to_owned(),
+				traffic: SessionTraffic::default(),
 				eth_info: Some(EthProtocolInfo {
 					version: 64,
 					difficulty: None,
-					head: 60.into()
+					head: 60.into(),
+					throughput: None,
 				}),
 				pip_info: None,
 			}
@@ -122,5 +134,38 @@ impl SyncProvider for TestSyncProvider {
 			}
 		]
 	}
+
+	fn received_transactions_stats(&self) -> ReceivedTransactionsStats {
+		ReceivedTransactionsStats {
+			received: 10,
+			duplicates: 4,
+			rejected: 1,
+			announced: 20,
+			requested: 5,
+		}
+	}
+
+	fn light_serving_load(&self) -> Option<ServingLoad> {
+		Some(ServingLoad {
+			time: Duration::from_micros(1500),
+			bytes: 2048,
+			total_budget: ServingBudget {
+				time: Some(Duration::from_millis(100)),
+				bytes: None,
+			},
+			peer_budget: ServingBudget::default(),
+			peers: vec![
+				PeerServingLoad {
+					peer: 1,
+					node_id: Some(2.into()),
+					reserved: true,
+					time: Duration::from_micros(500),
+					bytes: 1024,
+					credits: 100.into(),
+					credits_limit: 1000.into(),
+				}
+			],
+		})
+	}
 }
 
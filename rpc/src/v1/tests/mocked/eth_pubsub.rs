@@ -25,6 +25,7 @@ use std::time::Duration;
 use v1::{EthPubSub, EthPubSubClient, Metadata};
 
 use ethcore::client::{TestBlockChainClient, EachBlockWith, ChainNotify, ChainRoute, ChainRouteType};
+use miner::pool::TransactionEvent;
 use parity_reactor::EventLoop;
 
 const DURATION_ZERO: Duration = Duration::from_millis(0);
@@ -202,6 +203,56 @@ fn should_subscribe_to_pending_transactions() {
 	assert_eq!(res, None);
 }
 
+#[test]
+fn should_subscribe_to_transaction_events() {
+	// given
+	let el = EventLoop::spawn();
+	let client = TestBlockChainClient::new();
+
+	let pubsub = EthPubSubClient::new_test(Arc::new(client), el.remote());
+	let handler = pubsub.handler().upgrade().unwrap();
+	let pubsub = pubsub.to_delegate();
+
+	let mut io = MetaIoHandler::default();
+	io.extend_with(pubsub);
+
+	let mut metadata = Metadata::default();
+	let (sender, receiver) = futures::sync::mpsc::channel(8);
+	metadata.session = Some(Arc::new(Session::new(sender)));
+
+	// Fail if params are provided
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["transactionEvents", {}], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: transactionEvents","data":"\"Expected no parameters.\""},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Subscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_subscribe", "params": ["transactionEvents"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x416d77337e24399d","id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata.clone()), Some(response.to_owned()));
+
+	// Send transaction events
+	handler.notify_transaction_events(&[
+		TransactionEvent::Replaced { old: 5.into(), new: 7.into() },
+		TransactionEvent::Mined(7.into()),
+	]);
+
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","replacedBy":"0x0000000000000000000000000000000000000000000000000000000000000007","type":"replaced"},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	let (res, receiver) = receiver.into_future().wait().unwrap();
+	let response = r#"{"jsonrpc":"2.0","method":"eth_subscription","params":{"result":{"hash":"0x0000000000000000000000000000000000000000000000000000000000000007","replacedBy":null,"type":"mined"},"subscription":"0x416d77337e24399d"}}"#;
+	assert_eq!(res, Some(response.into()));
+
+	// And unsubscribe
+	let request = r#"{"jsonrpc": "2.0", "method": "eth_unsubscribe", "params": ["0x416d77337e24399d"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request, metadata), Some(response.to_owned()));
+
+	let (res, _receiver) = receiver.into_future().wait().unwrap();
+	assert_eq!(res, None);
+}
+
 #[test]
 fn should_return_unimplemented() {
 	// given
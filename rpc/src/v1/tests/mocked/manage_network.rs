@@ -14,7 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
-use sync::{ManageNetwork, NetworkConfiguration};
+use std::time::{Duration, UNIX_EPOCH};
+use ethereum_types::H512;
+use sync::{ManageNetwork, NetworkConfiguration, BannedPeerInfo};
 use self::ethcore_network::{ProtocolId, NetworkContext};
 
 extern crate ethcore_network;
@@ -30,5 +32,10 @@ impl ManageNetwork for TestManageNetwork {
 	fn start_network(&self) {}
 	fn stop_network(&self) {}
 	fn network_config(&self) -> NetworkConfiguration { NetworkConfiguration::new_local() }
+	fn banned_peers(&self) -> Vec<BannedPeerInfo> {
+		vec![BannedPeerInfo { id: 1.into(), until: UNIX_EPOCH + Duration::from_secs(1_540_000_000) }]
+	}
+	fn unban_peer(&self, id: H512) -> bool { id == 1.into() }
+	fn clear_banned_peers(&self) {}
 	fn with_proto_context(&self, _: ProtocolId, _: &mut FnMut(&NetworkContext)) { }
 }
@@ -19,7 +19,7 @@
 
 mod eth;
 mod eth_pubsub;
-mod manage_network;
+pub mod manage_network;
 mod net;
 mod parity;
 mod parity_accounts;
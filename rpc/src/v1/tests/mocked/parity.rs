@@ -29,7 +29,7 @@ use jsonrpc_core::IoHandler;
 use v1::{Parity, ParityClient};
 use v1::metadata::Metadata;
 use v1::helpers::{SignerService, NetworkSettings};
-use v1::tests::helpers::{TestSyncProvider, Config, TestMinerService, TestUpdater};
+use v1::tests::helpers::{TestSyncProvider, TestSnapshotService, Config, TestMinerService, TestUpdater};
 use super::manage_network::TestManageNetwork;
 use Host;
 
@@ -39,6 +39,7 @@ pub struct Dependencies {
 	pub miner: Arc<TestMinerService>,
 	pub client: Arc<TestBlockChainClient>,
 	pub sync: Arc<TestSyncProvider>,
+	pub snapshot: Arc<TestSnapshotService>,
 	pub updater: Arc<TestUpdater>,
 	pub health: NodeHealth,
 	pub logger: Arc<RotatingLogger>,
@@ -58,6 +59,7 @@ impl Dependencies {
 				network_id: 3,
 				num_peers: 120,
 			})),
+			snapshot: Arc::new(TestSnapshotService::new()),
 			health: NodeHealth::new(
 				Arc::new(FakeSync),
 				node_health::TimeChecker::new::<String>(&[], node_health::CpuPool::new(1)),
@@ -87,6 +89,7 @@ impl Dependencies {
 			self.client.clone(),
 			self.miner.clone(),
 			self.sync.clone(),
+			self.snapshot.clone(),
 			self.updater.clone(),
 			self.network.clone(),
 			self.health.clone(),
@@ -332,7 +335,7 @@ fn rpc_parity_net_peers() {
 	let io = deps.default_client();
 
 	let request = r#"{"jsonrpc": "2.0", "method": "parity_netPeers", "params":[], "id": 1}"#;
-	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777"},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake"},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","version":64},"pip":null}}]},"id":1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"active":0,"connected":120,"max":50,"peers":[{"caps":["eth/62","eth/63"],"id":"node1","name":"Parity/1","network":{"localAddress":"127.0.0.1:8888","remoteAddress":"127.0.0.1:7777","traffic":{"compressedBytesReceived":500,"compressedBytesSent":400,"rawBytesReceived":2000,"rawBytesSent":1000}},"protocols":{"eth":{"difficulty":"0x28","head":"0000000000000000000000000000000000000000000000000000000000000032","throughput":12.5,"version":62},"pip":null}},{"caps":["eth/63","eth/64"],"id":null,"name":"Parity/2","network":{"localAddress":"127.0.0.1:3333","remoteAddress":"Handshake","traffic":{"compressedBytesReceived":0,"compressedBytesSent":0,"rawBytesReceived":0,"rawBytesSent":0}},"protocols":{"eth":{"difficulty":null,"head":"000000000000000000000000000000000000000000000000000000000000003c","throughput":null,"version":64},"pip":null}}]},"id":1}"#;
 
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
@@ -473,6 +476,44 @@ fn rpc_parity_next_nonce() {
 	assert_eq!(io2.handle_request_sync(&request), Some(response2.to_owned()));
 }
 
+#[test]
+fn rpc_parity_transaction_by_sender_and_nonce_without_index() {
+	use std::sync::atomic::Ordering;
+
+	let deps = Dependencies::new();
+	deps.client.sender_nonce_index.store(false, Ordering::Relaxed);
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_getTransactionBySenderAndNonce",
+		"params": ["0x0000000000000000000000000000000000000005", "0x1"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The required index is not maintained. Run the node with --sender-nonce-index=on to enable it."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_list_account_transactions_without_index() {
+	use std::sync::atomic::Ordering;
+
+	let deps = Dependencies::new();
+	deps.client.account_transactions_index.store(false, Ordering::Relaxed);
+	let io = deps.default_client();
+
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_listAccountTransactions",
+		"params": ["0x0000000000000000000000000000000000000005", "earliest", "latest", null, 100],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"The required index is not maintained. Run the node with --account-transactions-index=on to enable it."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_transactions_stats() {
 	let deps = Dependencies::new();
@@ -484,6 +525,50 @@ fn rpc_parity_transactions_stats() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_received_transactions_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_receivedTransactionsStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"announced":20,"duplicates":4,"received":10,"rejected":1,"requested":5},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_packing_stats() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_packingStats", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"blocks":10,"estimatedFees":"0x3e8","estimatedSimpleFees":"0x384","improvedBlocks":3},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_light_serving_load() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_lightServingLoad", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"budget":{"bytes":null,"serveTime":100000},"bytes":2048,"peerBudget":{"bytes":null,"serveTime":null},"peers":[{"bytes":1024,"credits":"0x64","creditsLimit":"0x3e8","id":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000002","reserved":true,"serveTime":500}],"serveTime":1500},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
+#[test]
+fn rpc_parity_light_epochs() {
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_lightEpochs", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32000,"message":"This request is only available on light clients."},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_local_transactions() {
 	let deps = Dependencies::new();
@@ -520,6 +605,37 @@ fn rpc_parity_chain_status() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_sync_status() {
+	use ethcore::snapshot::RestorationStatus;
+	use sync::SyncState;
+
+	let deps = Dependencies::new();
+	let io = deps.default_client();
+
+	{
+		let mut status = deps.sync.status.write();
+		status.state = SyncState::SnapshotWaiting;
+		status.highest_block_number = Some(1000);
+		status.num_snapshot_chunks = 50;
+		status.snapshot_chunks_done = 30;
+		status.import_rate = Some(12.5);
+	}
+	deps.snapshot.set_status(RestorationStatus::Ongoing {
+		state_chunks: 40,
+		block_chunks: 10,
+		state_chunks_done: 20,
+		block_chunks_done: 5,
+	});
+	*deps.client.ancient_block.write() = Some((H256::default(), 5));
+	*deps.client.first_block.write() = Some((H256::from(U256::from(1234)), 3333));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_syncStatus", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":{"ancient":{"currentBlock":"0x5","targetBlock":"0xd05"},"blockQueue":{"max":0,"unverified":0,"verified":0,"verifying":0},"currentBlock":"0x0","eta":80,"highestBlock":"0x3e8","importRate":12.5,"snapshot":{"chunksDownloaded":30,"chunksRestored":25,"chunksTotal":50},"stage":"snapshotWaiting","startingBlock":"0x0"},"id":1}"#;
+
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_node_kind() {
 	let deps = Dependencies::new();
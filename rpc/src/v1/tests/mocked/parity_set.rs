@@ -20,7 +20,7 @@ use rustc_hex::FromHex;
 use ethereum_types::{U256, Address};
 
 use ethcore::miner::MinerService;
-use ethcore::client::TestBlockChainClient;
+use ethcore::client::{TestBlockChainClient, EachBlockWith};
 use sync::ManageNetwork;
 use futures_cpupool::CpuPool;
 
@@ -197,6 +197,28 @@ fn rpc_parity_set_transactions_limit() {
 	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_banned_peers() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_bannedPeers", "params":[], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":[{"id":"0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001","until":1540000000}],"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unbanPeer", "params":["0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":true,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_unbanPeer", "params":["0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000002"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":false,"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_hash_content() {
 	let miner = miner_service();
@@ -241,6 +263,34 @@ fn rpc_parity_remove_transaction() {
 	assert_eq!(io.handle_request_sync(&request), Some(response.to_owned()));
 }
 
+#[test]
+fn rpc_parity_retrace_blocks() {
+	let miner = miner_service();
+	let client = client_service();
+	let network = network_service();
+	let updater = updater_service();
+	let mut io = IoHandler::new();
+	io.extend_with(parity_set_client(&client, &miner, &updater, &network).to_delegate());
+
+	client.add_blocks(1001, EachBlockWith::Nothing);
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_retraceBlocks", "params":["0x3", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","result":"0x3e7","id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_retraceBlocks", "params":["0x1", "latest"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32041,"message":"At most 1000 blocks may be re-traced at once","data":"Split the block range into smaller ones."},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_retraceBlocks", "params":["0x3", "0x3ea"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Unknown block number"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+
+	let request = r#"{"jsonrpc": "2.0", "method": "parity_retraceBlocks", "params":["0x3", "pending"], "id": 1}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32602,"message":"Couldn't parse parameters: `BlockNumber::Pending` is not supported","data":"()"},"id":1}"#;
+	assert_eq!(io.handle_request_sync(request), Some(response.to_owned()));
+}
+
 #[test]
 fn rpc_parity_set_dapps_list() {
 	let miner = miner_service();
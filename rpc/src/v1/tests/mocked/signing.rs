@@ -36,6 +36,8 @@ use ethcore::account_provider::AccountProvider;
 use ethcore::client::TestBlockChainClient;
 use ethkey::Secret;
 use ethstore::ethkey::{Generator, Random};
+use miner::pool::VerifiedTransaction;
+use miner::pool::local_transactions::Status as LocalTransactionStatus;
 use parking_lot::Mutex;
 use serde_json;
 use transaction::{Transaction, Action, SignedTransaction};
@@ -483,3 +485,118 @@ fn should_compose_transaction() {
 	let res = tester.io.handle_request(&request).wait().unwrap();
 	assert_eq!(res, Some(response.to_owned()));
 }
+
+fn queue_local_transaction(tester: &SigningTester, from: Address, gas_price: U256) -> SignedTransaction {
+	let t = Transaction {
+		nonce: U256::zero(),
+		gas_price: gas_price,
+		gas: U256::from(0x76c0),
+		action: Action::Call(Address::from_str("d46e8dd67c5d32be8058bb8eb970870f07244567").unwrap()),
+		value: U256::from(0x9184e72au64),
+		data: vec![1, 2],
+		..Default::default()
+	};
+	let signature = tester.accounts.sign(from, None, t.hash(None)).unwrap();
+	let t = t.with_signature(signature, None);
+	let verified = Arc::new(VerifiedTransaction::from_pending_block_transaction(t.clone()));
+	tester.miner.local_transactions.lock().insert(t.hash(), LocalTransactionStatus::Pending(verified));
+	t
+}
+
+#[test]
+fn should_cancel_local_transaction_if_account_is_unlocked() {
+	// given
+	let tester = eth_signing();
+	let acc = tester.accounts.new_account("test").unwrap();
+	tester.accounts.unlock_account_permanently(acc, "test".into()).unwrap();
+	let original = queue_local_transaction(&tester, acc, U256::from(0x100));
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_cancelTransaction",
+		"params": [""#.to_owned() + format!("0x{:x}", original.hash()).as_ref() + r#""],
+		"id": 1
+	}"#;
+	let res = tester.io.handle_request_sync(&request);
+
+	// then
+	let imported = tester.miner.imported_transactions.lock()[0].clone();
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:x}", imported.hash()).as_ref() + r#"","id":1}"#;
+	assert_eq!(res, Some(response));
+	assert_eq!(imported.sender(), acc);
+	assert_eq!(imported.nonce, original.nonce);
+	assert_eq!(imported.gas_price, U256::from(0x120));
+	assert_eq!(imported.action, Action::Call(acc));
+	assert_eq!(imported.value, U256::zero());
+	assert!(imported.data.is_empty());
+}
+
+#[test]
+fn should_speed_up_local_transaction_if_account_is_unlocked() {
+	// given
+	let tester = eth_signing();
+	let acc = tester.accounts.new_account("test").unwrap();
+	tester.accounts.unlock_account_permanently(acc, "test".into()).unwrap();
+	let original = queue_local_transaction(&tester, acc, U256::from(0x100));
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_speedUpTransaction",
+		"params": [""#.to_owned() + format!("0x{:x}", original.hash()).as_ref() + r#"", "0x200"],
+		"id": 1
+	}"#;
+	let res = tester.io.handle_request_sync(&request);
+
+	// then
+	let imported = tester.miner.imported_transactions.lock()[0].clone();
+	let response = r#"{"jsonrpc":"2.0","result":""#.to_owned() + format!("0x{:x}", imported.hash()).as_ref() + r#"","id":1}"#;
+	assert_eq!(res, Some(response));
+	assert_eq!(imported.nonce, original.nonce);
+	assert_eq!(imported.gas_price, U256::from(0x200));
+	assert_eq!(imported.gas, original.gas);
+	assert_eq!(imported.action, original.action);
+	assert_eq!(imported.value, original.value);
+	assert_eq!(imported.data, original.data);
+}
+
+#[test]
+fn should_not_replace_transaction_with_too_low_gas_price() {
+	// given
+	let tester = eth_signing();
+	let acc = tester.accounts.new_account("test").unwrap();
+	tester.accounts.unlock_account_permanently(acc, "test".into()).unwrap();
+	let original = queue_local_transaction(&tester, acc, U256::from(0x100));
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_speedUpTransaction",
+		"params": [""#.to_owned() + format!("0x{:x}", original.hash()).as_ref() + r#"", "0x110"],
+		"id": 1
+	}"#;
+	let res = tester.io.handle_request_sync(&request).unwrap();
+
+	// then
+	assert!(res.contains(r#""code":-32010"#), "{}", res);
+	assert!(tester.miner.imported_transactions.lock().is_empty());
+}
+
+#[test]
+fn should_not_cancel_unknown_transaction() {
+	// given
+	let tester = eth_signing();
+
+	// when
+	let request = r#"{
+		"jsonrpc": "2.0",
+		"method": "parity_cancelTransaction",
+		"params": ["0x0000000000000000000000000000000000000000000000000000000000000005"],
+		"id": 1
+	}"#;
+	let response = r#"{"jsonrpc":"2.0","error":{"code":-32010,"message":"Transaction is not a local transaction waiting in the queue."},"id":1}"#;
+
+	// then
+	assert_eq!(tester.io.handle_request_sync(&request), Some(response.to_owned()));
+}
@@ -25,9 +25,9 @@ use node_health::Health;
 use v1::types::{
 	H160, H256, H512, U256, U64, Bytes, CallRequest,
 	Peers, Transaction, RpcSettings, Histogram,
-	TransactionStats, LocalTransactionStatus,
+	TransactionStats, ReceivedTransactionsStats, PackingStats, LocalTransactionStatus,
 	BlockNumber, ConsensusCapability, VersionInfo,
-	OperationsInfo, DappId, ChainStatus,
+	OperationsInfo, DappId, ChainStatus, SyncProgress, LightServingLoad, EpochTransition,
 	AccountInfo, HwAccountInfo, RichHeader,
 };
 
@@ -134,6 +134,12 @@ build_rpc_trait! {
 		#[rpc(name = "parity_listStorageKeys")]
 		fn list_storage_keys(&self, H160, u64, Option<H256>, Trailing<BlockNumber>) -> Result<Option<Vec<H256>>>;
 
+		/// Returns hashes of transactions sent by, sent to or internally touching the given address (first parameter)
+		/// between given blocks, or null if the blocks are not known. Returns at most 1000 transactions at once.
+		/// Requires the account transactions index to be enabled (`--account-transactions-index=on`).
+		#[rpc(name = "parity_listAccountTransactions")]
+		fn list_account_transactions(&self, H160, BlockNumber, BlockNumber, Option<H256>, u64) -> Result<Option<Vec<H256>>>;
+
 		/// Encrypt some data with a public key under ECIES.
 		/// First parameter is the 512-byte destination public key, second is the message.
 		#[rpc(name = "parity_encryptMessage")]
@@ -157,10 +163,28 @@ build_rpc_trait! {
 		#[rpc(name = "parity_pendingTransactionsStats")]
 		fn pending_transactions_stats(&self) -> Result<BTreeMap<H256, TransactionStats>>;
 
+		/// Returns statistics of transactions received from peers.
+		#[rpc(name = "parity_receivedTransactionsStats")]
+		fn received_transactions_stats(&self) -> Result<ReceivedTransactionsStats>;
+
+		/// Returns the load of serving light peers and request credits of each light peer.
+		#[rpc(name = "parity_lightServingLoad")]
+		fn light_serving_load(&self) -> Result<LightServingLoad>;
+
+		/// Returns the epoch transitions stored by the light client, verifying their proofs.
+		#[rpc(name = "parity_lightEpochs")]
+		fn light_epochs(&self) -> Result<Vec<EpochTransition>>;
+
 		/// Returns a list of current and past local transactions with status details.
 		#[rpc(name = "parity_localTransactions")]
 		fn local_transactions(&self) -> Result<BTreeMap<H256, LocalTransactionStatus>>;
 
+		/// Returns statistics comparing fees of blocks packed to maximize fees
+		/// to fees of the same blocks with transactions in priority order.
+		/// Fees are estimated from gas limits of the transactions.
+		#[rpc(name = "parity_packingStats")]
+		fn packing_stats(&self) -> Result<PackingStats>;
+
 		/// Returns current Dapps Server interface and port or an error if dapps server is disabled.
 		#[rpc(name = "parity_dappsUrl")]
 		fn dapps_url(&self) -> Result<String>;
@@ -207,6 +231,10 @@ build_rpc_trait! {
 		#[rpc(name = "parity_chainStatus")]
 		fn chain_status(&self) -> Result<ChainStatus>;
 
+		/// Get detailed progress of the chain synchronization.
+		#[rpc(name = "parity_syncStatus")]
+		fn sync_status(&self) -> Result<SyncProgress>;
+
 		/// Get node kind info.
 		#[rpc(name = "parity_nodeKind")]
 		fn node_kind(&self) -> Result<::v1::types::NodeKind>;
@@ -216,6 +244,11 @@ build_rpc_trait! {
 		#[rpc(name = "parity_getBlockHeaderByNumber")]
 		fn block_header(&self, Trailing<BlockNumber>) -> BoxFuture<RichHeader>;
 
+		/// Get mined transaction with given sender and nonce.
+		/// Only available if the node maintains the index of transactions by sender and nonce.
+		#[rpc(name = "parity_getTransactionBySenderAndNonce")]
+		fn transaction_by_sender_and_nonce(&self, H160, U256) -> Result<Option<Transaction>>;
+
 		/// Get IPFS CIDv0 given protobuf encoded bytes.
 		#[rpc(name = "parity_cidV0")]
 		fn ipfs_cid(&self, Bytes) -> Result<String>;
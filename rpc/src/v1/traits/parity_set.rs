@@ -18,7 +18,7 @@
 
 use jsonrpc_core::{BoxFuture, Result};
 
-use v1::types::{Bytes, H160, H256, U256, ReleaseInfo, Transaction, LocalDapp};
+use v1::types::{Bytes, H160, H256, H512, U256, ReleaseInfo, Transaction, LocalDapp, BlockNumber, BannedPeer};
 
 build_rpc_trait! {
 	/// Parity-specific rpc interface for operations altering the settings.
@@ -71,6 +71,18 @@ build_rpc_trait! {
 		#[rpc(name = "parity_acceptNonReservedPeers")]
 		fn accept_non_reserved_peers(&self) -> Result<bool>;
 
+		/// Returns peers currently banned for bad behaviour.
+		#[rpc(name = "parity_bannedPeers")]
+		fn banned_peers(&self) -> Result<Vec<BannedPeer>>;
+
+		/// Lift the ban of a peer. Returns `false` if the peer was not banned.
+		#[rpc(name = "parity_unbanPeer")]
+		fn unban_peer(&self, H512) -> Result<bool>;
+
+		/// Lift all peer bans.
+		#[rpc(name = "parity_clearBannedPeers")]
+		fn clear_banned_peers(&self) -> Result<bool>;
+
 		/// Start the network.
 		///
 		/// @deprecated - Use `set_mode("active")` instead.
@@ -119,5 +131,12 @@ build_rpc_trait! {
 		/// Returns `true` when transaction was removed, `false` if it was not found.
 		#[rpc(name = "parity_removeTransaction")]
 		fn remove_transaction(&self, H256) -> Result<Option<Transaction>>;
+
+		/// Re-executes blocks in the given range and writes their traces into the trace database,
+		/// also if tracing is disabled. At most 1000 blocks are re-traced at once.
+		/// Requires state of the blocks to be available.
+		/// Returns the number of blocks which have been traced.
+		#[rpc(name = "parity_retraceBlocks")]
+		fn retrace_blocks(&self, BlockNumber, BlockNumber) -> BoxFuture<U256>;
 	}
 }
@@ -17,7 +17,7 @@
 //! ParitySigning rpc interface.
 use jsonrpc_core::{BoxFuture, Result};
 
-use v1::types::{U256, H160, Bytes, ConfirmationResponse, TransactionRequest, Either};
+use v1::types::{U256, H160, H256, Bytes, ConfirmationResponse, TransactionRequest, Either};
 
 build_rpc_trait! {
 	/// Signing methods implementation.
@@ -48,5 +48,16 @@ build_rpc_trait! {
 		/// First parameter is the address with which it is encrypted, second is the ciphertext.
 		#[rpc(meta, name = "parity_decryptMessage")]
 		fn decrypt_message(&self, Self::Metadata, H160, Bytes) -> BoxFuture<Bytes>;
+
+		/// Cancel a local transaction waiting in the queue by replacing it with an empty transfer
+		/// to its sender with the same nonce and a higher gas price.
+		/// Returns the hash of the replacement transaction.
+		#[rpc(meta, name = "parity_cancelTransaction")]
+		fn cancel_transaction(&self, Self::Metadata, H256) -> BoxFuture<H256>;
+
+		/// Replace a local transaction waiting in the queue by the same transaction with given gas price.
+		/// Returns the hash of the replacement transaction.
+		#[rpc(meta, name = "parity_speedUpTransaction")]
+		fn speed_up_transaction(&self, Self::Metadata, H256, U256) -> BoxFuture<H256>;
 	}
 }
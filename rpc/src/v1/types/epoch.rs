@@ -0,0 +1,87 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Epoch transitions known to the light client.
+
+use light::client::{EpochTransitionStatus, EpochProofStatus as LightEpochProofStatus};
+use v1::types::{H256, U256};
+
+/// Status of an epoch transition proof.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum EpochProofStatus {
+	/// The proof is valid and the transition was checked against the previous epoch.
+	#[serde(rename="verified")]
+	Verified,
+	/// The proof is valid, but the previous epoch is not stored.
+	#[serde(rename="unconfirmed")]
+	Unconfirmed,
+	/// The proof or the transition failed verification.
+	#[serde(rename="invalid")]
+	Invalid,
+}
+
+/// An epoch transition stored by the light client.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EpochTransition {
+	/// Hash of the transition block.
+	pub hash: H256,
+	/// Number of the transition block.
+	pub number: U256,
+	/// Whether the transition block is on the canonical chain.
+	pub canonical: bool,
+	/// Status of the transition proof.
+	pub status: EpochProofStatus,
+	/// Reason the proof failed verification.
+	pub error: Option<String>,
+}
+
+impl From<EpochTransitionStatus> for EpochTransition {
+	fn from(t: EpochTransitionStatus) -> Self {
+		let (status, error) = match t.status {
+			LightEpochProofStatus::Verified => (EpochProofStatus::Verified, None),
+			LightEpochProofStatus::Unconfirmed => (EpochProofStatus::Unconfirmed, None),
+			LightEpochProofStatus::Invalid(e) => (EpochProofStatus::Invalid, Some(e)),
+		};
+
+		EpochTransition {
+			hash: t.block_hash.into(),
+			number: t.block_number.into(),
+			canonical: t.canonical,
+			status: status,
+			error: error,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json;
+	use light::client::{EpochTransitionStatus, EpochProofStatus};
+	use super::EpochTransition;
+
+	#[test]
+	fn serialize_epoch_transition() {
+		let t: EpochTransition = EpochTransitionStatus {
+			block_hash: 5.into(),
+			block_number: 16,
+			canonical: true,
+			status: EpochProofStatus::Invalid("Bad epoch proof".into()),
+		}.into();
+
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"hash":"0x0000000000000000000000000000000000000000000000000000000000000005","number":"0x10","canonical":true,"status":"invalid","error":"Bad epoch proof"}"#);
+	}
+}
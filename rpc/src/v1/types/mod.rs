@@ -26,12 +26,14 @@ mod confirmations;
 mod consensus_status;
 mod dapps;
 mod derivation;
+mod epoch;
 mod filter;
 mod hash;
 mod histogram;
 mod index;
 mod log;
 mod node_kind;
+mod packing_stats;
 mod provenance;
 mod receipt;
 mod rpc_settings;
@@ -60,19 +62,23 @@ pub use self::confirmations::{
 pub use self::consensus_status::*;
 pub use self::dapps::LocalDapp;
 pub use self::derivation::{DeriveHash, DeriveHierarchical, Derive};
+pub use self::epoch::{EpochTransition, EpochProofStatus};
 pub use self::filter::{Filter, FilterChanges};
 pub use self::hash::{H64, H160, H256, H512, H520, H2048};
 pub use self::histogram::Histogram;
 pub use self::index::Index;
 pub use self::log::Log;
 pub use self::node_kind::{NodeKind, Availability, Capability};
+pub use self::packing_stats::PackingStats;
 pub use self::provenance::{Origin, DappId};
 pub use self::receipt::Receipt;
 pub use self::rpc_settings::RpcSettings;
 pub use self::secretstore::EncryptedDocumentKey;
 pub use self::sync::{
-	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerProtocolsInfo,
-	TransactionStats, ChainStatus, EthProtocolInfo, PipProtocolInfo,
+	SyncStatus, SyncInfo, Peers, PeerInfo, PeerNetworkInfo, PeerTrafficInfo, PeerProtocolsInfo,
+	TransactionStats, ReceivedTransactionsStats, ChainStatus, EthProtocolInfo, PipProtocolInfo, BannedPeer, SyncProgress,
+	SyncStage, BlockQueueStatus, SnapshotSyncStatus, AncientBlocksStatus,
+	LightServingBudget, LightPeerLoad, LightServingLoad,
 };
 pub use self::trace::{LocalizedTrace, TraceResults};
 pub use self::trace_filter::TraceFilter;
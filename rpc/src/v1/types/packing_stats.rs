@@ -0,0 +1,47 @@
+// Copyright 2015-2017 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity. If not, see <http://www.gnu.org/licenses/>.
+
+//! Block packing statistics.
+
+use miner::pool::packing;
+use v1::types::U256;
+
+/// Statistics comparing fees estimated from gas limits for packing blocks to the simple priority order.
+#[derive(Default, Debug, Serialize)]
+pub struct PackingStats {
+	/// Number of packed blocks.
+	pub blocks: u64,
+	/// Number of blocks where packing selected transactions with higher estimated fees than the priority order.
+	#[serde(rename="improvedBlocks")]
+	pub improved_blocks: u64,
+	/// Total estimated fees of packed blocks.
+	#[serde(rename="estimatedFees")]
+	pub estimated_fees: U256,
+	/// Total estimated fees the same blocks would have with transactions in priority order.
+	#[serde(rename="estimatedSimpleFees")]
+	pub estimated_simple_fees: U256,
+}
+
+impl From<packing::PackingStats> for PackingStats {
+	fn from(s: packing::PackingStats) -> Self {
+		PackingStats {
+			blocks: s.blocks,
+			improved_blocks: s.improved_blocks,
+			estimated_fees: s.estimated_fees.into(),
+			estimated_simple_fees: s.estimated_simple_fees.into(),
+		}
+	}
+}
@@ -19,6 +19,7 @@
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::de::Error;
 use serde_json::{Value, from_value};
+use miner::pool;
 use v1::types::{RichHeader, Filter, Log, H256};
 
 /// Subscription result.
@@ -30,6 +31,8 @@ pub enum Result {
 	Log(Log),
 	/// Transaction hash
 	TransactionHash(H256),
+	/// Transaction replaced or mined
+	TransactionEvent(TransactionEvent),
 }
 
 impl Serialize for Result {
@@ -40,6 +43,37 @@ impl Serialize for Result {
 			Result::Header(ref header) => header.serialize(serializer),
 			Result::Log(ref log) => log.serialize(serializer),
 			Result::TransactionHash(ref hash) => hash.serialize(serializer),
+			Result::TransactionEvent(ref event) => event.serialize(serializer),
+		}
+	}
+}
+
+/// Event about a transaction leaving the pool.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransactionEvent {
+	/// Hash of the transaction.
+	pub hash: H256,
+	/// Event type (`replaced` or `mined`).
+	#[serde(rename="type")]
+	pub event_type: String,
+	/// Hash of the transaction that replaced it.
+	#[serde(rename="replacedBy")]
+	pub replaced_by: Option<H256>,
+}
+
+impl From<pool::TransactionEvent> for TransactionEvent {
+	fn from(event: pool::TransactionEvent) -> Self {
+		match event {
+			pool::TransactionEvent::Replaced { old, new } => TransactionEvent {
+				hash: old.into(),
+				event_type: "replaced".into(),
+				replaced_by: Some(new.into()),
+			},
+			pool::TransactionEvent::Mined(hash) => TransactionEvent {
+				hash: hash.into(),
+				event_type: "mined".into(),
+				replaced_by: None,
+			},
 		}
 	}
 }
@@ -57,6 +91,9 @@ pub enum Kind {
 	/// New Pending Transactions subscription.
 	#[serde(rename="newPendingTransactions")]
 	NewPendingTransactions,
+	/// Pending transactions being replaced or mined subscription.
+	#[serde(rename="transactionEvents")]
+	TransactionEvents,
 	/// Node syncing status subscription.
 	#[serde(rename="syncing")]
 	Syncing,
@@ -103,6 +140,7 @@ mod tests {
 		assert_eq!(serde_json::from_str::<Kind>(r#""newHeads""#).unwrap(), Kind::NewHeads);
 		assert_eq!(serde_json::from_str::<Kind>(r#""logs""#).unwrap(), Kind::Logs);
 		assert_eq!(serde_json::from_str::<Kind>(r#""newPendingTransactions""#).unwrap(), Kind::NewPendingTransactions);
+		assert_eq!(serde_json::from_str::<Kind>(r#""transactionEvents""#).unwrap(), Kind::TransactionEvents);
 		assert_eq!(serde_json::from_str::<Kind>(r#""syncing""#).unwrap(), Kind::Syncing);
 	}
 
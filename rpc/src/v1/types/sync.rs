@@ -15,7 +15,13 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::BTreeMap;
-use sync::{self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats};
+use std::time::{Duration, UNIX_EPOCH};
+use ethcore::client::BlockQueueInfo;
+use light::net::{ServingBudget, ServingLoad, PeerServingLoad};
+use sync::{
+	self, PeerInfo as SyncPeerInfo, TransactionStats as SyncTransactionStats,
+	ReceivedTransactionsStats as SyncReceivedTransactionsStats,
+};
 use serde::{Serialize, Serializer};
 use v1::types::{U256, H512};
 
@@ -76,6 +82,36 @@ pub struct PeerNetworkInfo {
 	/// Local endpoint address
 	#[serde(rename="localAddress")]
 	pub local_address: String,
+	/// Payload traffic of the session
+	pub traffic: PeerTrafficInfo,
+}
+
+/// Payload bytes sent and received over a peer session, before and after compression
+#[derive(Default, Debug, Serialize)]
+pub struct PeerTrafficInfo {
+	/// Uncompressed payload bytes sent
+	#[serde(rename="rawBytesSent")]
+	pub raw_bytes_sent: u64,
+	/// Payload bytes sent over the wire
+	#[serde(rename="compressedBytesSent")]
+	pub compressed_bytes_sent: u64,
+	/// Uncompressed payload bytes received
+	#[serde(rename="rawBytesReceived")]
+	pub raw_bytes_received: u64,
+	/// Payload bytes received over the wire
+	#[serde(rename="compressedBytesReceived")]
+	pub compressed_bytes_received: u64,
+}
+
+impl From<sync::SessionTraffic> for PeerTrafficInfo {
+	fn from(traffic: sync::SessionTraffic) -> Self {
+		PeerTrafficInfo {
+			raw_bytes_sent: traffic.raw_sent,
+			compressed_bytes_sent: traffic.compressed_sent,
+			raw_bytes_received: traffic.raw_received,
+			compressed_bytes_received: traffic.compressed_received,
+		}
+	}
 }
 
 /// Peer protocols information
@@ -96,6 +132,8 @@ pub struct EthProtocolInfo {
 	pub difficulty: Option<U256>,
 	/// SHA3 of peer best block hash
 	pub head: String,
+	/// Block download throughput in blocks per second
+	pub throughput: Option<f64>,
 }
 
 impl From<sync::EthProtocolInfo> for EthProtocolInfo {
@@ -104,6 +142,7 @@ impl From<sync::EthProtocolInfo> for EthProtocolInfo {
 			version: info.version,
 			difficulty: info.difficulty.map(Into::into),
 			head: format!("{:x}", info.head),
+			throughput: info.throughput,
 		}
 	}
 }
@@ -168,6 +207,7 @@ impl From<SyncPeerInfo> for PeerInfo {
 			network: PeerNetworkInfo {
 				remote_address: p.remote_address,
 				local_address: p.local_address,
+				traffic: p.traffic.into(),
 			},
 			protocols: PeerProtocolsInfo {
 				eth: p.eth_info.map(Into::into),
@@ -189,6 +229,33 @@ impl From<SyncTransactionStats> for TransactionStats {
 	}
 }
 
+/// Statistics of transactions received from peers.
+#[derive(Default, Debug, Serialize)]
+pub struct ReceivedTransactionsStats {
+	/// Number of transactions received.
+	pub received: u64,
+	/// Number of received transactions which were already known.
+	pub duplicates: u64,
+	/// Number of received transactions rejected by the transaction pool.
+	pub rejected: u64,
+	/// Number of transaction hashes announced by peers.
+	pub announced: u64,
+	/// Number of announced transactions requested from peers.
+	pub requested: u64,
+}
+
+impl From<SyncReceivedTransactionsStats> for ReceivedTransactionsStats {
+	fn from(s: SyncReceivedTransactionsStats) -> Self {
+		ReceivedTransactionsStats {
+			received: s.received,
+			duplicates: s.duplicates,
+			rejected: s.rejected,
+			announced: s.announced,
+			requested: s.requested,
+		}
+	}
+}
+
 /// Chain status.
 #[derive(Default, Debug, Serialize)]
 pub struct ChainStatus {
@@ -197,11 +264,254 @@ pub struct ChainStatus {
 	pub block_gap: Option<(U256, U256)>,
 }
 
+/// Peer banned for bad behaviour.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct BannedPeer {
+	/// Public node id
+	pub id: H512,
+	/// Unix timestamp (in seconds) the ban expires at
+	pub until: u64,
+}
+
+impl From<sync::BannedPeerInfo> for BannedPeer {
+	fn from(b: sync::BannedPeerInfo) -> Self {
+		BannedPeer {
+			id: b.id.into(),
+			until: b.until.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+		}
+	}
+}
+
+fn as_micros(duration: Duration) -> u64 {
+	duration.as_secs() * 1_000_000 + duration.subsec_micros() as u64
+}
+
+/// Resources which may be spent serving light peers each second.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct LightServingBudget {
+	/// Microseconds spent responding to requests, unlimited if `None`.
+	#[serde(rename="serveTime")]
+	pub serve_time: Option<u64>,
+	/// Bytes sent in responses, unlimited if `None`.
+	pub bytes: Option<u64>,
+}
+
+impl From<ServingBudget> for LightServingBudget {
+	fn from(budget: ServingBudget) -> Self {
+		LightServingBudget {
+			serve_time: budget.time.map(as_micros),
+			bytes: budget.bytes,
+		}
+	}
+}
+
+/// Load of serving a light peer.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct LightPeerLoad {
+	/// Public node id, if known
+	pub id: Option<H512>,
+	/// Whether the peer is reserved and not limited by budgets
+	pub reserved: bool,
+	/// Microseconds spent responding to the peer during the last second
+	#[serde(rename="serveTime")]
+	pub serve_time: u64,
+	/// Bytes sent to the peer during the last second
+	pub bytes: u64,
+	/// Request credits the peer has left
+	pub credits: U256,
+	/// Maximum request credits of the peer
+	#[serde(rename="creditsLimit")]
+	pub credits_limit: U256,
+}
+
+impl From<PeerServingLoad> for LightPeerLoad {
+	fn from(load: PeerServingLoad) -> Self {
+		LightPeerLoad {
+			id: load.node_id.map(Into::into),
+			reserved: load.reserved,
+			serve_time: as_micros(load.time),
+			bytes: load.bytes,
+			credits: load.credits.into(),
+			credits_limit: load.credits_limit.into(),
+		}
+	}
+}
+
+/// Load of serving light peers.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct LightServingLoad {
+	/// Microseconds spent responding to requests during the last second
+	#[serde(rename="serveTime")]
+	pub serve_time: u64,
+	/// Bytes sent in responses during the last second
+	pub bytes: u64,
+	/// Budget for serving all peers
+	pub budget: LightServingBudget,
+	/// Budget for serving each non-reserved peer
+	#[serde(rename="peerBudget")]
+	pub peer_budget: LightServingBudget,
+	/// Load of each connected light peer
+	pub peers: Vec<LightPeerLoad>,
+}
+
+impl From<ServingLoad> for LightServingLoad {
+	fn from(load: ServingLoad) -> Self {
+		LightServingLoad {
+			serve_time: as_micros(load.time),
+			bytes: load.bytes,
+			budget: load.total_budget.into(),
+			peer_budget: load.peer_budget.into(),
+			peers: load.peers.into_iter().map(Into::into).collect(),
+		}
+	}
+}
+
+/// Stage of the sync state machine.
+#[derive(Debug, Serialize, PartialEq)]
+pub enum SyncStage {
+	/// Collecting enough peers to start syncing.
+	#[serde(rename="waitingPeers")]
+	WaitingPeers,
+	/// Waiting for snapshot manifest download.
+	#[serde(rename="snapshotManifest")]
+	SnapshotManifest,
+	/// Downloading snapshot data.
+	#[serde(rename="snapshotData")]
+	SnapshotData,
+	/// Waiting for snapshot restoration progress.
+	#[serde(rename="snapshotWaiting")]
+	SnapshotWaiting,
+	/// Downloading new blocks.
+	#[serde(rename="blocks")]
+	Blocks,
+	/// Initial chain sync complete, waiting for new blocks.
+	#[serde(rename="idle")]
+	Idle,
+	/// Block downloading paused until the block queue frees some space.
+	#[serde(rename="waiting")]
+	Waiting,
+	/// Downloading blocks learned from new block hashes announcements.
+	#[serde(rename="newBlocks")]
+	NewBlocks,
+}
+
+impl Default for SyncStage {
+	fn default() -> Self {
+		SyncStage::Idle
+	}
+}
+
+impl From<sync::SyncState> for SyncStage {
+	fn from(state: sync::SyncState) -> Self {
+		match state {
+			sync::SyncState::WaitingPeers => SyncStage::WaitingPeers,
+			sync::SyncState::SnapshotManifest => SyncStage::SnapshotManifest,
+			sync::SyncState::SnapshotData => SyncStage::SnapshotData,
+			sync::SyncState::SnapshotWaiting => SyncStage::SnapshotWaiting,
+			sync::SyncState::Blocks => SyncStage::Blocks,
+			sync::SyncState::Idle => SyncStage::Idle,
+			sync::SyncState::Waiting => SyncStage::Waiting,
+			sync::SyncState::NewBlocks => SyncStage::NewBlocks,
+		}
+	}
+}
+
+/// Sizes of the block verification queue.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct BlockQueueStatus {
+	/// Number of blocks waiting for verification
+	pub unverified: usize,
+	/// Number of blocks being verified
+	pub verifying: usize,
+	/// Number of verified blocks waiting for import
+	pub verified: usize,
+	/// Maximal number of blocks in the queue
+	pub max: usize,
+}
+
+impl From<BlockQueueInfo> for BlockQueueStatus {
+	fn from(info: BlockQueueInfo) -> Self {
+		BlockQueueStatus {
+			unverified: info.unverified_queue_size,
+			verifying: info.verifying_queue_size,
+			verified: info.verified_queue_size,
+			max: info.max_queue_size,
+		}
+	}
+}
+
+/// Snapshot download and restoration progress.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct SnapshotSyncStatus {
+	/// Total number of chunks in the snapshot
+	#[serde(rename="chunksTotal")]
+	pub chunks_total: usize,
+	/// Number of chunks downloaded so far
+	#[serde(rename="chunksDownloaded")]
+	pub chunks_downloaded: usize,
+	/// Number of chunks restored so far
+	#[serde(rename="chunksRestored")]
+	pub chunks_restored: usize,
+}
+
+/// Ancient blocks download progress.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct AncientBlocksStatus {
+	/// Last imported ancient block
+	#[serde(rename="currentBlock")]
+	pub current_block: U256,
+	/// Last block of the gap being filled
+	#[serde(rename="targetBlock")]
+	pub target_block: U256,
+}
+
+/// Detailed progress of the chain synchronization.
+#[derive(Default, Debug, Serialize, PartialEq)]
+pub struct SyncProgress {
+	/// Stage of the sync process
+	pub stage: SyncStage,
+	/// Starting block
+	#[serde(rename="startingBlock")]
+	pub starting_block: U256,
+	/// Current block
+	#[serde(rename="currentBlock")]
+	pub current_block: U256,
+	/// Highest block seen so far
+	#[serde(rename="highestBlock")]
+	pub highest_block: Option<U256>,
+	/// Block verification queue
+	#[serde(rename="blockQueue")]
+	pub block_queue: BlockQueueStatus,
+	/// Snapshot progress, if warp syncing
+	pub snapshot: Option<SnapshotSyncStatus>,
+	/// Ancient blocks progress, if there is a gap in the chain
+	pub ancient: Option<AncientBlocksStatus>,
+	/// Blocks imported per second
+	#[serde(rename="importRate")]
+	pub import_rate: Option<f64>,
+	/// Estimated number of seconds until the highest block is reached
+	pub eta: Option<u64>,
+}
+
+impl SyncProgress {
+	/// Set the block import rate and estimate the time left to reach the highest block.
+	/// The rate is rounded to a tenth of a block to avoid notifying subscribers on every fluctuation.
+	pub fn set_import_rate(&mut self, current: u64, highest: Option<u64>, rate: Option<f64>) {
+		let rate = rate.map(|rate| (rate * 10.0).round() / 10.0);
+		self.import_rate = rate;
+		self.eta = match (highest, rate) {
+			(Some(highest), Some(rate)) if highest > current && rate > 0.0 =>
+				Some(((highest - current) as f64 / rate).ceil() as u64),
+			_ => None,
+		};
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use serde_json;
 	use std::collections::BTreeMap;
-	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus};
+	use super::{SyncInfo, SyncStatus, Peers, TransactionStats, ChainStatus, SyncProgress};
 
 	#[test]
 	fn test_serialize_sync_info() {
@@ -240,6 +550,29 @@ mod tests {
 		assert_eq!(serialized, r#"{"blockGap":["0x1","0x5"]}"#);
 	}
 
+	#[test]
+	fn test_serialize_sync_progress() {
+		let t = SyncProgress::default();
+		let serialized = serde_json::to_string(&t).unwrap();
+		assert_eq!(serialized, r#"{"stage":"idle","startingBlock":"0x0","currentBlock":"0x0","highestBlock":null,"blockQueue":{"unverified":0,"verifying":0,"verified":0,"max":0},"snapshot":null,"ancient":null,"importRate":null,"eta":null}"#);
+	}
+
+	#[test]
+	fn sync_progress_import_rate() {
+		let mut t = SyncProgress::default();
+
+		t.set_import_rate(100, Some(1100), Some(19.96));
+		assert_eq!(t.import_rate, Some(20.0));
+		assert_eq!(t.eta, Some(50));
+
+		t.set_import_rate(1100, Some(1100), Some(20.0));
+		assert_eq!(t.eta, None);
+
+		t.set_import_rate(100, Some(1100), None);
+		assert_eq!(t.import_rate, None);
+		assert_eq!(t.eta, None);
+	}
+
 	#[test]
 	fn test_serialize_transaction_stats() {
 		let stats = TransactionStats {
@@ -25,8 +25,9 @@ use mio::deprecated::{Handler, EventLoop};
 use mio::udp::*;
 use hash::keccak;
 use ethereum_types::{H256, H520};
-use rlp::{Rlp, RlpStream, encode_list};
+use rlp::{Rlp, RlpStream, encode_list, EMPTY_LIST_RLP};
 use node_table::*;
+use enr::{self, NodeRecord};
 use network::{Error, ErrorKind};
 use io::{StreamToken, IoContext};
 use ethkey::{Secret, KeyPair, sign, recover};
@@ -46,9 +47,15 @@ const PACKET_PING: u8 = 1;
 const PACKET_PONG: u8 = 2;
 const PACKET_FIND_NODE: u8 = 3;
 const PACKET_NEIGHBOURS: u8 = 4;
+const PACKET_ENR_REQUEST: u8 = 5;
+const PACKET_ENR_RESPONSE: u8 = 6;
 
 const PING_TIMEOUT: Duration = Duration::from_millis(300);
+const ENR_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+const PONG_TIMEOUT: Duration = Duration::from_secs(5);
+const BOND_EXPIRATION: Duration = Duration::from_secs(12 * 60 * 60); // How long an endpoint proof (Pong to our Ping) stays valid
 const MAX_NODES_PING: usize = 32; // Max nodes to add/ping at once
+const TOPIC_LOOKUP_INTERVAL: Duration = Duration::from_secs(10); // Min time between discovery rounds started by topic lookups
 
 #[derive(Clone, Debug)]
 pub struct NodeEntry {
@@ -100,6 +107,14 @@ pub struct Discovery {
 	check_timestamps: bool,
 	adding_nodes: Vec<NodeEntry>,
 	ip_filter: IpFilter,
+	record: NodeRecord,
+	record_entries: BTreeMap<String, Bytes>,
+	records: HashMap<NodeId, NodeRecord>,
+	enr_requests: HashMap<NodeId, (H256, Instant)>,
+	pings: HashMap<SocketAddr, (H256, Instant)>,
+	bonds: HashMap<NodeId, (SocketAddr, Instant)>,
+	topic_queries: HashSet<NodeId>,
+	last_topic_lookup: Option<Instant>,
 }
 
 pub struct TableUpdates {
@@ -108,8 +123,19 @@ pub struct TableUpdates {
 }
 
 impl Discovery {
-	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, ip_filter: IpFilter) -> Discovery {
+	pub fn new(key: &KeyPair, listen: SocketAddr, public: NodeEndpoint, token: StreamToken, ip_filter: IpFilter, record_entries: BTreeMap<String, Bytes>) -> Discovery {
 		let socket = UdpSocket::bind(&listen).expect("Error binding UDP socket");
+		// Start with the current time so that the sequence number keeps increasing across restarts.
+		let seq = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+		let (record, record_entries) = match Discovery::create_record(key.secret(), &public, seq, &record_entries) {
+			Ok(record) => (record, record_entries),
+			Err(e) => {
+				warn!(target: "discovery", "Error creating node record: {:?}", e);
+				let record = Discovery::create_record(key.secret(), &public, seq, &BTreeMap::new())
+					.expect("Record with endpoint only is always smaller than the size limit; qed");
+				(record, BTreeMap::new())
+			}
+		};
 		Discovery {
 			id: key.public().clone(),
 			id_hash: keccak(key.public()),
@@ -125,6 +151,93 @@ impl Discovery {
 			check_timestamps: true,
 			adding_nodes: Vec::new(),
 			ip_filter: ip_filter,
+			record: record,
+			record_entries: record_entries,
+			records: HashMap::new(),
+			enr_requests: HashMap::new(),
+			pings: HashMap::new(),
+			bonds: HashMap::new(),
+			topic_queries: HashSet::new(),
+			last_topic_lookup: None,
+		}
+	}
+
+	fn create_record(secret: &Secret, endpoint: &NodeEndpoint, seq: u64, entries: &BTreeMap<String, Bytes>) -> Result<NodeRecord, Error> {
+		let key = KeyPair::from_secret(secret.clone())?;
+		let mut all_entries = enr::endpoint_entries(endpoint);
+		all_entries.extend(entries.iter().map(|(k, v)| (k.clone(), v.clone())));
+		NodeRecord::new(&key, seq, &all_entries)
+	}
+
+	/// Replace entries of the local node record, besides the endpoint ones. Values must be RLP-encoded.
+	/// The record is signed again with an increased sequence number.
+	pub fn set_record_entries(&mut self, entries: BTreeMap<String, Bytes>) {
+		if entries == self.record_entries {
+			return;
+		}
+		match Discovery::create_record(&self.secret, &self.public_endpoint, self.record.seq() + 1, &entries) {
+			Ok(record) => {
+				trace!(target: "discovery", "Updated node record to seq {}", record.seq());
+				self.record = record;
+				self.record_entries = entries;
+			},
+			Err(e) => warn!(target: "discovery", "Error updating node record: {:?}", e),
+		}
+	}
+
+	/// Change the endpoint advertised to other nodes, e.g. after the external address of the NAT changed.
+	/// The record is signed again with an increased sequence number.
+	pub fn set_public_endpoint(&mut self, endpoint: NodeEndpoint) {
+		if endpoint == self.public_endpoint {
+			return;
+		}
+		match Discovery::create_record(&self.secret, &endpoint, self.record.seq() + 1, &self.record_entries) {
+			Ok(record) => {
+				trace!(target: "discovery", "Updated public endpoint to {:?}", endpoint);
+				self.record = record;
+				self.public_endpoint = endpoint;
+			},
+			Err(e) => warn!(target: "discovery", "Error updating node record: {:?}", e),
+		}
+	}
+
+	/// Nodes whose records advertise any of the given topics or capabilities.
+	pub fn nodes_with_topics(&self, topics: &[String]) -> HashSet<NodeId> {
+		self.records.values()
+			.filter(|record| topics.iter().any(|topic| record.has_topic(topic)))
+			.map(|record| record.id().clone())
+			.collect()
+	}
+
+	/// Look for nodes advertising any of the given topics if fewer than `wanted` are known.
+	/// Pings a few table nodes whose records are unknown, so that their records get requested,
+	/// and starts a new discovery round once all of them have been asked.
+	pub fn lookup_topics(&mut self, topics: &[String], wanted: usize) {
+		if topics.is_empty() || self.nodes_with_topics(topics).len() >= wanted {
+			return;
+		}
+		let unknown: Vec<NodeEntry> = self.node_buckets.iter()
+			.flat_map(|bucket| bucket.nodes.iter())
+			.filter(|node| {
+				let id = &node.address.id;
+				!self.records.contains_key(id) && !self.enr_requests.contains_key(id) && !self.topic_queries.contains(id)
+			})
+			.take(MAX_NODES_PING)
+			.map(|node| node.address.clone())
+			.collect();
+		if !unknown.is_empty() {
+			trace!(target: "discovery", "Looking up topics {:?} on {} nodes", topics, unknown.len());
+			for node in unknown {
+				self.topic_queries.insert(node.id.clone());
+				self.ping(&node.endpoint);
+			}
+			return;
+		}
+		let now = Instant::now();
+		let lookup_due = self.last_topic_lookup.map_or(true, |last| now.duration_since(last) >= TOPIC_LOOKUP_INTERVAL);
+		if self.discovery_round == DISCOVERY_MAX_STEPS && lookup_due {
+			self.last_topic_lookup = Some(now);
+			self.start();
 		}
 	}
 
@@ -216,8 +329,9 @@ impl Discovery {
 			let nearest = nearest.filter(|x| !self.discovery_nodes.contains(&x.id)).take(ALPHA).collect::<Vec<_>>();
 			for r in nearest {
 				let rlp = encode_list(&(&[self.discovery_id.clone()][..]));
-				self.send_packet(PACKET_FIND_NODE, &r.endpoint.udp_address(), &rlp)
-					.unwrap_or_else(|e| warn!("Error sending node discovery packet for {:?}: {:?}", &r.endpoint, e));
+				if let Err(e) = self.send_packet(PACKET_FIND_NODE, &r.endpoint.udp_address(), &rlp) {
+					warn!("Error sending node discovery packet for {:?}: {:?}", &r.endpoint, e);
+				}
 				self.discovery_nodes.insert(r.id.clone());
 				tried_count += 1;
 				trace!(target: "discovery", "Sent FindNode to {:?}", &r.endpoint);
@@ -252,20 +366,38 @@ impl Discovery {
 		self.public_endpoint.to_rlp_list(&mut rlp);
 		node.to_rlp_list(&mut rlp);
 		trace!(target: "discovery", "Sent Ping to {:?}", &node);
-		self.send_packet(PACKET_PING, &node.udp_address(), &rlp.drain())
-			.unwrap_or_else(|e| warn!("Error sending Ping packet: {:?}", e))
+		let seq = self.record.seq();
+		let address = node.udp_address();
+		match self.send_signed_packet(PACKET_PING, &address, &rlp.drain(), Some(seq)) {
+			Ok(hash) => { self.pings.insert(address, (hash, Instant::now())); },
+			Err(e) => warn!("Error sending Ping packet: {:?}", e),
+		}
+	}
+
+	/// Whether the node proved it owns the address by answering our Ping from it.
+	fn is_bonded(&self, node: &NodeId, from: &SocketAddr) -> bool {
+		self.bonds.get(node).map_or(false, |&(ref address, proved)| address == from && proved.elapsed() < BOND_EXPIRATION)
+	}
+
+	fn send_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8]) -> Result<H256, Error> {
+		self.send_signed_packet(packet_id, address, payload, None)
 	}
 
-	fn send_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8]) -> Result<(), Error> {
+	/// Sign and queue a packet, appending the expiration timestamp and, for Ping and Pong,
+	/// the sequence number of the local node record (EIP-868). Returns the packet hash.
+	fn send_signed_packet(&mut self, packet_id: u8, address: &SocketAddr, payload: &[u8], enr_seq: Option<u64>) -> Result<H256, Error> {
 		let mut rlp = RlpStream::new();
 		rlp.append_raw(&[packet_id], 1);
 		let source = Rlp::new(payload);
-		rlp.begin_list(source.item_count()? + 1);
+		rlp.begin_list(source.item_count()? + 1 + enr_seq.map_or(0, |_| 1));
 		for i in 0 .. source.item_count()? {
 			rlp.append_raw(source.at(i)?.as_raw(), 1);
 		}
 		let timestamp = 60 + SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs() as u32;
 		rlp.append(&timestamp);
+		if let Some(seq) = enr_seq {
+			rlp.append(&seq);
+		}
 
 		let bytes = rlp.drain();
 		let hash = keccak(bytes.as_ref());
@@ -283,7 +415,7 @@ impl Discovery {
 		let signed_hash = keccak(&packet[32..]);
 		packet[0..32].clone_from_slice(&signed_hash);
 		self.send_to(packet, address.clone());
-		Ok(())
+		Ok(signed_hash)
 	}
 
 	fn nearest_node_entries(target: &NodeId, buckets: &[NodeBucket]) -> Vec<NodeEntry> {
@@ -388,6 +520,8 @@ impl Discovery {
 			PACKET_PONG => self.on_pong(&rlp, &node_id, &from),
 			PACKET_FIND_NODE => self.on_find_node(&rlp, &node_id, &from),
 			PACKET_NEIGHBOURS => self.on_neighbours(&rlp, &node_id, &from),
+			PACKET_ENR_REQUEST => self.on_enr_request(&rlp, &node_id, &from, &hash_signed),
+			PACKET_ENR_RESPONSE => self.on_enr_response(&rlp, &node_id, &from),
 			_ => {
 				debug!("Unknown UDP packet: {}", packet_id);
 				Ok(None)
@@ -415,6 +549,7 @@ impl Discovery {
 		let dest = NodeEndpoint::from_rlp(&rlp.at(2)?)?;
 		let timestamp: u64 = rlp.val_at(3)?;
 		self.check_timestamp(timestamp)?;
+		let enr_seq: Option<u64> = rlp.val_at(4).ok();
 		let mut added_map = HashMap::new();
 		let entry = NodeEntry { id: node.clone(), endpoint: source.clone() };
 		if !entry.endpoint.is_valid() {
@@ -428,23 +563,93 @@ impl Discovery {
 		let mut response = RlpStream::new_list(2);
 		dest.to_rlp_list(&mut response);
 		response.append(&echo_hash);
-		self.send_packet(PACKET_PONG, from, &response.drain())?;
+		let seq = self.record.seq();
+		self.send_signed_packet(PACKET_PONG, from, &response.drain(), Some(seq))?;
+		if !added_map.is_empty() {
+			// ping back, so that the node can prove its endpoint before asking for our record.
+			if !self.is_bonded(node, from) {
+				self.ping(&NodeEndpoint { address: from.clone(), udp_port: from.port() });
+			}
+			// an earlier request might have been dropped because the node did not have our endpoint
+			// proof yet, it has it once the Pong above arrives.
+			self.enr_requests.remove(node);
+			self.request_record(node, from, enr_seq);
+		}
 
 		Ok(Some(TableUpdates { added: added_map, removed: HashSet::new() }))
 	}
 
 	fn on_pong(&mut self, rlp: &Rlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
 		trace!(target: "discovery", "Got Pong from {:?}", &from);
-		// TODO: validate pong packet in rlp.val_at(1)
 		let dest = NodeEndpoint::from_rlp(&rlp.at(0)?)?;
+		let echo_hash: H256 = rlp.val_at(1)?;
 		let timestamp: u64 = rlp.val_at(2)?;
 		self.check_timestamp(timestamp)?;
+		let enr_seq: Option<u64> = rlp.val_at(3).ok();
+		if self.pings.get(from).map_or(false, |&(hash, _)| hash == echo_hash) {
+			self.pings.remove(from);
+			self.bonds.insert(node.clone(), (from.clone(), Instant::now()));
+		}
 		let mut entry = NodeEntry { id: node.clone(), endpoint: dest };
 		if !entry.endpoint.is_valid() {
 			debug!(target: "discovery", "Bad address: {:?}", entry);
 			entry.endpoint.address = from.clone();
 		}
 		self.clear_ping(node);
+		self.request_record(node, from, enr_seq);
+		Ok(None)
+	}
+
+	/// Request the node record if the node announced a newer one than we know of.
+	fn request_record(&mut self, node: &NodeId, from: &SocketAddr, enr_seq: Option<u64>) {
+		let seq = match enr_seq {
+			Some(seq) => seq,
+			None => return,
+		};
+		if self.records.get(node).map_or(false, |record| record.seq() >= seq) || self.enr_requests.contains_key(node) {
+			return;
+		}
+		match self.send_packet(PACKET_ENR_REQUEST, from, &EMPTY_LIST_RLP) {
+			Ok(hash) => {
+				trace!(target: "discovery", "Sent ENRRequest to {:?}", from);
+				self.enr_requests.insert(node.clone(), (hash, Instant::now()));
+			},
+			Err(e) => warn!("Error sending ENRRequest packet: {:?}", e),
+		}
+	}
+
+	fn on_enr_request(&mut self, rlp: &Rlp, node: &NodeId, from: &SocketAddr, echo_hash: &H256) -> Result<Option<TableUpdates>, Error> {
+		trace!(target: "discovery", "Got ENRRequest from {:?}", &from);
+		let timestamp: u64 = rlp.val_at(0)?;
+		self.check_timestamp(timestamp)?;
+		if !self.is_bonded(node, from) {
+			debug!(target: "discovery", "ENRRequest from {:?} without endpoint proof", &from);
+			return Ok(None);
+		}
+		let mut response = RlpStream::new_list(2);
+		response.append(echo_hash);
+		response.append_raw(&self.record.rlp_bytes(), 1);
+		self.send_packet(PACKET_ENR_RESPONSE, from, &response.drain())?;
+		Ok(None)
+	}
+
+	fn on_enr_response(&mut self, rlp: &Rlp, node: &NodeId, from: &SocketAddr) -> Result<Option<TableUpdates>, Error> {
+		trace!(target: "discovery", "Got ENRResponse from {:?}", &from);
+		let request_hash: H256 = rlp.val_at(0)?;
+		match self.enr_requests.get(node) {
+			Some(&(hash, _)) if hash == request_hash => {},
+			_ => {
+				debug!(target: "discovery", "Unexpected ENRResponse from {:?}", &from);
+				return Ok(None);
+			}
+		}
+		self.enr_requests.remove(node);
+		let record = NodeRecord::from_rlp(rlp.at(1)?.as_raw())?;
+		if record.id() != node {
+			debug!(target: "discovery", "ENRResponse from {:?} contains record of another node", &from);
+			return Err(ErrorKind::BadProtocol.into());
+		}
+		self.records.insert(node.clone(), record);
 		Ok(None)
 	}
 
@@ -524,6 +729,14 @@ impl Discovery {
 				} else { true }
 			});
 		}
+		for id in &removed {
+			self.records.remove(id);
+			self.topic_queries.remove(id);
+			self.bonds.remove(id);
+		}
+		self.enr_requests.retain(|_, &mut (_, sent)| now.duration_since(sent) < ENR_REQUEST_TIMEOUT);
+		self.pings.retain(|_, &mut (_, sent)| now.duration_since(sent) < PONG_TIMEOUT);
+		self.bonds.retain(|_, &mut (_, proved)| now.duration_since(proved) < BOND_EXPIRATION);
 		removed
 	}
 
@@ -588,8 +801,8 @@ mod tests {
 		let key2 = Random.generate().unwrap();
 		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40444").unwrap(), udp_port: 40444 };
 		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40445").unwrap(), udp_port: 40445 };
-		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default());
-		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default());
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), BTreeMap::new());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), BTreeMap::new());
 
 		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7770").unwrap();
 		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@127.0.0.1:7771").unwrap();
@@ -617,11 +830,104 @@ mod tests {
 		assert_eq!(Discovery::nearest_node_entries(&NodeId::new(), &discovery2.node_buckets).len(), 3)
 	}
 
+	#[test]
+	fn exchanges_node_records() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40346").unwrap(), udp_port: 40346 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40347").unwrap(), udp_port: 40347 };
+		let mut entries = BTreeMap::new();
+		entries.insert("pip".to_owned(), enr::topics_entry(&[]));
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), entries);
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), BTreeMap::new());
+
+		discovery1.ping(&ep2);
+		for _ in 0 .. 3 {
+			while let Some(datagramm) = discovery1.send_queue.pop_front() {
+				discovery2.on_packet(&datagramm.payload, ep1.address.clone()).ok();
+			}
+			while let Some(datagramm) = discovery2.send_queue.pop_front() {
+				discovery1.on_packet(&datagramm.payload, ep2.address.clone()).ok();
+			}
+		}
+
+		let pip = vec!["pip".to_owned()];
+		assert!(discovery2.nodes_with_topics(&pip).contains(key1.public()));
+		assert!(discovery1.nodes_with_topics(&pip).is_empty());
+		assert_eq!(discovery1.records.get(key2.public()).map(|r| r.seq()), Some(discovery2.record.seq()));
+	}
+
+	#[test]
+	fn answers_enr_requests_of_bonded_nodes_only() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40350").unwrap(), udp_port: 40350 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40351").unwrap(), udp_port: 40351 };
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), BTreeMap::new());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), BTreeMap::new());
+
+		// no endpoint proof yet
+		discovery1.request_record(key2.public(), &ep2.address, Some(1));
+		let request = discovery1.send_queue.pop_front().unwrap();
+		discovery2.on_packet(&request.payload, ep1.address.clone()).unwrap();
+		assert!(discovery2.send_queue.is_empty());
+
+		// a Pong from another address is no proof either
+		discovery2.ping(&ep1);
+		let ping = discovery2.send_queue.pop_front().unwrap();
+		discovery1.on_packet(&ping.payload, ep2.address.clone()).unwrap();
+		let pong = discovery1.send_queue.pop_front().unwrap();
+		discovery2.on_packet(&pong.payload, SocketAddr::from_str("127.0.0.1:40352").unwrap()).unwrap();
+		discovery2.send_queue.clear();
+		discovery2.on_packet(&request.payload, ep1.address.clone()).unwrap();
+		assert!(discovery2.send_queue.is_empty());
+
+		discovery2.ping(&ep1);
+		let ping = discovery2.send_queue.pop_front().unwrap();
+		discovery1.send_queue.clear();
+		discovery1.on_packet(&ping.payload, ep2.address.clone()).unwrap();
+		let pong = discovery1.send_queue.pop_front().unwrap();
+		discovery2.on_packet(&pong.payload, ep1.address.clone()).unwrap();
+		discovery2.on_packet(&request.payload, ep1.address.clone()).unwrap();
+		assert!(discovery2.send_queue.iter().any(|datagramm| datagramm.payload[32 + 65] == PACKET_ENR_RESPONSE));
+	}
+
+	#[test]
+	fn looks_up_topics_of_known_nodes() {
+		let key1 = Random.generate().unwrap();
+		let key2 = Random.generate().unwrap();
+		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40348").unwrap(), udp_port: 40348 };
+		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40349").unwrap(), udp_port: 40349 };
+		let mut entries = BTreeMap::new();
+		entries.insert(enr::TOPICS_KEY.to_owned(), enr::topics_entry(&["pip".to_owned()]));
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), entries);
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), BTreeMap::new());
+
+		discovery2.init_node_list(vec![NodeEntry { id: key1.public().clone(), endpoint: ep1.clone() }]);
+		assert!(discovery2.send_queue.is_empty());
+
+		let pip = vec!["pip".to_owned()];
+		discovery2.lookup_topics(&pip, 1);
+		for _ in 0 .. 3 {
+			while let Some(datagramm) = discovery2.send_queue.pop_front() {
+				discovery1.on_packet(&datagramm.payload, ep2.address.clone()).ok();
+			}
+			while let Some(datagramm) = discovery1.send_queue.pop_front() {
+				discovery2.on_packet(&datagramm.payload, ep1.address.clone()).ok();
+			}
+		}
+		assert!(discovery2.nodes_with_topics(&pip).contains(key1.public()));
+
+		// enough nodes are known, so there is nothing left to look up.
+		discovery2.lookup_topics(&pip, 1);
+		assert!(discovery2.send_queue.is_empty());
+	}
+
 	#[test]
 	fn removes_expired() {
 		let key = Random.generate().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40446").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), BTreeMap::new());
 		for _ in 0..1200 {
 			discovery.add_node(NodeEntry { id: NodeId::random(), endpoint: ep.clone() });
 		}
@@ -650,7 +956,7 @@ mod tests {
 	fn packets() {
 		let key = Random.generate().unwrap();
 		let ep = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40447").unwrap(), udp_port: 40447 };
-		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default());
+		let mut discovery = Discovery::new(&key, ep.address.clone(), ep.clone(), 0, IpFilter::default(), BTreeMap::new());
 		discovery.check_timestamps = false;
 		let from = SocketAddr::from_str("99.99.99.99:40445").unwrap();
 
@@ -717,8 +1023,8 @@ mod tests {
 		let key2 = Random.generate().unwrap();
 		let ep1 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40344").unwrap(), udp_port: 40344 };
 		let ep2 = NodeEndpoint { address: SocketAddr::from_str("127.0.0.1:40345").unwrap(), udp_port: 40345 };
-		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default());
-		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default());
+		let mut discovery1 = Discovery::new(&key1, ep1.address.clone(), ep1.clone(), 0, IpFilter::default(), BTreeMap::new());
+		let mut discovery2 = Discovery::new(&key2, ep2.address.clone(), ep2.clone(), 0, IpFilter::default(), BTreeMap::new());
 
 		discovery1.ping(&ep2);
 		let ping_data = discovery1.send_queue.pop_front().unwrap();
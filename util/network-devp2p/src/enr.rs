@@ -0,0 +1,306 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Ethereum Node Records (EIP-778) signed with the "v4" identity scheme.
+
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use ethcore_bytes::Bytes;
+use ethereum_types::H256;
+use ethkey::{KeyPair, Signature, sign, recover};
+use hash::keccak;
+use rlp::{Rlp, RlpStream};
+use network::{Error, ErrorKind};
+use node_table::{NodeId, NodeEndpoint};
+
+/// Maximal size of an encoded record.
+pub const MAX_RECORD_SIZE: usize = 300;
+
+/// Key of the list of topics advertised by the node.
+pub const TOPICS_KEY: &'static str = "topics";
+
+/// Signed record of node information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeRecord {
+	/// Public key of the node, recovered from the signature.
+	id: NodeId,
+	/// Sequence number, increased whenever the record changes.
+	seq: u64,
+	/// Sorted key/value pairs. Values are RLP-encoded.
+	pairs: BTreeMap<Vec<u8>, Bytes>,
+	/// Signature over the record content, without the recovery id.
+	signature: Bytes,
+}
+
+impl NodeRecord {
+	/// Create a new record of the node with given key pair. Values of `entries` must be RLP-encoded.
+	pub fn new(key: &KeyPair, seq: u64, entries: &BTreeMap<String, Bytes>) -> Result<NodeRecord, Error> {
+		let mut pairs: BTreeMap<Vec<u8>, Bytes> = entries.iter()
+			.map(|(k, v)| (k.clone().into_bytes(), v.clone()))
+			.collect();
+		pairs.insert(b"id".to_vec(), encode_value(&&b"v4"[..]));
+		pairs.insert(b"secp256k1".to_vec(), encode_value(&&compress(key.public())[..]));
+
+		let signature = sign(key.secret(), &keccak(NodeRecord::content(seq, &pairs)))?;
+		let record = NodeRecord {
+			id: key.public().clone(),
+			seq: seq,
+			pairs: pairs,
+			signature: signature[..64].to_vec(),
+		};
+
+		if record.rlp_bytes().len() > MAX_RECORD_SIZE {
+			bail!(ErrorKind::OversizedPacket);
+		}
+		Ok(record)
+	}
+
+	/// Decode a record and verify its signature.
+	pub fn from_rlp(bytes: &[u8]) -> Result<NodeRecord, Error> {
+		if bytes.len() > MAX_RECORD_SIZE {
+			bail!(ErrorKind::OversizedPacket);
+		}
+
+		let rlp = Rlp::new(bytes);
+		let item_count = rlp.item_count()?;
+		if item_count < 2 || item_count % 2 != 0 {
+			bail!(ErrorKind::BadProtocol);
+		}
+
+		let signature: Vec<u8> = rlp.val_at(0)?;
+		let seq: u64 = rlp.val_at(1)?;
+		let mut pairs = BTreeMap::new();
+		for i in 0..(item_count - 2) / 2 {
+			let key: Vec<u8> = rlp.val_at(2 + 2 * i)?;
+			let value = rlp.at(3 + 2 * i)?.as_raw().to_vec();
+			// keys have to be sorted and unique.
+			if pairs.keys().next_back().map_or(false, |last| *last >= key) {
+				bail!(ErrorKind::BadProtocol);
+			}
+			pairs.insert(key, value);
+		}
+
+		let id: Vec<u8> = match pairs.get(&b"id"[..]) {
+			Some(id) => Rlp::new(id).as_val()?,
+			None => bail!(ErrorKind::BadProtocol),
+		};
+		let public_key: Vec<u8> = match pairs.get(&b"secp256k1"[..]) {
+			Some(key) => Rlp::new(key).as_val()?,
+			None => bail!(ErrorKind::BadProtocol),
+		};
+		if id != b"v4" || signature.len() != 64 || public_key.len() != 33 {
+			bail!(ErrorKind::BadProtocol);
+		}
+
+		// the recovery id is not part of the signature, try both.
+		let hash = keccak(NodeRecord::content(seq, &pairs));
+		let (r, s) = (H256::from_slice(&signature[..32]), H256::from_slice(&signature[32..]));
+		let id = (0..2u8)
+			.filter_map(|v| recover(&Signature::from_rsv(&r, &s, v), &hash).ok())
+			.find(|public| compress(public)[..] == public_key[..]);
+
+		match id {
+			Some(id) => Ok(NodeRecord {
+				id: id,
+				seq: seq,
+				pairs: pairs,
+				signature: signature,
+			}),
+			None => bail!(ErrorKind::BadProtocol),
+		}
+	}
+
+	/// RLP-encoded record.
+	pub fn rlp_bytes(&self) -> Bytes {
+		let mut s = RlpStream::new_list(2 + 2 * self.pairs.len());
+		s.append(&self.signature);
+		s.append(&self.seq);
+		for (key, value) in &self.pairs {
+			s.append(key);
+			s.append_raw(value, 1);
+		}
+		s.out()
+	}
+
+	/// Id of the node.
+	pub fn id(&self) -> &NodeId {
+		&self.id
+	}
+
+	/// Sequence number of the record.
+	pub fn seq(&self) -> u64 {
+		self.seq
+	}
+
+	/// RLP-encoded value of given key.
+	pub fn get(&self, key: &str) -> Option<&[u8]> {
+		self.pairs.get(key.as_bytes()).map(|v| &v[..])
+	}
+
+	/// Endpoint of the node, if it's included in the record.
+	pub fn endpoint(&self) -> Option<NodeEndpoint> {
+		let ip: Vec<u8> = Rlp::new(self.get("ip")?).as_val().ok()?;
+		let ip = match ip.len() {
+			4 => IpAddr::V4(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3])),
+			16 => {
+				let mut octets = [0u8; 16];
+				octets.copy_from_slice(&ip);
+				IpAddr::V6(Ipv6Addr::from(octets))
+			},
+			_ => return None,
+		};
+		let tcp: u16 = Rlp::new(self.get("tcp")?).as_val().ok()?;
+		let udp: u16 = match self.get("udp") {
+			Some(udp) => Rlp::new(udp).as_val().ok()?,
+			None => tcp,
+		};
+
+		Some(NodeEndpoint {
+			address: SocketAddr::new(ip, tcp),
+			udp_port: udp,
+		})
+	}
+
+	/// Topics advertised by the node.
+	pub fn topics(&self) -> Vec<String> {
+		self.get(TOPICS_KEY)
+			.and_then(|topics| Rlp::new(topics).as_list().ok())
+			.unwrap_or_else(Vec::new)
+	}
+
+	/// Whether the node advertises given topic, either as a capability key
+	/// (e.g. `eth` or `pip`) or in its list of topics.
+	pub fn has_topic(&self, topic: &str) -> bool {
+		self.get(topic).is_some() || self.topics().iter().any(|t| t == topic)
+	}
+
+	fn content(seq: u64, pairs: &BTreeMap<Vec<u8>, Bytes>) -> Bytes {
+		let mut s = RlpStream::new_list(1 + 2 * pairs.len());
+		s.append(&seq);
+		for (key, value) in pairs {
+			s.append(key);
+			s.append_raw(value, 1);
+		}
+		s.out()
+	}
+}
+
+/// Record entries describing given endpoint.
+pub fn endpoint_entries(endpoint: &NodeEndpoint) -> BTreeMap<String, Bytes> {
+	let ip = match endpoint.address.ip() {
+		IpAddr::V4(ip) => ip.octets().to_vec(),
+		IpAddr::V6(ip) => ip.octets().to_vec(),
+	};
+
+	let mut entries = BTreeMap::new();
+	entries.insert("ip".to_owned(), encode_value(&ip));
+	entries.insert("tcp".to_owned(), encode_value(&endpoint.address.port()));
+	entries.insert("udp".to_owned(), encode_value(&endpoint.udp_port));
+	entries
+}
+
+/// Record entry with given topics.
+pub fn topics_entry(topics: &[String]) -> Bytes {
+	let mut s = RlpStream::new();
+	s.append_list::<String, _>(topics);
+	s.out()
+}
+
+fn encode_value<E: ::rlp::Encodable>(value: &E) -> Bytes {
+	let mut s = RlpStream::new();
+	s.append(value);
+	s.out()
+}
+
+/// Compressed form of a secp256k1 public key.
+fn compress(public: &NodeId) -> [u8; 33] {
+	let mut compressed = [0u8; 33];
+	compressed[0] = 0x02 | (public[63] & 1);
+	compressed[1..].copy_from_slice(&public[..32]);
+	compressed
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use ethkey::Secret;
+	use rustc_hex::FromHex;
+
+	// https://eips.ethereum.org/EIPS/eip-778#test-vectors
+	const EXAMPLE_RECORD: &'static str = "f884b8407098ad865b00a582051940cb9cf36836572411a47278783077011599ed5cd16b76f2635f4e234738f30813a89eb9137e3e3df5266e3a1f11df72ecf1145ccb9c01826964827634826970847f00000189736563703235366b31a103ca634cae0d49acb401d8a4c6b6fe8c55b70d115bf400769cc1400f3258cd31388375647082765f";
+
+	fn example_key() -> KeyPair {
+		let secret: Secret = "b71c71a67e1177ad4e901695e1b4b9ee17ae16c6668d313eac2f96dbcda3f291".parse().unwrap();
+		KeyPair::from_secret(secret).unwrap()
+	}
+
+	#[test]
+	fn decodes_example_record() {
+		let record = NodeRecord::from_rlp(&EXAMPLE_RECORD.from_hex().unwrap()).unwrap();
+
+		assert_eq!(record.id(), example_key().public());
+		assert_eq!(record.seq(), 1);
+		assert_eq!(record.get("udp"), Some(&[0x82, 0x76, 0x5f][..]));
+		// no tcp port
+		assert_eq!(record.endpoint(), None);
+	}
+
+	#[test]
+	fn encodes_example_record() {
+		let mut entries = BTreeMap::new();
+		entries.insert("ip".to_owned(), encode_value(&&[127u8, 0, 0, 1][..]));
+		entries.insert("udp".to_owned(), encode_value(&30303u16));
+
+		let record = NodeRecord::new(&example_key(), 1, &entries).unwrap();
+
+		assert_eq!(record.rlp_bytes(), EXAMPLE_RECORD.from_hex().unwrap());
+	}
+
+	#[test]
+	fn rejects_modified_record() {
+		let mut bytes = EXAMPLE_RECORD.from_hex().unwrap();
+		// change the last byte of the udp port
+		let last = bytes.len() - 1;
+		bytes[last] ^= 1;
+
+		assert!(NodeRecord::from_rlp(&bytes).is_err());
+	}
+
+	#[test]
+	fn record_roundtrip() {
+		let endpoint = NodeEndpoint { address: "10.0.0.1:30303".parse().unwrap(), udp_port: 30304 };
+		let mut entries = endpoint_entries(&endpoint);
+		entries.insert("pip".to_owned(), topics_entry(&[]));
+		entries.insert(TOPICS_KEY.to_owned(), topics_entry(&["private-net".to_owned()]));
+
+		let record = NodeRecord::new(&example_key(), 5, &entries).unwrap();
+		let decoded = NodeRecord::from_rlp(&record.rlp_bytes()).unwrap();
+
+		assert_eq!(decoded, record);
+		assert_eq!(decoded.endpoint(), Some(endpoint));
+		assert!(decoded.has_topic("pip"));
+		assert!(decoded.has_topic("private-net"));
+		assert!(!decoded.has_topic("eth"));
+	}
+
+	#[test]
+	fn rejects_oversized_record() {
+		let mut entries = BTreeMap::new();
+		entries.insert(TOPICS_KEY.to_owned(), topics_entry(&vec!["topic".to_owned(); 64]));
+
+		assert!(NodeRecord::new(&example_key(), 1, &entries).is_err());
+	}
+}
@@ -15,7 +15,7 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, BTreeMap};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
@@ -24,14 +24,15 @@ use std::cmp::{min, max};
 use std::path::{Path, PathBuf};
 use std::io::{Read, Write, self};
 use std::fs;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
+use ethcore_bytes::Bytes;
 use ethkey::{KeyPair, Secret, Random, Generator};
 use hash::keccak;
 use mio::*;
 use mio::deprecated::{EventLoop};
 use mio::tcp::*;
 use ethereum_types::H256;
-use rlp::{RlpStream, Encodable};
+use rlp::{RlpStream, Encodable, EMPTY_LIST_RLP};
 
 use session::{Session, SessionData};
 use io::*;
@@ -40,9 +41,11 @@ use node_table::*;
 use network::{NetworkConfiguration, NetworkIoMessage, ProtocolId, PeerId, PacketId};
 use network::{NonReservedPeerMode, NetworkContext as NetworkContextTrait};
 use network::HostInfo as HostInfoTrait;
-use network::{SessionInfo, Error, ErrorKind, DisconnectReason, NetworkProtocolHandler};
+use network::{SessionInfo, Error, ErrorKind, DisconnectReason, NetworkProtocolHandler, PeerBehaviour};
 use discovery::{Discovery, TableUpdates, NodeEntry};
-use ip_utils::{map_external_address, select_public_address};
+use enr;
+use ip_utils::select_public_address;
+use nat::{self, PortMappingsMaintainer};
 use path::restrict_permissions_owner;
 use parking_lot::{Mutex, RwLock};
 use connection_filter::{ConnectionFilter, ConnectionDirection};
@@ -61,6 +64,7 @@ const DISCOVERY: StreamToken = SYS_TIMER + 3;
 const DISCOVERY_REFRESH: TimerToken = SYS_TIMER + 4;
 const DISCOVERY_ROUND: TimerToken = SYS_TIMER + 5;
 const NODE_TABLE: TimerToken = SYS_TIMER + 6;
+const NAT_MAPPING: TimerToken = SYS_TIMER + 7;
 const FIRST_SESSION: StreamToken = 0;
 const LAST_SESSION: StreamToken = FIRST_SESSION + MAX_SESSIONS - 1;
 const USER_TIMER: TimerToken = LAST_SESSION + 256;
@@ -75,6 +79,8 @@ const DISCOVERY_REFRESH_TIMEOUT: Duration = Duration::from_secs(60);
 const DISCOVERY_ROUND_TIMEOUT: Duration = Duration::from_millis(300);
 // for NODE_TABLE TimerToken
 const NODE_TABLE_TIMEOUT: Duration = Duration::from_secs(300);
+// for NAT_MAPPING TimerToken
+const NAT_MAPPING_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[derive(Debug, PartialEq, Eq)]
 /// Protocol info
@@ -102,7 +108,7 @@ pub struct NetworkContext<'s> {
 	sessions: Arc<RwLock<Slab<SharedSession>>>,
 	session: Option<SharedSession>,
 	session_id: Option<StreamToken>,
-	_reserved_peers: &'s HashSet<NodeId>,
+	reserved_peers: &'s HashSet<NodeId>,
 }
 
 impl<'s> NetworkContext<'s> {
@@ -121,7 +127,7 @@ impl<'s> NetworkContext<'s> {
 			session_id: id,
 			session: session,
 			sessions: sessions,
-			_reserved_peers: reserved_peers,
+			reserved_peers: reserved_peers,
 		}
 	}
 
@@ -167,6 +173,11 @@ impl<'s> NetworkContextTrait for NetworkContext<'s> {
 			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
 	}
 
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour) {
+		self.io.message(NetworkIoMessage::ReportPeer(peer, behaviour))
+			.unwrap_or_else(|e| warn!("Error sending network IO message: {:?}", e));
+	}
+
 	fn is_expired(&self) -> bool {
 		self.session.as_ref().map_or(false, |s| s.lock().expired())
 	}
@@ -194,6 +205,12 @@ impl<'s> NetworkContextTrait for NetworkContext<'s> {
 	}
 
 	fn subprotocol_name(&self) -> ProtocolId { self.protocol }
+
+	fn is_reserved_peer(&self, peer: PeerId) -> bool {
+		self.session_info(peer)
+			.and_then(|info| info.id)
+			.map_or(false, |id| self.reserved_peers.contains(&id))
+	}
 }
 
 /// Shared host information
@@ -247,6 +264,8 @@ pub struct Host {
 	tcp_listener: Mutex<TcpListener>,
 	sessions: Arc<RwLock<Slab<SharedSession>>>,
 	discovery: Mutex<Option<Discovery>>,
+	record_entries: Mutex<BTreeMap<String, Bytes>>,
+	port_mappings: Mutex<Option<PortMappingsMaintainer>>,
 	nodes: RwLock<NodeTable>,
 	handlers: RwLock<HashMap<ProtocolId, Arc<NetworkProtocolHandler + Sync>>>,
 	timers: RwLock<HashMap<TimerToken, ProtocolTimer>>,
@@ -287,6 +306,10 @@ impl Host {
 
 		let boot_nodes = config.boot_nodes.clone();
 		let reserved_nodes = config.reserved_nodes.clone();
+		let mut record_entries = BTreeMap::new();
+		if !config.discovery_topics.is_empty() {
+			record_entries.insert(enr::TOPICS_KEY.to_owned(), enr::topics_entry(&config.discovery_topics));
+		}
 		config.max_handshakes = min(config.max_handshakes, MAX_HANDSHAKES as u32);
 
 		let mut host = Host {
@@ -300,6 +323,8 @@ impl Host {
 				local_endpoint: local_endpoint,
 			}),
 			discovery: Mutex::new(None),
+			record_entries: Mutex::new(record_entries),
+			port_mappings: Mutex::new(None),
 			tcp_listener: Mutex::new(tcp_listener),
 			sessions: Arc::new(RwLock::new(Slab::new_starting_at(FIRST_SESSION, MAX_SESSIONS))),
 			nodes: RwLock::new(NodeTable::new(path)),
@@ -388,6 +413,21 @@ impl Host {
 		Ok(())
 	}
 
+	/// Nodes currently banned for bad behaviour, along with the time their bans expire.
+	pub fn banned_nodes(&self) -> Vec<(NodeId, SystemTime)> {
+		self.nodes.read().banned()
+	}
+
+	/// Lift the ban of a node. Returns `false` if the node was not banned.
+	pub fn unban_node(&self, id: &NodeId) -> bool {
+		self.nodes.write().unban(id)
+	}
+
+	/// Lift all bans.
+	pub fn clear_bans(&self) {
+		self.nodes.write().clear_bans();
+	}
+
 	pub fn external_url(&self) -> Option<String> {
 		let info = self.info.read();
 		info.public_endpoint.as_ref().map(|e| format!("{}", Node::new(info.id().clone(), e.clone())))
@@ -410,6 +450,9 @@ impl Host {
 			trace!(target: "network", "Disconnecting on shutdown: {}", p);
 			self.kill_connection(p, io, true);
 		}
+		if let Some(mut mappings) = self.port_mappings.lock().take() {
+			mappings.stop();
+		}
 		io.unregister_handler()?;
 		Ok(())
 	}
@@ -440,9 +483,11 @@ impl Host {
 				let public_address = select_public_address(local_endpoint.address.port());
 				let public_endpoint = NodeEndpoint { address: public_address, udp_port: local_endpoint.udp_port };
 				if self.info.read().config.nat_enabled {
-					match map_external_address(&local_endpoint) {
-						Some(endpoint) => {
-							info!("NAT mapped to external address {}", endpoint.address);
+					match nat::map_ports(&local_endpoint) {
+						Some(mappings) => {
+							let endpoint = mappings.endpoint();
+							info!("NAT mapped to external address {} with {}", endpoint.address, mappings.protocol());
+							*self.port_mappings.lock() = Some(PortMappingsMaintainer::start(mappings, NAT_MAPPING_TIMEOUT)?);
 							endpoint
 						},
 						None => public_endpoint
@@ -466,7 +511,7 @@ impl Host {
 			if info.config.discovery_enabled && info.config.non_reserved_mode == NonReservedPeerMode::Accept {
 				let mut udp_addr = local_endpoint.address.clone();
 				udp_addr.set_port(local_endpoint.udp_port);
-				Some(Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, allow_ips))
+				Some(Discovery::new(&info.keys, udp_addr, public_endpoint, DISCOVERY, allow_ips, self.record_entries.lock().clone()))
 			} else { None }
 		};
 
@@ -478,11 +523,30 @@ impl Host {
 			io.register_timer(DISCOVERY_REFRESH, DISCOVERY_REFRESH_TIMEOUT)?;
 			io.register_timer(DISCOVERY_ROUND, DISCOVERY_ROUND_TIMEOUT)?;
 		}
+		if self.port_mappings.lock().is_some() {
+			io.register_timer(NAT_MAPPING, NAT_MAPPING_TIMEOUT)?;
+		}
 		io.register_timer(NODE_TABLE, NODE_TABLE_TIMEOUT)?;
 		io.register_stream(TCP_ACCEPT)?;
 		Ok(())
 	}
 
+	/// Advertise the new public endpoint if the NAT port mappings maintainer saw the external address change.
+	fn maintain_port_mappings(&self, io: &IoContext<NetworkIoMessage>) {
+		let endpoint = self.port_mappings.lock().as_ref().and_then(|mappings| mappings.take_changed_endpoint());
+
+		if let Some(endpoint) = endpoint {
+			info!("NAT external address changed to {}", endpoint.address);
+			self.info.write().public_endpoint = Some(endpoint.clone());
+			if let Some(ref mut discovery) = *self.discovery.lock() {
+				discovery.set_public_endpoint(endpoint);
+			}
+			if let Some(url) = self.external_url() {
+				io.message(NetworkIoMessage::NetworkStarted(url)).unwrap_or_else(|e| warn!("Error sending IO notification: {:?}", e));
+			}
+		}
+	}
+
 	fn maintain_network(&self, io: &IoContext<NetworkIoMessage>) {
 		self.keep_alive(io);
 		self.connect_peers(io);
@@ -554,10 +618,15 @@ impl Host {
 			return;
 		}
 
-		// iterate over all nodes, reserved ones coming first.
+		// iterate over all nodes, reserved ones coming first, followed by the ones advertising
+		// our topics (or capabilities, if there are none) in their node records.
 		// if we are pinned to only reserved nodes, ignore all others.
 		let nodes = reserved_nodes.iter().cloned().chain(if !pin {
-			self.nodes.read().nodes(allow_ips)
+			let mut nodes = self.nodes.read().nodes(allow_ips);
+			let topics = self.preferred_topics();
+			let preferred = self.discovery.lock().as_ref().map_or_else(HashSet::new, |d| d.nodes_with_topics(&topics));
+			nodes.sort_by_key(|id| !preferred.contains(id));
+			nodes
 		} else {
 			Vec::new()
 		});
@@ -576,6 +645,25 @@ impl Host {
 		debug!(target: "network", "Connecting peers: {} sessions, {} pending + {} started", egress_count + ingress_count, handshake_count, started);
 	}
 
+	fn preferred_topics(&self) -> Vec<String> {
+		let info = self.info.read();
+		if !info.config.discovery_topics.is_empty() {
+			return info.config.discovery_topics.clone();
+		}
+		let mut capabilities: Vec<String> = info.capabilities.iter().map(|c| String::from_utf8_lossy(&c.protocol).into_owned()).collect();
+		capabilities.dedup();
+		capabilities
+	}
+
+	/// Update entries of the local node record and propagate them to discovery.
+	fn update_record_entries<F>(&self, f: F) where F: FnOnce(&mut BTreeMap<String, Bytes>) {
+		let mut entries = self.record_entries.lock();
+		f(&mut *entries);
+		if let Some(ref mut discovery) = *self.discovery.lock() {
+			discovery.set_record_entries(entries.clone());
+		}
+	}
+
 	fn connect_peer(&self, id: &NodeId, io: &IoContext<NetworkIoMessage>) {
 		if self.have_session(id) {
 			trace!(target: "network", "Aborted connect. Node already connected.");
@@ -699,6 +787,13 @@ impl Host {
 										}
 									}
 								},
+								ErrorKind::BadProtocol => {
+									if let Some(id) = s.id() {
+										if !self.reserved_nodes.read().contains(id) {
+											self.nodes.write().report(id, PeerBehaviour::BadProtocol);
+										}
+									}
+								},
 								_ => {},
 							}
 							kill = true;
@@ -738,6 +833,13 @@ impl Host {
 								}
 							}
 
+							if self.nodes.read().is_banned(&id) && !self.reserved_nodes.read().contains(&id) {
+								trace!(target: "network", "Rejected connection of banned node {:?}", id);
+								s.disconnect(io, DisconnectReason::UselessPeer);
+								kill = true;
+								break;
+							}
+
 							if !self.filter.as_ref().map_or(true, |f| f.connection_allowed(&self_id, &id, ConnectionDirection::Inbound)) {
 								trace!(target: "network", "Inbound connection not allowed for {:?}", id);
 								s.disconnect(io, DisconnectReason::UnexpectedIdentity);
@@ -962,7 +1064,14 @@ impl IoHandler<NetworkIoMessage> for Host {
 				io.update_registration(DISCOVERY).unwrap_or_else(|e| debug!("Error updating discovery registration: {:?}", e));
 			},
 			DISCOVERY_ROUND => {
-				let node_changes = { self.discovery.lock().as_mut().map_or(None, |d| d.round()) };
+				let topics = self.preferred_topics();
+				let min_peers = self.info.read().config.min_peers as usize;
+				let node_changes = {
+					self.discovery.lock().as_mut().map_or(None, |d| {
+						d.lookup_topics(&topics, min_peers);
+						d.round()
+					})
+				};
 				if let Some(node_changes) = node_changes {
 					self.update_nodes(io, node_changes);
 				}
@@ -971,8 +1080,10 @@ impl IoHandler<NetworkIoMessage> for Host {
 			NODE_TABLE => {
 				trace!(target: "network", "Refreshing node table");
 				self.nodes.write().clear_useless();
+				self.nodes.write().prune_reputations();
 				self.nodes.write().save();
 			},
+			NAT_MAPPING => self.maintain_port_mappings(io),
 			_ => match self.timers.read().get(&token).cloned() {
 				Some(timer) => match self.handlers.read().get(&timer.protocol).cloned() {
 					None => { warn!(target: "network", "No handler found for protocol: {:?}", timer.protocol) },
@@ -1003,14 +1114,25 @@ impl IoHandler<NetworkIoMessage> for Host {
 					&*self.info.read(),
 				);
 				self.handlers.write().insert(*protocol, h);
-				let mut info = self.info.write();
-				for &(version, packet_count) in versions {
-					info.capabilities.push(CapabilityInfo {
-						protocol: *protocol,
-						version,
-						packet_count,
-					});
+				{
+					let mut info = self.info.write();
+					for &(version, packet_count) in versions {
+						info.capabilities.push(CapabilityInfo {
+							protocol: *protocol,
+							version,
+							packet_count,
+						});
+					}
 				}
+				// advertise the protocol in the node record, unless the handler has set the entry already.
+				let key = String::from_utf8_lossy(protocol).into_owned();
+				self.update_record_entries(|entries| { entries.entry(key).or_insert_with(|| EMPTY_LIST_RLP.to_vec()); });
+			},
+			NetworkIoMessage::SetNodeRecordEntry {
+				ref key,
+				ref value,
+			} => {
+				self.update_record_entries(|entries| { entries.insert(key.clone(), value.clone()); });
 			},
 			NetworkIoMessage::AddTimer {
 				ref protocol,
@@ -1048,6 +1170,21 @@ impl IoHandler<NetworkIoMessage> for Host {
 				trace!(target: "network", "Disabling peer {}", peer);
 				self.kill_connection(*peer, io, false);
 			},
+			NetworkIoMessage::ReportPeer(ref peer, ref behaviour) => {
+				let session = { self.sessions.read().get(*peer).cloned() };
+				let id = session.as_ref().and_then(|s| s.lock().id().cloned());
+				let banned = match id {
+					Some(ref id) if !self.reserved_nodes.read().contains(id) => self.nodes.write().report(id, *behaviour),
+					_ => false,
+				};
+				if banned {
+					if let Some(session) = session {
+						session.lock().disconnect(io, DisconnectReason::UselessPeer);
+					}
+					debug!(target: "network", "Banned peer {} after {:?}", peer, behaviour);
+					self.kill_connection(*peer, io, false);
+				}
+			},
 			NetworkIoMessage::InitPublicInterface =>
 				self.init_public_interface(io).unwrap_or_else(|e| warn!("Error initializing public interface: {:?}", e)),
 			_ => {}	// ignore others.
@@ -18,9 +18,6 @@
 
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
 use std::io;
-use igd::{PortMappingProtocol, search_gateway_from_timeout};
-use std::time::Duration;
-use node_table::{NodeEndpoint};
 use ipnetwork::{IpNetwork};
 
 /// Socket address extension for rustc beta. To be replaces with now unstable API
@@ -317,52 +314,12 @@ pub fn select_public_address(port: u16) -> SocketAddr {
 	SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), port))
 }
 
-pub fn map_external_address(local: &NodeEndpoint) -> Option<NodeEndpoint> {
-	if let SocketAddr::V4(ref local_addr) = local.address {
-		match search_gateway_from_timeout(local_addr.ip().clone(), Duration::new(5, 0)) {
-			Err(ref err) => debug!("Gateway search error: {}", err),
-			Ok(gateway) => {
-				match gateway.get_external_ip() {
-					Err(ref err) => {
-						debug!("IP request error: {}", err);
-					},
-					Ok(external_addr) => {
-						match gateway.add_any_port(PortMappingProtocol::TCP, SocketAddrV4::new(local_addr.ip().clone(), local_addr.port()), 0, "Parity Node/TCP") {
-							Err(ref err) => {
-								debug!("Port mapping error: {}", err);
-							},
-							Ok(tcp_port) => {
-								match gateway.add_any_port(PortMappingProtocol::UDP, SocketAddrV4::new(local_addr.ip().clone(), local.udp_port), 0, "Parity Node/UDP") {
-									Err(ref err) => {
-										debug!("Port mapping error: {}", err);
-									},
-									Ok(udp_port) => {
-										return Some(NodeEndpoint { address: SocketAddr::V4(SocketAddrV4::new(external_addr, tcp_port)), udp_port: udp_port });
-									},
-								}
-							},
-						}
-					},
-				}
-			},
-		}
-	}
-	None
-}
-
 #[test]
 fn can_select_public_address() {
 	let pub_address = select_public_address(40477);
 	assert!(pub_address.port() == 40477);
 }
 
-#[ignore]
-#[test]
-fn can_map_external_address_or_fail() {
-	let pub_address = select_public_address(40478);
-	let _ = map_external_address(&NodeEndpoint { address: pub_address, udp_port: 40478 });
-}
-
 #[test]
 fn ipv4_properties() {
 	fn check(octets: &[u8; 4], unspec: bool, loopback: bool,
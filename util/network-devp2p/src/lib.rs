@@ -103,9 +103,11 @@ mod connection;
 mod handshake;
 mod session;
 mod discovery;
+mod enr;
 mod service;
 mod node_table;
 mod ip_utils;
+mod nat;
 mod connection_filter;
 
 pub use service::NetworkService;
@@ -0,0 +1,728 @@
+// Copyright 2015-2018 Parity Technologies (UK) Ltd.
+// This file is part of Parity.
+
+// Parity is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Parity is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Parity.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Port mappings on NAT gateways with UPnP IGD, NAT-PMP (RFC 6886) or PCP (RFC 6887).
+//! Mappings are leased, renewed before the lease expires and removed on shutdown.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::{Duration, Instant};
+use igd::{self, PortMappingProtocol, search_gateway_from_timeout};
+use parking_lot::Mutex;
+use rand;
+use network::Error;
+use node_table::NodeEndpoint;
+
+/// Lease requested for port mappings.
+pub const MAPPING_LEASE: Duration = Duration::from_secs(20 * 60);
+/// How often the external address of the gateway is checked between renewals.
+const EXTERNAL_ADDRESS_CHECK: Duration = Duration::from_secs(5 * 60);
+/// Timeout of the UPnP gateway search.
+const IGD_SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+/// Port NAT-PMP and PCP servers listen on.
+const PMP_PORT: u16 = 5351;
+/// Timeout of the first NAT-PMP/PCP request, doubled on every retry.
+const PMP_INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+const PMP_RETRIES: usize = 4;
+const NAT_PMP_VERSION: u8 = 0;
+const PCP_VERSION: u8 = 2;
+const PCP_OPCODE_MAP: u8 = 1;
+/// Result code of both NAT-PMP and PCP for requests with an unsupported version.
+const UNSUPPORTED_VERSION: u8 = 1;
+
+const MAPPING_DESCRIPTION: &'static str = "Parity Node";
+
+/// Transport protocol of a port mapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protocol {
+	Tcp,
+	Udp,
+}
+
+/// Gateway able to map external ports to the local node.
+pub trait PortMapper: Send {
+	/// Name of the mapping protocol.
+	fn name(&self) -> &'static str;
+
+	/// External address of the gateway.
+	fn external_ip(&mut self) -> Result<Ipv4Addr, Error>;
+
+	/// Map `local` for the duration of `lease`, preferably to `external_port` or to any port if it's 0.
+	/// Returns the mapped external port.
+	fn add_mapping(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16, lease: Duration) -> Result<u16, Error>;
+
+	/// Remove a mapping created with `add_mapping`.
+	fn remove_mapping(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16) -> Result<(), Error>;
+}
+
+/// UPnP Internet Gateway Device.
+pub struct IgdMapper {
+	gateway: igd::Gateway,
+}
+
+impl IgdMapper {
+	pub fn new(gateway: igd::Gateway) -> Self {
+		IgdMapper {
+			gateway: gateway,
+		}
+	}
+
+	/// Look for a gateway in the local network.
+	pub fn search(local: Ipv4Addr) -> Option<Self> {
+		match search_gateway_from_timeout(local, IGD_SEARCH_TIMEOUT) {
+			Ok(gateway) => Some(IgdMapper::new(gateway)),
+			Err(ref err) => {
+				debug!(target: "network", "Gateway search error: {}", err);
+				None
+			},
+		}
+	}
+
+	fn map(&self, protocol: PortMappingProtocol, local: SocketAddrV4, external_port: u16, lease: u32) -> Result<u16, Error> {
+		match external_port {
+			0 => self.gateway.add_any_port(protocol, local, lease, MAPPING_DESCRIPTION)
+				.map_err(|e| format!("Port mapping error: {}", e).into()),
+			port => self.gateway.add_port(protocol, port, local, lease, MAPPING_DESCRIPTION)
+				.map(|_| port)
+				.map_err(|e| format!("Port mapping error: {}", e).into()),
+		}
+	}
+}
+
+fn igd_protocol(protocol: Protocol) -> PortMappingProtocol {
+	match protocol {
+		Protocol::Tcp => PortMappingProtocol::TCP,
+		Protocol::Udp => PortMappingProtocol::UDP,
+	}
+}
+
+impl PortMapper for IgdMapper {
+	fn name(&self) -> &'static str {
+		"UPnP"
+	}
+
+	fn external_ip(&mut self) -> Result<Ipv4Addr, Error> {
+		self.gateway.get_external_ip().map_err(|e| format!("IP request error: {}", e).into())
+	}
+
+	fn add_mapping(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16, lease: Duration) -> Result<u16, Error> {
+		let protocol = igd_protocol(protocol);
+		// some gateways only support permanent mappings.
+		self.map(protocol, local, external_port, lease.as_secs() as u32)
+			.or_else(|_| self.map(protocol, local, external_port, 0))
+	}
+
+	fn remove_mapping(&mut self, protocol: Protocol, _local: SocketAddrV4, external_port: u16) -> Result<(), Error> {
+		self.gateway.remove_port(igd_protocol(protocol), external_port).map_err(|e| format!("Port removal error: {}", e).into())
+	}
+}
+
+/// Gateway speaking PCP, or NAT-PMP if it doesn't support PCP.
+pub struct PmpMapper {
+	gateway: SocketAddr,
+	local: Ipv4Addr,
+	version: u8,
+	/// Nonce identifying our PCP mappings.
+	nonce: [u8; 12],
+	/// External address assigned to the last PCP mapping. PCP has no other way to query it.
+	pcp_external_ip: Option<Ipv4Addr>,
+}
+
+impl PmpMapper {
+	pub fn new(gateway: SocketAddr, local: Ipv4Addr) -> Self {
+		PmpMapper {
+			gateway: gateway,
+			local: local,
+			version: PCP_VERSION,
+			nonce: rand::random(),
+			pcp_external_ip: None,
+		}
+	}
+
+	/// Send a request built for the address of the local interface and wait for the response.
+	fn request<F>(&self, build: F) -> Result<Vec<u8>, Error> where F: Fn(Ipv4Addr) -> Vec<u8> {
+		let socket = UdpSocket::bind(SocketAddr::new(IpAddr::V4(self.local), 0))?;
+		socket.connect(self.gateway)?;
+		let client = match socket.local_addr()? {
+			SocketAddr::V4(addr) => *addr.ip(),
+			SocketAddr::V6(_) => bail!("NAT-PMP requires IPv4"),
+		};
+		let request = build(client);
+
+		let mut timeout = PMP_INITIAL_TIMEOUT;
+		let mut buf = [0u8; 1100];
+		for _ in 0..PMP_RETRIES {
+			socket.send(&request)?;
+			socket.set_read_timeout(Some(timeout))?;
+			match socket.recv(&mut buf) {
+				Ok(len) => return Ok(buf[..len].to_vec()),
+				Err(ref e) if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => timeout = timeout * 2,
+				Err(e) => return Err(e.into()),
+			}
+		}
+		bail!("No response from the NAT-PMP gateway")
+	}
+
+	/// Create or delete (with a zero lifetime) a PCP mapping. Returns `None` if the gateway doesn't support PCP.
+	fn pcp_map(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16, lifetime: u32) -> Result<Option<u16>, Error> {
+		let nonce = self.nonce;
+		let response = self.request(|client| {
+			let mut request = vec![0u8; 60];
+			request[0] = PCP_VERSION;
+			request[1] = PCP_OPCODE_MAP;
+			put_u32(&mut request[4..8], lifetime);
+			request[8..24].copy_from_slice(&client.to_ipv6_mapped().octets());
+			request[24..36].copy_from_slice(&nonce);
+			request[36] = match protocol { Protocol::Tcp => 6, Protocol::Udp => 17 };
+			put_u16(&mut request[40..42], local.port());
+			put_u16(&mut request[42..44], external_port);
+			request[44..60].copy_from_slice(&Ipv4Addr::new(0, 0, 0, 0).to_ipv6_mapped().octets());
+			request
+		})?;
+
+		// NAT-PMP only gateways respond with their own version.
+		if response.len() < 4 || response[0] != PCP_VERSION || response[3] == UNSUPPORTED_VERSION {
+			return Ok(None);
+		}
+		if response.len() < 60 || response[1] != 0x80 | PCP_OPCODE_MAP || response[24..36] != nonce[..] {
+			bail!("Invalid PCP response");
+		}
+		if response[3] != 0 {
+			bail!(format!("PCP error code {}", response[3]));
+		}
+		let ip = &response[56..60];
+		self.pcp_external_ip = Some(Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]));
+		Ok(Some(get_u16(&response[42..44])))
+	}
+
+	/// Create or delete (with a zero lifetime) a NAT-PMP mapping.
+	fn pmp_map(&self, protocol: Protocol, local: SocketAddrV4, external_port: u16, lifetime: u32) -> Result<u16, Error> {
+		let opcode = match protocol { Protocol::Udp => 1, Protocol::Tcp => 2 };
+		let response = self.request(|_| {
+			let mut request = vec![0u8; 12];
+			request[1] = opcode;
+			put_u16(&mut request[4..6], local.port());
+			put_u16(&mut request[6..8], external_port);
+			put_u32(&mut request[8..12], lifetime);
+			request
+		})?;
+
+		check_pmp_response(&response, opcode, 16)?;
+		Ok(get_u16(&response[10..12]))
+	}
+
+	fn map(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16, lifetime: u32) -> Result<u16, Error> {
+		if self.version == PCP_VERSION {
+			match self.pcp_map(protocol, local, external_port, lifetime)? {
+				Some(port) => return Ok(port),
+				None => {
+					trace!(target: "network", "Gateway doesn't support PCP, falling back to NAT-PMP");
+					self.version = NAT_PMP_VERSION;
+				},
+			}
+		}
+		self.pmp_map(protocol, local, external_port, lifetime)
+	}
+}
+
+fn check_pmp_response(response: &[u8], opcode: u8, len: usize) -> Result<(), Error> {
+	if response.len() < 4 || response[0] != NAT_PMP_VERSION || response[1] != 0x80 | opcode {
+		bail!("Invalid NAT-PMP response");
+	}
+	match get_u16(&response[2..4]) {
+		0 if response.len() >= len => Ok(()),
+		0 => bail!("Invalid NAT-PMP response"),
+		code => bail!(format!("NAT-PMP error code {}", code)),
+	}
+}
+
+impl PortMapper for PmpMapper {
+	fn name(&self) -> &'static str {
+		match self.version {
+			PCP_VERSION => "PCP",
+			_ => "NAT-PMP",
+		}
+	}
+
+	fn external_ip(&mut self) -> Result<Ipv4Addr, Error> {
+		if self.version == PCP_VERSION {
+			return self.pcp_external_ip.ok_or_else(|| "No PCP mapping".into());
+		}
+
+		let response = self.request(|_| vec![NAT_PMP_VERSION, 0])?;
+		check_pmp_response(&response, 0, 12)?;
+		Ok(Ipv4Addr::new(response[8], response[9], response[10], response[11]))
+	}
+
+	fn add_mapping(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16, lease: Duration) -> Result<u16, Error> {
+		self.map(protocol, local, external_port, lease.as_secs() as u32)
+	}
+
+	fn remove_mapping(&mut self, protocol: Protocol, local: SocketAddrV4, external_port: u16) -> Result<(), Error> {
+		let external_port = match self.version {
+			PCP_VERSION => external_port,
+			_ => 0,
+		};
+		self.map(protocol, local, external_port, 0).map(|_| ())
+	}
+}
+
+fn put_u16(buf: &mut [u8], value: u16) {
+	buf[0] = (value >> 8) as u8;
+	buf[1] = value as u8;
+}
+
+fn put_u32(buf: &mut [u8], value: u32) {
+	put_u16(&mut buf[0..2], (value >> 16) as u16);
+	put_u16(&mut buf[2..4], value as u16);
+}
+
+fn get_u16(buf: &[u8]) -> u16 {
+	((buf[0] as u16) << 8) | buf[1] as u16
+}
+
+/// TCP and UDP port mappings of the local node on a NAT gateway.
+pub struct PortMappings {
+	mapper: Box<PortMapper>,
+	local: SocketAddrV4,
+	local_udp_port: u16,
+	external_ip: Ipv4Addr,
+	tcp_port: u16,
+	udp_port: u16,
+	lease: Duration,
+	renewed: Instant,
+	checked: Instant,
+}
+
+impl PortMappings {
+	/// Map the ports of the local endpoint on given gateway.
+	pub fn new(mut mapper: Box<PortMapper>, local: &NodeEndpoint, lease: Duration) -> Result<Self, Error> {
+		let local_address = match local.address {
+			SocketAddr::V4(address) => address,
+			SocketAddr::V6(_) => bail!("Port mapping requires an IPv4 address"),
+		};
+		let local_udp = SocketAddrV4::new(*local_address.ip(), local.udp_port);
+
+		let tcp_port = mapper.add_mapping(Protocol::Tcp, local_address, 0, lease)?;
+		let udp_port = match mapper.add_mapping(Protocol::Udp, local_udp, 0, lease) {
+			Ok(port) => port,
+			Err(e) => {
+				let _ = mapper.remove_mapping(Protocol::Tcp, local_address, tcp_port);
+				return Err(e);
+			},
+		};
+		let external_ip = match mapper.external_ip() {
+			Ok(ip) => ip,
+			Err(e) => {
+				let _ = mapper.remove_mapping(Protocol::Tcp, local_address, tcp_port);
+				let _ = mapper.remove_mapping(Protocol::Udp, local_udp, udp_port);
+				return Err(e);
+			},
+		};
+
+		let now = Instant::now();
+		Ok(PortMappings {
+			mapper: mapper,
+			local: local_address,
+			local_udp_port: local.udp_port,
+			external_ip: external_ip,
+			tcp_port: tcp_port,
+			udp_port: udp_port,
+			lease: lease,
+			renewed: now,
+			checked: now,
+		})
+	}
+
+	/// Name of the mapping protocol.
+	pub fn protocol(&self) -> &'static str {
+		self.mapper.name()
+	}
+
+	/// Endpoint of the node on the external side of the gateway.
+	pub fn endpoint(&self) -> NodeEndpoint {
+		NodeEndpoint {
+			address: SocketAddr::V4(SocketAddrV4::new(self.external_ip, self.tcp_port)),
+			udp_port: self.udp_port,
+		}
+	}
+
+	/// Renew the mappings once half of the lease passed and check the external address of the gateway.
+	/// Returns the new endpoint if it changed.
+	pub fn maintain(&mut self, now: Instant) -> Result<Option<NodeEndpoint>, Error> {
+		let endpoint = self.endpoint();
+		let renew = now >= self.renewed + self.lease / 2;
+		if renew {
+			let local_udp = SocketAddrV4::new(*self.local.ip(), self.local_udp_port);
+			// the gateway might have lost the mappings and given the ports away in the meantime.
+			let (local, tcp_port, lease) = (self.local, self.tcp_port, self.lease);
+			self.tcp_port = self.mapper.add_mapping(Protocol::Tcp, local, tcp_port, lease)
+				.or_else(|_| self.mapper.add_mapping(Protocol::Tcp, local, 0, lease))?;
+			let udp_port = self.udp_port;
+			self.udp_port = self.mapper.add_mapping(Protocol::Udp, local_udp, udp_port, lease)
+				.or_else(|_| self.mapper.add_mapping(Protocol::Udp, local_udp, 0, lease))?;
+			self.renewed = now;
+		}
+		if renew || now >= self.checked + EXTERNAL_ADDRESS_CHECK {
+			self.external_ip = self.mapper.external_ip()?;
+			self.checked = now;
+		}
+
+		let new_endpoint = self.endpoint();
+		match new_endpoint == endpoint {
+			true => Ok(None),
+			false => Ok(Some(new_endpoint)),
+		}
+	}
+
+	/// Remove the mappings from the gateway.
+	pub fn remove(&mut self) {
+		let local_udp = SocketAddrV4::new(*self.local.ip(), self.local_udp_port);
+		let (local, tcp_port, udp_port) = (self.local, self.tcp_port, self.udp_port);
+		if let Err(e) = self.mapper.remove_mapping(Protocol::Tcp, local, tcp_port) {
+			debug!(target: "network", "Error removing TCP port mapping: {}", e);
+		}
+		if let Err(e) = self.mapper.remove_mapping(Protocol::Udp, local_udp, udp_port) {
+			debug!(target: "network", "Error removing UDP port mapping: {}", e);
+		}
+	}
+}
+
+/// Keeps port mappings alive on a dedicated thread, so that the blocking gateway requests
+/// never stall the network IO loop.
+pub struct PortMappingsMaintainer {
+	changed: Arc<Mutex<Option<NodeEndpoint>>>,
+	stop: Option<mpsc::Sender<()>>,
+	thread: Option<thread::JoinHandle<()>>,
+}
+
+impl PortMappingsMaintainer {
+	/// Start maintaining the mappings every `interval`. The mappings are removed from the gateway once stopped.
+	pub fn start(mappings: PortMappings, interval: Duration) -> io::Result<Self> {
+		let changed = Arc::new(Mutex::new(None));
+		let (stop, stopped) = mpsc::channel();
+		let thread = {
+			let changed = changed.clone();
+			let mut mappings = mappings;
+			thread::Builder::new().name("NAT mappings".into()).spawn(move || {
+				while let Err(mpsc::RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+					match mappings.maintain(Instant::now()) {
+						Ok(Some(endpoint)) => *changed.lock() = Some(endpoint),
+						Ok(None) => {},
+						Err(e) => warn!(target: "network", "Error renewing NAT port mappings: {}", e),
+					}
+				}
+				mappings.remove();
+			})?
+		};
+
+		Ok(PortMappingsMaintainer {
+			changed: changed,
+			stop: Some(stop),
+			thread: Some(thread),
+		})
+	}
+
+	/// Take the new external endpoint if it changed since the last call.
+	pub fn take_changed_endpoint(&self) -> Option<NodeEndpoint> {
+		self.changed.lock().take()
+	}
+
+	/// Stop maintaining the mappings and remove them from the gateway.
+	pub fn stop(&mut self) {
+		self.stop.take();
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+impl Drop for PortMappingsMaintainer {
+	fn drop(&mut self) {
+		self.stop();
+	}
+}
+
+/// Map the ports of the local endpoint on the gateway of the local network, trying UPnP first
+/// and NAT-PMP/PCP on the default gateway next.
+pub fn map_ports(local: &NodeEndpoint) -> Option<PortMappings> {
+	let local_ip = match local.address {
+		SocketAddr::V4(address) => *address.ip(),
+		SocketAddr::V6(_) => return None,
+	};
+
+	let mut mappers: Vec<Box<PortMapper>> = Vec::new();
+	if let Some(mapper) = IgdMapper::search(local_ip) {
+		mappers.push(Box::new(mapper));
+	}
+	if let Some(gateway) = default_gateway() {
+		mappers.push(Box::new(PmpMapper::new(SocketAddr::new(IpAddr::V4(gateway), PMP_PORT), local_ip)));
+	}
+
+	for mapper in mappers {
+		let name = mapper.name();
+		match PortMappings::new(mapper, local, MAPPING_LEASE) {
+			Ok(mappings) => return Some(mappings),
+			Err(e) => debug!(target: "network", "{} port mapping error: {}", name, e),
+		}
+	}
+	None
+}
+
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+	use std::fs::File;
+	use std::io::Read;
+
+	let mut routes = String::new();
+	File::open("/proc/net/route").and_then(|mut f| f.read_to_string(&mut routes)).ok()?;
+	parse_default_gateway(&routes)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Option<Ipv4Addr> {
+	None
+}
+
+/// Find the default gateway in the contents of `/proc/net/route`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_default_gateway(routes: &str) -> Option<Ipv4Addr> {
+	routes.lines().skip(1)
+		.map(|line| line.split_whitespace().collect::<Vec<_>>())
+		.find(|columns| columns.len() > 2 && columns[1] == "00000000")
+		.and_then(|columns| u32::from_str_radix(columns[2], 16).ok())
+		// addresses are in host byte order.
+		.map(|gateway| Ipv4Addr::new(gateway as u8, (gateway >> 8) as u8, (gateway >> 16) as u8, (gateway >> 24) as u8))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::{Read, Write};
+	use std::net::TcpListener;
+	use std::sync::Arc;
+	use std::thread;
+	use parking_lot::Mutex;
+	use ip_utils::select_public_address;
+
+	type Requests = Arc<Mutex<Vec<String>>>;
+
+	fn local_endpoint() -> NodeEndpoint {
+		NodeEndpoint { address: "127.0.0.1:30303".parse().unwrap(), udp_port: 30301 }
+	}
+
+	/// Minimal UPnP control endpoint answering SOAP requests.
+	fn fake_igd(external_ip: Arc<Mutex<Ipv4Addr>>) -> (SocketAddrV4, Requests) {
+		let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+		let addr = match listener.local_addr().unwrap() {
+			SocketAddr::V4(addr) => addr,
+			SocketAddr::V6(_) => unreachable!(),
+		};
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let log = requests.clone();
+
+		thread::spawn(move || for stream in listener.incoming() {
+			let mut stream = match stream {
+				Ok(stream) => stream,
+				Err(_) => break,
+			};
+			let request = read_http_request(&mut stream);
+			let action = ["GetExternalIPAddress", "AddAnyPortMapping", "AddPortMapping", "DeletePortMapping"].iter()
+				.find(|action| request.contains(&format!("#{}", action)))
+				.cloned()
+				.unwrap_or("Unknown");
+			let tcp = request.contains("<NewProtocol>TCP</NewProtocol>");
+			let body = match action {
+				"GetExternalIPAddress" => format!("<NewExternalIPAddress>{}</NewExternalIPAddress>", *external_ip.lock()),
+				"AddAnyPortMapping" => format!("<NewReservedPort>{}</NewReservedPort>", if tcp { 40000 } else { 40001 }),
+				_ => String::new(),
+			};
+			log.lock().push(match action {
+				"GetExternalIPAddress" => action.to_owned(),
+				_ => format!("{} {}", action, if tcp { "TCP" } else { "UDP" }),
+			});
+
+			let envelope = format!(
+				"<?xml version=\"1.0\"?><s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" \
+				s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body>\
+				<u:{0}Response xmlns:u=\"urn:schemas-upnp-org:service:WANIPConnection:1\">{1}</u:{0}Response>\
+				</s:Body></s:Envelope>", action, body);
+			let response = format!(
+				"HTTP/1.1 200 OK\r\nContent-Type: text/xml; charset=\"utf-8\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+				envelope.len(), envelope);
+			let _ = stream.write_all(response.as_bytes());
+		});
+
+		(addr, requests)
+	}
+
+	fn read_http_request<R: Read>(stream: &mut R) -> String {
+		let mut request = Vec::new();
+		let mut buf = [0u8; 1024];
+		loop {
+			let len = stream.read(&mut buf).unwrap();
+			request.extend_from_slice(&buf[..len]);
+			let text = String::from_utf8_lossy(&request).into_owned();
+			if let Some(end) = text.find("\r\n\r\n") {
+				let content_length = text[..end].lines()
+					.find(|line| line.to_lowercase().starts_with("content-length:"))
+					.and_then(|line| line[15..].trim().parse::<usize>().ok())
+					.unwrap_or(0);
+				if len == 0 || request.len() >= end + 4 + content_length {
+					return text;
+				}
+			} else if len == 0 {
+				return text;
+			}
+		}
+	}
+
+	/// NAT-PMP gateway without PCP support.
+	fn fake_pmp(external_ip: Ipv4Addr) -> (SocketAddr, Requests) {
+		let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+		let addr = socket.local_addr().unwrap();
+		let requests = Arc::new(Mutex::new(Vec::new()));
+		let log = requests.clone();
+
+		thread::spawn(move || loop {
+			let mut buf = [0u8; 1100];
+			let (len, from) = match socket.recv_from(&mut buf) {
+				Ok(received) => received,
+				Err(_) => break,
+			};
+			let request = &buf[..len];
+			let mut response = vec![NAT_PMP_VERSION, 0x80 | request[1], 0, 0, 0, 0, 0, 1];
+			match (request[0], request[1]) {
+				(NAT_PMP_VERSION, 0) => {
+					log.lock().push("address".to_owned());
+					response.extend_from_slice(&external_ip.octets());
+				},
+				(NAT_PMP_VERSION, opcode) => {
+					let lifetime = ((request[8] as u32) << 24) | ((request[9] as u32) << 16) | ((request[10] as u32) << 8) | request[11] as u32;
+					log.lock().push(format!("map {} {}", opcode, lifetime));
+					let external_port = match get_u16(&request[6..8]) {
+						0 if lifetime == 0 => 0,
+						0 => 40000 + opcode as u16,
+						port => port,
+					};
+					response.extend_from_slice(&request[4..6]);
+					response.extend_from_slice(&[(external_port >> 8) as u8, external_port as u8]);
+					response.extend_from_slice(&request[8..12]);
+				},
+				_ => {
+					log.lock().push("pcp".to_owned());
+					response[1] = 0x80;
+					response[3] = UNSUPPORTED_VERSION;
+				},
+			}
+			socket.send_to(&response, from).unwrap();
+		});
+
+		(addr, requests)
+	}
+
+	#[test]
+	fn igd_mapping_lifecycle() {
+		let external_ip = Arc::new(Mutex::new(Ipv4Addr::new(1, 2, 3, 4)));
+		let (addr, requests) = fake_igd(external_ip.clone());
+		let mapper = IgdMapper::new(igd::Gateway { addr: addr, control_url: "/control".to_owned() });
+
+		let mut mappings = PortMappings::new(Box::new(mapper), &local_endpoint(), MAPPING_LEASE).unwrap();
+		assert_eq!(mappings.endpoint(), NodeEndpoint { address: "1.2.3.4:40000".parse().unwrap(), udp_port: 40001 });
+
+		// nothing to do yet
+		let start = Instant::now();
+		assert_eq!(mappings.maintain(start).unwrap(), None);
+
+		// renewal picks up the new external address
+		*external_ip.lock() = Ipv4Addr::new(5, 6, 7, 8);
+		assert_eq!(mappings.maintain(start + MAPPING_LEASE / 2).unwrap(),
+			Some(NodeEndpoint { address: "5.6.7.8:40000".parse().unwrap(), udp_port: 40001 }));
+
+		mappings.remove();
+		assert_eq!(*requests.lock(), vec![
+			"AddAnyPortMapping TCP", "AddAnyPortMapping UDP", "GetExternalIPAddress",
+			"AddPortMapping TCP", "AddPortMapping UDP", "GetExternalIPAddress",
+			"DeletePortMapping TCP", "DeletePortMapping UDP",
+		]);
+	}
+
+	#[test]
+	fn pmp_mapping_lifecycle() {
+		let (gateway, requests) = fake_pmp(Ipv4Addr::new(1, 2, 3, 4));
+		let mapper = PmpMapper::new(gateway, Ipv4Addr::new(127, 0, 0, 1));
+
+		let mut mappings = PortMappings::new(Box::new(mapper), &local_endpoint(), MAPPING_LEASE).unwrap();
+		assert_eq!(mappings.protocol(), "NAT-PMP");
+		assert_eq!(mappings.endpoint(), NodeEndpoint { address: "1.2.3.4:40002".parse().unwrap(), udp_port: 40001 });
+
+		// the address is checked between renewals
+		let start = Instant::now();
+		assert_eq!(mappings.maintain(start + EXTERNAL_ADDRESS_CHECK).unwrap(), None);
+
+		mappings.remove();
+		assert_eq!(*requests.lock(), vec![
+			"pcp", "map 2 1200", "map 1 1200", "address",
+			"address",
+			"map 2 0", "map 1 0",
+		]);
+	}
+
+	#[test]
+	fn maintainer_reports_changed_endpoint_and_removes_mappings() {
+		let external_ip = Arc::new(Mutex::new(Ipv4Addr::new(1, 2, 3, 4)));
+		let (addr, requests) = fake_igd(external_ip.clone());
+		let mapper = IgdMapper::new(igd::Gateway { addr: addr, control_url: "/control".to_owned() });
+		let mappings = PortMappings::new(Box::new(mapper), &local_endpoint(), Duration::from_millis(20)).unwrap();
+
+		*external_ip.lock() = Ipv4Addr::new(5, 6, 7, 8);
+		let mut maintainer = PortMappingsMaintainer::start(mappings, Duration::from_millis(20)).unwrap();
+		let expected = NodeEndpoint { address: "5.6.7.8:40000".parse().unwrap(), udp_port: 40001 };
+		let mut changed = None;
+		for _ in 0..250 {
+			changed = maintainer.take_changed_endpoint();
+			if changed.is_some() {
+				break;
+			}
+			thread::sleep(Duration::from_millis(20));
+		}
+		assert_eq!(changed, Some(expected));
+		assert_eq!(maintainer.take_changed_endpoint(), None);
+
+		maintainer.stop();
+		let requests = requests.lock();
+		assert_eq!(&requests[requests.len() - 2..], &["DeletePortMapping TCP", "DeletePortMapping UDP"]);
+	}
+
+	#[test]
+	fn parses_default_gateway() {
+		let routes = "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+			eth0\t0000A8C0\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0\n\
+			eth0\t00000000\t0101A8C0\t0003\t0\t0\t0\t00000000\t0\t0\t0\n";
+
+		assert_eq!(parse_default_gateway(routes), Some(Ipv4Addr::new(192, 168, 1, 1)));
+		assert_eq!(parse_default_gateway("Iface\tDestination\tGateway\n"), None);
+	}
+
+	#[ignore]
+	#[test]
+	fn can_map_external_address_or_fail() {
+		let pub_address = select_public_address(40478);
+		let _ = map_ports(&NodeEndpoint { address: pub_address, udp_port: 40478 });
+	}
+}
@@ -17,7 +17,7 @@
 use discovery::{TableUpdates, NodeEntry};
 use ethereum_types::H512;
 use ip_utils::*;
-use network::{Error, ErrorKind, AllowIP, IpFilter};
+use network::{Error, ErrorKind, AllowIP, IpFilter, PeerBehaviour};
 use rlp::{Rlp, RlpStream, DecoderError};
 use serde_json;
 use std::collections::{HashMap, HashSet};
@@ -33,7 +33,7 @@ use rand::{self, Rng};
 /// Node public key
 pub type NodeId = H512;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Node address info
 pub struct NodeEndpoint {
 	/// IP(V4 or V6) address
@@ -238,19 +238,56 @@ impl Hash for Node {
 const MAX_NODES: usize = 1024;
 const NODES_FILE: &str = "nodes.json";
 
+/// Highest reputation a node can reach.
+const MAX_REPUTATION: i32 = 100;
+/// Nodes with reputation at or below this threshold get banned.
+const BAN_THRESHOLD: i32 = -100;
+/// Time after which the reputation of a node is halved.
+const REPUTATION_HALF_LIFE: Duration = Duration::from_secs(60 * 30);
+/// Duration of a ban.
+const BAN_DURATION: Duration = Duration::from_secs(60 * 60 * 2);
+
+/// Reputation of a node, decaying exponentially towards zero over time.
+#[derive(Debug, Clone, Copy)]
+struct Reputation {
+	score: f64,
+	updated: SystemTime,
+}
+
+impl Reputation {
+	/// Exact score at given time, after decay.
+	fn decayed(&self, now: SystemTime) -> f64 {
+		let elapsed = now.duration_since(self.updated)
+			.map(|d| d.as_secs() as f64 + d.subsec_nanos() as f64 / 1_000_000_000f64)
+			.unwrap_or(0f64);
+		self.score * 0.5f64.powf(elapsed / REPUTATION_HALF_LIFE.as_secs() as f64)
+	}
+
+	/// Score at given time, after decay, rounded to the nearest integer.
+	fn score(&self, now: SystemTime) -> i32 {
+		self.decayed(now).round() as i32
+	}
+}
+
 /// Node table backed by disk file.
 pub struct NodeTable {
 	nodes: HashMap<NodeId, Node>,
 	useless_nodes: HashSet<NodeId>,
+	reputations: HashMap<NodeId, Reputation>,
+	/// Banned nodes and the time their bans expire.
+	bans: HashMap<NodeId, SystemTime>,
 	path: Option<String>,
 }
 
 impl NodeTable {
 	pub fn new(path: Option<String>) -> NodeTable {
+		let (nodes, bans) = NodeTable::load(path.clone());
 		NodeTable {
-			path: path.clone(),
-			nodes: NodeTable::load(path),
+			path: path,
+			nodes: nodes,
 			useless_nodes: HashSet::new(),
+			reputations: HashMap::new(),
+			bans: bans,
 		}
 	}
 
@@ -274,8 +311,9 @@ impl NodeTable {
 		let mut failures = Vec::new();
 		let mut unknown = Vec::new();
 
+		let now = SystemTime::now();
 		let nodes = self.nodes.values()
-			.filter(|n| !self.useless_nodes.contains(&n.id));
+			.filter(|n| !self.useless_nodes.contains(&n.id) && !self.is_banned(&n.id));
 
 		for node in nodes {
 			// discard contact points older that aren't recent
@@ -293,10 +331,12 @@ impl NodeTable {
 		}
 
 		success.sort_by(|a, b| {
+			let a_score = self.reputation_at(&a.id, now);
+			let b_score = self.reputation_at(&b.id, now);
 			let a = a.last_contact.expect("vector only contains values with defined last_contact; qed");
 			let b = b.last_contact.expect("vector only contains values with defined last_contact; qed");
-			// inverse ordering, most recent successes come first
-			b.time().cmp(&a.time())
+			// inverse ordering, best reputation and most recent successes come first
+			b_score.cmp(&a_score).then_with(|| b.time().cmp(&a.time()))
 		});
 
 		failures.sort_by(|a, b| {
@@ -378,6 +418,68 @@ impl NodeTable {
 		self.useless_nodes.clear();
 	}
 
+	fn reputation_at(&self, id: &NodeId, now: SystemTime) -> i32 {
+		self.reputations.get(id).map_or(0, |r| r.score(now))
+	}
+
+	/// Current reputation of a node.
+	pub fn reputation(&self, id: &NodeId) -> i32 {
+		self.reputation_at(id, SystemTime::now())
+	}
+
+	/// Update reputation of a node according to its behaviour. Bans the node once its
+	/// reputation drops to the threshold. Returns `true` if the node got banned.
+	pub fn report(&mut self, id: &NodeId, behaviour: PeerBehaviour) -> bool {
+		let now = SystemTime::now();
+		let score = {
+			let reputation = self.reputations.entry(id.clone()).or_insert(Reputation { score: 0f64, updated: now });
+			reputation.score = (reputation.decayed(now) + behaviour.reputation_change() as f64).min(MAX_REPUTATION as f64);
+			reputation.updated = now;
+			reputation.score(now)
+		};
+		trace!(target: "network", "Reputation of {:?} after {:?}: {}", id, behaviour, score);
+
+		if score > BAN_THRESHOLD {
+			return false;
+		}
+		self.reputations.remove(id);
+		self.bans.insert(id.clone(), now + BAN_DURATION);
+		true
+	}
+
+	/// Check if a node is currently banned.
+	pub fn is_banned(&self, id: &NodeId) -> bool {
+		self.bans.get(id).map_or(false, |until| *until > SystemTime::now())
+	}
+
+	/// Currently banned nodes, along with the time their bans expire.
+	pub fn banned(&self) -> Vec<(NodeId, SystemTime)> {
+		let now = SystemTime::now();
+		self.bans.iter()
+			.filter(|&(_, until)| *until > now)
+			.map(|(id, until)| (id.clone(), *until))
+			.collect()
+	}
+
+	/// Lift the ban of a node. Returns `false` if the node was not banned.
+	pub fn unban(&mut self, id: &NodeId) -> bool {
+		let banned = self.is_banned(id);
+		self.bans.remove(id);
+		banned
+	}
+
+	/// Lift all bans.
+	pub fn clear_bans(&mut self) {
+		self.bans.clear();
+	}
+
+	/// Forget reputations which decayed to zero and bans which expired.
+	pub fn prune_reputations(&mut self) {
+		let now = SystemTime::now();
+		self.reputations.retain(|_, reputation| reputation.score(now) != 0);
+		self.bans.retain(|_, until| *until > now);
+	}
+
 	/// Save the nodes.json file.
 	pub fn save(&self) {
 		let mut path = match self.path {
@@ -396,7 +498,13 @@ impl NodeTable {
 			.map(|node| node.clone())
 			.map(Into::into)
 			.collect();
-		let table = json::NodeTable { nodes };
+		let bans = self.banned().iter()
+			.filter_map(|&(ref id, until)| until.duration_since(time::UNIX_EPOCH).ok().map(|d| json::Ban {
+				id: format!("{:x}", id),
+				until: d.as_secs(),
+			}))
+			.collect();
+		let table = json::NodeTable { nodes, bans };
 
 		match fs::File::create(&path) {
 			Ok(file) => {
@@ -410,7 +518,7 @@ impl NodeTable {
 		}
 	}
 
-	fn load(path: Option<String>) -> HashMap<NodeId, Node> {
+	fn load(path: Option<String>) -> (HashMap<NodeId, Node>, HashMap<NodeId, SystemTime>) {
 		let path = match path {
 			Some(path) => PathBuf::from(path).join(NODES_FILE),
 			None => return Default::default(),
@@ -426,10 +534,14 @@ impl NodeTable {
 		let res: Result<json::NodeTable, _> = serde_json::from_reader(file);
 		match res {
 			Ok(table) => {
-				table.nodes.into_iter()
+				let nodes = table.nodes.into_iter()
 					.filter_map(|n| n.into_node())
 					.map(|n| (n.id.clone(), n))
-					.collect()
+					.collect();
+				let bans = table.bans.into_iter()
+					.filter_map(|b| b.id.parse().ok().map(|id| (id, time::UNIX_EPOCH + Duration::from_secs(b.until))))
+					.collect();
+				(nodes, bans)
 			},
 			Err(e) => {
 				warn!("Error reading node table file: {:?}", e);
@@ -459,6 +571,14 @@ mod json {
 	#[derive(Serialize, Deserialize)]
 	pub struct NodeTable {
 		pub nodes: Vec<Node>,
+		#[serde(default)]
+		pub bans: Vec<Ban>,
+	}
+
+	#[derive(Serialize, Deserialize)]
+	pub struct Ban {
+		pub id: String,
+		pub until: u64,
 	}
 
 	#[derive(Serialize, Deserialize)]
@@ -669,6 +789,85 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn table_reputation_bans() {
+		let mut table = NodeTable::new(None);
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let id1 = node1.id.clone();
+		let id2 = node2.id.clone();
+		table.add_node(node1);
+		table.add_node(node2);
+
+		assert!(!table.report(&id1, PeerBehaviour::UsefulResponse));
+		assert_eq!(table.reputation(&id1), 1);
+		assert!(!table.report(&id2, PeerBehaviour::InvalidData));
+		assert!(!table.report(&id2, PeerBehaviour::InvalidData));
+		assert_eq!(table.reputation(&id2), -80);
+		assert!(table.report(&id2, PeerBehaviour::Spam));
+
+		assert!(table.is_banned(&id2));
+		assert_eq!(table.banned().len(), 1);
+		assert_eq!(table.nodes(IpFilter::default()), vec![id1.clone()]);
+
+		assert!(table.unban(&id2));
+		assert!(!table.unban(&id2));
+		assert_eq!(table.nodes(IpFilter::default()).len(), 2);
+	}
+
+	#[test]
+	fn reputation_decays() {
+		let now = SystemTime::now();
+		let reputation = Reputation { score: -80f64, updated: now - REPUTATION_HALF_LIFE * 2 };
+		assert_eq!(reputation.score(now), -20);
+		let reputation = Reputation { score: -80f64, updated: now - REPUTATION_HALF_LIFE / 2 };
+		assert_eq!(reputation.score(now), -57);
+		let reputation = Reputation { score: 50f64, updated: now - REPUTATION_HALF_LIFE * 40 };
+		assert_eq!(reputation.score(now), 0);
+	}
+
+	#[test]
+	fn reputation_keeps_partial_decay_between_updates() {
+		let now = SystemTime::now();
+		let half = REPUTATION_HALF_LIFE / 2;
+		let reputation = Reputation { score: -80f64, updated: now - REPUTATION_HALF_LIFE };
+		// updated in the middle, e.g. by a report.
+		let reputation = Reputation { score: reputation.decayed(now - half), updated: now - half };
+		assert_eq!(reputation.score(now), -40);
+	}
+
+	#[test]
+	fn table_prunes_decayed_reputations() {
+		let mut table = NodeTable::new(None);
+		let node1 = Node::from_str("enode://a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let node2 = Node::from_str("enode://b979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c@22.99.55.44:7770").unwrap();
+		let now = SystemTime::now();
+		table.reputations.insert(node1.id.clone(), Reputation { score: -40f64, updated: now });
+		table.reputations.insert(node2.id.clone(), Reputation { score: -40f64, updated: now - REPUTATION_HALF_LIFE * 10 });
+
+		table.prune_reputations();
+
+		assert_eq!(table.reputations.len(), 1);
+		assert_eq!(table.reputation(&node1.id), -40);
+		assert_eq!(table.reputation(&node2.id), 0);
+	}
+
+	#[test]
+	fn table_save_load_bans() {
+		let tempdir = TempDir::new("").unwrap();
+		let id = H512::from_str("a979fb575495b8d6db44f750317d0f4622bf4c2aa3365d6af7c284339968eef29b69ad0dce72a4d8db5ebb4968de0e3bec910127f134779fbcb0cb6d3331163c").unwrap();
+
+		{
+			let mut table = NodeTable::new(Some(tempdir.path().to_str().unwrap().to_owned()));
+			while !table.report(&id, PeerBehaviour::BadProtocol) {}
+		}
+
+		{
+			let table = NodeTable::new(Some(tempdir.path().to_str().unwrap().to_owned()));
+			assert!(table.is_banned(&id));
+		}
+	}
+
 	#[test]
 	fn custom_allow() {
 		let filter = IpFilter {
@@ -15,11 +15,12 @@
 // along with Parity.  If not, see <http://www.gnu.org/licenses/>.
 
 use network::{Error, NetworkConfiguration, NetworkProtocolHandler, NonReservedPeerMode};
-use network::{NetworkContext, PeerId, ProtocolId, NetworkIoMessage};
+use network::{NetworkContext, PeerId, ProtocolId, NetworkIoMessage, NodeId};
 use host::Host;
 use io::*;
 use parking_lot::RwLock;
 use std::sync::Arc;
+use std::time::SystemTime;
 use ansi_term::Colour;
 use connection_filter::ConnectionFilter;
 
@@ -82,6 +83,15 @@ impl NetworkService {
 		Ok(())
 	}
 
+	/// Set an entry of the local node record advertised over discovery. `value` must be RLP-encoded.
+	pub fn set_node_record_entry(&self, key: &str, value: Vec<u8>) -> Result<(), Error> {
+		self.io_service.send_message(NetworkIoMessage::SetNodeRecordEntry {
+			key: key.to_owned(),
+			value,
+		})?;
+		Ok(())
+	}
+
 	/// Returns host identifier string as advertised to other peers
 	pub fn host_info(&self) -> String {
 		self.host_info.clone()
@@ -161,6 +171,23 @@ impl NetworkService {
 		}
 	}
 
+	/// Nodes currently banned for bad behaviour, along with the time their bans expire.
+	pub fn banned_peers(&self) -> Vec<(NodeId, SystemTime)> {
+		self.host.read().as_ref().map(|h| h.banned_nodes()).unwrap_or_else(Vec::new)
+	}
+
+	/// Lift the ban of a node. Returns `false` if the node was not banned.
+	pub fn unban_peer(&self, id: &NodeId) -> bool {
+		self.host.read().as_ref().map_or(false, |h| h.unban_node(id))
+	}
+
+	/// Lift all bans.
+	pub fn clear_banned_peers(&self) {
+		if let Some(ref host) = *self.host.read() {
+			host.clear_bans();
+		}
+	}
+
 	/// Set the non-reserved peer mode.
 	pub fn set_non_reserved_mode(&self, mode: NonReservedPeerMode) {
 		let host = self.host.read();
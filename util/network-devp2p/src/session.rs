@@ -120,6 +120,7 @@ impl Session {
 				originated: originated,
 				remote_address: "Handshake".to_owned(),
 				local_address: local_addr,
+				traffic: Default::default(),
 			},
 			ping_time: Instant::now(),
 			pong_time: None,
@@ -288,6 +289,8 @@ impl Session {
 			trace!(target: "network", "compressed {} to {}", payload.len(), len);
 			payload = &compressed[0..len];
 		}
+		self.info.traffic.raw_sent += data.len() as u64;
+		self.info.traffic.compressed_sent += payload.len() as u64;
 		rlp.append_raw(payload, 1);
 		self.send(io, &rlp.drain())
 	}
@@ -347,6 +350,8 @@ impl Session {
 		} else {
 			packet.data[1..].to_owned()
 		};
+		self.info.traffic.raw_received += data.len() as u64;
+		self.info.traffic.compressed_received += (packet.data.len() - 1) as u64;
 		match packet_id {
 			PACKET_HELLO => {
 				let rlp = Rlp::new(&data); //TODO: validate rlp expected size
@@ -88,6 +88,44 @@ pub enum NetworkIoMessage {
 	DisablePeer(PeerId),
 	/// Network has been started with the host as the given enode.
 	NetworkStarted(String),
+	/// Set an entry of the local node record.
+	SetNodeRecordEntry {
+		/// Entry key.
+		key: String,
+		/// RLP-encoded entry value.
+		value: Vec<u8>,
+	},
+	/// Update reputation of a peer.
+	ReportPeer(PeerId, PeerBehaviour),
+}
+
+/// Behaviour of a peer reported by protocol handlers. Affects the reputation of the node,
+/// which gets banned for a while once its reputation drops too low.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeerBehaviour {
+	/// Requested data was delivered in time.
+	UsefulResponse,
+	/// Request was not answered in time.
+	Timeout,
+	/// Unrequested or excessive messages.
+	Spam,
+	/// Invalid data, e.g. a bad block or proof.
+	InvalidData,
+	/// Violation of the protocol rules.
+	BadProtocol,
+}
+
+impl PeerBehaviour {
+	/// Change of the reputation caused by this behaviour.
+	pub fn reputation_change(&self) -> i32 {
+		match *self {
+			PeerBehaviour::UsefulResponse => 1,
+			PeerBehaviour::Timeout => -10,
+			PeerBehaviour::Spam => -20,
+			PeerBehaviour::InvalidData => -40,
+			PeerBehaviour::BadProtocol => -50,
+		}
+	}
 }
 
 /// Shared session information
@@ -111,6 +149,22 @@ pub struct SessionInfo {
 	pub remote_address: String,
 	/// Local endpoint address of the session
 	pub local_address: String,
+	/// Payload traffic of the session
+	pub traffic: SessionTraffic,
+}
+
+/// Payload bytes sent and received over a session, before and after compression.
+/// Both values are equal if the session doesn't use compression.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionTraffic {
+	/// Uncompressed payload bytes sent
+	pub raw_sent: u64,
+	/// Payload bytes sent over the wire
+	pub compressed_sent: u64,
+	/// Uncompressed payload bytes received
+	pub raw_received: u64,
+	/// Payload bytes received over the wire
+	pub compressed_received: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -182,6 +236,8 @@ pub struct NetworkConfiguration {
 	pub nat_enabled: bool,
 	/// Enable discovery
 	pub discovery_enabled: bool,
+	/// Topics advertised in the node record. Nodes advertising any of them are preferred when connecting.
+	pub discovery_topics: Vec<String>,
 	/// List of initial node addresses
 	pub boot_nodes: Vec<String>,
 	/// Use provided node key instead of default
@@ -221,6 +277,7 @@ impl NetworkConfiguration {
 			udp_port: None,
 			nat_enabled: true,
 			discovery_enabled: true,
+			discovery_topics: Vec::new(),
 			boot_nodes: Vec::new(),
 			use_secret: None,
 			min_peers: 25,
@@ -270,6 +327,9 @@ pub trait NetworkContext {
 	/// Disconnect peer. Reconnect can be attempted later.
 	fn disconnect_peer(&self, peer: PeerId);
 
+	/// Report behaviour of a peer, updating its reputation.
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour);
+
 	/// Check if the session is still active.
 	fn is_expired(&self) -> bool;
 
@@ -287,6 +347,9 @@ pub trait NetworkContext {
 
 	/// Returns this object's subprotocol name.
 	fn subprotocol_name(&self) -> ProtocolId;
+
+	/// Returns whether the peer is one of the reserved peers.
+	fn is_reserved_peer(&self, peer: PeerId) -> bool;
 }
 
 impl<'a, T> NetworkContext for &'a T where T: ?Sized + NetworkContext {
@@ -314,6 +377,10 @@ impl<'a, T> NetworkContext for &'a T where T: ?Sized + NetworkContext {
 		(**self).disconnect_peer(peer)
 	}
 
+	fn report_peer(&self, peer: PeerId, behaviour: PeerBehaviour) {
+		(**self).report_peer(peer, behaviour)
+	}
+
 	fn is_expired(&self) -> bool {
 		(**self).is_expired()
 	}
@@ -337,6 +404,10 @@ impl<'a, T> NetworkContext for &'a T where T: ?Sized + NetworkContext {
 	fn subprotocol_name(&self) -> ProtocolId {
 		(**self).subprotocol_name()
 	}
+
+	fn is_reserved_peer(&self, peer: PeerId) -> bool {
+		(**self).is_reserved_peer(peer)
+	}
 }
 
 pub trait HostInfo {